@@ -10,4 +10,48 @@ pub enum EscrowError {
     InvalidMintA,
     #[msg("Invalid mint B: mint_b does not match escrow mint_b")]
     InvalidMintB,
+    #[msg("Invalid escrow account: not an escrow owned by this program")]
+    InvalidEscrowAccount,
+    #[msg("Escrow already migrated: account is at the current layout version")]
+    EscrowAlreadyMigrated,
+    #[msg("Freezable mint: mint has a freeze authority and the maker opted out")]
+    FreezableMint,
+    #[msg("Invalid AMM pool: pool does not trade mint_a against mint_b")]
+    InvalidAmmPool,
+    #[msg("Insufficient swap output: pool returned less than the receive amount")]
+    InsufficientSwapOutput,
+    #[msg("Invalid end time: auction must end in the future")]
+    InvalidEndTime,
+    #[msg("Auction ended: bids are no longer accepted")]
+    AuctionEnded,
+    #[msg("Auction not ended: cannot settle before the end time")]
+    AuctionNotEnded,
+    #[msg("Bid too low: bid must meet the reserve and minimum increment")]
+    BidTooLow,
+    #[msg("Invalid bidder: account does not match the auction's highest bidder")]
+    InvalidBidder,
+    #[msg("Invalid expiry: expiry must be zero or in the future")]
+    InvalidExpiry,
+    #[msg("Escrow expired: the escrow can no longer be taken")]
+    EscrowExpired,
+    #[msg("Invalid cancellation fee: fee exceeds the configured maximum")]
+    InvalidCancelFee,
+    #[msg("Missing treasury account: treasury Token A account is required for a bps fee")]
+    MissingTreasuryAccount,
+    #[msg("Invalid receive amount: receive must be greater than zero")]
+    InvalidReceiveAmount,
+    #[msg("Identical mints: mint_a and mint_b must differ")]
+    IdenticalMints,
+    #[msg("Invalid authority: signer is not the config authority")]
+    InvalidAuthority,
+    #[msg("Program paused: new escrows and fills are disabled")]
+    ProgramPaused,
+    #[msg("Invalid token authority: taker is neither the owner nor the delegate of the Token B account")]
+    InvalidTokenAuthority,
+    #[msg("Invalid AMM program: account is not the configured, executable AMM program")]
+    InvalidAmmProgram,
+    #[msg("Invalid cancellation fee: lamports exceed the configured maximum")]
+    InvalidCancelFeeLamports,
+    #[msg("Invalid maximum cancellation fee: basis points must not exceed 10000")]
+    InvalidMaxCancelFee,
 }
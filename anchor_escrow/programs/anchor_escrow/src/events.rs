@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when a maker opens an escrow
+#[event]
+pub struct EscrowMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    /// Token A deposited into the vault
+    pub amount: u64,
+    /// Token B requested in exchange
+    pub receive: u64,
+    /// Maker-supplied reference (e.g. an off-chain deal ID)
+    pub memo: [u8; 32],
+}
+
+/// Emitted when a taker fills an escrow
+#[event]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    /// Token A released to the taker (or sold through the AMM)
+    pub amount: u64,
+    /// Token B paid to the maker
+    pub receive: u64,
+    /// Maker-supplied reference (e.g. an off-chain deal ID)
+    pub memo: [u8; 32],
+}
+
+/// Emitted when a maker cancels an escrow
+#[event]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    /// Token A returned to the maker
+    pub amount: u64,
+    /// Maker-supplied reference (e.g. an off-chain deal ID)
+    pub memo: [u8; 32],
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{Auction, Config},
+};
+
+#[derive(Accounts)]
+pub struct Bid<'info> {
+    /// The bidder placing a new highest bid
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// Auction account storing the terms and current highest bid
+    #[account(
+        mut,
+        has_one = mint_b,
+        seeds = [b"auction", auction.maker.as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Token B mint (the token bids are made in)
+    pub mint_b: Box<Account<'info, Mint>>,
+
+    /// Bid vault holding the highest Token B bid (owned by auction)
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = auction,
+    )]
+    pub bid_vault: Box<Account<'info, TokenAccount>>,
+
+    /// Bidder's associated token account for Token B (source of the bid)
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_ata_b: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The bidder being outbid; must match the auction's highest bidder
+    #[account(address = auction.highest_bidder @ EscrowError::InvalidBidder)]
+    pub previous_bidder: UncheckedAccount<'info>,
+
+    /// Previous bidder's associated token account for Token B (receives the outbid refund)
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = mint_b,
+        associated_token::authority = previous_bidder,
+    )]
+    pub previous_bidder_ata_b: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Program config; bids are rejected while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+impl<'info> Bid<'info> {
+    /// Transfer the new bid from the bidder into the bid vault
+    pub fn escrow_bid(&mut self, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: self.bidder_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.bid_vault.to_account_info(),
+            authority: self.bidder.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
+    }
+
+    /// Return the outbid amount from the bid vault to the previous bidder
+    pub fn refund_previous_bid(&mut self) -> Result<()> {
+        if self.auction.highest_bid == 0 {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.auction.maker.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            from: self.bid_vault.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.previous_bidder_ata_b.to_account_info(),
+            authority: self.auction.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, self.auction.highest_bid, self.mint_b.decimals)
+    }
+}
+
+/// Handler for the bid instruction
+pub fn handler(ctx: Context<Bid>, amount: u64) -> Result<()> {
+    // Validate that the auction is still running
+    require_gt!(
+        ctx.accounts.auction.end_time,
+        Clock::get()?.unix_timestamp,
+        EscrowError::AuctionEnded
+    );
+    // Validate that the bid meets the reserve and minimum increment
+    let min_bid = ctx
+        .accounts
+        .auction
+        .min_bid()
+        .ok_or(EscrowError::BidTooLow)?;
+    require_gte!(amount, min_bid, EscrowError::BidTooLow);
+
+    // Escrow the new bid, then refund the bidder being outbid
+    ctx.accounts.escrow_bid(amount)?;
+    ctx.accounts.refund_previous_bid()?;
+
+    // Record the new highest bid
+    let auction = &mut ctx.accounts.auction;
+    auction.highest_bidder = ctx.accounts.bidder.key();
+    auction.highest_bid = amount;
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{Auction, Config},
+};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateAuction<'info> {
+    /// The maker who auctions Token A
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// Auction account that stores the auction terms and current bid
+    #[account(
+        init,
+        payer = maker,
+        space = Auction::SPACE,
+        seeds = [b"auction", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Token A mint (the token being auctioned)
+    pub mint_a: Box<Account<'info, Mint>>,
+
+    /// Token B mint (the token bids are made in)
+    pub mint_b: Box<Account<'info, Mint>>,
+
+    /// Maker's associated token account for Token A (source of deposit)
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_a: Box<Account<'info, TokenAccount>>,
+
+    /// Vault account owned by the auction to hold Token A
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// Bid vault owned by the auction to hold the highest Token B bid
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint_b,
+        associated_token::authority = auction,
+    )]
+    pub bid_vault: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Program config; new auctions are rejected while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+impl<'info> CreateAuction<'info> {
+    /// Initialize the auction with the maker as the placeholder highest bidder
+    pub fn init_auction(
+        &mut self,
+        seed: u64,
+        reserve: u64,
+        min_increment: u64,
+        end_time: i64,
+        bumps: &CreateAuctionBumps,
+    ) -> Result<()> {
+        self.auction.set_inner(Auction {
+            seed,
+            maker: self.maker.key(),
+            mint_a: self.mint_a.key(),
+            mint_b: self.mint_b.key(),
+            reserve,
+            min_increment,
+            end_time,
+            highest_bidder: self.maker.key(),
+            highest_bid: 0,
+            bump: bumps.auction,
+        });
+        Ok(())
+    }
+
+    /// Transfer Token A from maker to vault
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: self.maker_ata_a.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)
+    }
+}
+
+/// Handler for the create_auction instruction
+pub fn handler(
+    ctx: Context<CreateAuction>,
+    seed: u64,
+    amount: u64,
+    reserve: u64,
+    min_increment: u64,
+    end_time: i64,
+) -> Result<()> {
+    // Validate that the auctioned amount and reserve are greater than zero
+    require_gt!(amount, 0, EscrowError::InvalidAmount);
+    require_gt!(reserve, 0, EscrowError::InvalidAmount);
+    // Validate that the auction ends in the future
+    require_gt!(
+        end_time,
+        Clock::get()?.unix_timestamp,
+        EscrowError::InvalidEndTime
+    );
+
+    // Initialize auction terms
+    ctx.accounts
+        .init_auction(seed, reserve, min_increment, end_time, &ctx.bumps)?;
+
+    // Deposit Token A into vault
+    ctx.accounts.deposit(amount)?;
+
+    Ok(())
+}
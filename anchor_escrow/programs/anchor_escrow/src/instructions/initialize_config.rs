@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, program::AnchorEscrow, state::Config};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// The program's upgrade authority, which becomes the config authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Program-wide config account
+    #[account(
+        init,
+        payer = authority,
+        space = Config::SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// This program, used to locate its program data account
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, AnchorEscrow>,
+
+    /// Program data holding the upgrade authority
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ EscrowError::InvalidAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Handler for the initialize_config instruction
+pub fn handler(
+    ctx: Context<InitializeConfig>,
+    treasury: Pubkey,
+    max_cancel_fee_bps: u16,
+    amm_program: Pubkey,
+    max_cancel_fee_lamports: u64,
+) -> Result<()> {
+    // Validate that the fee cap is at most 100%
+    require_gte!(10_000, max_cancel_fee_bps, EscrowError::InvalidMaxCancelFee);
+
+    ctx.accounts.config.set_inner(Config {
+        authority: ctx.accounts.authority.key(),
+        treasury,
+        max_cancel_fee_bps,
+        paused: false,
+        bump: ctx.bumps.config,
+        amm_program,
+        max_cancel_fee_lamports,
+    });
+
+    Ok(())
+}
@@ -4,7 +4,10 @@ use anchor_spl::{
     token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked},
 };
 
-use crate::state::Escrow;
+use crate::{
+    events::EscrowMade,
+    state::{CancelFee, Config, Escrow, EscrowStats},
+};
 
 #[derive(Accounts)]
 #[instruction(seed: u64)]
@@ -17,7 +20,7 @@ pub struct Make<'info> {
     #[account(
         init,
         payer = maker,
-        space = 8 + Escrow::INIT_SPACE,
+        space = Escrow::SPACE,
         seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
         bump,
     )]
@@ -49,11 +52,50 @@ pub struct Make<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// Program-wide escrow counters (created on first use)
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = EscrowStats::SPACE,
+        seeds = [b"stats"],
+        bump,
+    )]
+    pub stats: Box<Account<'info, EscrowStats>>,
+
+    /// Program config; new escrows are rejected while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ crate::errors::EscrowError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
 }
 
 impl<'info> Make<'info> {
+    /// Reject mints whose freeze authority could lock the vault mid-settlement
+    pub fn check_not_freezable(&self) -> Result<()> {
+        require!(
+            self.mint_a.freeze_authority.is_none(),
+            crate::errors::EscrowError::FreezableMint
+        );
+        require!(
+            self.mint_b.freeze_authority.is_none(),
+            crate::errors::EscrowError::FreezableMint
+        );
+        Ok(())
+    }
+
     /// Initialize the escrow account with exchange terms
-    pub fn init_escrow(&mut self, seed: u64, receive: u64, bumps: &MakeBumps) -> Result<()> {
+    pub fn init_escrow(
+        &mut self,
+        seed: u64,
+        receive: u64,
+        expiry: i64,
+        cancel_fee: CancelFee,
+        memo: [u8; 32],
+        bumps: &MakeBumps,
+    ) -> Result<()> {
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
@@ -61,10 +103,20 @@ impl<'info> Make<'info> {
             mint_b: self.mint_b.key(),
             receive,
             bump: bumps.escrow,
+            version: Escrow::VERSION,
+            expiry,
+            cancel_fee,
+            memo,
         });
         Ok(())
     }
 
+    /// Count the new escrow as open in the program-wide stats
+    pub fn record_open(&mut self, bumps: &MakeBumps) {
+        self.stats.open_escrows = self.stats.open_escrows.saturating_add(1);
+        self.stats.bump = bumps.stats;
+    }
+
     /// Transfer Token A from maker to vault
     pub fn deposit(&mut self, amount: u64) -> Result<()> {
         let cpi_accounts = TransferChecked {
@@ -80,18 +132,153 @@ impl<'info> Make<'info> {
     }
 }
 
-/// Handler for the make instruction
-pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
+/// Validate the exchange terms independently of the accounts
+pub fn validate_terms(mint_a: &Pubkey, mint_b: &Pubkey, receive: u64, amount: u64) -> Result<()> {
+    // Validate that the escrow actually exchanges two different tokens
+    require_keys_neq!(*mint_a, *mint_b, crate::errors::EscrowError::IdenticalMints);
     // Validate that receive amount is greater than zero
-    require_gt!(receive, 0, crate::errors::EscrowError::InvalidAmount);
+    require_gt!(receive, 0, crate::errors::EscrowError::InvalidReceiveAmount);
     // Validate that deposit amount is greater than zero
     require_gt!(amount, 0, crate::errors::EscrowError::InvalidAmount);
+    Ok(())
+}
+
+/// Validate a cancellation fee against the config's cap for its kind
+pub fn validate_cancel_fee(
+    cancel_fee: CancelFee,
+    max_cancel_fee_bps: u16,
+    max_cancel_fee_lamports: u64,
+) -> Result<()> {
+    match cancel_fee {
+        CancelFee::None => {}
+        CancelFee::Lamports(lamports) => {
+            require_gte!(
+                max_cancel_fee_lamports,
+                lamports,
+                crate::errors::EscrowError::InvalidCancelFeeLamports
+            );
+        }
+        CancelFee::Bps(bps) => {
+            require_gte!(
+                max_cancel_fee_bps,
+                bps,
+                crate::errors::EscrowError::InvalidCancelFee
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Handler for the make instruction
+pub fn handler(
+    ctx: Context<Make>,
+    seed: u64,
+    receive: u64,
+    amount: u64,
+    reject_freezable: bool,
+    expiry: i64,
+    cancel_fee: CancelFee,
+    memo: [u8; 32],
+) -> Result<()> {
+    // Validate mints and amounts
+    validate_terms(
+        &ctx.accounts.mint_a.key(),
+        &ctx.accounts.mint_b.key(),
+        receive,
+        amount,
+    )?;
+    // Validate that the expiry, if set, lies in the future
+    if expiry != 0 {
+        require_gt!(
+            expiry,
+            Clock::get()?.unix_timestamp,
+            crate::errors::EscrowError::InvalidExpiry
+        );
+    }
+    // Validate that the cancellation fee stays within its bounds
+    validate_cancel_fee(
+        cancel_fee,
+        ctx.accounts.config.max_cancel_fee_bps,
+        ctx.accounts.config.max_cancel_fee_lamports,
+    )?;
+    // Optionally refuse mints that carry a freeze authority
+    if reject_freezable {
+        ctx.accounts.check_not_freezable()?;
+    }
 
     // Initialize escrow with exchange terms
-    ctx.accounts.init_escrow(seed, receive, &ctx.bumps)?;
+    ctx.accounts
+        .init_escrow(seed, receive, expiry, cancel_fee, memo, &ctx.bumps)?;
 
     // Deposit Token A into vault
     ctx.accounts.deposit(amount)?;
 
+    // Update program-wide stats
+    ctx.accounts.record_open(&ctx.bumps);
+
+    emit!(EscrowMade {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        mint_a: ctx.accounts.mint_a.key(),
+        mint_b: ctx.accounts.mint_b.key(),
+        amount,
+        receive,
+        memo,
+    });
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::EscrowError;
+
+    #[test]
+    fn accepts_valid_terms() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(validate_terms(&mint_a, &mint_b, 1, 1).is_ok());
+    }
+
+    #[test]
+    fn rejects_identical_mints() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(
+            validate_terms(&mint, &mint, 1, 1).unwrap_err(),
+            EscrowError::IdenticalMints.into()
+        );
+    }
+
+    #[test]
+    fn rejects_zero_receive() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            validate_terms(&mint_a, &mint_b, 0, 1).unwrap_err(),
+            EscrowError::InvalidReceiveAmount.into()
+        );
+    }
+
+    #[test]
+    fn rejects_zero_amount() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            validate_terms(&mint_a, &mint_b, 1, 0).unwrap_err(),
+            EscrowError::InvalidAmount.into()
+        );
+    }
+
+    #[test]
+    fn bounds_cancel_fees() {
+        assert!(validate_cancel_fee(CancelFee::None, 0, 0).is_ok());
+        assert!(validate_cancel_fee(CancelFee::Bps(500), 500, 0).is_ok());
+        assert_eq!(
+            validate_cancel_fee(CancelFee::Bps(501), 500, 1_000_000).unwrap_err(),
+            EscrowError::InvalidCancelFee.into()
+        );
+        assert!(validate_cancel_fee(CancelFee::Lamports(1_000_000), 0, 1_000_000).is_ok());
+        assert_eq!(
+            validate_cancel_fee(CancelFee::Lamports(1_000_001), 10_000, 1_000_000).unwrap_err(),
+            EscrowError::InvalidCancelFeeLamports.into()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::{errors::EscrowError, state::Escrow};
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    /// Pays the rent for the added bytes (migration is permissionless)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Escrow in a possibly outdated layout. `Account<Escrow>` cannot
+    /// deserialize it yet, so owner and discriminator are validated manually.
+    #[account(mut, owner = crate::ID @ EscrowError::InvalidEscrowAccount)]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Migrate<'info> {
+    /// Grow the escrow to the current layout size, topping up rent from the payer
+    pub fn resize_escrow(&mut self) -> Result<()> {
+        let escrow = self.escrow.to_account_info();
+
+        check_discriminator(&escrow.try_borrow_data()?)?;
+
+        if escrow.data_len() >= Escrow::SPACE {
+            return Ok(());
+        }
+
+        let required = Rent::get()?.minimum_balance(Escrow::SPACE);
+        let shortfall = required.saturating_sub(escrow.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = Transfer {
+                from: self.payer.to_account_info(),
+                to: escrow.clone(),
+            };
+            let cpi_program = self.system_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+            transfer(cpi_ctx, shortfall)?;
+        }
+
+        // New bytes are zero-initialized, so an unmigrated escrow reads as version 0
+        escrow.resize(Escrow::SPACE)?;
+
+        Ok(())
+    }
+
+    /// Rewrite the escrow at the current layout version
+    pub fn upgrade_layout(&mut self) -> Result<()> {
+        let escrow_info = self.escrow.to_account_info();
+        let mut data = escrow_info.try_borrow_mut_data()?;
+        upgrade_data(&mut data)
+    }
+}
+
+/// Validate that `data` starts with the escrow discriminator
+fn check_discriminator(data: &[u8]) -> Result<()> {
+    let len = Escrow::DISCRIMINATOR.len();
+    require!(
+        data.len() >= len && data[..len] == *Escrow::DISCRIMINATOR,
+        EscrowError::InvalidEscrowAccount
+    );
+    Ok(())
+}
+
+/// Stamp the current version onto escrow `data` already grown to `Escrow::SPACE`
+fn upgrade_data(data: &mut [u8]) -> Result<()> {
+    let mut escrow = Escrow::try_deserialize(&mut &data[..])?;
+    require_gt!(
+        Escrow::VERSION,
+        escrow.version,
+        EscrowError::EscrowAlreadyMigrated
+    );
+
+    escrow.version = Escrow::VERSION;
+    escrow.try_serialize(&mut &mut data[..])
+}
+
+/// Handler for the migrate instruction
+pub fn handler(ctx: Context<Migrate>) -> Result<()> {
+    // Realloc to the new layout size and fund the extra rent
+    ctx.accounts.resize_escrow()?;
+
+    // Fill in the new fields and stamp the current version
+    ctx.accounts.upgrade_layout()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::CancelFee;
+
+    /// Size of an escrow made before versioning: the space reserved for an
+    /// 8-byte discriminator, then seed, maker, mint_a, mint_b, receive and bump
+    const V0_SPACE: usize = 8 + 8 + 32 * 3 + 8 + 1;
+
+    fn v0_escrow(maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey) -> Vec<u8> {
+        let mut data = Escrow::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(maker.as_ref());
+        data.extend_from_slice(mint_a.as_ref());
+        data.extend_from_slice(mint_b.as_ref());
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.push(254);
+        data.resize(V0_SPACE, 0);
+        data
+    }
+
+    #[test]
+    fn migrates_v0_layout() {
+        let (maker, mint_a, mint_b) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut data = v0_escrow(&maker, &mint_a, &mint_b);
+        check_discriminator(&data).unwrap();
+
+        // The runtime zero-fills the bytes `resize` adds
+        data.resize(Escrow::SPACE, 0);
+        upgrade_data(&mut data).unwrap();
+
+        let escrow = Escrow::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(escrow.version, Escrow::VERSION);
+        assert_eq!((escrow.seed, escrow.receive, escrow.bump), (7, 500, 254));
+        assert_eq!(
+            (escrow.maker, escrow.mint_a, escrow.mint_b),
+            (maker, mint_a, mint_b)
+        );
+        assert_eq!(escrow.expiry, 0);
+        assert!(escrow.cancel_fee == CancelFee::None);
+        assert_eq!(escrow.memo, [0; 32]);
+    }
+
+    #[test]
+    fn rejects_migrated_escrow() {
+        let mut data = v0_escrow(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        );
+        data.resize(Escrow::SPACE, 0);
+        upgrade_data(&mut data).unwrap();
+        assert_eq!(
+            upgrade_data(&mut data).unwrap_err(),
+            EscrowError::EscrowAlreadyMigrated.into()
+        );
+    }
+
+    #[test]
+    fn rejects_other_discriminators() {
+        let mut data = v0_escrow(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        );
+        data[0] = 2;
+        assert_eq!(
+            check_discriminator(&data).unwrap_err(),
+            EscrowError::InvalidEscrowAccount.into()
+        );
+        assert!(check_discriminator(&[]).is_err());
+    }
+}
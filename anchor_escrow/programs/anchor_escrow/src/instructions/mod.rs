@@ -1,7 +1,21 @@
+pub mod bid;
+pub mod create_auction;
+pub mod initialize_config;
 pub mod make;
+pub mod migrate;
 pub mod refund;
+pub mod settle_auction;
 pub mod take;
+pub mod take_via_amm;
+pub mod update_config;
 
+pub use bid::*;
+pub use create_auction::*;
+pub use initialize_config::*;
 pub use make::*;
+pub use migrate::*;
 pub use refund::*;
+pub use settle_auction::*;
 pub use take::*;
+pub use take_via_amm::*;
+pub use update_config::*;
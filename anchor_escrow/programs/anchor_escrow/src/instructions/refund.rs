@@ -1,10 +1,18 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked},
+    token::{
+        close_account, spl_token::native_mint, transfer_checked, CloseAccount, Mint, Token,
+        TokenAccount, TransferChecked,
+    },
 };
 
-use crate::state::Escrow;
+use crate::{
+    errors::EscrowError,
+    events::EscrowRefunded,
+    state::{CancelFee, Config, Escrow, EscrowStats},
+};
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
@@ -46,9 +54,83 @@ pub struct Refund<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// Program-wide escrow counters (created on first use)
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = EscrowStats::SPACE,
+        seeds = [b"stats"],
+        bump,
+    )]
+    pub stats: Box<Account<'info, EscrowStats>>,
+
+    /// Program config; refunds are allowed even while paused
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    /// Protocol treasury receiving the cancellation fee
+    #[account(mut, address = config.treasury)]
+    pub treasury: SystemAccount<'info>,
+
+    /// Treasury's Token A account, required when the fee is taken in bps of the deposit
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_ata_a: Option<Box<Account<'info, TokenAccount>>>,
 }
 
 impl<'info> Refund<'info> {
+    /// Pay the cancellation fee configured at make time to the treasury
+    pub fn pay_cancel_fee(&mut self) -> Result<()> {
+        match self.escrow.cancel_fee {
+            CancelFee::None => Ok(()),
+            CancelFee::Lamports(lamports) => {
+                let cpi_accounts = Transfer {
+                    from: self.maker.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                };
+                let cpi_program = self.system_program.to_account_info();
+                let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+                transfer(cpi_ctx, lamports)
+            }
+            CancelFee::Bps(bps) => {
+                let fee = (self.vault.amount as u128 * bps as u128 / 10_000) as u64;
+                if fee == 0 {
+                    return Ok(());
+                }
+
+                let treasury_ata_a = self
+                    .treasury_ata_a
+                    .as_ref()
+                    .ok_or(EscrowError::MissingTreasuryAccount)?;
+
+                let signer_seeds: &[&[&[u8]]] = &[&[
+                    b"escrow",
+                    self.maker.key.as_ref(),
+                    &self.escrow.seed.to_le_bytes(),
+                    &[self.escrow.bump],
+                ]];
+
+                // Transfer the fee share of Token A from vault to treasury
+                let cpi_accounts = TransferChecked {
+                    from: self.vault.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: treasury_ata_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                };
+                let cpi_program = self.token_program.to_account_info();
+                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+                transfer_checked(cpi_ctx, fee, self.mint_a.decimals)?;
+                self.vault.reload()
+            }
+        }
+    }
+
     /// Withdraw all Token A from vault back to maker and close the vault
     pub fn refund_and_close_vault(&mut self) -> Result<()> {
         let signer_seeds: &[&[&[u8]]] = &[&[
@@ -81,12 +163,54 @@ impl<'info> Refund<'info> {
 
         close_account(cpi_ctx)
     }
+
+    /// Record the cancellation in the program-wide stats
+    pub fn record_refund(&mut self, bumps: &RefundBumps) {
+        self.stats.refunds = self.stats.refunds.saturating_add(1);
+        self.stats.open_escrows = self.stats.open_escrows.saturating_sub(1);
+        self.stats.bump = bumps.stats;
+    }
+
+    /// Close the maker's wSOL account so the refunded Token A arrives as lamports
+    pub fn unwrap_native_a(&mut self) -> Result<()> {
+        if self.mint_a.key() != native_mint::ID {
+            return Ok(());
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: self.maker_ata_a.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        close_account(cpi_ctx)
+    }
 }
 
 /// Handler for the refund instruction
 pub fn handler(ctx: Context<Refund>) -> Result<()> {
+    // Charge the cancellation fee unless the escrow has already expired
+    if !ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp) {
+        ctx.accounts.pay_cancel_fee()?;
+    }
+
     // Withdraw Token A from vault back to maker and close vault
     ctx.accounts.refund_and_close_vault()?;
 
+    // Unwrap wSOL Token A into lamports for the maker
+    ctx.accounts.unwrap_native_a()?;
+
+    // Update program-wide stats
+    ctx.accounts.record_refund(&ctx.bumps);
+
+    emit!(EscrowRefunded {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        amount: ctx.accounts.vault.amount,
+        memo: ctx.accounts.escrow.memo,
+    });
+
     Ok(())
 }
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked},
+};
+
+use crate::{errors::EscrowError, state::Auction};
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// Anyone may settle an ended auction and pays for missing token accounts
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    /// The maker who created the auction (receives the winning bid and rent)
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Auction account storing the terms and winning bid (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"auction", maker.key().as_ref(), auction.seed.to_le_bytes().as_ref()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// Token A mint
+    pub mint_a: Box<Account<'info, Mint>>,
+
+    /// Token B mint
+    pub mint_b: Box<Account<'info, Mint>>,
+
+    /// Vault holding Token A (owned by auction)
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = auction,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// Bid vault holding the winning Token B bid (owned by auction)
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = auction,
+    )]
+    pub bid_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The winning bidder (the maker if nobody bid); must match the auction's highest bidder
+    #[account(address = auction.highest_bidder @ EscrowError::InvalidBidder)]
+    pub winner: UncheckedAccount<'info>,
+
+    /// Winner's associated token account for Token A (receives Token A)
+    #[account(
+        init_if_needed,
+        payer = settler,
+        associated_token::mint = mint_a,
+        associated_token::authority = winner,
+    )]
+    pub winner_ata_a: Box<Account<'info, TokenAccount>>,
+
+    /// Maker's associated token account for Token B (receives the winning bid)
+    #[account(
+        init_if_needed,
+        payer = settler,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettleAuction<'info> {
+    /// Pay out both vaults and close them, returning rent to the maker
+    pub fn settle_and_close_vaults(&mut self) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            self.maker.key.as_ref(),
+            &self.auction.seed.to_le_bytes(),
+            &[self.auction.bump],
+        ]];
+
+        // Transfer all Token A from vault to the winner
+        let cpi_accounts = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.winner_ata_a.to_account_info(),
+            authority: self.auction.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+
+        // Transfer the winning bid from the bid vault to the maker
+        if self.bid_vault.amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: self.bid_vault.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+                to: self.maker_ata_b.to_account_info(),
+                authority: self.auction.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            transfer_checked(cpi_ctx, self.bid_vault.amount, self.mint_b.decimals)?;
+        }
+
+        // Close both vaults and return rent to maker
+        for account in [self.vault.to_account_info(), self.bid_vault.to_account_info()] {
+            let cpi_accounts = CloseAccount {
+                account,
+                destination: self.maker.to_account_info(),
+                authority: self.auction.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            close_account(cpi_ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Handler for the settle_auction instruction
+pub fn handler(ctx: Context<SettleAuction>) -> Result<()> {
+    // Validate that bidding has closed
+    require_gte!(
+        Clock::get()?.unix_timestamp,
+        ctx.accounts.auction.end_time,
+        EscrowError::AuctionNotEnded
+    );
+
+    // Hand Token A to the winner, the bid to the maker, and close the vaults
+    ctx.accounts.settle_and_close_vaults()?;
+
+    Ok(())
+}
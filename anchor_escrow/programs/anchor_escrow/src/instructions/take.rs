@@ -1,14 +1,21 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked},
+    token::{
+        close_account, spl_token::native_mint, sync_native, transfer_checked, CloseAccount, Mint,
+        SyncNative, Token, TokenAccount, TransferChecked,
+    },
 };
 
-use crate::state::Escrow;
+use crate::{
+    events::EscrowTaken,
+    state::{Config, Escrow, EscrowStats, PairStats},
+};
 
 #[derive(Accounts)]
 pub struct Take<'info> {
-    /// The taker who accepts the exchange terms
+    /// The taker who accepts the exchange terms. May be a delegate of
+    /// `taker_ata_b` (e.g. a session key) rather than its owner.
     #[account(mut)]
     pub taker: Signer<'info>,
 
@@ -51,11 +58,13 @@ pub struct Take<'info> {
     )]
     pub taker_ata_a: Box<Account<'info, TokenAccount>>,
 
-    /// Taker's associated token account for Token B (source of Token B)
+    /// Token B account owned by, or delegated to, the taker (source of Token B)
     #[account(
         mut,
-        associated_token::mint = mint_b,
-        associated_token::authority = taker,
+        token::mint = mint_b,
+        constraint = taker_ata_b.owner == taker.key()
+            || taker_ata_b.delegate == COption::Some(taker.key())
+            @ crate::errors::EscrowError::InvalidTokenAuthority,
     )]
     pub taker_ata_b: Box<Account<'info, TokenAccount>>,
 
@@ -71,10 +80,55 @@ pub struct Take<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    /// Program-wide escrow counters (created on first use)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = EscrowStats::SPACE,
+        seeds = [b"stats"],
+        bump,
+    )]
+    pub stats: Box<Account<'info, EscrowStats>>,
+
+    /// Cumulative volume for this mint pair (created on first fill)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = PairStats::SPACE,
+        seeds = [b"pair_stats", PairStats::pair_hash(&mint_a.key(), &mint_b.key()).as_ref()],
+        bump,
+    )]
+    pub pair_stats: Box<Account<'info, PairStats>>,
+
+    /// Program config; fills are rejected while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ crate::errors::EscrowError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
 }
 
 impl<'info> Take<'info> {
-    /// Transfer Token B from taker to maker
+    /// Sync the taker's wSOL account so lamports sent to it count as Token B
+    pub fn sync_native_b(&mut self) -> Result<()> {
+        if self.mint_b.key() != native_mint::ID {
+            return Ok(());
+        }
+
+        let cpi_accounts = SyncNative {
+            account: self.taker_ata_b.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        sync_native(cpi_ctx)?;
+        self.taker_ata_b.reload()
+    }
+
+    /// Transfer Token B from taker to maker. The token program checks the
+    /// delegated allowance when the taker signs as delegate.
     pub fn transfer_to_maker(&mut self) -> Result<()> {
         let cpi_accounts = TransferChecked {
             from: self.taker_ata_b.to_account_info(),
@@ -120,15 +174,74 @@ impl<'info> Take<'info> {
 
         close_account(cpi_ctx)
     }
+
+    /// Record the fill in the program-wide and per-pair stats
+    pub fn record_fill(&mut self, bumps: &TakeBumps) {
+        self.stats.fills = self.stats.fills.saturating_add(1);
+        self.stats.open_escrows = self.stats.open_escrows.saturating_sub(1);
+        self.stats.bump = bumps.stats;
+
+        self.pair_stats.mint_a = self.mint_a.key();
+        self.pair_stats.mint_b = self.mint_b.key();
+        self.pair_stats.fills = self.pair_stats.fills.saturating_add(1);
+        self.pair_stats.volume_a = self.pair_stats.volume_a.saturating_add(self.vault.amount as u128);
+        self.pair_stats.volume_b = self
+            .pair_stats
+            .volume_b
+            .saturating_add(self.escrow.receive as u128);
+        self.pair_stats.bump = bumps.pair_stats;
+    }
+
+    /// Close the taker's wSOL account so the received Token A arrives as lamports
+    pub fn unwrap_native_a(&mut self) -> Result<()> {
+        if self.mint_a.key() != native_mint::ID {
+            return Ok(());
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: self.taker_ata_a.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        close_account(cpi_ctx)
+    }
 }
 
 /// Handler for the take instruction
 pub fn handler(ctx: Context<Take>) -> Result<()> {
+    // Validate that the escrow has not expired
+    require!(
+        !ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp),
+        crate::errors::EscrowError::EscrowExpired
+    );
+
+    // Pick up lamports the taker wrapped into a wSOL Token B account
+    ctx.accounts.sync_native_b()?;
+
     // First, transfer Token B from taker to maker
     ctx.accounts.transfer_to_maker()?;
 
     // Then, withdraw Token A from vault to taker and close vault
     ctx.accounts.withdraw_and_close_vault()?;
 
+    // Unwrap wSOL Token A into lamports for the taker. The maker's wSOL Token B
+    // account stays wrapped since closing it would need the maker's signature.
+    ctx.accounts.unwrap_native_a()?;
+
+    // Update program-wide and per-pair stats
+    ctx.accounts.record_fill(&ctx.bumps);
+
+    emit!(EscrowTaken {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        taker: ctx.accounts.taker.key(),
+        amount: ctx.accounts.vault.amount,
+        receive: ctx.accounts.escrow.receive,
+        memo: ctx.accounts.escrow.memo,
+    });
+
     Ok(())
 }
@@ -0,0 +1,336 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    errors::EscrowError,
+    events::EscrowTaken,
+    state::{Config, Escrow, EscrowStats, PairStats},
+};
+use solana_challenge_common::layouts::amm_config;
+
+/// Discriminator of the AMM `Swap` instruction
+const AMM_SWAP_DISCRIMINATOR: u8 = 3;
+
+#[derive(Accounts)]
+pub struct TakeViaAmm<'info> {
+    /// The taker who routes the fill through the AMM and keeps any surplus
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    /// The original maker who created the escrow
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    /// Escrow account storing exchange terms (will be closed)
+    #[account(
+        mut,
+        close = maker,
+        has_one = maker,
+        has_one = mint_a,
+        has_one = mint_b,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// Token A mint
+    pub mint_a: Box<Account<'info, Mint>>,
+
+    /// Token B mint
+    pub mint_b: Box<Account<'info, Mint>>,
+
+    /// Vault holding Token A (owned by escrow), sold into the pool
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// Escrow's Token B account receiving the swap output (closed afterwards)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = escrow,
+    )]
+    pub escrow_ata_b: Box<Account<'info, TokenAccount>>,
+
+    /// Taker's associated token account for Token B (receives the surplus)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = taker,
+    )]
+    pub taker_ata_b: Box<Account<'info, TokenAccount>>,
+
+    /// Maker's associated token account for Token B (receives Token B)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = maker,
+    )]
+    pub maker_ata_b: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: AMM pool config for mint_a/mint_b; mints are validated in `swap_vault`
+    #[account(owner = config.amm_program @ EscrowError::InvalidAmmPool)]
+    pub amm_config: UncheckedAccount<'info>,
+
+    /// CHECK: AMM Token X vault, validated by the AMM program
+    #[account(mut)]
+    pub amm_vault_x: UncheckedAccount<'info>,
+
+    /// CHECK: AMM Token Y vault, validated by the AMM program
+    #[account(mut)]
+    pub amm_vault_y: UncheckedAccount<'info>,
+
+    /// CHECK: blueshift_native_amm program, as recorded in the config
+    #[account(
+        executable,
+        address = config.amm_program @ EscrowError::InvalidAmmProgram,
+    )]
+    pub amm_program: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Program-wide escrow counters (created on first use)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = EscrowStats::SPACE,
+        seeds = [b"stats"],
+        bump,
+    )]
+    pub stats: Box<Account<'info, EscrowStats>>,
+
+    /// Cumulative volume for this mint pair (created on first fill)
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = PairStats::SPACE,
+        seeds = [b"pair_stats", PairStats::pair_hash(&mint_a.key(), &mint_b.key()).as_ref()],
+        bump,
+    )]
+    pub pair_stats: Box<Account<'info, PairStats>>,
+
+    /// Program config; fills are rejected while paused
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ EscrowError::ProgramPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+impl<'info> TakeViaAmm<'info> {
+    /// Sell the whole vault through the AMM, requiring at least `receive` Token B out
+    pub fn swap_vault(&mut self) -> Result<()> {
+        // Work out the swap direction from the pool's mints
+        let is_x = {
+            let data = self.amm_config.try_borrow_data()?;
+            // Pools still on an older layout have to be migrated first
+            require_eq!(data.len(), amm_config::LEN, EscrowError::InvalidAmmPool);
+
+            let mint_x = &data[amm_config::MINT_X..amm_config::MINT_X + 32];
+            let mint_y = &data[amm_config::MINT_Y..amm_config::MINT_Y + 32];
+            let (mint_a, mint_b) = (self.mint_a.key(), self.mint_b.key());
+
+            if mint_x == mint_a.as_ref() && mint_y == mint_b.as_ref() {
+                true
+            } else if mint_x == mint_b.as_ref() && mint_y == mint_a.as_ref() {
+                false
+            } else {
+                return err!(EscrowError::InvalidAmmPool);
+            }
+        };
+
+        // The escrow PDA acts as the swapping user: vault is its Token A side,
+        // escrow_ata_b its Token B side
+        let (user_x_ata, user_y_ata) = match is_x {
+            true => (self.vault.to_account_info(), self.escrow_ata_b.to_account_info()),
+            false => (self.escrow_ata_b.to_account_info(), self.vault.to_account_info()),
+        };
+        let (mint_x, mint_y) = match is_x {
+            true => (self.mint_a.to_account_info(), self.mint_b.to_account_info()),
+            false => (self.mint_b.to_account_info(), self.mint_a.to_account_info()),
+        };
+
+        // Swap data: is_x (1) + amount (8) + min (8) + expiration (8)
+        let mut data = Vec::with_capacity(26);
+        data.push(AMM_SWAP_DISCRIMINATOR);
+        data.push(is_x as u8);
+        data.extend_from_slice(&self.vault.amount.to_le_bytes());
+        data.extend_from_slice(&self.escrow.receive.to_le_bytes());
+        data.extend_from_slice(&i64::MAX.to_le_bytes());
+
+        let ix = Instruction {
+            program_id: self.amm_program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(self.escrow.key(), true),
+                AccountMeta::new(user_x_ata.key(), false),
+                AccountMeta::new(user_y_ata.key(), false),
+                AccountMeta::new(self.amm_vault_x.key(), false),
+                AccountMeta::new(self.amm_vault_y.key(), false),
+                AccountMeta::new_readonly(self.amm_config.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+                AccountMeta::new_readonly(mint_x.key(), false),
+                AccountMeta::new_readonly(mint_y.key(), false),
+                // The AMM's own ID in the referrer slot: no referrer
+                AccountMeta::new_readonly(self.amm_program.key(), false),
+            ],
+            data,
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes(),
+            &[self.escrow.bump],
+        ]];
+
+        invoke_signed(
+            &ix,
+            &[
+                self.escrow.to_account_info(),
+                user_x_ata,
+                user_y_ata,
+                self.amm_vault_x.to_account_info(),
+                self.amm_vault_y.to_account_info(),
+                self.amm_config.to_account_info(),
+                self.token_program.to_account_info(),
+                mint_x,
+                mint_y,
+                self.amm_program.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        // Don't trust the pool: check what actually arrived
+        self.escrow_ata_b.reload()?;
+        require_gte!(
+            self.escrow_ata_b.amount,
+            self.escrow.receive,
+            EscrowError::InsufficientSwapOutput
+        );
+
+        Ok(())
+    }
+
+    /// Pay the maker `receive`, hand the surplus to the taker and close the escrow's token accounts
+    pub fn settle_and_close(&mut self) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            self.maker.key.as_ref(),
+            &self.escrow.seed.to_le_bytes(),
+            &[self.escrow.bump],
+        ]];
+
+        // Transfer the requested Token B to the maker
+        let cpi_accounts = TransferChecked {
+            from: self.escrow_ata_b.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_ata_b.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(cpi_ctx, self.escrow.receive, self.mint_b.decimals)?;
+
+        // Transfer any surplus Token B to the taker
+        let surplus = self.escrow_ata_b.amount - self.escrow.receive;
+        if surplus > 0 {
+            let cpi_accounts = TransferChecked {
+                from: self.escrow_ata_b.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+                to: self.taker_ata_b.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+            transfer_checked(cpi_ctx, surplus, self.mint_b.decimals)?;
+        }
+
+        // Close the escrow's Token B account and return rent to the taker who funded it
+        let cpi_accounts = CloseAccount {
+            account: self.escrow_ata_b.to_account_info(),
+            destination: self.taker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        close_account(cpi_ctx)?;
+
+        // Close the emptied vault and return rent to maker
+        let cpi_accounts = CloseAccount {
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        close_account(cpi_ctx)
+    }
+
+    /// Record the fill in the program-wide and per-pair stats
+    pub fn record_fill(&mut self, amount_a: u64, bumps: &TakeViaAmmBumps) {
+        self.stats.fills = self.stats.fills.saturating_add(1);
+        self.stats.open_escrows = self.stats.open_escrows.saturating_sub(1);
+        self.stats.bump = bumps.stats;
+
+        self.pair_stats.mint_a = self.mint_a.key();
+        self.pair_stats.mint_b = self.mint_b.key();
+        self.pair_stats.fills = self.pair_stats.fills.saturating_add(1);
+        self.pair_stats.volume_a = self.pair_stats.volume_a.saturating_add(amount_a as u128);
+        self.pair_stats.volume_b = self
+            .pair_stats
+            .volume_b
+            .saturating_add(self.escrow.receive as u128);
+        self.pair_stats.bump = bumps.pair_stats;
+    }
+}
+
+/// Handler for the take_via_amm instruction
+pub fn handler(ctx: Context<TakeViaAmm>) -> Result<()> {
+    // Validate that the escrow has not expired
+    require!(
+        !ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp),
+        EscrowError::EscrowExpired
+    );
+
+    let amount_a = ctx.accounts.vault.amount;
+
+    // Swap the vault's Token A into Token B through the pool
+    ctx.accounts.swap_vault()?;
+
+    // Pay the maker, keep the surplus for the taker, close token accounts
+    ctx.accounts.settle_and_close()?;
+
+    // Update program-wide and per-pair stats
+    ctx.accounts.record_fill(amount_a, &ctx.bumps);
+
+    emit!(EscrowTaken {
+        escrow: ctx.accounts.escrow.key(),
+        maker: ctx.accounts.maker.key(),
+        taker: ctx.accounts.taker.key(),
+        amount: amount_a,
+        receive: ctx.accounts.escrow.receive,
+        memo: ctx.accounts.escrow.memo,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::EscrowError, state::Config};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The config authority
+    pub authority: Signer<'info>,
+
+    /// Program-wide config account
+    #[account(
+        mut,
+        has_one = authority @ EscrowError::InvalidAuthority,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Handler for the update_config instruction
+pub fn handler(
+    ctx: Context<UpdateConfig>,
+    new_authority: Pubkey,
+    treasury: Pubkey,
+    max_cancel_fee_bps: u16,
+    paused: bool,
+    amm_program: Pubkey,
+    max_cancel_fee_lamports: u64,
+) -> Result<()> {
+    // Validate that the fee cap is at most 100%
+    require_gte!(10_000, max_cancel_fee_bps, EscrowError::InvalidMaxCancelFee);
+
+    let config = &mut ctx.accounts.config;
+    config.authority = new_authority;
+    config.treasury = treasury;
+    config.max_cancel_fee_bps = max_cancel_fee_bps;
+    config.paused = paused;
+    config.amm_program = amm_program;
+    config.max_cancel_fee_lamports = max_cancel_fee_lamports;
+
+    Ok(())
+}
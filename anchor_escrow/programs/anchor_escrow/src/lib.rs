@@ -1,10 +1,22 @@
+//! Escrow program for exchanging two SPL tokens.
+//!
+//! Other Anchor programs can compose with it by enabling the `cpi` feature,
+//! which exposes typed `cpi::{make, take, refund, ...}` builders with their
+//! `cpi::accounts` structs. PDA helpers live on the account types in [`state`].
+
+// `make` takes more arguments than clippy allows, and the lint also fires on
+// the CPI wrappers Anchor generates for it
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 
-mod errors;
+pub mod errors;
+pub mod events;
 mod instructions;
-mod state;
+pub mod state;
 
 use instructions::*;
+use state::CancelFee;
 
 declare_id!("22222222222222222222222222222222222222222222");
 
@@ -14,8 +26,26 @@ pub mod anchor_escrow {
 
     /// Create a new escrow: maker deposits Token A and sets exchange terms
     #[instruction(discriminator = 0)]
-    pub fn make(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
-        instructions::make::handler(ctx, seed, receive, amount)
+    pub fn make(
+        ctx: Context<Make>,
+        seed: u64,
+        receive: u64,
+        amount: u64,
+        reject_freezable: bool,
+        expiry: i64,
+        cancel_fee: CancelFee,
+        memo: [u8; 32],
+    ) -> Result<()> {
+        instructions::make::handler(
+            ctx,
+            seed,
+            receive,
+            amount,
+            reject_freezable,
+            expiry,
+            cancel_fee,
+            memo,
+        )
     }
 
     /// Accept the escrow: taker sends Token B, receives Token A
@@ -29,4 +59,81 @@ pub mod anchor_escrow {
     pub fn refund(ctx: Context<Refund>) -> Result<()> {
         instructions::refund::handler(ctx)
     }
+
+    /// Migrate an escrow created under an older layout to the current version
+    #[instruction(discriminator = 3)]
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        instructions::migrate::handler(ctx)
+    }
+
+    /// Fill the escrow by swapping its Token A through a blueshift_native_amm pool
+    #[instruction(discriminator = 4)]
+    pub fn take_via_amm(ctx: Context<TakeViaAmm>) -> Result<()> {
+        instructions::take_via_amm::handler(ctx)
+    }
+
+    /// Start an English auction: maker deposits Token A to be sold for Token B bids
+    #[instruction(discriminator = 5)]
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        seed: u64,
+        amount: u64,
+        reserve: u64,
+        min_increment: u64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::create_auction::handler(ctx, seed, amount, reserve, min_increment, end_time)
+    }
+
+    /// Place a higher bid in Token B, refunding the bidder being outbid
+    #[instruction(discriminator = 6)]
+    pub fn bid(ctx: Context<Bid>, amount: u64) -> Result<()> {
+        instructions::bid::handler(ctx, amount)
+    }
+
+    /// Settle an ended auction: winner receives Token A, maker receives the bid
+    #[instruction(discriminator = 7)]
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        instructions::settle_auction::handler(ctx)
+    }
+
+    /// Create the program config; only the program's upgrade authority may call this
+    #[instruction(discriminator = 8)]
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury: Pubkey,
+        max_cancel_fee_bps: u16,
+        amm_program: Pubkey,
+        max_cancel_fee_lamports: u64,
+    ) -> Result<()> {
+        instructions::initialize_config::handler(
+            ctx,
+            treasury,
+            max_cancel_fee_bps,
+            amm_program,
+            max_cancel_fee_lamports,
+        )
+    }
+
+    /// Update the program config, including the emergency pause flag
+    #[instruction(discriminator = 9)]
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_authority: Pubkey,
+        treasury: Pubkey,
+        max_cancel_fee_bps: u16,
+        paused: bool,
+        amm_program: Pubkey,
+        max_cancel_fee_lamports: u64,
+    ) -> Result<()> {
+        instructions::update_config::handler(
+            ctx,
+            new_authority,
+            treasury,
+            max_cancel_fee_bps,
+            paused,
+            amm_program,
+            max_cancel_fee_lamports,
+        )
+    }
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
 
 /// Escrow account that stores all the exchange terms
 #[account(discriminator = 1)]
@@ -16,4 +17,181 @@ pub struct Escrow {
     pub receive: u64,
     /// Bump seed for PDA derivation (cached for efficiency)
     pub bump: u8,
+    /// Layout version (0 = pre-versioning account awaiting `migrate`)
+    pub version: u8,
+    /// Unix timestamp after which the escrow can no longer be taken (0 = never expires)
+    pub expiry: i64,
+    /// Fee owed to the treasury when the maker refunds before expiry
+    pub cancel_fee: CancelFee,
+    /// Maker-supplied reference correlating the escrow with an off-chain deal
+    pub memo: [u8; 32],
+}
+
+impl Escrow {
+    /// Layout version written by `make` and `migrate`
+    pub const VERSION: u8 = 3;
+
+    /// Total account size including the 8-byte discriminator
+    pub const SPACE: usize = 8 + Escrow::INIT_SPACE;
+
+    /// Derive the escrow PDA for a maker and seed
+    pub fn find_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), seed.to_le_bytes().as_ref()],
+            &crate::ID,
+        )
+    }
+
+    /// Whether the escrow has passed its expiry at the given time
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry != 0 && now >= self.expiry
+    }
+}
+
+/// Maker cancellation fee, configured at make time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CancelFee {
+    /// No fee (also what migrated pre-fee escrows decode to)
+    None,
+    /// Flat fee in lamports, paid by the maker
+    Lamports(u64),
+    /// Basis points of the deposited Token A, taken from the vault
+    Bps(u16),
+}
+
+/// Program-wide escrow activity counters
+#[account(discriminator = 2)]
+#[derive(InitSpace)]
+pub struct EscrowStats {
+    /// Number of escrows filled through `take`
+    pub fills: u64,
+    /// Number of escrows cancelled through `refund`
+    pub refunds: u64,
+    /// Number of escrows currently open
+    pub open_escrows: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl EscrowStats {
+    /// Total account size including the 8-byte discriminator
+    pub const SPACE: usize = 8 + EscrowStats::INIT_SPACE;
+
+    /// Derive the program-wide stats PDA
+    pub fn find_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"stats"], &crate::ID)
+    }
+}
+
+/// Cumulative fill volume for a single (mint_a, mint_b) pair
+#[account(discriminator = 3)]
+#[derive(InitSpace)]
+pub struct PairStats {
+    /// Token A mint of the pair
+    pub mint_a: Pubkey,
+    /// Token B mint of the pair
+    pub mint_b: Pubkey,
+    /// Number of escrows filled for this pair
+    pub fills: u64,
+    /// Total Token A released to takers
+    pub volume_a: u128,
+    /// Total Token B paid to makers
+    pub volume_b: u128,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl PairStats {
+    /// Total account size including the 8-byte discriminator
+    pub const SPACE: usize = 8 + PairStats::INIT_SPACE;
+
+    /// Derive the stats PDA for a mint pair
+    pub fn find_address(mint_a: &Pubkey, mint_b: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"pair_stats", Self::pair_hash(mint_a, mint_b).as_ref()],
+            &crate::ID,
+        )
+    }
+
+    /// Hash of the ordered mint pair, used as the PDA seed
+    pub fn pair_hash(mint_a: &Pubkey, mint_b: &Pubkey) -> [u8; 32] {
+        hashv(&[mint_a.as_ref(), mint_b.as_ref()]).to_bytes()
+    }
+}
+
+/// English auction selling a Token A deposit to the highest Token B bidder
+#[account(discriminator = 4)]
+#[derive(InitSpace)]
+pub struct Auction {
+    /// Seed used for PDA derivation
+    pub seed: u64,
+    /// The maker's wallet address (seller of Token A)
+    pub maker: Pubkey,
+    /// Token A mint address (the token being auctioned)
+    pub mint_a: Pubkey,
+    /// Token B mint address (the token bids are made in)
+    pub mint_b: Pubkey,
+    /// Minimum acceptable first bid in Token B
+    pub reserve: u64,
+    /// Minimum amount a new bid must exceed the current one by
+    pub min_increment: u64,
+    /// Unix timestamp after which no bids are accepted and the auction can settle
+    pub end_time: i64,
+    /// Current highest bidder (the maker while there are no bids)
+    pub highest_bidder: Pubkey,
+    /// Current highest bid escrowed in the bid vault
+    pub highest_bid: u64,
+    /// Bump seed for PDA derivation (cached for efficiency)
+    pub bump: u8,
+}
+
+impl Auction {
+    /// Total account size including the 8-byte discriminator
+    pub const SPACE: usize = 8 + Auction::INIT_SPACE;
+
+    /// Derive the auction PDA for a maker and seed
+    pub fn find_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"auction", maker.as_ref(), seed.to_le_bytes().as_ref()],
+            &crate::ID,
+        )
+    }
+
+    /// Smallest bid that would currently be accepted
+    pub fn min_bid(&self) -> Option<u64> {
+        match self.highest_bid {
+            0 => Some(self.reserve),
+            bid => bid.checked_add(self.min_increment),
+        }
+    }
+}
+
+/// Program-wide admin configuration
+#[account(discriminator = 5)]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Admin allowed to update the config and pause the program
+    pub authority: Pubkey,
+    /// Protocol treasury receiving maker cancellation fees
+    pub treasury: Pubkey,
+    /// Highest cancellation fee in basis points a maker may configure
+    pub max_cancel_fee_bps: u16,
+    /// Emergency stop: blocks make/take (refunds always work)
+    pub paused: bool,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+    /// AMM program `take_via_amm` routes fills through
+    pub amm_program: Pubkey,
+    /// Highest flat cancellation fee in lamports a maker may configure
+    pub max_cancel_fee_lamports: u64,
+}
+
+impl Config {
+    /// Total account size including the 8-byte discriminator
+    pub const SPACE: usize = 8 + Config::INIT_SPACE;
+
+    /// Derive the program config PDA
+    pub fn find_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"config"], &crate::ID)
+    }
 }
@@ -0,0 +1,659 @@
+//! blueshift_native_amm, one flow per group of instructions, each on a fresh pool
+//!
+//! Most instructions have an SDK builder; the rest are packed here the way
+//! the program reads them: discriminator, then the fields little-endian.
+
+use blueshift_client::amm::{self, address, instruction, pubkey, PoolAddresses};
+use blueshift_native_amm::{
+    AcceptAuthority, BatchSwap, ClaimFees, ClaimRewards, Config, CreateGauge, Donate, FlashLoan,
+    FlashRepay, FundGauge, ProposeAuthority, Quote, ReadOracle, Route, SetFeeRecipient, SetOracle,
+    SetPriceImpactLimit, SetState, Skim, Stake, Sync, Unstake, UpdateFee,
+};
+use solana_instruction::{AccountMeta, BorrowedAccountMeta, BorrowedInstruction, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    ledger::{Ledger, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    Measurements,
+};
+
+const SEED: u64 = 1;
+/// Swap fee of the pool and both protocol fee bounds, in basis points
+const FEE: u16 = 30;
+/// Protocol share of the swap fee in basis points, so `ClaimFees` has
+/// something to pay out
+const PROTOCOL_FEE: u16 = 2_000;
+/// X and Y the authority first deposits, for as much LP
+const RESERVE: u64 = 1_000_000_000;
+/// What each measured instruction moves
+const AMOUNT: u64 = 1_000_000;
+/// `AmmState::Disabled`
+const DISABLED: u8 = 2;
+/// Seconds a lock or reward stream runs for
+const DURATION: i64 = 3_600;
+
+const SYSVAR_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("Sysvar1111111111111111111111111111111111111");
+
+/// A pool of two new mints with `authority` in charge of it and of the
+/// protocol config, holding X, Y and LP tokens
+struct Pool {
+    ledger: Ledger,
+    authority: Pubkey,
+    addresses: PoolAddresses,
+}
+
+impl Pool {
+    /// Create the protocol config and the pool; returns what both cost
+    fn new(elf: &[u8]) -> (Self, [u64; 2]) {
+        let mut ledger = Ledger::new(elf);
+        let authority = ledger.wallet();
+
+        // Pools take their mints in canonical order
+        let (mint_a, mint_b) = (ledger.mint(), ledger.mint());
+        let (mint_x, mint_y) = match mint_a < mint_b {
+            true => (mint_a, mint_b),
+            false => (mint_b, mint_a),
+        };
+        let addresses = PoolAddresses::find(
+            SEED,
+            address(&mint_x),
+            address(&mint_y),
+            address(&TOKEN_PROGRAM_ID),
+        );
+        let config = pubkey(&addresses.config);
+        ledger.token_account(pubkey(&addresses.vault_x), &mint_x, &config, 0);
+        ledger.token_account(pubkey(&addresses.vault_y), &mint_y, &config, 0);
+
+        let owner = address(&authority);
+        let ix = amm::initialize_protocol_ix(owner, FEE, FEE, PROTOCOL_FEE);
+        let initialize_protocol = ledger.run("initialize_protocol", &instruction(ix));
+        let ix = amm::initialize_ix(owner, &addresses, SEED, FEE, Some(owner));
+        let initialize = ledger.run("initialize", &instruction(ix));
+
+        ledger.fund(&authority, &mint_x, 4 * RESERVE);
+        ledger.fund(&authority, &mint_y, 4 * RESERVE);
+        ledger.fund(&authority, &pubkey(&addresses.mint_lp), 0);
+
+        let pool = Self {
+            ledger,
+            authority,
+            addresses,
+        };
+        (pool, [initialize_protocol, initialize])
+    }
+
+    /// A pool the authority has deposited `RESERVE` of each token into
+    fn funded(elf: &[u8]) -> Self {
+        let mut pool = Self::new(elf).0;
+        pool.deposit();
+        pool
+    }
+
+    /// Deposit `RESERVE` of each token for as much LP; returns what it cost
+    fn deposit(&mut self) -> u64 {
+        let ix = amm::deposit_ix(
+            self.owner(),
+            &self.addresses,
+            RESERVE,
+            RESERVE,
+            RESERVE,
+            i64::MAX,
+        );
+        self.ledger.run("deposit", &instruction(ix))
+    }
+
+    fn owner(&self) -> amm::Address {
+        address(&self.authority)
+    }
+
+    fn config(&self) -> Pubkey {
+        pubkey(&self.addresses.config)
+    }
+
+    fn vaults(&self) -> [Pubkey; 2] {
+        [
+            pubkey(&self.addresses.vault_x),
+            pubkey(&self.addresses.vault_y),
+        ]
+    }
+
+    fn mints(&self) -> [Pubkey; 3] {
+        [
+            pubkey(&self.addresses.mint_x),
+            pubkey(&self.addresses.mint_y),
+            pubkey(&self.addresses.mint_lp),
+        ]
+    }
+
+    /// The authority's X, Y and LP token accounts
+    fn atas(&self) -> [Pubkey; 3] {
+        self.addresses
+            .user_atas(&self.owner())
+            .map(|ata| pubkey(&ata))
+    }
+
+    fn run(&mut self, name: &str, ix: &Instruction) -> u64 {
+        self.ledger.run(name, ix)
+    }
+
+    fn run_amm(&mut self, name: &str, ix: amm::Instruction) -> u64 {
+        self.ledger.run(name, &instruction(ix))
+    }
+
+    /// An instruction `authority` signs for with the pool's config
+    fn admin_ix(&self, discriminator: u8, data: &[u8]) -> Instruction {
+        amm_ix(
+            discriminator,
+            data,
+            vec![
+                AccountMeta::new_readonly(self.authority, true),
+                AccountMeta::new(self.config(), false),
+            ],
+        )
+    }
+
+    /// `ClaimFees` and `Skim`: pay the authority out of the vaults
+    fn payout_ix(&self, discriminator: u8) -> Instruction {
+        let [vault_x, vault_y] = self.vaults();
+        let [mint_x, mint_y, _] = self.mints();
+        let [ata_x, ata_y, _] = self.atas();
+        amm_ix(
+            discriminator,
+            &[],
+            vec![
+                AccountMeta::new_readonly(self.authority, true),
+                AccountMeta::new(self.config(), false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new(ata_x, false),
+                AccountMeta::new(ata_y, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+            ],
+        )
+    }
+
+    /// `Sync` and `FlashRepay`: the config and its vaults
+    fn vaults_ix(&self, discriminator: u8) -> Instruction {
+        let [vault_x, vault_y] = self.vaults();
+        amm_ix(
+            discriminator,
+            &[],
+            vec![
+                AccountMeta::new(self.config(), false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+        )
+    }
+
+    /// `Route` and `BatchSwap`: the authority's token accounts against the
+    /// pool, once per hop or slice
+    fn legs_ix(&self, discriminator: u8, data: &[u8], legs: usize) -> Instruction {
+        let [vault_x, vault_y] = self.vaults();
+        let [mint_x, mint_y, _] = self.mints();
+        let [ata_x, ata_y, _] = self.atas();
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        for _ in 0..legs {
+            accounts.extend([
+                AccountMeta::new(self.config(), false),
+                AccountMeta::new(vault_x, false),
+                AccountMeta::new(vault_y, false),
+                AccountMeta::new_readonly(mint_x, false),
+                AccountMeta::new_readonly(mint_y, false),
+                AccountMeta::new(ata_x, false),
+                AccountMeta::new(ata_y, false),
+            ]);
+        }
+        amm_ix(discriminator, data, accounts)
+    }
+}
+
+fn amm_ix(discriminator: u8, data: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut packed = Vec::with_capacity(1 + data.len());
+    packed.push(discriminator);
+    packed.extend_from_slice(data);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: packed,
+    }
+}
+
+/// Concatenate instruction data fields
+fn fields(fields: &[&[u8]]) -> Vec<u8> {
+    fields.concat()
+}
+
+pub fn measure(elf: &[u8]) -> Measurements {
+    let mut measurements = liquidity(elf);
+    measurements.extend(swaps(elf));
+    measurements.extend(admin(elf));
+    measurements.extend(locks(elf));
+    measurements.extend(gauge(elf));
+    measurements.extend(flash(elf));
+    measurements.extend(lifecycle(elf));
+    measurements
+}
+
+/// Creating the pool, then every way in and out of it
+fn liquidity(elf: &[u8]) -> Measurements {
+    let (mut pool, [initialize_protocol, initialize]) = Pool::new(elf);
+    let deposit = pool.deposit();
+    let owner = pool.owner();
+    let p = pool.addresses.clone();
+
+    let ix = amm::deposit_exact_ix(owner, &p, AMOUNT, AMOUNT, 1, i64::MAX);
+    let deposit_exact = pool.run_amm("deposit_exact", ix);
+    let ix = amm::deposit_with_slippage_ix(owner, &p, AMOUNT, AMOUNT, AMOUNT, 100, i64::MAX);
+    let deposit_with_slippage = pool.run_amm("deposit_with_slippage", ix);
+    let ix = amm::deposit_single_ix(owner, &p, true, AMOUNT, 1, i64::MAX, None);
+    let deposit_single = pool.run_amm("deposit_single", ix);
+
+    let ix = amm::withdraw_ix(owner, &p, AMOUNT, 1, 1, i64::MAX);
+    let withdraw = pool.run_amm("withdraw", ix);
+    let ix = amm::withdraw_with_slippage_ix(owner, &p, AMOUNT, AMOUNT, AMOUNT, 100, i64::MAX);
+    let withdraw_with_slippage = pool.run_amm("withdraw_with_slippage", ix);
+    let ix = amm::withdraw_single_ix(owner, &p, false, AMOUNT, 1, i64::MAX, None);
+    let withdraw_single = pool.run_amm("withdraw_single", ix);
+
+    // Donate: [donor, vault_x, vault_y, donor_x_ata, donor_y_ata, config,
+    // token_program, mint_x, mint_y]; amount_x, max_y
+    let [vault_x, vault_y] = pool.vaults();
+    let [mint_x, mint_y, _] = pool.mints();
+    let [ata_x, ata_y, _] = pool.atas();
+    let ix = amm_ix(
+        *Donate::DISCRIMINATOR,
+        &fields(&[&AMOUNT.to_le_bytes(), &u64::MAX.to_le_bytes()]),
+        vec![
+            AccountMeta::new_readonly(pool.authority, true),
+            AccountMeta::new(vault_x, false),
+            AccountMeta::new(vault_y, false),
+            AccountMeta::new(ata_x, false),
+            AccountMeta::new(ata_y, false),
+            AccountMeta::new(pool.config(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(mint_y, false),
+        ],
+    );
+    let donate = pool.run("donate", &ix);
+
+    vec![
+        ("initialize_protocol", initialize_protocol),
+        ("initialize", initialize),
+        ("deposit", deposit),
+        ("deposit_exact", deposit_exact),
+        ("deposit_with_slippage", deposit_with_slippage),
+        ("deposit_single", deposit_single),
+        ("withdraw", withdraw),
+        ("withdraw_with_slippage", withdraw_with_slippage),
+        ("withdraw_single", withdraw_single),
+        ("donate", donate),
+    ]
+}
+
+/// Trading against the pool, and settling what trading leaves in the vaults
+fn swaps(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let owner = pool.owner();
+    let p = pool.addresses.clone();
+
+    let ix = amm::swap_ix(owner, &p, true, AMOUNT, 1, i64::MAX, None, None);
+    let swap = pool.run_amm("swap", ix);
+
+    // BatchSwap: min_out, expiration, then is_x and amount per slice
+    let slice = fields(&[&[1], &AMOUNT.to_le_bytes()]);
+    let data = fields(&[&1u64.to_le_bytes(), &i64::MAX.to_le_bytes(), &slice, &slice]);
+    let ix = pool.legs_ix(*BatchSwap::DISCRIMINATOR, &data, 2);
+    let batch_swap = pool.run("batch_swap", &ix);
+
+    // Route: amount, min, expiration, then one direction per hop
+    let data = fields(&[
+        &AMOUNT.to_le_bytes(),
+        &1u64.to_le_bytes(),
+        &i64::MAX.to_le_bytes(),
+        &[0],
+    ]);
+    let ix = pool.legs_ix(*Route::DISCRIMINATOR, &data, 1);
+    let route = pool.run("route", &ix);
+
+    // Quote: [config, token_program, mint_x, mint_y]; is_x, amount
+    let [mint_x, mint_y, _] = pool.mints();
+    let ix = amm_ix(
+        *Quote::DISCRIMINATOR,
+        &fields(&[&[1], &AMOUNT.to_le_bytes()]),
+        vec![
+            AccountMeta::new_readonly(pool.config(), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(mint_y, false),
+        ],
+    );
+    let quote = pool.run("quote", &ix);
+
+    let ix = amm_ix(
+        *ReadOracle::DISCRIMINATOR,
+        &[],
+        vec![AccountMeta::new_readonly(pool.config(), false)],
+    );
+    let read_oracle = pool.run("read_oracle", &ix);
+
+    let sync = pool.run("sync", &pool.vaults_ix(*Sync::DISCRIMINATOR));
+    let claim_fees = pool.run("claim_fees", &pool.payout_ix(*ClaimFees::DISCRIMINATOR));
+
+    // Tokens sent straight to a vault are the excess Skim pays out
+    let [vault_x, _] = pool.vaults();
+    let balance = pool.ledger.balance(&vault_x);
+    pool.ledger.set_balance(&vault_x, balance + AMOUNT);
+    let skim = pool.run("skim", &pool.payout_ix(*Skim::DISCRIMINATOR));
+
+    vec![
+        ("swap", swap),
+        ("batch_swap", batch_swap),
+        ("route", route),
+        ("quote", quote),
+        ("read_oracle", read_oracle),
+        ("sync", sync),
+        ("claim_fees", claim_fees),
+        ("skim", skim),
+    ]
+}
+
+/// The authority's settings, then handing the pool to a new authority
+fn admin(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let owner = pool.owner();
+    let p = pool.addresses.clone();
+
+    let ix = amm::set_protocol_config_ix(owner, FEE, FEE, PROTOCOL_FEE);
+    let set_protocol_config = pool.run_amm("set_protocol_config", ix);
+
+    // UpdateFee: fee, protocol_fee, referral_fee
+    let data = fields(&[
+        &FEE.to_le_bytes(),
+        &PROTOCOL_FEE.to_le_bytes(),
+        &0u16.to_le_bytes(),
+    ]);
+    let ix = pool.admin_ix(*UpdateFee::DISCRIMINATOR, &data);
+    let update_fee = pool.run("update_fee", &ix);
+
+    let ix = pool.admin_ix(*SetPriceImpactLimit::DISCRIMINATOR, &500u16.to_le_bytes());
+    let set_price_impact_limit = pool.run("set_price_impact_limit", &ix);
+
+    // A zero recipient, or a zero oracle with no band, is the default
+    let ix = pool.admin_ix(*SetFeeRecipient::DISCRIMINATOR, &[0; 32]);
+    let set_fee_recipient = pool.run("set_fee_recipient", &ix);
+    let data = fields(&[&[0; 32], &0u16.to_le_bytes()]);
+    let ix = pool.admin_ix(*SetOracle::DISCRIMINATOR, &data);
+    let set_oracle = pool.run("set_oracle", &ix);
+
+    let ix = amm::set_swap_throttle_ix(owner, &p, true);
+    let set_swap_throttle = pool.run_amm("set_swap_throttle", ix);
+
+    // Cut the config back to its version 5 layout for MigrateConfig to grow
+    let config = pool.config();
+    let mut account = pool.ledger.get(&config).clone();
+    account.data.truncate(Config::V5_LEN);
+    account.data[Config::VERSION_OFFSET] = 5;
+    pool.ledger.set(config, account);
+    let ix = amm::migrate_config_ix(owner, &p);
+    let migrate_config = pool.run_amm("migrate_config", ix);
+
+    let successor = pool.ledger.wallet();
+    let ix = pool.admin_ix(*ProposeAuthority::DISCRIMINATOR, successor.as_ref());
+    let propose_authority = pool.run("propose_authority", &ix);
+    let ix = amm_ix(
+        *AcceptAuthority::DISCRIMINATOR,
+        &[],
+        vec![
+            AccountMeta::new_readonly(successor, true),
+            AccountMeta::new(config, false),
+        ],
+    );
+    let accept_authority = pool.run("accept_authority", &ix);
+
+    vec![
+        ("set_protocol_config", set_protocol_config),
+        ("update_fee", update_fee),
+        ("set_price_impact_limit", set_price_impact_limit),
+        ("set_fee_recipient", set_fee_recipient),
+        ("set_oracle", set_oracle),
+        ("set_swap_throttle", set_swap_throttle),
+        ("migrate_config", migrate_config),
+        ("propose_authority", propose_authority),
+        ("accept_authority", accept_authority),
+    ]
+}
+
+/// Locking LP tokens, then taking them out once the lock ends
+fn locks(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let owner = pool.owner();
+    let p = pool.addresses.clone();
+
+    let (lock, _) = amm::lock_address(&p.config, &owner, 0);
+    let [_, _, mint_lp] = pool.mints();
+    let lock_ata = pubkey(&p.user_atas(&lock)[2]);
+    pool.ledger
+        .token_account(lock_ata, &mint_lp, &pubkey(&lock), 0);
+
+    let unlock_at = pool.ledger.mollusk.sysvars.clock.unix_timestamp + DURATION;
+    let ix = amm::lock_liquidity_ix(owner, &p, 0, AMOUNT, unlock_at, false);
+    let lock_liquidity = pool.run_amm("lock_liquidity", ix);
+
+    pool.ledger.mollusk.sysvars.clock.unix_timestamp = unlock_at;
+    let ix = amm::unlock_liquidity_ix(owner, &p, 0);
+    let unlock_liquidity = pool.run_amm("unlock_liquidity", ix);
+
+    vec![
+        ("lock_liquidity", lock_liquidity),
+        ("unlock_liquidity", unlock_liquidity),
+    ]
+}
+
+/// A reward gauge streaming a third token to the authority's staked LP
+fn gauge(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let config = pool.config();
+    let [_, _, mint_lp] = pool.mints();
+    let [_, _, owner_lp_ata] = pool.atas();
+    let authority = pool.authority;
+
+    let (gauge, gauge_bump) =
+        Pubkey::find_program_address(&[b"gauge", config.as_ref()], &PROGRAM_ID);
+    let (position, position_bump) =
+        Pubkey::find_program_address(&[b"stake", gauge.as_ref(), authority.as_ref()], &PROGRAM_ID);
+    let reward_mint = pool.ledger.mint();
+    let reward_ata = pool.ledger.fund(&authority, &reward_mint, AMOUNT);
+    let lp_vault = pool.ledger.fund(&gauge, &mint_lp, 0);
+    let reward_vault = pool.ledger.fund(&gauge, &reward_mint, 0);
+
+    let ix = amm_ix(
+        *CreateGauge::DISCRIMINATOR,
+        &[gauge_bump],
+        vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(gauge, false),
+            AccountMeta::new_readonly(mint_lp, false),
+            AccountMeta::new_readonly(reward_mint, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let create_gauge = pool.run("create_gauge", &ix);
+
+    // FundGauge: amount, duration
+    let data = fields(&[&AMOUNT.to_le_bytes(), &(DURATION as u64).to_le_bytes()]);
+    let ix = amm_ix(
+        *FundGauge::DISCRIMINATOR,
+        &data,
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(gauge, false),
+            AccountMeta::new(reward_ata, false),
+            AccountMeta::new(reward_vault, false),
+            AccountMeta::new_readonly(reward_mint, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let fund_gauge = pool.run("fund_gauge", &ix);
+
+    // Stake: amount, position_bump
+    let data = fields(&[&AMOUNT.to_le_bytes(), &[position_bump]]);
+    let ix = amm_ix(
+        *Stake::DISCRIMINATOR,
+        &data,
+        vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(gauge, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(owner_lp_ata, false),
+            AccountMeta::new(lp_vault, false),
+            AccountMeta::new_readonly(mint_lp, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let stake = pool.run("stake", &ix);
+
+    // Halfway through the stream there are rewards to claim
+    pool.ledger.mollusk.sysvars.clock.unix_timestamp += DURATION / 2;
+    let ix = amm_ix(
+        *ClaimRewards::DISCRIMINATOR,
+        &[],
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(gauge, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(reward_ata, false),
+            AccountMeta::new(reward_vault, false),
+            AccountMeta::new_readonly(reward_mint, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let claim_rewards = pool.run("claim_rewards", &ix);
+
+    let ix = amm_ix(
+        *Unstake::DISCRIMINATOR,
+        &AMOUNT.to_le_bytes(),
+        vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(gauge, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(owner_lp_ata, false),
+            AccountMeta::new(lp_vault, false),
+            AccountMeta::new_readonly(mint_lp, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    );
+    let unstake = pool.run("unstake", &ix);
+
+    vec![
+        ("create_gauge", create_gauge),
+        ("fund_gauge", fund_gauge),
+        ("stake", stake),
+        ("claim_rewards", claim_rewards),
+        ("unstake", unstake),
+    ]
+}
+
+/// A flash loan of both tokens, then its repayment
+fn flash(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let [vault_x, vault_y] = pool.vaults();
+    let [mint_x, mint_y, _] = pool.mints();
+    let [ata_x, ata_y, _] = pool.atas();
+    let instructions_id = solana_instructions_sysvar::ID;
+
+    // FlashLoan: amount_x, amount_y
+    let loan = amm_ix(
+        *FlashLoan::DISCRIMINATOR,
+        &fields(&[&AMOUNT.to_le_bytes(), &AMOUNT.to_le_bytes()]),
+        vec![
+            AccountMeta::new_readonly(pool.authority, true),
+            AccountMeta::new(pool.config(), false),
+            AccountMeta::new(vault_x, false),
+            AccountMeta::new(vault_y, false),
+            AccountMeta::new(ata_x, false),
+            AccountMeta::new(ata_y, false),
+            AccountMeta::new_readonly(instructions_id, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(mint_x, false),
+            AccountMeta::new_readonly(mint_y, false),
+        ],
+    );
+    let repay = pool.vaults_ix(*FlashRepay::DISCRIMINATOR);
+
+    // The loan looks for its repayment later in the transaction
+    let borrowed: Vec<_> = [&loan, &repay].map(borrow).into();
+    let data = solana_instructions_sysvar::construct_instructions_data(&borrowed);
+    let account = pool.ledger.rent_exempt(data, &SYSVAR_PROGRAM_ID);
+    pool.ledger.set(instructions_id, account);
+
+    let before = [vault_x, vault_y].map(|vault| pool.ledger.balance(&vault));
+    let flash_loan = pool.run("flash_loan", &loan);
+
+    // Pay back principal and more than the fee
+    for (vault, before) in [vault_x, vault_y].into_iter().zip(before) {
+        pool.ledger.set_balance(&vault, before + AMOUNT);
+    }
+    let flash_repay = pool.run("flash_repay", &repay);
+
+    vec![("flash_loan", flash_loan), ("flash_repay", flash_repay)]
+}
+
+fn borrow(ix: &Instruction) -> BorrowedInstruction<'_> {
+    BorrowedInstruction {
+        program_id: &ix.program_id,
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| BorrowedAccountMeta {
+                pubkey: &meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: &ix.data,
+    }
+}
+
+/// Disabling a pool and emptying it, and closing one that was never funded
+fn lifecycle(elf: &[u8]) -> Measurements {
+    let mut pool = Pool::funded(elf);
+    let owner = pool.owner();
+    let p = pool.addresses.clone();
+
+    let ix = pool.admin_ix(*SetState::DISCRIMINATOR, &[DISABLED]);
+    let set_state = pool.run("set_state", &ix);
+    let ix = amm::emergency_withdraw_ix(owner, &p, AMOUNT);
+    let emergency_withdraw = pool.run_amm("emergency_withdraw", ix);
+
+    // No LP supply and empty vaults
+    let (mut pool, _) = Pool::new(elf);
+    let ix = amm::close_pool_ix(pool.owner(), &pool.addresses, pool.owner());
+    let close_pool = pool.run_amm("close_pool", ix);
+
+    vec![
+        ("set_state", set_state),
+        ("emergency_withdraw", emergency_withdraw),
+        ("close_pool", close_pool),
+    ]
+}
+
+/// Instructions left out, and why
+pub const NOT_MEASURED: &[(&str, &str)] = &[(
+    "create_lp_metadata",
+    "CPIs into Metaplex Token Metadata, which mollusk doesn't ship",
+)];
@@ -0,0 +1,121 @@
+//! anchor_escrow: config, escrows made, taken, migrated and refunded, and an
+//! auction bid on and settled
+
+use blueshift_client::{
+    amm::PROGRAM_ID as AMM_PROGRAM_ID,
+    anchor_escrow::{
+        bid_ix, create_auction_ix, escrow_address, initialize_config_ix, make_ix, migrate_ix,
+        program_data_address, refund_ix, settle_auction_ix, take_ix, update_config_ix, AuctionArgs,
+        CancelFee, MakeArgs,
+    },
+};
+
+use crate::{ledger::Ledger, Measurements};
+
+/// Token A deposited into each escrow and the auction
+const DEPOSIT: u64 = 1_000;
+/// Token B asked for the escrow taken, and the auction's reserve
+const RECEIVE: u64 = 500;
+/// Largest cancellation fee the config allows, in basis points
+const MAX_CANCEL_FEE_BPS: u16 = 500;
+/// Largest flat cancellation fee the config allows, in lamports
+const MAX_CANCEL_FEE_LAMPORTS: u64 = 1_000_000;
+/// Byte of `Escrow::version`: 1-byte discriminator, seed, maker, mint_a,
+/// mint_b, receive, bump
+const VERSION_OFFSET: usize = 1 + 8 + 32 * 3 + 8 + 1;
+
+pub fn measure(elf: &[u8]) -> Measurements {
+    let mut ledger = Ledger::new(elf);
+
+    let (authority, treasury) = (ledger.wallet(), ledger.wallet());
+    ledger.set_upgrade_authority(&program_data_address(), &authority);
+    let ix = initialize_config_ix(
+        &authority,
+        &treasury,
+        MAX_CANCEL_FEE_BPS,
+        &AMM_PROGRAM_ID,
+        MAX_CANCEL_FEE_LAMPORTS,
+    );
+    let initialize_config = ledger.run("initialize_config", &ix);
+    let ix = update_config_ix(
+        &authority,
+        &authority,
+        &treasury,
+        MAX_CANCEL_FEE_BPS,
+        false,
+        &AMM_PROGRAM_ID,
+        MAX_CANCEL_FEE_LAMPORTS,
+    );
+    let update_config = ledger.run("update_config", &ix);
+
+    // Three deposits' worth of Token A, Token B for the escrow taken and a bid
+    let (maker, taker, bidder) = (ledger.wallet(), ledger.wallet(), ledger.wallet());
+    let (mint_a, mint_b) = (ledger.mint(), ledger.mint());
+    ledger.fund(&maker, &mint_a, 3 * DEPOSIT);
+    ledger.fund(&taker, &mint_b, RECEIVE);
+    ledger.fund(&bidder, &mint_b, RECEIVE);
+
+    let make = |ledger: &mut Ledger, seed| {
+        let args = MakeArgs {
+            seed,
+            receive: RECEIVE,
+            amount: DEPOSIT,
+            reject_freezable: false,
+            expiry: 0,
+            cancel_fee: CancelFee::None,
+            memo: [0; 32],
+        };
+        ledger.run("make", &make_ix(&maker, &mint_a, &mint_b, &args))
+    };
+
+    let make_cu = make(&mut ledger, 1);
+    let ix = take_ix(&taker, &maker, &mint_a, &mint_b, 1, None);
+    let take = ledger.run("take", &ix);
+
+    // Escrow 2 is set back to the pre-versioning layout, migrated and refunded
+    make(&mut ledger, 2);
+    let (escrow, _) = escrow_address(&maker, 2);
+    let mut account = ledger.get(&escrow).clone();
+    account.data[VERSION_OFFSET] = 0;
+    ledger.set(escrow, account);
+    let migrate = ledger.run("migrate", &migrate_ix(&maker, &escrow));
+    let ix = refund_ix(&maker, &mint_a, 2, &treasury, &CancelFee::None);
+    let refund = ledger.run("refund", &ix);
+
+    // The auction takes one bid, outbidding the maker it starts with, and
+    // is settled once it ends
+    let end_time = ledger.mollusk.sysvars.clock.unix_timestamp + 3_600;
+    let args = AuctionArgs {
+        seed: 3,
+        amount: DEPOSIT,
+        reserve: RECEIVE,
+        min_increment: 1,
+        end_time,
+    };
+    let ix = create_auction_ix(&maker, &mint_a, &mint_b, &args);
+    let create_auction = ledger.run("create_auction", &ix);
+    let ix = bid_ix(&bidder, &maker, &mint_b, 3, &maker, RECEIVE);
+    let bid = ledger.run("bid", &ix);
+
+    ledger.mollusk.sysvars.clock.unix_timestamp = end_time;
+    let ix = settle_auction_ix(&bidder, &maker, &mint_a, &mint_b, 3, &bidder);
+    let settle_auction = ledger.run("settle_auction", &ix);
+
+    vec![
+        ("initialize_config", initialize_config),
+        ("update_config", update_config),
+        ("make", make_cu),
+        ("take", take),
+        ("migrate", migrate),
+        ("refund", refund),
+        ("create_auction", create_auction),
+        ("bid", bid),
+        ("settle_auction", settle_auction),
+    ]
+}
+
+/// Instructions left out, and why
+pub const NOT_MEASURED: &[(&str, &str)] = &[(
+    "take_via_amm",
+    "needs a pool on blueshift_native_amm's standalone-id build",
+)];
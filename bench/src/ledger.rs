@@ -0,0 +1,128 @@
+//! Accounts one flow of instructions runs against in mollusk
+
+use blueshift_client::associated_token_address;
+use blueshift_test_support::{self as fixtures, Accounts};
+use mollusk_svm::{
+    program::{keyed_account_for_system_program, loader_keys::LOADER_V3},
+    Mollusk,
+};
+use mollusk_svm_programs_token::{associated_token, token};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+pub use blueshift_test_support::{PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+pub struct Ledger {
+    pub mollusk: Mollusk,
+    accounts: Accounts,
+}
+
+impl Ledger {
+    /// The program built into `elf` at `PROGRAM_ID`, next to SPL Token and
+    /// the Associated Token program
+    pub fn new(elf: &[u8]) -> Self {
+        let mut mollusk = Mollusk::default();
+        mollusk.add_program_with_elf_and_loader(&PROGRAM_ID, elf, &LOADER_V3);
+        token::add_program(&mut mollusk);
+        associated_token::add_program(&mut mollusk);
+
+        Self {
+            mollusk,
+            accounts: Accounts(vec![
+                keyed_account_for_system_program(),
+                token::keyed_account(),
+                associated_token::keyed_account(),
+            ]),
+        }
+    }
+
+    pub fn set(&mut self, key: Pubkey, account: Account) {
+        self.accounts.set(key, account);
+    }
+
+    pub fn get(&self, key: &Pubkey) -> &Account {
+        self.accounts.get(key)
+    }
+
+    /// Run `instruction`, keep the resulting accounts and return the compute
+    /// units it used. Accounts that don't exist yet start out empty.
+    pub fn run(&mut self, name: &str, instruction: &Instruction) -> u64 {
+        self.accounts.add_missing(instruction);
+
+        let result = self
+            .mollusk
+            .process_instruction(instruction, &self.accounts.0);
+        if let Err(e) = result.raw_result {
+            panic!("{name} failed: {e:?}");
+        }
+        for (key, account) in result.resulting_accounts {
+            self.set(key, account);
+        }
+        result.compute_units_consumed
+    }
+
+    /// `data`, owned by `owner` and holding its rent-exempt minimum
+    pub fn rent_exempt(&self, data: Vec<u8>, owner: &Pubkey) -> Account {
+        fixtures::rent_exempt(data, owner)
+    }
+
+    /// A new system account holding plenty of lamports
+    pub fn wallet(&mut self) -> Pubkey {
+        let wallet = Pubkey::new_unique();
+        self.set(wallet, fixtures::wallet());
+        wallet
+    }
+
+    /// A new initialized 6-decimal mint with no freeze authority
+    pub fn mint(&mut self) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.set(mint, fixtures::mint(6));
+        mint
+    }
+
+    /// Put an initialized token account holding `amount` at `address`, with
+    /// no delegate or close authority
+    pub fn token_account(&mut self, address: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        self.set(address, fixtures::token_account(mint, owner, amount));
+    }
+
+    /// `wallet`'s associated token account for `mint`
+    pub fn ata(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Give `wallet` an associated token account for `mint` holding `amount`
+    pub fn fund(&mut self, wallet: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let ata = self.ata(wallet, mint);
+        self.token_account(ata, mint, wallet, amount);
+        ata
+    }
+
+    /// Token balance of the token account at `key`
+    pub fn balance(&self, key: &Pubkey) -> u64 {
+        fixtures::token_balance(&self.get(key).data)
+    }
+
+    /// Overwrite the token balance of the token account at `key`, as a
+    /// transfer in from outside would
+    pub fn set_balance(&mut self, key: &Pubkey, amount: u64) {
+        let mut account = self.get(key).clone();
+        account.data[64..72].copy_from_slice(&amount.to_le_bytes());
+        self.set(*key, account);
+    }
+
+    /// Deploy the program as upgradeable with `authority` as its upgrade
+    /// authority: the program account pointing at its ProgramData, and the
+    /// ProgramData header naming `authority`
+    pub fn set_upgrade_authority(&mut self, program_data: &Pubkey, authority: &Pubkey) {
+        let mut program = vec![2, 0, 0, 0];
+        program.extend_from_slice(program_data.as_ref());
+        let mut account = self.rent_exempt(program, &LOADER_V3);
+        account.executable = true;
+        self.set(PROGRAM_ID, account);
+
+        let account = self.rent_exempt(fixtures::program_data(authority), &LOADER_V3);
+        self.set(*program_data, account);
+    }
+}
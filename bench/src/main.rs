@@ -0,0 +1,179 @@
+//! Compute units of every instruction of every challenge program
+//!
+//! Runs each program's SBF build in mollusk through flows that reach every
+//! instruction, and prints what each one costs. All programs have to be built
+//! first (`cargo build-sbf` in `pinocchio_vault`, `pinocchio_escrow` and
+//! `blueshift_native_amm`, `anchor build` in `blueshift_anchor_vault` and
+//! `anchor_escrow`).
+//!
+//! The run fails when an instruction uses more compute units than
+//! `baseline.txt` allows, or when the measured instructions and the baseline's
+//! differ either way. `cargo run --release -- --save` records the current numbers as the
+//! baseline.
+
+mod amm;
+mod anchor_escrow;
+mod ledger;
+mod pinocchio_escrow;
+mod vaults;
+
+use std::{collections::HashMap, fs, process::ExitCode};
+
+/// Compute units of each instruction a flow ran, in order
+type Measurements = Vec<(&'static str, u64)>;
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/baseline.txt");
+
+/// Compute units an instruction may grow by over the baseline, in percent
+const CU_TOLERANCE_PCT: u64 = 2;
+
+/// A program, where its build puts its SBF binary, and the flows measuring it
+struct Program {
+    name: &'static str,
+    so: &'static str,
+    measure: fn(&[u8]) -> Measurements,
+    not_measured: &'static [(&'static str, &'static str)],
+}
+
+const PROGRAMS: &[Program] = &[
+    Program {
+        name: "anchor_vault",
+        so: "blueshift_anchor_vault/target/deploy/blueshift_anchor_vault.so",
+        measure: vaults::anchor,
+        not_measured: &[],
+    },
+    Program {
+        name: "pinocchio_vault",
+        so: "pinocchio_vault/target/deploy/blueshift_vault.so",
+        measure: vaults::pinocchio,
+        not_measured: &[],
+    },
+    Program {
+        name: "anchor_escrow",
+        so: "anchor_escrow/target/deploy/anchor_escrow.so",
+        measure: anchor_escrow::measure,
+        not_measured: anchor_escrow::NOT_MEASURED,
+    },
+    Program {
+        name: "pinocchio_escrow",
+        so: "pinocchio_escrow/target/deploy/pinocchio_escrow.so",
+        measure: pinocchio_escrow::measure,
+        not_measured: &[],
+    },
+    Program {
+        name: "amm",
+        so: "blueshift_native_amm/target/deploy/blueshift_native_amm.so",
+        measure: amm::measure,
+        not_measured: amm::NOT_MEASURED,
+    },
+];
+
+fn read_elf(relative: &str) -> Vec<u8> {
+    let path = format!("{}/../{relative}", env!("CARGO_MANIFEST_DIR"));
+    fs::read(&path).unwrap_or_else(|e| panic!("{path}: {e}; build the program first"))
+}
+
+/// `<program>.<instruction> <compute units>` per line, as written by
+/// `--save`; lines starting with `#` are comments
+fn read_baseline() -> Option<HashMap<String, u64>> {
+    let text = fs::read_to_string(BASELINE).ok()?;
+    let baseline = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            Some((name.to_string(), value.trim().parse().ok()?))
+        })
+        .collect();
+    Some(baseline)
+}
+
+fn write_baseline(measurements: &[(String, u64)]) {
+    let mut text = String::from(
+        "# Compute units per instruction; `cargo run --release -- --save` rewrites this file\n",
+    );
+    for (name, units) in measurements {
+        text.push_str(&format!("{name} {units}\n"));
+    }
+    fs::write(BASELINE, text).unwrap_or_else(|e| panic!("{BASELINE}: {e}"));
+}
+
+/// Change from `base` to `units` in percent
+fn delta_pct(units: u64, base: u64) -> f64 {
+    (units as f64 - base as f64) / base as f64 * 100.0
+}
+
+fn main() -> ExitCode {
+    let save = std::env::args().any(|arg| arg == "--save");
+
+    let mut measurements = Vec::new();
+    for program in PROGRAMS {
+        let elf = read_elf(program.so);
+        for (instruction, units) in (program.measure)(&elf) {
+            measurements.push((format!("{}.{instruction}", program.name), units));
+        }
+    }
+
+    if save {
+        write_baseline(&measurements);
+        println!("saved {} measurements to {BASELINE}", measurements.len());
+        return ExitCode::SUCCESS;
+    }
+
+    let baseline = read_baseline().unwrap_or_default();
+
+    println!("| instruction | CU | baseline | delta |");
+    println!("|---|---:|---:|---:|");
+    let mut regressed = false;
+    let mut unrecorded = 0;
+    for (name, units) in &measurements {
+        let Some(&base) = baseline.get(name) else {
+            println!("| {name} | {units} | none | **not in the baseline** |");
+            unrecorded += 1;
+            continue;
+        };
+        let limit = base + base * CU_TOLERANCE_PCT / 100;
+        let flag = if *units > limit {
+            regressed = true;
+            " **regressed**"
+        } else {
+            ""
+        };
+        println!(
+            "| {name} | {units} | {base} | {:+.1}%{flag} |",
+            delta_pct(*units, base)
+        );
+    }
+
+    // An instruction dropped from the flows would otherwise pass unnoticed
+    let mut missing: Vec<_> = baseline
+        .keys()
+        .filter(|name| measurements.iter().all(|(measured, _)| measured != *name))
+        .collect();
+    missing.sort();
+    for name in &missing {
+        println!("\n{name} is in the baseline but was not measured");
+    }
+
+    println!("\nNot measured:");
+    for program in PROGRAMS {
+        for (instruction, reason) in program.not_measured {
+            println!("- {}.{instruction}: {reason}", program.name);
+        }
+    }
+
+    if regressed {
+        println!("\ncompute units regressed by more than {CU_TOLERANCE_PCT}% over the baseline");
+    }
+    if unrecorded > 0 {
+        println!("\n{unrecorded} instructions have no baseline in {BASELINE}");
+    }
+    if unrecorded > 0 || !missing.is_empty() {
+        println!("\nrecord the new set of instructions with --save");
+    }
+    if regressed || unrecorded > 0 || !missing.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
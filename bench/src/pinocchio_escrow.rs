@@ -0,0 +1,102 @@
+//! pinocchio_escrow: config, then offers made, changed, filled and cancelled
+
+use blueshift_client::pinocchio_escrow::{
+    batch_refund_ix, claim_fees_ix, initialize_config_ix, make_ix, program_data_address,
+    read_escrow_ix, reduce_deposit_ix, refund_ix, take_ix, transfer_escrow_ix, update_receive_ix,
+    MakeArgs,
+};
+
+use crate::{
+    ledger::{Ledger, TOKEN_PROGRAM_ID},
+    Measurements,
+};
+
+/// Protocol fee on Token B, in basis points
+const FEE_BPS: u16 = 100;
+/// Token A deposited into each escrow
+const DEPOSIT: u64 = 1_000;
+/// Token B asked for each whole deposit
+const RECEIVE: u64 = 500;
+
+pub fn measure(elf: &[u8]) -> Measurements {
+    let mut ledger = Ledger::new(elf);
+    let token_program = TOKEN_PROGRAM_ID;
+
+    let authority = ledger.wallet();
+    ledger.set_upgrade_authority(&program_data_address(), &authority);
+    let ix = initialize_config_ix(&authority, FEE_BPS, 0);
+    let initialize_config = ledger.run("initialize_config", &ix);
+
+    // Four escrows' worth of Token A, and Token B for the one taken
+    let (maker, taker) = (ledger.wallet(), ledger.wallet());
+    let (mint_a, mint_b) = (ledger.mint(), ledger.mint());
+    ledger.fund(&maker, &mint_a, 4 * DEPOSIT);
+    ledger.fund(&taker, &mint_b, RECEIVE);
+    let fee_destination = ledger.fund(&authority, &mint_b, 0);
+
+    let make = |ledger: &mut Ledger, seed| {
+        let args = MakeArgs {
+            seed,
+            receive: RECEIVE,
+            amount: DEPOSIT,
+            expiry: 0,
+            taker: None,
+            hashlock: None,
+            reject_freezable: false,
+        };
+        let ix = make_ix(&maker, &mint_a, &mint_b, &token_program, &args, None);
+        ledger.run("make", &ix)
+    };
+
+    // Escrow 1 is repriced, halved and filled
+    let make_cu = make(&mut ledger, 1);
+    let read_escrow = ledger.run("read_escrow", &read_escrow_ix(&maker, 1));
+    let ix = update_receive_ix(&maker, &maker, 1, RECEIVE);
+    let update_receive = ledger.run("update_receive", &ix);
+    let ix = reduce_deposit_ix(&maker, &mint_a, &token_program, &maker, 1, DEPOSIT / 2);
+    let reduce_deposit = ledger.run("reduce_deposit", &ix);
+    let ix = take_ix(
+        &taker,
+        &maker,
+        &mint_a,
+        &mint_b,
+        &token_program,
+        &maker,
+        1,
+        DEPOSIT / 2,
+        None,
+        None,
+        None,
+    );
+    let take = ledger.run("take", &ix);
+    let ix = claim_fees_ix(&authority, &mint_b, &fee_destination, &token_program);
+    let claim_fees = ledger.run("claim_fees", &ix);
+
+    // Escrow 2 changes hands and its new maker cancels it
+    make(&mut ledger, 2);
+    let new_maker = ledger.wallet();
+    ledger.fund(&new_maker, &mint_a, 0);
+    let ix = transfer_escrow_ix(&maker, &maker, 2, &new_maker);
+    let transfer_escrow = ledger.run("transfer_escrow", &ix);
+    let ix = refund_ix(&new_maker, &mint_a, &token_program, &maker, 2, None);
+    let refund = ledger.run("refund", &ix);
+
+    // Escrows 3 and 4 are cancelled together
+    make(&mut ledger, 3);
+    make(&mut ledger, 4);
+    let ix = batch_refund_ix(&maker, &mint_a, &token_program, &maker, &[3, 4]);
+    let batch_refund = ledger.run("batch_refund", &ix);
+
+    vec![
+        ("initialize_config", initialize_config),
+        ("make", make_cu),
+        ("read_escrow", read_escrow),
+        ("update_receive", update_receive),
+        ("reduce_deposit", reduce_deposit),
+        ("take", take),
+        ("claim_fees", claim_fees),
+        ("transfer_escrow", transfer_escrow),
+        ("refund", refund),
+        ("batch_refund", batch_refund),
+    ]
+}
@@ -0,0 +1,28 @@
+//! blueshift_anchor_vault and pinocchio_vault: Deposit → Withdraw
+
+use blueshift_client::{anchor_vault, pinocchio_vault};
+
+use crate::{ledger::Ledger, Measurements};
+
+/// Lamports parked in the vault
+const DEPOSIT: u64 = 1_000_000_000;
+
+pub fn anchor(elf: &[u8]) -> Measurements {
+    let mut ledger = Ledger::new(elf);
+    let owner = ledger.wallet();
+
+    let deposit = ledger.run("deposit", &anchor_vault::deposit_ix(&owner, DEPOSIT));
+    let withdraw = ledger.run("withdraw", &anchor_vault::withdraw_ix(&owner));
+
+    vec![("deposit", deposit), ("withdraw", withdraw)]
+}
+
+pub fn pinocchio(elf: &[u8]) -> Measurements {
+    let mut ledger = Ledger::new(elf);
+    let owner = ledger.wallet();
+
+    let deposit = ledger.run("deposit", &pinocchio_vault::deposit_ix(&owner, DEPOSIT));
+    let withdraw = ledger.run("withdraw", &pinocchio_vault::withdraw_ix(&owner));
+
+    vec![("deposit", deposit), ("withdraw", withdraw)]
+}
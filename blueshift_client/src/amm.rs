@@ -0,0 +1,135 @@
+//! `blueshift_native_amm`: constant-product pools with LP locks, gauges and a
+//! protocol config
+//!
+//! The builders and PDA finders are the program's own `client` module,
+//! re-exported. They take and return the program's `Address`, and build its
+//! own `Instruction`; [`instruction`] turns that into a
+//! `solana_instruction::Instruction`, and [`pubkey`] / [`address`] convert
+//! keys either way.
+//!
+//! ```ignore
+//! let pool = amm::PoolAddresses::find(seed, mint_x, mint_y, token_program);
+//! let swap = amm::swap_ix(user, &pool, true, amount, min, expiration, None, None);
+//! transaction_instructions.push(amm::instruction(swap));
+//! ```
+
+use crate::Error;
+
+pub use blueshift_native_amm::client::*;
+pub use blueshift_native_amm::{AmmState, Config, Gauge, LpLock, PoolRegistry, ProtocolConfig};
+pub use solana_address::Address;
+
+/// AMM program ID
+pub const PROGRAM_ID: solana_pubkey::Pubkey =
+    solana_pubkey::Pubkey::new_from_array(blueshift_native_amm::ID.to_bytes());
+
+/// `address` as a `Pubkey`
+#[inline]
+pub fn pubkey(address: &Address) -> solana_pubkey::Pubkey {
+    solana_pubkey::Pubkey::new_from_array(address.to_bytes())
+}
+
+/// `pubkey` as an `Address`, to pass to the AMM builders
+#[inline]
+pub fn address(pubkey: &solana_pubkey::Pubkey) -> Address {
+    Address::new_from_array(pubkey.to_bytes())
+}
+
+/// An AMM builder's instruction as a `solana_instruction::Instruction`
+pub fn instruction(ix: Instruction) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: pubkey(&ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| solana_instruction::AccountMeta {
+                pubkey: pubkey(&meta.address),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: ix.data,
+    }
+}
+
+// ==================== Accounts ====================
+//
+// Every AMM account is byte arrays only, so it is read in place once its
+// length checks out.
+
+/// Read a pool config account of the current layout; older layouts need
+/// `MigrateConfig` first
+pub fn config(data: &[u8]) -> Result<&Config, Error> {
+    if data.len() != Config::LEN {
+        return Err(Error::InvalidAccountData);
+    }
+    Ok(unsafe { Config::from_bytes_unchecked(data) })
+}
+
+/// Read the protocol config account
+pub fn protocol_config(data: &[u8]) -> Result<&ProtocolConfig, Error> {
+    if data.len() != ProtocolConfig::LEN {
+        return Err(Error::InvalidAccountData);
+    }
+    Ok(unsafe { ProtocolConfig::from_bytes_unchecked(data) })
+}
+
+/// Read a pool registry account
+pub fn pool_registry(data: &[u8]) -> Result<&PoolRegistry, Error> {
+    if data.len() != PoolRegistry::LEN {
+        return Err(Error::InvalidAccountData);
+    }
+    Ok(unsafe { PoolRegistry::from_bytes_unchecked(data) })
+}
+
+/// Read a gauge account
+pub fn gauge(data: &[u8]) -> Result<&Gauge, Error> {
+    if data.len() != Gauge::LEN {
+        return Err(Error::InvalidAccountData);
+    }
+    Ok(unsafe { Gauge::from_bytes_unchecked(data) })
+}
+
+/// Read an LP lock account
+pub fn lp_lock(data: &[u8]) -> Result<&LpLock, Error> {
+    if data.len() != LpLock::LEN {
+        return Err(Error::InvalidAccountData);
+    }
+    Ok(unsafe { LpLock::from_bytes_unchecked(data) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instructions_keep_their_accounts_and_data() {
+        let [user, mint_x, mint_y, token_program] =
+            [1, 2, 3, 4].map(|n| Address::new_from_array([n; 32]));
+        let pool = PoolAddresses::find(7, mint_x, mint_y, token_program);
+        let ix = emergency_withdraw_ix(user, &pool, 10);
+        let converted = instruction(ix.clone());
+
+        assert_eq!(converted.program_id, PROGRAM_ID);
+        assert_eq!(converted.data, ix.data);
+        assert_eq!(converted.accounts.len(), ix.accounts.len());
+        for (meta, original) in converted.accounts.iter().zip(&ix.accounts) {
+            assert_eq!(address(&meta.pubkey), original.address);
+            assert_eq!(
+                (meta.is_signer, meta.is_writable),
+                (original.is_signer, original.is_writable)
+            );
+        }
+    }
+
+    #[test]
+    fn accounts_of_another_length_are_rejected() {
+        let data = vec![0u8; Config::LEN];
+        assert!(config(&data).is_ok());
+        assert_eq!(
+            config(&data[..Config::V5_LEN]).err(),
+            Some(Error::InvalidAccountData)
+        );
+        assert_eq!(lp_lock(&data).err(), Some(Error::InvalidAccountData));
+    }
+}
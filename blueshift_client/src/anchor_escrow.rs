@@ -0,0 +1,511 @@
+//! `anchor_escrow`: escrow offers and English auctions, with program-wide and
+//! per-pair fill statistics
+//!
+//! Everything runs under the spl-token program. Escrows and auctions are
+//! addressed by their maker and seed.
+
+use ::anchor_escrow::{accounts, instruction};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_challenge_common::ids;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    associated_token_address, Error, ASSOCIATED_TOKEN_PROGRAM_ID, SYSTEM_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+};
+
+pub use ::anchor_escrow::state::{Auction, CancelFee, Config, Escrow, EscrowStats, PairStats};
+
+/// Escrow program ID
+pub const PROGRAM_ID: Pubkey = ::anchor_escrow::ID;
+
+// ==================== Addresses ====================
+
+/// Derive the escrow PDA for `maker` and `seed`
+pub fn escrow_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Escrow::find_address(maker, seed)
+}
+
+/// Derive the auction PDA for `maker` and `seed`
+pub fn auction_address(maker: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Auction::find_address(maker, seed)
+}
+
+/// Derive the program-wide stats PDA
+pub fn stats_address() -> (Pubkey, u8) {
+    EscrowStats::find_address()
+}
+
+/// Derive the fill statistics PDA of the `mint_a` / `mint_b` pair
+pub fn pair_stats_address(mint_a: &Pubkey, mint_b: &Pubkey) -> (Pubkey, u8) {
+    PairStats::find_address(mint_a, mint_b)
+}
+
+/// Derive the program config PDA
+pub fn config_address() -> (Pubkey, u8) {
+    Config::find_address()
+}
+
+/// Derive this program's ProgramData account (upgradeable loader)
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[PROGRAM_ID.as_ref()],
+        &Pubkey::new_from_array(ids::BPF_LOADER_UPGRADEABLE_ID),
+    )
+    .0
+}
+
+fn ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID)
+}
+
+// ==================== Escrows ====================
+
+/// Terms of a new escrow offer
+pub struct MakeArgs {
+    pub seed: u64,
+    /// Token B asked for the whole deposit
+    pub receive: u64,
+    /// Token A deposited into the vault
+    pub amount: u64,
+    /// Reject mints that have a freeze authority
+    pub reject_freezable: bool,
+    /// Unix timestamp after which the offer can't be taken (0 = never)
+    pub expiry: i64,
+    /// Fee the maker owes the treasury for refunding before expiry
+    pub cancel_fee: CancelFee,
+    /// Off-chain reference to correlate the escrow with
+    pub memo: [u8; 32],
+}
+
+/// Build a `make` instruction
+pub fn make_ix(maker: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey, args: &MakeArgs) -> Instruction {
+    let (escrow, _) = escrow_address(maker, args.seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Make {
+            maker: *maker,
+            escrow,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            maker_ata_a: ata(maker, mint_a),
+            vault: ata(&escrow, mint_a),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats: stats_address().0,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::Make {
+            seed: args.seed,
+            receive: args.receive,
+            amount: args.amount,
+            reject_freezable: args.reject_freezable,
+            expiry: args.expiry,
+            cancel_fee: args.cancel_fee,
+            memo: args.memo,
+        }
+        .data(),
+    }
+}
+
+/// Build a `take` instruction filling `maker`'s escrow `seed`
+///
+/// Token B comes from the taker's ATA, or from `taker_ata_b` when set: any
+/// Token B account the taker owns or is an approved delegate of.
+pub fn take_ix(
+    taker: &Pubkey,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+    taker_ata_b: Option<&Pubkey>,
+) -> Instruction {
+    let (escrow, _) = escrow_address(maker, seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Take {
+            taker: *taker,
+            maker: *maker,
+            escrow,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            vault: ata(&escrow, mint_a),
+            taker_ata_a: ata(taker, mint_a),
+            taker_ata_b: taker_ata_b.copied().unwrap_or_else(|| ata(taker, mint_b)),
+            maker_ata_b: ata(maker, mint_b),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats: stats_address().0,
+            pair_stats: pair_stats_address(mint_a, mint_b).0,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::Take {}.data(),
+    }
+}
+
+/// Build a `refund` instruction cancelling `maker`'s escrow `seed`
+///
+/// `cancel_fee` is the escrow's; a fee in basis points is paid out of the
+/// vault into the treasury's Token A account, which the instruction then
+/// passes.
+pub fn refund_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    seed: u64,
+    treasury: &Pubkey,
+    cancel_fee: &CancelFee,
+) -> Instruction {
+    let (escrow, _) = escrow_address(maker, seed);
+    let treasury_ata_a = match cancel_fee {
+        CancelFee::Bps(_) => Some(ata(treasury, mint_a)),
+        CancelFee::None | CancelFee::Lamports(_) => None,
+    };
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Refund {
+            maker: *maker,
+            escrow,
+            mint_a: *mint_a,
+            vault: ata(&escrow, mint_a),
+            maker_ata_a: ata(maker, mint_a),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats: stats_address().0,
+            config: config_address().0,
+            treasury: *treasury,
+            treasury_ata_a,
+        }
+        .to_account_metas(None),
+        data: instruction::Refund {}.data(),
+    }
+}
+
+/// Build a `migrate` instruction bringing `escrow` to the current layout,
+/// `payer` covering any extra rent
+pub fn migrate_ix(payer: &Pubkey, escrow: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Migrate {
+            payer: *payer,
+            escrow: *escrow,
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Migrate {}.data(),
+    }
+}
+
+/// Build a `take_via_amm` instruction filling `maker`'s escrow `seed` by
+/// swapping its Token A through the AMM pool at `amm_config`
+///
+/// The pool's vaults are in [`crate::amm::PoolAddresses`]; `amm_program` has
+/// to be the one recorded in the escrow config.
+#[allow(clippy::too_many_arguments)]
+pub fn take_via_amm_ix(
+    taker: &Pubkey,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+    amm_config: &Pubkey,
+    amm_vault_x: &Pubkey,
+    amm_vault_y: &Pubkey,
+    amm_program: &Pubkey,
+) -> Instruction {
+    let (escrow, _) = escrow_address(maker, seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::TakeViaAmm {
+            taker: *taker,
+            maker: *maker,
+            escrow,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            vault: ata(&escrow, mint_a),
+            escrow_ata_b: ata(&escrow, mint_b),
+            taker_ata_b: ata(taker, mint_b),
+            maker_ata_b: ata(maker, mint_b),
+            amm_config: *amm_config,
+            amm_vault_x: *amm_vault_x,
+            amm_vault_y: *amm_vault_y,
+            amm_program: *amm_program,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats: stats_address().0,
+            pair_stats: pair_stats_address(mint_a, mint_b).0,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::TakeViaAmm {}.data(),
+    }
+}
+
+// ==================== Auctions ====================
+
+/// Terms of a new auction
+pub struct AuctionArgs {
+    pub seed: u64,
+    /// Token A up for auction
+    pub amount: u64,
+    /// Lowest acceptable first bid in Token B
+    pub reserve: u64,
+    /// Least a new bid must beat the highest one by
+    pub min_increment: u64,
+    /// Unix timestamp at which bidding closes
+    pub end_time: i64,
+}
+
+/// Build a `create_auction` instruction
+pub fn create_auction_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    args: &AuctionArgs,
+) -> Instruction {
+    let (auction, _) = auction_address(maker, args.seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::CreateAuction {
+            maker: *maker,
+            auction,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            maker_ata_a: ata(maker, mint_a),
+            vault: ata(&auction, mint_a),
+            bid_vault: ata(&auction, mint_b),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::CreateAuction {
+            seed: args.seed,
+            amount: args.amount,
+            reserve: args.reserve,
+            min_increment: args.min_increment,
+            end_time: args.end_time,
+        }
+        .data(),
+    }
+}
+
+/// Build a `bid` instruction of `amount` Token B on `maker`'s auction `seed`,
+/// refunding `previous_bidder`, the auction's `highest_bidder`
+pub fn bid_ix(
+    bidder: &Pubkey,
+    maker: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+    previous_bidder: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (auction, _) = auction_address(maker, seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Bid {
+            bidder: *bidder,
+            auction,
+            mint_b: *mint_b,
+            bid_vault: ata(&auction, mint_b),
+            bidder_ata_b: ata(bidder, mint_b),
+            previous_bidder: *previous_bidder,
+            previous_bidder_ata_b: ata(previous_bidder, mint_b),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::Bid { amount }.data(),
+    }
+}
+
+/// Build a `settle_auction` instruction for `maker`'s ended auction `seed`,
+/// paying `winner`, the auction's `highest_bidder`
+pub fn settle_auction_ix(
+    settler: &Pubkey,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    seed: u64,
+    winner: &Pubkey,
+) -> Instruction {
+    let (auction, _) = auction_address(maker, seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::SettleAuction {
+            settler: *settler,
+            maker: *maker,
+            auction,
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            vault: ata(&auction, mint_a),
+            bid_vault: ata(&auction, mint_b),
+            winner: *winner,
+            winner_ata_a: ata(winner, mint_a),
+            maker_ata_b: ata(maker, mint_b),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SettleAuction {}.data(),
+    }
+}
+
+// ==================== Config ====================
+
+/// Build an `initialize_config` instruction; `authority` must be the
+/// program's upgrade authority
+pub fn initialize_config_ix(
+    authority: &Pubkey,
+    treasury: &Pubkey,
+    max_cancel_fee_bps: u16,
+    amm_program: &Pubkey,
+    max_cancel_fee_lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::InitializeConfig {
+            authority: *authority,
+            config: config_address().0,
+            program: PROGRAM_ID,
+            program_data: program_data_address(),
+            system_program: SYSTEM_PROGRAM_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitializeConfig {
+            treasury: *treasury,
+            max_cancel_fee_bps,
+            amm_program: *amm_program,
+            max_cancel_fee_lamports,
+        }
+        .data(),
+    }
+}
+
+/// Build an `update_config` instruction replacing the whole config
+pub fn update_config_ix(
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+    treasury: &Pubkey,
+    max_cancel_fee_bps: u16,
+    paused: bool,
+    amm_program: &Pubkey,
+    max_cancel_fee_lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::UpdateConfig {
+            authority: *authority,
+            config: config_address().0,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateConfig {
+            new_authority: *new_authority,
+            treasury: *treasury,
+            max_cancel_fee_bps,
+            paused,
+            amm_program: *amm_program,
+            max_cancel_fee_lamports,
+        }
+        .data(),
+    }
+}
+
+// ==================== Accounts ====================
+
+fn deserialize<T: AccountDeserialize>(mut data: &[u8]) -> Result<T, Error> {
+    T::try_deserialize(&mut data).map_err(|_| Error::InvalidAccountData)
+}
+
+/// Read an escrow account
+pub fn escrow(data: &[u8]) -> Result<Escrow, Error> {
+    deserialize(data)
+}
+
+/// Read an auction account
+pub fn auction(data: &[u8]) -> Result<Auction, Error> {
+    deserialize(data)
+}
+
+/// Read the program-wide stats account
+pub fn stats(data: &[u8]) -> Result<EscrowStats, Error> {
+    deserialize(data)
+}
+
+/// Read a mint pair's stats account
+pub fn pair_stats(data: &[u8]) -> Result<PairStats, Error> {
+    deserialize(data)
+}
+
+/// Read the program config account
+pub fn config(data: &[u8]) -> Result<Config, Error> {
+    deserialize(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+
+    use super::*;
+
+    #[test]
+    fn config_round_trips_and_other_accounts_do_not_read_as_it() {
+        let written = Config {
+            authority: Pubkey::new_from_array([1; 32]),
+            treasury: Pubkey::new_from_array([2; 32]),
+            max_cancel_fee_bps: 250,
+            paused: true,
+            bump: 254,
+            amm_program: Pubkey::new_from_array([3; 32]),
+            max_cancel_fee_lamports: 1_000_000,
+        };
+        let mut data = Vec::with_capacity(Config::SPACE);
+        written.try_serialize(&mut data).unwrap();
+
+        let read = config(&data).unwrap();
+        assert_eq!(read.authority, written.authority);
+        assert_eq!(read.treasury, written.treasury);
+        assert_eq!(read.amm_program, written.amm_program);
+        assert_eq!(read.max_cancel_fee_lamports, 1_000_000);
+        assert_eq!(
+            (read.max_cancel_fee_bps, read.paused, read.bump),
+            (250, true, 254)
+        );
+
+        assert_eq!(escrow(&data).err(), Some(Error::InvalidAccountData));
+        assert_eq!(config(&data[..8]).err(), Some(Error::InvalidAccountData));
+    }
+
+    #[test]
+    fn refund_passes_the_treasury_token_account_only_for_bps_fees() {
+        let [maker, mint_a, treasury] = [1, 2, 3].map(|n| Pubkey::new_from_array([n; 32]));
+        let treasury_ata_a = associated_token_address(&treasury, &mint_a, &TOKEN_PROGRAM_ID);
+        let passes_treasury_ata = |cancel_fee| {
+            refund_ix(&maker, &mint_a, 7, &treasury, &cancel_fee)
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == treasury_ata_a)
+        };
+
+        assert!(passes_treasury_ata(CancelFee::Bps(100)));
+        assert!(!passes_treasury_ata(CancelFee::Lamports(100)));
+        assert!(!passes_treasury_ata(CancelFee::None));
+    }
+}
@@ -0,0 +1,64 @@
+//! `blueshift_anchor_vault`: the Anchor take on the lamport vault
+//!
+//! Like `pinocchio_vault`, the vault is a data-less system account whose
+//! balance is the deposit.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use blueshift_anchor_vault::{accounts, instruction};
+use solana_challenge_common::seeds;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::SYSTEM_PROGRAM_ID;
+
+/// Anchor vault program ID
+pub const PROGRAM_ID: Pubkey = blueshift_anchor_vault::ID;
+
+/// Derive `owner`'s vault PDA
+pub fn vault_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&seeds::vault(owner.as_array()), &PROGRAM_ID)
+}
+
+fn vault_accounts(owner: &Pubkey) -> Vec<AccountMeta> {
+    accounts::VaultAction {
+        signer: *owner,
+        vault: vault_address(owner).0,
+        system_program: SYSTEM_PROGRAM_ID,
+    }
+    .to_account_metas(None)
+}
+
+/// Build a `deposit` instruction moving `amount` lamports into `owner`'s
+/// empty vault; `amount` must exceed the rent-exempt minimum
+pub fn deposit_ix(owner: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data: instruction::Deposit { amount }.data(),
+    }
+}
+
+/// Build a `withdraw` instruction returning all of `owner`'s vault to them
+pub fn withdraw_ix(owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data: instruction::Withdraw {}.data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_is_the_programs_pda() {
+        let owner = Pubkey::new_from_array([1; 32]);
+        let expected = Pubkey::find_program_address(&[b"vault", owner.as_ref()], &PROGRAM_ID);
+        assert_eq!(vault_address(&owner), expected);
+
+        let ix = withdraw_ix(&owner);
+        assert_eq!(ix.accounts[1].pubkey, expected.0);
+        assert!(ix.accounts[0].is_signer && ix.accounts[1].is_writable);
+    }
+}
@@ -0,0 +1,21 @@
+use core::fmt;
+
+/// Why account data couldn't be read as the requested account type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong size, discriminator or layout version for the account type
+    InvalidAccountData,
+    /// The program closed the account; only its tombstone is left
+    AccountClosed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidAccountData => f.write_str("account data is not of the requested type"),
+            Error::AccountClosed => f.write_str("account was closed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,54 @@
+//! Off-chain client for the challenge programs: instruction builders, PDA
+//! finders and account deserializers for the anchor vault, the pinocchio
+//! vault, both escrows and the AMM, all speaking `solana_instruction` and
+//! `solana_pubkey` types.
+//!
+//! `pinocchio_escrow` and `blueshift_native_amm` already ship builders behind
+//! their `client` feature; their modules here re-export them. The AMM's own
+//! builders work on `Address` and return its `Instruction`, which
+//! [`amm::instruction`] converts. The other three are built here on top of the
+//! programs' own discriminators and, for the Anchor ones, their generated
+//! `instruction` and `accounts` structs.
+//!
+//! All five programs are deployed at the challenge's program ID, so each
+//! module's `PROGRAM_ID` is the same address; a deployment elsewhere means
+//! rebuilding the program with its own ID.
+//!
+//! Deserializers only look at account data. Callers check the account's
+//! owner is the program before trusting what they return.
+
+pub mod amm;
+pub mod anchor_escrow;
+pub mod anchor_vault;
+pub mod error;
+pub mod pinocchio_escrow;
+pub mod pinocchio_vault;
+
+pub use error::Error;
+pub use solana_instruction::{AccountMeta, Instruction};
+pub use solana_pubkey::Pubkey;
+
+use solana_challenge_common::{ids, seeds};
+
+/// System Program ID
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::SYSTEM_PROGRAM_ID);
+
+/// SPL Token Program ID
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::TOKEN_PROGRAM_ID);
+
+/// Associated Token Account Program ID
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::new_from_array(ids::ASSOCIATED_TOKEN_PROGRAM_ID);
+
+/// Derive `wallet`'s associated token account for `mint` under `token_program`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &seeds::associated_token_account(
+            wallet.as_array(),
+            token_program.as_array(),
+            mint.as_array(),
+        ),
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
@@ -0,0 +1,80 @@
+//! `pinocchio_escrow`: partially fillable escrow offers with a protocol fee
+//!
+//! The instruction builders and PDA finders are the program's own `client`
+//! module, re-exported.
+
+use ::pinocchio_escrow::state::is_closed;
+
+use crate::Error;
+
+pub use ::pinocchio_escrow::client::*;
+pub use ::pinocchio_escrow::state::{Config, Escrow, EscrowTerms, MakerCounter};
+
+/// Read an escrow account of the current layout
+pub fn escrow(data: &[u8]) -> Result<&Escrow, Error> {
+    if is_closed(data) {
+        return Err(Error::AccountClosed);
+    }
+    Escrow::load(data).map_err(|_| Error::InvalidAccountData)
+}
+
+/// Read the program config account
+pub fn config(data: &[u8]) -> Result<&Config, Error> {
+    Config::load(data).map_err(|_| Error::InvalidAccountData)
+}
+
+/// Read the terms a `ReadEscrow` instruction returns as return data
+pub fn terms(return_data: &[u8]) -> Result<&EscrowTerms, Error> {
+    EscrowTerms::load(return_data).map_err(|_| Error::InvalidAccountData)
+}
+
+/// A maker's open escrows, as their `MakerCounter` lists them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenEscrows {
+    /// Number of open escrows, including any beyond `seeds`
+    pub open: u64,
+    /// Seeds of up to `MakerCounter::MAX_SEEDS` of them, most recent last
+    pub seeds: Vec<u64>,
+}
+
+/// Read a maker counter account
+pub fn open_escrows(data: &[u8]) -> Result<OpenEscrows, Error> {
+    let counter = MakerCounter::load(data).map_err(|_| Error::InvalidAccountData)?;
+    Ok(OpenEscrows {
+        open: counter.open(),
+        seeds: (0..counter.len as usize).map(|i| counter.seed(i)).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_escrows_lists_the_counted_seeds() {
+        let mut data = vec![0u8; MakerCounter::LEN];
+        let counter = MakerCounter::load_uninit_mut(&mut data).unwrap();
+        counter.set_inner([254]);
+        for seed in [7, 9, 11] {
+            counter.record_open(seed);
+        }
+        counter.record_close(11);
+        assert_eq!(
+            open_escrows(&data),
+            Ok(OpenEscrows {
+                open: 2,
+                seeds: vec![7, 9],
+            })
+        );
+
+        // More listed than the counter holds
+        data[2] = MakerCounter::MAX_SEEDS as u8 + 1;
+        assert_eq!(open_escrows(&data), Err(Error::InvalidAccountData));
+    }
+
+    #[test]
+    fn closed_escrows_read_as_closed() {
+        assert_eq!(escrow(&[0xff]).err(), Some(Error::AccountClosed));
+        assert_eq!(escrow(&[0; 8]).err(), Some(Error::InvalidAccountData));
+    }
+}
@@ -0,0 +1,50 @@
+//! `pinocchio_vault`: one lamport vault per owner
+//!
+//! The vault is a system account holding the deposit as lamports and no data,
+//! so there is nothing to deserialize; its balance is the deposit.
+
+use blueshift_vault::{Deposit, Withdraw};
+use solana_challenge_common::seeds;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::SYSTEM_PROGRAM_ID;
+
+/// Vault program ID
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array(blueshift_vault::ID);
+
+/// Derive `owner`'s vault PDA
+pub fn vault_address(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&seeds::vault(owner.as_array()), &PROGRAM_ID)
+}
+
+fn vault_accounts(owner: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(vault_address(owner).0, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ]
+}
+
+/// Build a `Deposit` instruction moving `amount` lamports into `owner`'s
+/// empty vault
+pub fn deposit_ix(owner: &Pubkey, amount: u64) -> Instruction {
+    let mut data = Vec::with_capacity(1 + 8);
+    data.push(*Deposit::DISCRIMINATOR);
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data,
+    }
+}
+
+/// Build a `Withdraw` instruction returning all of `owner`'s vault to them
+pub fn withdraw_ix(owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vault_accounts(owner),
+        data: vec![*Withdraw::DISCRIMINATOR],
+    }
+}
@@ -0,0 +1,829 @@
+//! Instruction builders for off-chain clients (`client` feature).
+//!
+//! They derive the pool's PDAs and pack instruction data from the same
+//! `#[repr(C, packed)]` structs the program reads, so integrators don't have to
+//! mirror the layouts by hand. `expiration` arguments are unix timestamps, or
+//! slots via `expiration::slot_expiration`.
+
+use core::mem::offset_of;
+use std::vec::Vec;
+
+use pinocchio::Address;
+use solana_challenge_common::seeds::POOL_CONFIG_SEED;
+
+pub use crate::pda::{associated_token_address, protocol_config_address};
+
+use crate::{
+    metadata::METADATA_PROGRAM_ID, pda::mint_lp_address, quote::DEFAULT_LP_DECIMALS, ClosePool,
+    CreateLpMetadata, CreateLpMetadataInstructionData, Deposit, DepositExact,
+    DepositExactInstructionData, DepositInstructionData, DepositSingle,
+    DepositSingleInstructionData, DepositWithSlippage, DepositWithSlippageInstructionData,
+    EmergencyWithdraw, EmergencyWithdrawInstructionData, Initialize, InitializeInstructionData,
+    InitializeProtocol, InitializeProtocolInstructionData, LockLiquidity,
+    LockLiquidityInstructionData, MigrateConfig, SetProtocolConfig,
+    SetProtocolConfigInstructionData, SetSwapThrottle, SetSwapThrottleInstructionData, Swap,
+    SwapInstructionData, UnlockLiquidity, Withdraw, WithdrawInstructionData, WithdrawSingle,
+    WithdrawSingleInstructionData, WithdrawWithSlippage, WithdrawWithSlippageInstructionData,
+};
+
+// ==================== Instruction ====================
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub address: Address,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    #[inline]
+    pub fn writable(address: Address, is_signer: bool) -> Self {
+        Self {
+            address,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    #[inline]
+    pub fn readonly(address: Address) -> Self {
+        Self {
+            address,
+            is_signer: false,
+            is_writable: false,
+        }
+    }
+
+    #[inline]
+    pub fn readonly_signer(address: Address) -> Self {
+        Self {
+            address,
+            is_signer: true,
+            is_writable: false,
+        }
+    }
+}
+
+/// A program instruction; map it onto the SDK's `Instruction` type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Address,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// Discriminator followed by the packed instruction data, exactly as the
+/// program's `read_unaligned` expects it
+fn pack<T>(discriminator: u8, data: &T) -> Vec<u8> {
+    let bytes = unsafe {
+        core::slice::from_raw_parts(data as *const T as *const u8, core::mem::size_of::<T>())
+    };
+    let mut packed = Vec::with_capacity(1 + bytes.len());
+    packed.push(discriminator);
+    packed.extend_from_slice(bytes);
+    packed
+}
+
+// ==================== Addresses ====================
+
+/// The accounts derived from a pool's seed, mints and token program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolAddresses {
+    pub config: Address,
+    pub config_bump: u8,
+    pub mint_lp: Address,
+    pub lp_bump: u8,
+    pub vault_x: Address,
+    pub vault_y: Address,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub token_program: Address,
+}
+
+impl PoolAddresses {
+    pub fn find(seed: u64, mint_x: Address, mint_y: Address, token_program: Address) -> Self {
+        let (config, config_bump) = Address::find_program_address(
+            &[
+                POOL_CONFIG_SEED,
+                &seed.to_le_bytes(),
+                mint_x.as_ref(),
+                mint_y.as_ref(),
+            ],
+            &crate::ID,
+        );
+        let (mint_lp, lp_bump) = mint_lp_address(&config);
+
+        Self {
+            vault_x: associated_token_address(&config, &mint_x, &token_program),
+            vault_y: associated_token_address(&config, &mint_y, &token_program),
+            config,
+            config_bump,
+            mint_lp,
+            lp_bump,
+            mint_x,
+            mint_y,
+            token_program,
+        }
+    }
+
+    /// `wallet`'s token accounts for X, Y and the LP mint
+    pub fn user_atas(&self, wallet: &Address) -> [Address; 3] {
+        [
+            associated_token_address(wallet, &self.mint_x, &self.token_program),
+            associated_token_address(wallet, &self.mint_y, &self.token_program),
+            associated_token_address(wallet, &self.mint_lp, &self.token_program),
+        ]
+    }
+}
+
+/// Registry of the pool of `mint_x` / `mint_y` at fee tier `fee`, and its bump;
+/// derive it for each of `FEE_TIERS` to find every pool of a pair
+pub fn pool_registry_address(mint_x: &Address, mint_y: &Address, fee: u16) -> (Address, u8) {
+    Address::find_program_address(
+        &[b"registry", mint_x.as_ref(), mint_y.as_ref(), &fee.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// `owner`'s LP lock number `id` in the pool of `config`, and its bump
+pub fn lock_address(config: &Address, owner: &Address, id: u64) -> (Address, u8) {
+    Address::find_program_address(
+        &[b"lock", config.as_ref(), owner.as_ref(), &id.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// Metaplex metadata account of `mint`
+pub fn metadata_address(mint: &Address) -> Address {
+    Address::find_program_address(
+        &[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+// ==================== Builders ====================
+
+/// Create the pool and register it under its pair and fee tier; `authority:
+/// None` makes it immutable. Mints must be in canonical order
+/// (`mint_x < mint_y`) and `fee` one of `FEE_TIERS`, within the protocol
+/// config's bounds.
+pub fn initialize_ix(
+    initializer: Address,
+    pool: &PoolAddresses,
+    seed: u64,
+    fee: u16,
+    authority: Option<Address>,
+) -> Instruction {
+    let (registry, registry_bump) = pool_registry_address(&pool.mint_x, &pool.mint_y, fee);
+    let data = InitializeInstructionData {
+        seed,
+        fee,
+        mint_x: pool.mint_x.to_bytes(),
+        mint_y: pool.mint_y.to_bytes(),
+        config_bump: [pool.config_bump],
+        lp_bump: [pool.lp_bump],
+        registry_bump: [registry_bump],
+        lp_decimals: DEFAULT_LP_DECIMALS,
+        authority: authority.map_or([0; 32], |authority| authority.to_bytes()),
+    };
+    let mut data = pack(*Initialize::DISCRIMINATOR, &data);
+    // The short form (no authority) is the canonical encoding of an immutable pool
+    if authority.is_none() {
+        data.truncate(data.len() - 32);
+    }
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(initializer, true),
+            AccountMeta::writable(pool.mint_lp, false),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pinocchio_system::ID),
+            AccountMeta::readonly(pool.token_program),
+            AccountMeta::readonly(pool.mint_x),
+            AccountMeta::readonly(pool.mint_y),
+            AccountMeta::writable(registry, false),
+            AccountMeta::readonly(protocol_config_address().0),
+        ]),
+        data,
+    }
+}
+
+/// Mint `amount` LP tokens, paying at most `max_x` / `max_y`
+pub fn deposit_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    amount: u64,
+    max_x: u64,
+    max_y: u64,
+    expiration: i64,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, user_lp_ata] = pool.user_atas(&user);
+    let data = DepositInstructionData {
+        amount,
+        max_x,
+        max_y,
+        expiration,
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(user),
+            AccountMeta::writable(pool.mint_lp, false),
+            AccountMeta::writable(pool.vault_x, false),
+            AccountMeta::writable(pool.vault_y, false),
+            AccountMeta::writable(user_x_ata, false),
+            AccountMeta::writable(user_y_ata, false),
+            AccountMeta::writable(user_lp_ata, false),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pool.token_program),
+            AccountMeta::readonly(pool.mint_x),
+            AccountMeta::readonly(pool.mint_y),
+        ]),
+        data: pack(*Deposit::DISCRIMINATOR, &data),
+    }
+}
+
+/// Deposit up to `amount_x` / `amount_y`, minting as much LP as they cover
+/// and at least `min_lp`
+pub fn deposit_exact_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    amount_x: u64,
+    amount_y: u64,
+    min_lp: u64,
+    expiration: i64,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, user_lp_ata] = pool.user_atas(&user);
+    let data = DepositExactInstructionData {
+        amount_x,
+        amount_y,
+        min_lp,
+        expiration,
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(user),
+            AccountMeta::writable(pool.mint_lp, false),
+            AccountMeta::writable(pool.vault_x, false),
+            AccountMeta::writable(pool.vault_y, false),
+            AccountMeta::writable(user_x_ata, false),
+            AccountMeta::writable(user_y_ata, false),
+            AccountMeta::writable(user_lp_ata, false),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pool.token_program),
+            AccountMeta::readonly(pool.mint_x),
+            AccountMeta::readonly(pool.mint_y),
+        ]),
+        data: pack(*DepositExact::DISCRIMINATOR, &data),
+    }
+}
+
+/// Deposit for `amount` LP quoted at `amount_x` / `amount_y`, paying at most
+/// `slippage_bps` more of either
+pub fn deposit_with_slippage_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    amount: u64,
+    amount_x: u64,
+    amount_y: u64,
+    slippage_bps: u16,
+    expiration: i64,
+) -> Instruction {
+    let mut ix = deposit_ix(user, pool, amount, amount_x, amount_y, expiration);
+    let data = DepositWithSlippageInstructionData {
+        amount,
+        amount_x,
+        amount_y,
+        slippage_bps,
+        expiration,
+    };
+    ix.data = pack(*DepositWithSlippage::DISCRIMINATOR, &data);
+    ix
+}
+
+/// Deposit `amount` of X (`is_x`) or Y alone, minting at least `min_lp`;
+/// `oracle` is the pool's Pyth price account, required if it has one
+pub fn deposit_single_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    is_x: bool,
+    amount: u64,
+    min_lp: u64,
+    expiration: i64,
+    oracle: Option<Address>,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, user_lp_ata] = pool.user_atas(&user);
+    let data = DepositSingleInstructionData {
+        is_x: is_x as u8,
+        amount,
+        min_lp,
+        expiration,
+    };
+    let mut accounts = Vec::from([
+        AccountMeta::readonly_signer(user),
+        AccountMeta::writable(pool.mint_lp, false),
+        AccountMeta::writable(pool.vault_x, false),
+        AccountMeta::writable(pool.vault_y, false),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(user_lp_ata, false),
+        AccountMeta::writable(pool.config, false),
+        AccountMeta::readonly(pool.token_program),
+        AccountMeta::readonly(pool.mint_x),
+        AccountMeta::readonly(pool.mint_y),
+    ]);
+    if let Some(oracle) = oracle {
+        accounts.push(AccountMeta::readonly(oracle));
+    }
+
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: pack(*DepositSingle::DISCRIMINATOR, &data),
+    }
+}
+
+/// Burn `amount` LP tokens, receiving at least `min_x` / `min_y`
+pub fn withdraw_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    amount: u64,
+    min_x: u64,
+    min_y: u64,
+    expiration: i64,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, user_lp_ata] = pool.user_atas(&user);
+    let data = WithdrawInstructionData {
+        amount,
+        min_x,
+        min_y,
+        expiration,
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(user),
+            AccountMeta::writable(pool.mint_lp, false),
+            AccountMeta::writable(pool.vault_x, false),
+            AccountMeta::writable(pool.vault_y, false),
+            AccountMeta::writable(user_x_ata, false),
+            AccountMeta::writable(user_y_ata, false),
+            AccountMeta::writable(user_lp_ata, false),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pool.token_program),
+            AccountMeta::readonly(pool.mint_x),
+            AccountMeta::readonly(pool.mint_y),
+        ]),
+        data: pack(*Withdraw::DISCRIMINATOR, &data),
+    }
+}
+
+/// Burn `amount` LP tokens for their share of a Disabled pool's reserves
+pub fn emergency_withdraw_ix(user: Address, pool: &PoolAddresses, amount: u64) -> Instruction {
+    let mut ix = withdraw_ix(user, pool, amount, 0, 0, 0);
+    ix.data = pack(
+        *EmergencyWithdraw::DISCRIMINATOR,
+        &EmergencyWithdrawInstructionData { amount },
+    );
+    ix
+}
+
+/// Burn `amount` LP tokens quoted at `amount_x` / `amount_y`, receiving at
+/// most `slippage_bps` less of either
+pub fn withdraw_with_slippage_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    amount: u64,
+    amount_x: u64,
+    amount_y: u64,
+    slippage_bps: u16,
+    expiration: i64,
+) -> Instruction {
+    let mut ix = withdraw_ix(user, pool, amount, amount_x, amount_y, expiration);
+    let data = WithdrawWithSlippageInstructionData {
+        amount,
+        amount_x,
+        amount_y,
+        slippage_bps,
+        expiration,
+    };
+    ix.data = pack(*WithdrawWithSlippage::DISCRIMINATOR, &data);
+    ix
+}
+
+/// Burn `amount` LP tokens for X (`is_x`) or Y alone, receiving at least
+/// `min_out`; `oracle` is the pool's Pyth price account, required if it has one
+pub fn withdraw_single_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    is_x: bool,
+    amount: u64,
+    min_out: u64,
+    expiration: i64,
+    oracle: Option<Address>,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, user_lp_ata] = pool.user_atas(&user);
+    let data = WithdrawSingleInstructionData {
+        is_x: is_x as u8,
+        amount,
+        min_out,
+        expiration,
+    };
+    let mut accounts = Vec::from([
+        AccountMeta::readonly_signer(user),
+        AccountMeta::writable(pool.mint_lp, false),
+        AccountMeta::writable(pool.vault_x, false),
+        AccountMeta::writable(pool.vault_y, false),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(user_lp_ata, false),
+        AccountMeta::writable(pool.config, false),
+        AccountMeta::readonly(pool.token_program),
+        AccountMeta::readonly(pool.mint_x),
+        AccountMeta::readonly(pool.mint_y),
+    ]);
+    if let Some(oracle) = oracle {
+        accounts.push(AccountMeta::readonly(oracle));
+    }
+
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: pack(*WithdrawSingle::DISCRIMINATOR, &data),
+    }
+}
+
+/// Swap `amount` of X (`is_x`) or Y, receiving at least `min`; `referrer` is
+/// a token account in the input token paid the pool's referral share of the fee,
+/// and `oracle` the pool's Pyth price account, required if it has one
+#[allow(clippy::too_many_arguments)]
+pub fn swap_ix(
+    user: Address,
+    pool: &PoolAddresses,
+    is_x: bool,
+    amount: u64,
+    min: u64,
+    expiration: i64,
+    referrer: Option<Address>,
+    oracle: Option<Address>,
+) -> Instruction {
+    let [user_x_ata, user_y_ata, _] = pool.user_atas(&user);
+    let data = SwapInstructionData {
+        is_x: is_x as u8,
+        amount,
+        min,
+        expiration,
+    };
+    let mut accounts = Vec::from([
+        AccountMeta::readonly_signer(user),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(pool.vault_x, false),
+        AccountMeta::writable(pool.vault_y, false),
+        AccountMeta::writable(pool.config, false),
+        AccountMeta::readonly(pool.token_program),
+        AccountMeta::readonly(pool.mint_x),
+        AccountMeta::readonly(pool.mint_y),
+    ]);
+    if let Some(referrer) = referrer {
+        accounts.push(AccountMeta::writable(referrer, false));
+    }
+    if let Some(oracle) = oracle {
+        if referrer.is_none() {
+            accounts.push(AccountMeta::readonly(crate::ID));
+        }
+        accounts.push(AccountMeta::readonly(oracle));
+    }
+
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: pack(*Swap::DISCRIMINATOR, &data),
+    }
+}
+
+/// Have `delegate`, approved on the input token account of a `swap_ix` built
+/// for its owner, sign the swap instead of the owner; the output still goes to
+/// the owner
+pub fn signed_by_delegate(mut ix: Instruction, delegate: Address) -> Instruction {
+    ix.accounts[0] = AccountMeta::readonly_signer(delegate);
+    ix
+}
+
+/// Pay a `deposit_ix`, `withdraw_ix` or `swap_ix` instruction's native-mint
+/// side (wSOL) in or out of the user's wallet as lamports. The user's wSOL
+/// account must exist; it is closed by the instruction.
+pub fn with_native_sol(mut ix: Instruction) -> Instruction {
+    // The wallet pays the wrapped lamports and receives the unwrapped ones
+    ix.accounts[0].is_writable = true;
+
+    // A swap's first tail account is the referrer; keep the slot empty
+    if ix.data[0] == *Swap::DISCRIMINATOR && ix.accounts.len() == 9 {
+        ix.accounts.push(AccountMeta::readonly(crate::ID));
+    }
+    ix.accounts.push(AccountMeta::readonly(pinocchio_system::ID));
+    ix.data.push(1);
+    ix
+}
+
+/// Give an `initialize_ix` pool's LP mint `lp_decimals` (at most
+/// `MAX_LP_DECIMALS`) instead of `DEFAULT_LP_DECIMALS`
+pub fn with_lp_decimals(mut ix: Instruction, lp_decimals: u8) -> Instruction {
+    ix.data[1 + offset_of!(InitializeInstructionData, lp_decimals)] = lp_decimals;
+    ix
+}
+
+/// Close a pool with no LP supply and empty vaults, sending the rent to
+/// `destination`
+pub fn close_pool_ix(
+    authority: Address,
+    pool: &PoolAddresses,
+    destination: Address,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(authority),
+            AccountMeta::writable(destination, false),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pool.mint_lp),
+            AccountMeta::writable(pool.vault_x, false),
+            AccountMeta::writable(pool.vault_y, false),
+            AccountMeta::readonly(pool.token_program),
+        ]),
+        data: Vec::from([*ClosePool::DISCRIMINATOR]),
+    }
+}
+
+/// Turn the pool's per-slot swap throttle on or off
+pub fn set_swap_throttle_ix(
+    authority: Address,
+    pool: &PoolAddresses,
+    enabled: bool,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(authority),
+            AccountMeta::writable(pool.config, false),
+        ]),
+        data: pack(
+            *SetSwapThrottle::DISCRIMINATOR,
+            &SetSwapThrottleInstructionData {
+                enabled: enabled as u8,
+            },
+        ),
+    }
+}
+
+/// Bring the pool's config to the current layout, with `payer` funding the
+/// extra rent
+pub fn migrate_config_ix(payer: Address, pool: &PoolAddresses) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(payer, true),
+            AccountMeta::writable(pool.config, false),
+            AccountMeta::readonly(pool.vault_x),
+            AccountMeta::readonly(pool.vault_y),
+            AccountMeta::readonly(pool.token_program),
+            AccountMeta::readonly(pinocchio_system::ID),
+        ]),
+        data: Vec::from([*MigrateConfig::DISCRIMINATOR]),
+    }
+}
+
+/// Create the deployment's protocol config with `authority` (who pays for it)
+/// in charge: pools may be created with fees from `min_fee` to `max_fee`, and
+/// start with a `protocol_fee` share of them
+pub fn initialize_protocol_ix(
+    authority: Address,
+    min_fee: u16,
+    max_fee: u16,
+    protocol_fee: u16,
+) -> Instruction {
+    let (protocol, bump) = protocol_config_address();
+    let data = InitializeProtocolInstructionData {
+        min_fee,
+        max_fee,
+        protocol_fee,
+        bump: [bump],
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(authority, true),
+            AccountMeta::writable(protocol, false),
+            AccountMeta::readonly(pinocchio_system::ID),
+        ]),
+        data: pack(*InitializeProtocol::DISCRIMINATOR, &data),
+    }
+}
+
+/// Replace the protocol config's pool fee bounds and default protocol share
+pub fn set_protocol_config_ix(
+    authority: Address,
+    min_fee: u16,
+    max_fee: u16,
+    protocol_fee: u16,
+) -> Instruction {
+    let data = SetProtocolConfigInstructionData {
+        min_fee,
+        max_fee,
+        protocol_fee,
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::readonly_signer(authority),
+            AccountMeta::writable(protocol_config_address().0, false),
+        ]),
+        data: pack(*SetProtocolConfig::DISCRIMINATOR, &data),
+    }
+}
+
+/// Give the pool's LP mint a name, symbol and URI for wallets to show; the
+/// authority pays the metadata account's rent
+///
+/// Panics if a string is longer than the metadata program allows.
+pub fn create_lp_metadata_ix(
+    authority: Address,
+    pool: &PoolAddresses,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Instruction {
+    let mut data = CreateLpMetadataInstructionData {
+        name: [0; 32],
+        symbol: [0; 10],
+        uri: [0; 200],
+    };
+    data.name[..name.len()].copy_from_slice(name.as_bytes());
+    data.symbol[..symbol.len()].copy_from_slice(symbol.as_bytes());
+    data.uri[..uri.len()].copy_from_slice(uri.as_bytes());
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(authority, true),
+            AccountMeta::readonly(pool.config),
+            AccountMeta::readonly(pool.mint_lp),
+            AccountMeta::writable(metadata_address(&pool.mint_lp), false),
+            AccountMeta::readonly(METADATA_PROGRAM_ID),
+            AccountMeta::readonly(pinocchio_system::ID),
+        ]),
+        data: pack(*CreateLpMetadata::DISCRIMINATOR, &data),
+    }
+}
+
+/// Lock `amount` of `owner`'s LP tokens until `unlock_at`, vesting linearly
+/// until then if `linear`; `id` must be unused among the owner's locks in the
+/// pool. The lock's LP token account (its ATA) must exist.
+pub fn lock_liquidity_ix(
+    owner: Address,
+    pool: &PoolAddresses,
+    id: u64,
+    amount: u64,
+    unlock_at: i64,
+    linear: bool,
+) -> Instruction {
+    let [_, _, owner_lp_ata] = pool.user_atas(&owner);
+    let (lock, lock_bump) = lock_address(&pool.config, &owner, id);
+    let data = LockLiquidityInstructionData {
+        id,
+        amount,
+        unlock_at,
+        linear: linear as u8,
+        lock_bump: [lock_bump],
+    };
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(owner, true),
+            AccountMeta::readonly(pool.config),
+            AccountMeta::writable(lock, false),
+            AccountMeta::writable(owner_lp_ata, false),
+            AccountMeta::writable(pool.user_atas(&lock)[2], false),
+            AccountMeta::readonly(pool.mint_lp),
+            AccountMeta::readonly(pinocchio_system::ID),
+            AccountMeta::readonly(pool.token_program),
+        ]),
+        data: pack(*LockLiquidity::DISCRIMINATOR, &data),
+    }
+}
+
+/// Take out what has vested of `owner`'s lock `id`
+pub fn unlock_liquidity_ix(owner: Address, pool: &PoolAddresses, id: u64) -> Instruction {
+    let [_, _, owner_lp_ata] = pool.user_atas(&owner);
+    let (lock, _) = lock_address(&pool.config, &owner, id);
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: Vec::from([
+            AccountMeta::writable(owner, true),
+            AccountMeta::writable(lock, false),
+            AccountMeta::writable(owner_lp_ata, false),
+            AccountMeta::writable(pool.user_atas(&lock)[2], false),
+            AccountMeta::readonly(pool.mint_lp),
+            AccountMeta::readonly(pool.token_program),
+        ]),
+        data: Vec::from([*UnlockLiquidity::DISCRIMINATOR]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> PoolAddresses {
+        PoolAddresses::find(
+            7,
+            Address::new_from_array([1; 32]),
+            Address::new_from_array([2; 32]),
+            pinocchio_token::ID,
+        )
+    }
+
+    #[test]
+    fn swap_data_matches_the_program_layout() {
+        let user = Address::new_from_array([9; 32]);
+        let ix = swap_ix(user, &pool(), true, 100, 90, -1, None, None);
+        let data = &ix.data;
+        assert_eq!(data.len(), 1 + 1 + 8 + 8 + 8);
+        assert_eq!(data[0], *Swap::DISCRIMINATOR);
+        assert_eq!(data[1], 1);
+        assert_eq!(data[2..10], 100u64.to_le_bytes());
+        assert_eq!(data[10..18], 90u64.to_le_bytes());
+        assert_eq!(data[18..], (-1i64).to_le_bytes());
+        assert!(SwapInstructionData::try_from(&data[1..]).is_ok());
+        assert_eq!(ix.accounts.len(), 9);
+
+        let referrer = Address::new_from_array([8; 32]);
+        let referred = swap_ix(user, &pool(), true, 100, 90, -1, Some(referrer), None);
+        assert_eq!(referred.data, ix.data);
+        assert_eq!(referred.accounts[9], AccountMeta::writable(referrer, false));
+
+        // The oracle goes in the tail, after a placeholder when there's no referrer
+        let oracle = Address::new_from_array([6; 32]);
+        let guarded = swap_ix(user, &pool(), true, 100, 90, -1, None, Some(oracle));
+        assert_eq!(guarded.accounts[9], AccountMeta::readonly(crate::ID));
+        assert_eq!(guarded.accounts[10], AccountMeta::readonly(oracle));
+    }
+
+    #[test]
+    fn native_sol_keeps_the_referrer_slot_and_flags_the_data() {
+        let user = Address::new_from_array([9; 32]);
+        let ix = with_native_sol(swap_ix(user, &pool(), true, 100, 90, -1, None, None));
+        assert_eq!(ix.accounts[0], AccountMeta::writable(user, true));
+        assert_eq!(ix.accounts[9], AccountMeta::readonly(crate::ID));
+        assert_eq!(ix.accounts[10], AccountMeta::readonly(pinocchio_system::ID));
+
+        let (data, native) =
+            crate::native::split_native_flag::<SwapInstructionData>(&ix.data[1..]);
+        assert!(native);
+        assert!(SwapInstructionData::try_from(data).is_ok());
+
+        let deposit = with_native_sol(deposit_ix(user, &pool(), 10, 20, 30, -1));
+        assert_eq!(deposit.accounts.len(), 12);
+        assert_eq!(deposit.data.last(), Some(&1));
+    }
+
+    #[test]
+    fn immutable_pools_use_the_short_initialize_form() {
+        let pool = pool();
+        let initializer = Address::new_from_array([9; 32]);
+        let immutable = initialize_ix(initializer, &pool, 7, 30, None);
+        let owned = initialize_ix(initializer, &pool, 7, 30, Some(initializer));
+
+        assert_eq!(immutable.data.len() + 32, owned.data.len());
+        assert_eq!(owned.data[owned.data.len() - 32..], [9; 32]);
+        assert!(InitializeInstructionData::try_from(&immutable.data[1..]).is_ok());
+        assert!(InitializeInstructionData::try_from(&owned.data[1..]).is_ok());
+    }
+
+    #[test]
+    fn pool_addresses_are_the_program_pdas() {
+        let pool = pool();
+        let config = Address::create_program_address(
+            &[
+                POOL_CONFIG_SEED,
+                &7u64.to_le_bytes(),
+                pool.mint_x.as_ref(),
+                pool.mint_y.as_ref(),
+                &[pool.config_bump],
+            ],
+            &crate::ID,
+        )
+        .unwrap();
+        assert_eq!(config, pool.config);
+    }
+}
@@ -0,0 +1,205 @@
+//! Constant product math for swaps, deposits and withdrawals.
+//!
+//! Intermediates are u128 (or 256-bit inside `mul_div`) with checked
+//! arithmetic, and every division rounds in the pool's favour: fees and
+//! deposits up, swap outputs and withdrawals down. Any sequence of operations
+//! therefore leaves each LP token backed by at least as much as before.
+
+use pinocchio::error::ProgramError;
+
+use crate::{math::mul_div, AmmError};
+
+/// Basis points in one
+const BPS: u128 = 10_000;
+
+/// A priced swap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Amount going into the pool, fee included
+    pub deposit: u64,
+    /// Amount leaving the pool
+    pub withdraw: u64,
+    /// Part of `deposit` charged as the swap fee
+    pub fee: u64,
+}
+
+/// Swap `amount_in` into a pool holding `reserve_in` / `reserve_out`, charging
+/// `fee` (bps) on the input. Only the input net of the fee is priced, so the
+/// fee stays in the pool on top of the product.
+pub fn swap(
+    reserve_in: u64,
+    reserve_out: u64,
+    fee: u16,
+    amount_in: u64,
+) -> Result<SwapResult, ProgramError> {
+    if fee as u128 >= BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(AmmError::EmptyPool.into());
+    }
+
+    let fee_amount = to_u64(mul_div(amount_in as u128, fee as u128, BPS, true)?)?;
+    let net_in = (amount_in - fee_amount) as u128;
+    let withdraw = to_u64(mul_div(
+        net_in,
+        reserve_out as u128,
+        reserve_in as u128 + net_in,
+        false,
+    )?)?;
+
+    Ok(SwapResult {
+        deposit: amount_in,
+        withdraw,
+        fee: fee_amount,
+    })
+}
+
+/// Amounts of X and Y that must reach the vaults to mint `lp_amount` in a
+/// pool that already holds liquidity; rounded up
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if lp_supply == 0 {
+        return Err(AmmError::EmptyPool.into());
+    }
+    let x = mul_div(lp_amount as u128, reserve_x as u128, lp_supply as u128, true)?;
+    let y = mul_div(lp_amount as u128, reserve_y as u128, lp_supply as u128, true)?;
+    Ok((to_u64(x)?, to_u64(y)?))
+}
+
+/// Amounts of X and Y leaving the vaults when `lp_amount` of `lp_supply` is
+/// burned; rounded down, so burning the whole supply empties the reserves
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if lp_supply == 0 {
+        return Err(AmmError::EmptyPool.into());
+    }
+    if lp_amount > lp_supply {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let x = mul_div(lp_amount as u128, reserve_x as u128, lp_supply as u128, false)?;
+    let y = mul_div(lp_amount as u128, reserve_y as u128, lp_supply as u128, false)?;
+    Ok((to_u64(x)?, to_u64(y)?))
+}
+
+/// Fail with `ProductDecreased` unless `after` (x, y) balances multiply to at
+/// least what `before` did
+pub fn check_product(before: (u64, u64), after: (u64, u64)) -> Result<(), ProgramError> {
+    if (after.0 as u128 * after.1 as u128) < before.0 as u128 * before.1 as u128 {
+        return Err(AmmError::ProductDecreased.into());
+    }
+    Ok(())
+}
+
+#[inline(always)]
+fn to_u64(value: u128) -> Result<u64, ProgramError> {
+    u64::try_from(value).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn swap_charges_the_fee_on_the_input() {
+        // 30 bps of 1_000 is 3; 997 priced against 1_000_000 / 1_000_000
+        assert_eq!(
+            swap(1_000_000, 1_000_000, 30, 1_000),
+            Ok(SwapResult {
+                deposit: 1_000,
+                withdraw: 996,
+                fee: 3,
+            })
+        );
+        // The fee rounds up: 30 bps of 1 is 1
+        assert_eq!(swap(1_000_000, 1_000_000, 30, 1).unwrap().fee, 1);
+        assert_eq!(
+            swap(0, 1_000_000, 30, 1_000),
+            Err(AmmError::EmptyPool.into())
+        );
+        assert_eq!(
+            swap(1_000, 1_000, 10_000, 1_000),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn deposits_round_up_and_withdrawals_down() {
+        assert_eq!(deposit_amounts(1_000, 3_000, 7, 1), Ok((143, 429)));
+        assert_eq!(withdraw_amounts(1_000, 3_000, 7, 1), Ok((142, 428)));
+        assert_eq!(withdraw_amounts(1_000, 3_000, 7, 7), Ok((1_000, 3_000)));
+        assert_eq!(
+            withdraw_amounts(1_000, 3_000, 7, 8),
+            Err(ProgramError::InsufficientFunds)
+        );
+        assert_eq!(
+            deposit_amounts(1_000, 3_000, 0, 1),
+            Err(AmmError::EmptyPool.into())
+        );
+    }
+
+    #[test]
+    fn the_product_may_only_grow() {
+        assert_eq!(check_product((1_000, 1_000), (1_100, 910)), Ok(()));
+        assert_eq!(check_product((1_000, 1_000), (1_000, 1_000)), Ok(()));
+        assert_eq!(
+            check_product((1_000, 1_000), (1_100, 909)),
+            Err(AmmError::ProductDecreased.into())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn swaps_never_shrink_the_product(
+            reserve_in in 1..=u64::MAX / 2,
+            reserve_out in 1..=u64::MAX,
+            fee in 0u16..10_000,
+            amount_in in 0..=u64::MAX / 2,
+        ) {
+            let Ok(result) = swap(reserve_in, reserve_out, fee, amount_in) else {
+                return Ok(());
+            };
+            prop_assert!(result.withdraw < reserve_out);
+            prop_assert!(result.fee <= amount_in);
+
+            // Even with the whole fee taken out of the pool
+            let net_in = (amount_in - result.fee) as u128;
+            let before = reserve_in as u128 * reserve_out as u128;
+            let after = (reserve_in as u128 + net_in) * (reserve_out - result.withdraw) as u128;
+            prop_assert!(after >= before);
+        }
+
+        #[test]
+        fn deposits_and_withdrawals_never_dilute_lps(
+            reserve_x in 1..=u64::MAX / 2,
+            reserve_y in 1..=u64::MAX / 2,
+            lp_supply in 1..=u64::MAX / 2,
+            lp_amount in 1..=u64::MAX / 2,
+        ) {
+            // Minting and burning the same LP never pays out more than it cost
+            if let Ok((x, y)) = deposit_amounts(reserve_x, reserve_y, lp_supply, lp_amount) {
+                if let (Some(rx), Some(ry)) = (reserve_x.checked_add(x), reserve_y.checked_add(y)) {
+                    let (out_x, out_y) =
+                        withdraw_amounts(rx, ry, lp_supply + lp_amount, lp_amount).unwrap();
+                    prop_assert!(out_x <= x && out_y <= y);
+                }
+            }
+
+            // What stays behind backs the remaining LP at least as well as before
+            let lp_amount = lp_amount.min(lp_supply);
+            let (x, y) = withdraw_amounts(reserve_x, reserve_y, lp_supply, lp_amount).unwrap();
+            let left = (lp_supply - lp_amount) as u128;
+            prop_assert!((reserve_x - x) as u128 * lp_supply as u128 >= reserve_x as u128 * left);
+            prop_assert!((reserve_y - y) as u128 * lp_supply as u128 >= reserve_y as u128 * left);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use pinocchio::error::ProgramError;
+
+/// Failures callers need to tell apart, returned as `ProgramError::Custom`.
+/// Code 1 is the older catch-all for expired orders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AmmError {
+    /// The swap would move the pool's price further than its
+    /// `max_price_impact_bps`
+    PriceImpactTooHigh = 2,
+    /// `MigrateConfig` on a config already at the current layout version
+    ConfigAlreadyMigrated = 3,
+    /// The swap's execution price is further from the pool's Pyth price than
+    /// its `oracle_band_bps`
+    OraclePriceOutOfBand = 4,
+    /// The pool's Pyth price is stale, not trading or not positive
+    OraclePriceUnavailable = 5,
+    /// Pricing against a pool without reserves or LP supply
+    EmptyPool = 6,
+    /// The vaults multiply to less after a swap than before it
+    ProductDecreased = 7,
+    /// `ClosePool` while LP tokens are outstanding or a vault holds tokens
+    PoolNotEmpty = 8,
+    /// `Initialize` with a fee that isn't one of `FEE_TIERS`
+    UnsupportedFeeTier = 9,
+    /// `UnlockLiquidity` with nothing vested that isn't released yet
+    LiquidityLocked = 10,
+    /// A signer swapping back the other way in the slot of its last swap,
+    /// on a pool with the swap throttle on
+    SwapThrottled = 11,
+    /// `Initialize` with a fee outside the protocol config's bounds
+    FeeOutOfBounds = 12,
+}
+
+impl From<AmmError> for ProgramError {
+    fn from(error: AmmError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
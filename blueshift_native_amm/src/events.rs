@@ -0,0 +1,173 @@
+//! Binary events logged with `sol_log_data`.
+//!
+//! Each event is logged as two fields: its one-byte discriminator and its
+//! `#[repr(C, packed)]` body, integers little-endian. Events that move reserves
+//! carry the reserves after the change, so an indexer can follow a pool's
+//! price and depth from the logs alone.
+
+use pinocchio::Address;
+
+/// A `#[repr(C, packed)]` plain-data event
+pub trait Event: Sized {
+    const DISCRIMINATOR: u8;
+
+    #[inline(always)]
+    fn as_bytes(&self) -> &[u8] {
+        // Events are packed structs of integers and addresses: no padding,
+        // no pointers
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    #[inline(always)]
+    fn emit(&self) {
+        log_data(&[&[Self::DISCRIMINATOR], self.as_bytes()]);
+    }
+}
+
+#[inline(always)]
+fn log_data(data: &[&[u8]]) {
+    #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+    unsafe {
+        pinocchio::syscalls::sol_log_data(data.as_ptr() as *const u8, data.len() as u64);
+    }
+
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+    core::hint::black_box(data);
+}
+
+// ==================== Events ====================
+
+/// Logged by `Initialize`
+#[repr(C, packed)]
+pub struct PoolCreated {
+    pub pool: Address,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub mint_lp: Address,
+    pub authority: Address,
+    pub seed: u64,
+    pub fee: u16,
+}
+
+impl Event for PoolCreated {
+    const DISCRIMINATOR: u8 = 0;
+}
+
+/// Logged by `Swap` and each hop of `Route`
+#[repr(C, packed)]
+pub struct SwapEvent {
+    pub pool: Address,
+    pub user: Address,
+    pub is_x: u8,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for SwapEvent {
+    const DISCRIMINATOR: u8 = 1;
+}
+
+/// Logged by `Deposit`; amounts are what the user paid
+#[repr(C, packed)]
+pub struct AddLiquidityEvent {
+    pub pool: Address,
+    pub user: Address,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_minted: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for AddLiquidityEvent {
+    const DISCRIMINATOR: u8 = 2;
+}
+
+/// Logged by `Withdraw`; amounts are what the user received
+#[repr(C, packed)]
+pub struct RemoveLiquidityEvent {
+    pub pool: Address,
+    pub user: Address,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_burned: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for RemoveLiquidityEvent {
+    const DISCRIMINATOR: u8 = 3;
+}
+
+/// Logged by `Sync` once donations are folded into the reserves
+#[repr(C, packed)]
+pub struct SyncEvent {
+    pub pool: Address,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for SyncEvent {
+    const DISCRIMINATOR: u8 = 4;
+}
+
+/// Logged by `FlashLoan`; the LP share of the fee is already in the reserves
+#[repr(C, packed)]
+pub struct FlashLoanEvent {
+    pub pool: Address,
+    pub borrower: Address,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub fee_x: u64,
+    pub fee_y: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for FlashLoanEvent {
+    const DISCRIMINATOR: u8 = 5;
+}
+
+/// Logged by `Donate`; amounts are what reached the vaults
+#[repr(C, packed)]
+pub struct DonateEvent {
+    pub pool: Address,
+    pub donor: Address,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+impl Event for DonateEvent {
+    const DISCRIMINATOR: u8 = 6;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_packed_in_field_order() {
+        let event = SyncEvent {
+            pool: Address::new_from_array([7; 32]),
+            reserve_x: 1,
+            reserve_y: u64::MAX,
+        };
+        let bytes = event.as_bytes();
+        assert_eq!(bytes.len(), 48);
+        assert_eq!(&bytes[..32], &[7; 32]);
+        assert_eq!(&bytes[32..40], &1u64.to_le_bytes());
+        assert_eq!(&bytes[40..], &[0xff; 8]);
+
+        assert_eq!(core::mem::size_of::<SwapEvent>(), 32 + 32 + 1 + 5 * 8);
+    }
+}
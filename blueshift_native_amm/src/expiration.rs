@@ -0,0 +1,58 @@
+//! Order deadlines.
+//!
+//! `expiration` fields hold a unix timestamp, or a slot when tagged with
+//! `SLOT_EXPIRATION`; slots are what MEV-aware traders reason in, as they
+//! don't drift with validator clocks. Timestamps that large are never
+//! reached, so existing deadlines keep their meaning.
+
+use pinocchio::{error::ProgramError, sysvars::clock::Clock};
+
+/// Tag for deadlines in slots: `SLOT_EXPIRATION | slot`
+pub const SLOT_EXPIRATION: i64 = 1 << 62;
+
+/// Expiration for an order valid up to (not including) `slot`
+#[inline(always)]
+pub const fn slot_expiration(slot: u64) -> i64 {
+    SLOT_EXPIRATION | (slot & (SLOT_EXPIRATION as u64 - 1)) as i64
+}
+
+/// Whether an order with `expiration` is past its deadline at the given slot
+/// and unix timestamp
+#[inline(always)]
+pub const fn is_expired(expiration: i64, slot: u64, unix_timestamp: i64) -> bool {
+    match expiration >= SLOT_EXPIRATION {
+        true => slot >= (expiration ^ SLOT_EXPIRATION) as u64,
+        false => unix_timestamp >= expiration,
+    }
+}
+
+/// Fail with code 1 (order expired) once `clock` is past `expiration`
+#[inline(always)]
+pub fn check_expiration(expiration: i64, clock: &Clock) -> Result<(), ProgramError> {
+    if is_expired(expiration, clock.slot, clock.unix_timestamp) {
+        return Err(ProgramError::Custom(1)); // Order expired
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_expire_on_the_clock() {
+        assert!(!is_expired(1_000, u64::MAX, 999));
+        assert!(is_expired(1_000, 0, 1_000));
+        // Negative deadlines stay timestamps, long past
+        assert!(is_expired(-1, 0, 0));
+        assert!(!is_expired(i64::MAX, 0, i64::MAX - 1));
+    }
+
+    #[test]
+    fn slot_deadlines_expire_on_the_slot() {
+        let expiration = slot_expiration(500);
+        assert!(!is_expired(expiration, 499, i64::MAX));
+        assert!(is_expired(expiration, 500, 0));
+        assert!(is_expired(slot_expiration(0), 0, 0));
+    }
+}
@@ -0,0 +1,495 @@
+//! LP staking gauge: a per-pool PDA that holds staked LP tokens and streams a
+//! reward token to stakers pro rata over funded periods.
+//!
+//! Rewards accrue through a global `reward_per_share` (Q64.64, rewards per
+//! staked LP token) that each `StakePosition` settles against when its stake
+//! changes or it claims. Time with nothing staked earns nobody rewards; what
+//! was streamed over it stays in the reward vault.
+
+use core::mem::size_of;
+use pinocchio::{
+    AccountView,
+    account::{Ref, RefMut},
+    error::ProgramError,
+};
+
+use crate::math::mul_div;
+
+/// Fixed point one for `reward_per_share`
+const Q64: u128 = 1 << 64;
+
+// ==================== Gauge ====================
+
+/// PDA: ["gauge", config]
+#[repr(C)]
+pub struct Gauge {
+    config: [u8; 32],
+    reward_mint: [u8; 32],
+    lp_vault: [u8; 32],
+    reward_vault: [u8; 32],
+    reward_rate: [u8; 8],
+    period_end: [u8; 8],
+    last_update: [u8; 8],
+    reward_per_share: [u8; 16],
+    total_staked: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl Gauge {
+    pub const LEN: usize = size_of::<Gauge>();
+
+    // ==================== Read Helpers ====================
+
+    #[inline(always)]
+    pub fn load(account_view: &AccountView) -> Result<Ref<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    /// Return a `Gauge` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Gauge`.
+    /// `Gauge` has an alignment of 1 byte. This method does not perform a length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Gauge)
+    }
+
+    /// Return a mutable `Gauge` reference from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `Gauge`.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Gauge)
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &[u8; 32] {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn reward_mint(&self) -> &[u8; 32] {
+        &self.reward_mint
+    }
+
+    /// The gauge's LP token account (its ATA), holding all stakes
+    #[inline(always)]
+    pub fn lp_vault(&self) -> &[u8; 32] {
+        &self.lp_vault
+    }
+
+    /// The gauge's reward token account (its ATA), holding unstreamed rewards
+    #[inline(always)]
+    pub fn reward_vault(&self) -> &[u8; 32] {
+        &self.reward_vault
+    }
+
+    /// Reward tokens streamed per second until `period_end`
+    #[inline(always)]
+    pub fn reward_rate(&self) -> u64 {
+        u64::from_le_bytes(self.reward_rate)
+    }
+
+    #[inline(always)]
+    pub fn period_end(&self) -> i64 {
+        i64::from_le_bytes(self.period_end)
+    }
+
+    #[inline(always)]
+    pub fn last_update(&self) -> i64 {
+        i64::from_le_bytes(self.last_update)
+    }
+
+    #[inline(always)]
+    pub fn reward_per_share(&self) -> u128 {
+        u128::from_le_bytes(self.reward_per_share)
+    }
+
+    #[inline(always)]
+    pub fn total_staked(&self) -> u64 {
+        u64::from_le_bytes(self.total_staked)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check `lp_vault` against the recorded LP vault
+    #[inline(always)]
+    pub fn check_lp_vault(&self, lp_vault: &AccountView) -> Result<(), ProgramError> {
+        if lp_vault.address().as_ref() != self.lp_vault.as_slice() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// Check `reward_vault` (and its mint) against the recorded reward vault
+    #[inline(always)]
+    pub fn check_reward_vault(
+        &self,
+        reward_vault: &AccountView,
+        reward_mint: &AccountView,
+    ) -> Result<(), ProgramError> {
+        if reward_vault.address().as_ref() != self.reward_vault.as_slice()
+            || reward_mint.address().as_ref() != self.reward_mint.as_slice()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// `reward_per_share` brought up to `now` (capped at the period's end)
+    #[inline(always)]
+    pub fn reward_per_share_at(&self, now: i64) -> Result<u128, ProgramError> {
+        let stored = self.reward_per_share();
+        let total_staked = self.total_staked();
+        let applicable = now.min(self.period_end());
+        let last_update = self.last_update();
+        if total_staked == 0 || applicable <= last_update {
+            return Ok(stored);
+        }
+
+        let rewards = self.reward_rate() as u128 * (applicable - last_update) as u128;
+        let per_share = mul_div(rewards, Q64, total_staked as u128, false)?;
+        stored
+            .checked_add(per_share)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    // ==================== Write Helpers ====================
+
+    #[inline(always)]
+    pub fn load_mut(account_view: &AccountView) -> Result<RefMut<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(
+            account_view.try_borrow_mut()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    /// Load mutable reference without owner check.
+    /// Used when the account was just created.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the account is valid and not otherwise borrowed.
+    #[inline(always)]
+    pub unsafe fn load_mut_unchecked(account_view: &AccountView) -> Result<&mut Self, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self::from_bytes_unchecked_mut(
+            account_view.borrow_unchecked_mut(),
+        ))
+    }
+
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        config: [u8; 32],
+        reward_mint: [u8; 32],
+        bump: [u8; 1],
+    ) {
+        self.config = config;
+        self.reward_mint = reward_mint;
+        self.lp_vault = [0; 32];
+        self.reward_vault = [0; 32];
+        self.reward_rate = [0; 8];
+        self.period_end = [0; 8];
+        self.last_update = [0; 8];
+        self.reward_per_share = [0; 16];
+        self.total_staked = [0; 8];
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn set_vaults(&mut self, lp_vault: [u8; 32], reward_vault: [u8; 32]) {
+        self.lp_vault = lp_vault;
+        self.reward_vault = reward_vault;
+    }
+
+    /// Accrue rewards up to `now`; call before any stake changes. Returns the
+    /// current `reward_per_share`.
+    #[inline(always)]
+    pub fn update(&mut self, now: i64) -> Result<u128, ProgramError> {
+        let reward_per_share = self.reward_per_share_at(now)?;
+        self.reward_per_share = reward_per_share.to_le_bytes();
+
+        let applicable = now.min(self.period_end());
+        if applicable > self.last_update() {
+            self.last_update = applicable.to_le_bytes();
+        }
+        Ok(reward_per_share)
+    }
+
+    /// Stream `amount` (plus whatever is left of the current period) over the
+    /// next `duration` seconds
+    #[inline(always)]
+    pub fn notify_reward(
+        &mut self,
+        amount: u64,
+        duration: u64,
+        now: i64,
+    ) -> Result<(), ProgramError> {
+        if duration == 0 || duration > i64::MAX as u64 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.update(now)?;
+
+        let period_end = self.period_end();
+        let leftover = match now < period_end {
+            true => (period_end - now) as u128 * self.reward_rate() as u128,
+            false => 0,
+        };
+        let reward_rate = (amount as u128 + leftover) / duration as u128;
+        if reward_rate == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let reward_rate = u64::try_from(reward_rate).map_err(|_| ProgramError::ArithmeticOverflow)?;
+        let period_end = now
+            .checked_add(duration as i64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        self.reward_rate = reward_rate.to_le_bytes();
+        self.last_update = now.to_le_bytes();
+        self.period_end = period_end.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn add_stake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let total_staked = self.total_staked().checked_add(amount);
+        self.total_staked = total_staked.ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn remove_stake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let total_staked = self.total_staked().checked_sub(amount);
+        self.total_staked = total_staked.ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        Ok(())
+    }
+}
+
+// ==================== StakePosition ====================
+
+/// PDA: ["stake", gauge, owner]
+#[repr(C)]
+pub struct StakePosition {
+    gauge: [u8; 32],
+    owner: [u8; 32],
+    amount: [u8; 8],
+    reward_per_share_paid: [u8; 16],
+    rewards_owed: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl StakePosition {
+    pub const LEN: usize = size_of::<StakePosition>();
+
+    /// Return a mutable `StakePosition` reference from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `StakePosition`.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut StakePosition)
+    }
+
+    #[inline(always)]
+    pub fn gauge(&self) -> &[u8; 32] {
+        &self.gauge
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &[u8; 32] {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    #[inline(always)]
+    pub fn reward_per_share_paid(&self) -> u128 {
+        u128::from_le_bytes(self.reward_per_share_paid)
+    }
+
+    #[inline(always)]
+    pub fn rewards_owed(&self) -> u64 {
+        u64::from_le_bytes(self.rewards_owed)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check that this is `owner`'s position in `gauge`
+    #[inline(always)]
+    pub fn check(&self, gauge: &AccountView, owner: &AccountView) -> Result<(), ProgramError> {
+        if gauge.address().as_ref() != self.gauge.as_slice() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if owner.address().as_ref() != self.owner.as_slice() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        Ok(())
+    }
+
+    // ==================== Write Helpers ====================
+
+    #[inline(always)]
+    pub fn load_mut(account_view: &AccountView) -> Result<RefMut<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(
+            account_view.try_borrow_mut()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, gauge: [u8; 32], owner: [u8; 32], bump: [u8; 1]) {
+        self.gauge = gauge;
+        self.owner = owner;
+        self.amount = [0; 8];
+        self.reward_per_share_paid = [0; 16];
+        self.rewards_owed = [0; 8];
+        self.bump = bump;
+    }
+
+    /// Credit what the stake earned up to `reward_per_share`
+    #[inline(always)]
+    pub fn settle(&mut self, reward_per_share: u128) -> Result<(), ProgramError> {
+        let per_share = reward_per_share
+            .checked_sub(self.reward_per_share_paid())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let earned = mul_div(self.amount() as u128, per_share, Q64, false)?;
+        let rewards_owed = u64::try_from(self.rewards_owed() as u128 + earned)
+            .map_err(|_| ProgramError::ArithmeticOverflow)?;
+
+        self.rewards_owed = rewards_owed.to_le_bytes();
+        self.reward_per_share_paid = reward_per_share.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn add_stake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let staked = self.amount().checked_add(amount);
+        self.amount = staked.ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn remove_stake(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let staked = self.amount().checked_sub(amount);
+        self.amount = staked.ok_or(ProgramError::InsufficientFunds)?.to_le_bytes();
+        Ok(())
+    }
+
+    /// Take the settled rewards for payout
+    #[inline(always)]
+    pub fn take_rewards(&mut self) -> u64 {
+        let rewards_owed = self.rewards_owed();
+        self.rewards_owed = [0; 8];
+        rewards_owed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gauge() -> [u8; Gauge::LEN] {
+        let mut bytes = [0u8; Gauge::LEN];
+        let gauge = unsafe { Gauge::from_bytes_unchecked_mut(&mut bytes) };
+        gauge.set_inner([1; 32], [2; 32], [255]);
+        bytes
+    }
+
+    fn position() -> [u8; StakePosition::LEN] {
+        let mut bytes = [0u8; StakePosition::LEN];
+        let position = unsafe { StakePosition::from_bytes_unchecked_mut(&mut bytes) };
+        position.set_inner([5; 32], [6; 32], [254]);
+        bytes
+    }
+
+    /// Settle, then change the stake, as the instructions do
+    fn stake(gauge: &mut Gauge, position: &mut StakePosition, amount: u64, now: i64) {
+        let reward_per_share = gauge.update(now).unwrap();
+        position.settle(reward_per_share).unwrap();
+        position.add_stake(amount).unwrap();
+        gauge.add_stake(amount).unwrap();
+    }
+
+    #[test]
+    fn rewards_stream_pro_rata_to_stake() {
+        let mut gauge_bytes = gauge();
+        let gauge = unsafe { Gauge::from_bytes_unchecked_mut(&mut gauge_bytes) };
+        let (mut a, mut b) = (position(), position());
+        let a = unsafe { StakePosition::from_bytes_unchecked_mut(&mut a) };
+        let b = unsafe { StakePosition::from_bytes_unchecked_mut(&mut b) };
+
+        // 1_000 over 100s = 10/s; A alone for 50s, then A and B equally
+        gauge.notify_reward(1_000, 100, 1_000).unwrap();
+        stake(gauge, a, 10, 1_000);
+        stake(gauge, b, 10, 1_050);
+
+        // Past the period end nothing more accrues
+        let reward_per_share = gauge.update(1_200).unwrap();
+        a.settle(reward_per_share).unwrap();
+        b.settle(reward_per_share).unwrap();
+        assert_eq!(a.take_rewards(), 750);
+        assert_eq!(b.take_rewards(), 250);
+        assert_eq!(a.take_rewards(), 0);
+    }
+
+    #[test]
+    fn refunding_rolls_the_leftover_into_the_new_period() {
+        let mut gauge_bytes = gauge();
+        let gauge = unsafe { Gauge::from_bytes_unchecked_mut(&mut gauge_bytes) };
+
+        gauge.notify_reward(1_000, 100, 0).unwrap();
+        gauge.notify_reward(500, 100, 50).unwrap();
+        assert_eq!(gauge.reward_rate(), 10); // (500 left + 500) / 100
+        assert_eq!(gauge.period_end(), 150);
+
+        // Too little to stream a whole token per second
+        assert_eq!(gauge.notify_reward(99, 100, 200), Err(ProgramError::InvalidArgument));
+        assert_eq!(gauge.notify_reward(1, 0, 200), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn unstaking_more_than_staked_fails() {
+        let mut bytes = position();
+        let position = unsafe { StakePosition::from_bytes_unchecked_mut(&mut bytes) };
+        position.add_stake(5).unwrap();
+        assert_eq!(position.remove_stake(6), Err(ProgramError::InsufficientFunds));
+        assert_eq!(position.remove_stake(5), Ok(()));
+    }
+}
@@ -0,0 +1,64 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct AcceptAuthorityAccounts<'a> {
+    pub pending_authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AcceptAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [pending_authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !pending_authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            pending_authority,
+            config,
+        })
+    }
+}
+
+// ==================== AcceptAuthority Instruction ====================
+
+pub struct AcceptAuthority<'a> {
+    pub accounts: AcceptAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AcceptAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = AcceptAuthorityAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAuthority<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the signer is the proposed authority
+        //    (the zero address never signs, so nothing pending never matches)
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let pending_authority = *config.pending_authority();
+        if self.accounts.pending_authority.address().as_ref() != pending_authority.as_slice() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        // 2. Hand over the pool and clear the proposal
+        config.set_authority(pending_authority);
+        config.set_pending_authority([0; 32]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    expiration::check_expiration, instructions::route::LEG_ACCOUNTS, RouteAccounts, SwapLeg,
+};
+
+// ==================== Instruction Data ====================
+
+/// Fixed part of the data; one `BatchSwapSlice` per swap follows
+#[repr(C, packed)]
+pub struct BatchSwapInstructionData {
+    pub min_out: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for BatchSwapInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+/// One swap of the batch, against the pool of the matching account group
+#[repr(C, packed)]
+pub struct BatchSwapSlice {
+    pub is_x: u8, // bool as u8 for packed struct
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for BatchSwapSlice {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+impl BatchSwapSlice {
+    #[inline]
+    pub fn is_x(&self) -> bool {
+        self.is_x != 0
+    }
+}
+
+// ==================== BatchSwap Instruction ====================
+
+/// Several swaps executed one after the other, all or nothing, e.g. an order
+/// sliced TWAP-style. Takes `Route`'s accounts, one group per slice; groups
+/// may repeat a pool. Every slice must pay into the same user token account,
+/// and `min_out` is checked once, on the total delivered there.
+pub struct BatchSwap<'a> {
+    pub accounts: RouteAccounts<'a>,
+    pub instruction_data: BatchSwapInstructionData,
+    pub slices: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for BatchSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RouteAccounts::try_from(accounts)?;
+
+        let header = core::mem::size_of::<BatchSwapInstructionData>();
+        if data.len() < header {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (data, slices) = data.split_at(header);
+        let instruction_data = BatchSwapInstructionData::try_from(data)?;
+
+        // Validate the aggregate minimum is greater than zero
+        if instruction_data.min_out == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Validate slice count and that every slice has its accounts
+        let slice_len = core::mem::size_of::<BatchSwapSlice>();
+        if slices.len() % slice_len != 0
+            || !(1..=Self::MAX_SLICES).contains(&(slices.len() / slice_len))
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if accounts.legs.len() != slices.len() / slice_len * LEG_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            slices,
+        })
+    }
+}
+
+impl<'a> BatchSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+
+    /// Most swaps a single batch may hold
+    pub const MAX_SLICES: usize = 8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
+        let clock = Clock::get()?;
+        check_expiration(self.instruction_data.expiration, &clock)?;
+
+        // 2. Swap each slice through its pool, adding up what it delivers
+        let mut total_out: u64 = 0;
+        let mut destination: Option<&AccountView> = None;
+
+        for (accounts, slice) in self
+            .accounts
+            .legs
+            .chunks_exact(LEG_ACCOUNTS)
+            .zip(self.slices.chunks_exact(core::mem::size_of::<BatchSwapSlice>()))
+        {
+            let [config, vault_x, vault_y, mint_x, mint_y, user_x_ata, user_y_ata] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let slice = BatchSwapSlice::try_from(slice)?;
+            if slice.amount == 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let leg = SwapLeg {
+                config,
+                vault_x,
+                vault_y,
+                mint_x,
+                mint_y,
+                user_x_ata,
+                user_y_ata,
+                referrer: None,
+                // No tail accounts: pools with transfer hooks or an oracle
+                // can't be batched
+                hook_accounts: &[],
+            };
+
+            let is_x = slice.is_x();
+            let user_out = match is_x {
+                true => user_y_ata,
+                false => user_x_ata,
+            };
+
+            // All slices must pay into one account for `min_out` to add up
+            match destination {
+                Some(destination) if destination.address() != user_out.address() => {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                _ => destination = Some(user_out),
+            }
+
+            // Single slices only need to produce something; the caller's
+            // minimum applies to the total
+            let amount_out = leg
+                .swap(
+                    self.accounts.user,
+                    self.accounts.token_program,
+                    is_x,
+                    slice.amount,
+                    1,
+                    &clock,
+                )?
+                .amount_out;
+            total_out = total_out
+                .checked_add(amount_out)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        // 3. Check for slippage on the total
+        if total_out < self.instruction_data.min_out {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
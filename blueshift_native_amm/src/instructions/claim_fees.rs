@@ -0,0 +1,147 @@
+use pinocchio::{
+    AccountView,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    token::{check_token_program, token_owner, MintInfo, TransferChecked},
+    Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct ClaimFeesAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub recipient_x_ata: &'a AccountView,
+    pub recipient_y_ata: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, vault_x, vault_y, recipient_x_ata, recipient_y_ata, token_program, mint_x, mint_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority,
+            config,
+            vault_x,
+            vault_y,
+            recipient_x_ata,
+            recipient_y_ata,
+            token_program,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+// ==================== ClaimFees Instruction ====================
+
+/// Pays the accrued protocol fees to the fee recipient (the authority unless
+/// one is set)
+pub struct ClaimFees<'a> {
+    pub accounts: ClaimFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ClaimFeesAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Verify the pool authority signed, then take the accrued fees
+        //    (config is released before the transfers, which borrow it as signer)
+        let (fees_x, fees_y) = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+            config.check_authority(self.accounts.authority)?;
+            if config.flash_loan_active() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let fees = (config.protocol_fees_x(), config.protocol_fees_y());
+            config.set_protocol_fees_x(0);
+            config.set_protocol_fees_y(0);
+            fees
+        };
+
+        let config = Config::load(self.accounts.config)?;
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Verify the token program, mints and destinations
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let epoch = Clock::get()?.epoch;
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, epoch)?;
+        config.check_fee_destination(&token_owner(
+            self.accounts.recipient_x_ata,
+            self.accounts.token_program,
+        )?)?;
+        config.check_fee_destination(&token_owner(
+            self.accounts.recipient_y_ata,
+            self.accounts.token_program,
+        )?)?;
+
+        // 4. Prepare config PDA signer for vault transfers
+        let config_seeds = config.signer_seeds();
+
+        // 5. Transfer the accrued token X fees to the fee recipient
+        if fees_x > 0 {
+            let config_signer = Signer::from(&config_seeds);
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.recipient_x_ata,
+                authority: self.accounts.config,
+                amount: fees_x,
+                decimals: mint_x.decimals,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[config_signer])?;
+        }
+
+        // 6. Transfer the accrued token Y fees to the fee recipient
+        if fees_y > 0 {
+            let config_signer = Signer::from(&config_seeds);
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.recipient_y_ata,
+                authority: self.accounts.config,
+                amount: fees_y,
+                decimals: mint_y.decimals,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[config_signer])?;
+        }
+
+        Ok(())
+    }
+}
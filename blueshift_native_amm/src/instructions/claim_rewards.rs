@@ -0,0 +1,113 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    token::{check_token_program, MintInfo, TransferChecked},
+    Gauge, StakePosition,
+};
+
+// ==================== Accounts ====================
+
+pub struct ClaimRewardsAccounts<'a> {
+    pub owner: &'a AccountView,
+    pub gauge: &'a AccountView,
+    pub position: &'a AccountView,
+    pub owner_reward_ata: &'a AccountView,
+    pub reward_vault: &'a AccountView,
+    pub reward_mint: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimRewardsAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, gauge, position, owner_reward_ata, reward_vault, reward_mint, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            owner,
+            gauge,
+            position,
+            owner_reward_ata,
+            reward_vault,
+            reward_mint,
+            token_program,
+        })
+    }
+}
+
+// ==================== ClaimRewards Instruction ====================
+
+/// Pays the owner every reward their position has earned so far
+pub struct ClaimRewards<'a> {
+    pub accounts: ClaimRewardsAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimRewards<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ClaimRewardsAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimRewards<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Settle and take the owed rewards. Scoped so the gauge isn't
+        //    borrowed when it signs below.
+        let clock = Clock::get()?;
+        let (rewards, config, bump) = {
+            let mut gauge = Gauge::load_mut(self.accounts.gauge)?;
+            gauge.check_reward_vault(self.accounts.reward_vault, self.accounts.reward_mint)?;
+            let mut position = StakePosition::load_mut(self.accounts.position)?;
+            position.check(self.accounts.gauge, self.accounts.owner)?;
+
+            position.settle(gauge.update(clock.unix_timestamp)?)?;
+            (position.take_rewards(), *gauge.config(), gauge.bump())
+        };
+
+        if rewards == 0 {
+            return Ok(());
+        }
+
+        // 2. Pay them from the reward vault, signed by the gauge
+        check_token_program(self.accounts.token_program)?;
+        let reward_mint =
+            MintInfo::load(self.accounts.reward_mint, self.accounts.token_program, clock.epoch)?;
+        let gauge_seeds = [
+            Seed::from(b"gauge"),
+            Seed::from(&config),
+            Seed::from(&bump),
+        ];
+        let gauge_signer = Signer::from(&gauge_seeds);
+
+        TransferChecked {
+            from: self.accounts.reward_vault,
+            mint: self.accounts.reward_mint,
+            to: self.accounts.owner_reward_ata,
+            authority: self.accounts.gauge,
+            amount: rewards,
+            decimals: reward_mint.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_signed(&[gauge_signer])?;
+
+        Ok(())
+    }
+}
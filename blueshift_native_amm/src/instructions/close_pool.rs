@@ -0,0 +1,127 @@
+use pinocchio::{
+    AccountView,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use solana_challenge_common::lamports;
+
+use crate::{
+    pda::check_mint_lp,
+    token::{check_token_program, token_amount, CloseAccount, MintInfo},
+    AmmError, Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct ClosePoolAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub destination: &'a AccountView,
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClosePoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, destination, config, mint_lp, vault_x, vault_y, token_program] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Rent sent to an account closed below would be lost
+        if [config, vault_x, vault_y]
+            .iter()
+            .any(|account| account.address() == destination.address())
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            authority,
+            destination,
+            config,
+            mint_lp,
+            vault_x,
+            vault_y,
+            token_program,
+        })
+    }
+}
+
+// ==================== ClosePool Instruction ====================
+
+/// Closes a dead pool (no LP supply, empty vaults), sending the rent of its
+/// vaults and config to `destination`. The LP mint stays: it is created
+/// without a close authority, so neither token program can close it, and
+/// the pool's seed can't be initialized again.
+pub struct ClosePool<'a> {
+    pub accounts: ClosePoolAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClosePool<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ClosePoolAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClosePool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Verify the pool authority signed and no flash loan is open
+        let config = Config::load(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+        if config.flash_loan_active() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Verify the LP mint is the pool's and fully burned
+        check_token_program(self.accounts.token_program)?;
+        check_mint_lp(self.accounts.config.address(), self.accounts.mint_lp.address())?;
+        let epoch = Clock::get()?.epoch;
+        if MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, epoch)?.supply != 0 {
+            return Err(AmmError::PoolNotEmpty.into());
+        }
+
+        // 4. Prepare config PDA signer for closing the vaults
+        let config_seeds = config.signer_seeds();
+
+        // 5. Close both vaults once empty; one never created is skipped
+        for vault in [self.accounts.vault_x, self.accounts.vault_y] {
+            if vault.lamports() == 0 {
+                continue;
+            }
+            if token_amount(vault, self.accounts.token_program)? != 0 {
+                return Err(AmmError::PoolNotEmpty.into());
+            }
+            let config_signer = Signer::from(&config_seeds);
+            CloseAccount {
+                account: vault,
+                destination: self.accounts.destination,
+                authority: self.accounts.config,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[config_signer])?;
+        }
+
+        // 6. Close the config, moving its rent to the destination
+        drop(config);
+        let balance =
+            lamports::sweep(self.accounts.config.lamports(), self.accounts.destination.lamports())
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.accounts.destination.set_lamports(balance);
+        self.accounts.config.close()
+    }
+}
@@ -0,0 +1,146 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::{
+    pda::{check_mint_lp, vault_addresses},
+    token::{check_token_program, MintInfo},
+    Config, Gauge,
+};
+
+// ==================== Accounts ====================
+
+pub struct CreateGaugeAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub gauge: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub reward_mint: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateGaugeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, gauge, mint_lp, reward_mint, _system_program, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Rewards are paid by the pool's token program; paying them in the LP
+        // token would let claims drain the stakes
+        check_token_program(token_program)?;
+        MintInfo::load(reward_mint, token_program, 0)?;
+        if reward_mint.address() == mint_lp.address() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            authority,
+            config,
+            gauge,
+            mint_lp,
+            reward_mint,
+            token_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct CreateGaugeInstructionData {
+    pub gauge_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for CreateGaugeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== CreateGauge Instruction ====================
+
+/// Creates the pool's LP staking gauge, paying rewards in `reward_mint`.
+/// Its LP and reward vaults are the gauge's ATAs, created by the client.
+pub struct CreateGauge<'a> {
+    pub accounts: CreateGaugeAccounts<'a>,
+    pub instruction_data: CreateGaugeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CreateGauge<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CreateGaugeAccounts::try_from(accounts)?;
+        let instruction_data = CreateGaugeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CreateGauge<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Verify the pool authority signed; the LP mint lives under the
+        //    pool's token program
+        let config = Config::load(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+        if !self.accounts.mint_lp.owned_by(self.accounts.token_program.address()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        drop(config);
+
+        // 2. Create the gauge account
+        let gauge_seeds = [
+            Seed::from(b"gauge"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(&self.instruction_data.gauge_bump),
+        ];
+        let gauge_signer = Signer::from(&gauge_seeds);
+
+        create_account_with_minimum_balance_signed(
+            self.accounts.gauge,
+            Gauge::LEN,
+            &crate::ID,
+            self.accounts.authority,
+            None,  // rent_sysvar - use syscall
+            &[gauge_signer],
+        )?;
+
+        // 3. Fill Gauge data
+        let gauge = unsafe { Gauge::load_mut_unchecked(self.accounts.gauge)? };
+        gauge.set_inner(
+            self.accounts.config.address().to_bytes(),
+            self.accounts.reward_mint.address().to_bytes(),
+            self.instruction_data.gauge_bump,
+        );
+
+        // Verify the LP mint and record the vault ATAs once, so later
+        // instructions check them with a comparison
+        check_mint_lp(self.accounts.config.address(), self.accounts.mint_lp.address())?;
+        let [lp_vault, reward_vault] = vault_addresses(
+            self.accounts.gauge.address(),
+            self.accounts.token_program.address(),
+            [self.accounts.mint_lp.address(), self.accounts.reward_mint.address()],
+        );
+        gauge.set_vaults(lp_vault, reward_vault);
+
+        Ok(())
+    }
+}
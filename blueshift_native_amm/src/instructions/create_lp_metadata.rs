@@ -0,0 +1,132 @@
+use pinocchio::{
+    AccountView,
+    cpi::Signer,
+    error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{
+    metadata::{
+        CreateMetadataAccountV3, MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN, METADATA_PROGRAM_ID,
+    },
+    Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct CreateLpMetadataAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub metadata: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CreateLpMetadataAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, mint_lp, metadata, metadata_program, system_program] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // The config signs below, so it must only reach the metadata program
+        if metadata_program.address() != &METADATA_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(Self {
+            authority,
+            config,
+            mint_lp,
+            metadata,
+            system_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+/// Zero-padded name, symbol and URI; trailing zeroes are dropped
+#[repr(C, packed)]
+pub struct CreateLpMetadataInstructionData {
+    pub name: [u8; MAX_NAME_LEN],
+    pub symbol: [u8; MAX_SYMBOL_LEN],
+    pub uri: [u8; MAX_URI_LEN],
+}
+
+impl TryFrom<&[u8]> for CreateLpMetadataInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+/// `bytes` up to its trailing zero padding
+#[inline(always)]
+fn trim(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+// ==================== CreateLpMetadata Instruction ====================
+
+/// Creates the Metaplex metadata account of the pool's LP mint, so wallets
+/// show a name and symbol for it. The config signs as mint and update
+/// authority; the pool authority pays the rent. Once per pool, and not for
+/// immutable pools.
+pub struct CreateLpMetadata<'a> {
+    pub accounts: CreateLpMetadataAccounts<'a>,
+    pub instruction_data: CreateLpMetadataInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CreateLpMetadata<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CreateLpMetadataAccounts::try_from(accounts)?;
+        let instruction_data = CreateLpMetadataInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CreateLpMetadata<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let config = Config::load(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Prepare config PDA signer; the metadata program checks it is
+        //    the mint authority, which only holds for the pool's LP mint
+        let config_seeds = config.signer_seeds();
+        let config_signer = Signer::from(&config_seeds);
+
+        // 3. Create the metadata account
+        let name = self.instruction_data.name;
+        let symbol = self.instruction_data.symbol;
+        let uri = self.instruction_data.uri;
+        CreateMetadataAccountV3 {
+            metadata: self.accounts.metadata,
+            mint: self.accounts.mint_lp,
+            mint_authority: self.accounts.config,
+            payer: self.accounts.authority,
+            update_authority: self.accounts.config,
+            system_program: self.accounts.system_program,
+            name: trim(&name),
+            symbol: trim(&symbol),
+            uri: trim(&uri),
+        }
+        .invoke_signed(&[config_signer])
+    }
+}
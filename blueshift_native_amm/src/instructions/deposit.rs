@@ -1,18 +1,19 @@
-use constant_product_curve::ConstantProduct;
 use pinocchio::{
     AccountView,
-    Address,
-    cpi::{Seed, Signer},
+    cpi::{set_return_data, Signer},
     error::ProgramError,
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::{MintTo, Transfer},
-    state::{Mint, TokenAccount},
-};
 
-use crate::{AmmState, Config};
+use crate::{
+    events::{AddLiquidityEvent, Event},
+    expiration::check_expiration,
+    native::{native_side, split_native_flag, unwrap, wrap},
+    quote::deposit_amounts,
+    token::{check_token_program, token_amount, MintInfo, MintTo, TransferChecked},
+    Config, PoolOperation,
+};
 
 // ==================== Accounts ====================
 
@@ -26,13 +27,17 @@ pub struct DepositAccounts<'a> {
     pub user_lp_ata: &'a AccountView,
     pub config: &'a AccountView,
     pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    /// Extra accounts for Token-2022 transfer hooks on either mint
+    pub hook_accounts: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, hook_accounts @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -48,6 +53,9 @@ impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            mint_x,
+            mint_y,
+            hook_accounts,
         })
     }
 }
@@ -73,11 +81,37 @@ impl TryFrom<&[u8]> for DepositInstructionData {
     }
 }
 
+// ==================== Return Data ====================
+
+/// Returned by `Deposit`: what was taken from the user (transfer fees
+/// included) and the LP tokens minted
+#[repr(C, packed)]
+pub struct DepositOutcome {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_minted: u64,
+}
+
+impl DepositOutcome {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&{ self.amount_x }.to_le_bytes());
+        bytes[8..16].copy_from_slice(&{ self.amount_y }.to_le_bytes());
+        bytes[16..].copy_from_slice(&{ self.lp_minted }.to_le_bytes());
+        bytes
+    }
+}
+
 // ==================== Deposit Instruction ====================
 
 pub struct Deposit<'a> {
     pub accounts: DepositAccounts<'a>,
     pub instruction_data: DepositInstructionData,
+    /// Pay the pool's native-mint side in as lamports
+    pub native: bool,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
@@ -85,6 +119,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = DepositAccounts::try_from(accounts)?;
+        let (data, native) = split_native_flag::<DepositInstructionData>(data);
         let instruction_data = DepositInstructionData::try_from(data)?;
 
         // Validate amounts are greater than zero
@@ -98,6 +133,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
         Ok(Self {
             accounts,
             instruction_data,
+            native,
         })
     }
 }
@@ -106,115 +142,118 @@ impl<'a> Deposit<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
 
     pub fn process(&mut self) -> ProgramResult {
-        // 1. Check expiration using Clock sysvar
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
         let clock = Clock::get()?;
-        if clock.unix_timestamp >= self.instruction_data.expiration {
-            return Err(ProgramError::Custom(1)); // Order expired
-        }
+        check_expiration(self.instruction_data.expiration, &clock)?;
 
         // 2. Load and validate config
         let config = Config::load(self.accounts.config)?;
 
-        // Verify pool state allows deposits
-        if config.state() != AmmState::Initialized as u8 {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        // Verify pool state allows deposits (Initialized only)
+        config.check_state(PoolOperation::Deposit)?;
 
-        // 3. Verify vault_x is valid ATA (only on-chain, syscall not available off-chain)
-        // ATA seeds: [wallet, token_program_id, mint]
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
-        {
-            let (vault_x_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_x(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_x_addr.ne(self.accounts.vault_x.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
-        }
+        // 3. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
 
-        // 4. Verify vault_y is valid ATA
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+        // 4. Verify the token program and mints, and read the mints
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
         {
-            let (vault_y_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_y(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_y_addr.ne(self.accounts.vault_y.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
+            return Err(ProgramError::InvalidAccountData);
         }
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, clock.epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, clock.epoch)?;
+        let mint_lp =
+            MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, clock.epoch)?;
 
-        // 5. Deserialize the token accounts
-        let mint_lp = unsafe { Mint::from_account_view_unchecked(self.accounts.mint_lp)? };
-        let vault_x_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
-
-        // 6. Calculate deposit amounts
-        let (x, y) = match mint_lp.supply() == 0
-            && vault_x_account.amount() == 0
-            && vault_y_account.amount() == 0
-        {
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
+
+        // 5. Calculate what the vaults must receive (rounded up, so the
+        //    remainder stays with the pool) and, grossed up by the mints'
+        //    transfer fees, what the user sends
+        let (net_x, net_y, x, y) = match mint_lp.supply == 0 && reserve_x == 0 && reserve_y == 0 {
             // First deposit: use user's max amounts directly
-            true => (self.instruction_data.max_x, self.instruction_data.max_y),
+            true => (0, 0, self.instruction_data.max_x, self.instruction_data.max_y),
             // Subsequent deposits: calculate required amounts based on desired LP
             false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    vault_x_account.amount(),
-                    vault_y_account.amount(),
-                    mint_lp.supply(),
+                let (x, y) = deposit_amounts(
+                    reserve_x,
+                    reserve_y,
+                    mint_lp.supply,
                     self.instruction_data.amount,
-                    6, // LP token decimals
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                (amounts.x, amounts.y)
+                )?;
+                (x, y, mint_x.amount_with_fee(x)?, mint_y.amount_with_fee(y)?)
             }
         };
 
-        // 7. Check for slippage
+        // 6. Check for slippage
         if !(x <= self.instruction_data.max_x && y <= self.instruction_data.max_y) {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // 8. Transfer token X from user to vault
-        Transfer {
+        // 7. Wrap the native side's amount from the user's lamports
+        let native_x = match self.native {
+            true => Some(native_side(self.accounts.mint_x, self.accounts.mint_y)?),
+            false => None,
+        };
+        if let Some(native_x) = native_x {
+            let (account, lamports) = match native_x {
+                true => (self.accounts.user_x_ata, x),
+                false => (self.accounts.user_y_ata, y),
+            };
+            wrap(
+                self.accounts.user,
+                account,
+                lamports,
+                self.accounts.token_program,
+                self.accounts.hook_accounts,
+            )?;
+        }
+
+        // 8. Transfer token X from user to vault; the vault balances are read
+        //    around the transfers so the reserves only count what arrived
+        let vault_x_amount = token_amount(self.accounts.vault_x, self.accounts.token_program)?;
+        let vault_y_amount = token_amount(self.accounts.vault_y, self.accounts.token_program)?;
+        TransferChecked {
             from: self.accounts.user_x_ata,
+            mint: self.accounts.mint_x,
             to: self.accounts.vault_x,
             authority: self.accounts.user,
             amount: x,
+            decimals: mint_x.decimals,
+            token_program: self.accounts.token_program.address(),
         }
-        .invoke()?;
+        .invoke_with_hook_accounts(self.accounts.hook_accounts)?;
 
         // 9. Transfer token Y from user to vault
-        Transfer {
+        TransferChecked {
             from: self.accounts.user_y_ata,
+            mint: self.accounts.mint_y,
             to: self.accounts.vault_y,
             authority: self.accounts.user,
             amount: y,
+            decimals: mint_y.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_with_hook_accounts(self.accounts.hook_accounts)?;
+
+        // What arrived must cover the LP tokens' share; a transfer fee larger
+        // than the mint advertised would otherwise dilute the other LPs
+        let received_x = token_amount(self.accounts.vault_x, self.accounts.token_program)?
+            .checked_sub(vault_x_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let received_y = token_amount(self.accounts.vault_y, self.accounts.token_program)?
+            .checked_sub(vault_y_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if received_x < net_x || received_y < net_y {
+            return Err(ProgramError::InsufficientFunds);
         }
-        .invoke()?;
 
         // 10. Mint LP tokens to user
         // Config PDA is the mint authority, so we need to sign with config seeds
-        let seed_binding = config.seed().to_le_bytes();
-        let bump_binding = config.config_bump();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(config.mint_x()),
-            Seed::from(config.mint_y()),
-            Seed::from(&bump_binding),
-        ];
+        let config_seeds = config.signer_seeds();
         let config_signer = Signer::from(&config_seeds);
 
         MintTo {
@@ -222,9 +261,56 @@ impl<'a> Deposit<'a> {
             account: self.accounts.user_lp_ata,
             mint_authority: self.accounts.config,
             amount: self.instruction_data.amount,
+            token_program: self.accounts.token_program.address(),
         }
         .invoke_signed(&[config_signer])?;
 
+        // 11. Unwrap the native side back into the user's wallet
+        if let Some(native_x) = native_x {
+            unwrap(
+                match native_x {
+                    true => self.accounts.user_x_ata,
+                    false => self.accounts.user_y_ata,
+                },
+                self.accounts.user,
+                self.accounts.token_program,
+            )?;
+        }
+
+        // 12. Add what reached the vaults to the reserves
+        // (config is re-borrowed mutably once the CPIs are done)
+        drop(config);
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let reserve_x = reserve_x
+            .checked_add(received_x)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_y = reserve_y
+            .checked_add(received_y)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        config.set_reserves(reserve_x, reserve_y);
+
+        // Log the deposit for indexers
+        AddLiquidityEvent {
+            pool: *self.accounts.config.address(),
+            user: *self.accounts.user.address(),
+            amount_x: x,
+            amount_y: y,
+            lp_minted: self.instruction_data.amount,
+            reserve_x,
+            reserve_y,
+        }
+        .emit();
+
+        // 13. Report the amounts to CPI callers and simulators
+        set_return_data(
+            &DepositOutcome {
+                amount_x: x,
+                amount_y: y,
+                lp_minted: self.instruction_data.amount,
+            }
+            .to_bytes(),
+        );
+
         Ok(())
     }
 }
@@ -0,0 +1,121 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    quote::lp_for_amounts, token::MintInfo, Config, Deposit, DepositAccounts,
+    DepositInstructionData,
+};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct DepositExactInstructionData {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub min_lp: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for DepositExactInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== DepositExact Instruction ====================
+
+/// Deposits up to `amount_x` of X and `amount_y` of Y, minting as much LP as
+/// they cover (rounded down, at least `min_lp`). Only the side in excess of
+/// the pool's ratio is left partly with the user. The first deposit mints
+/// the geometric mean of the two. Takes `Deposit`'s accounts and returns its
+/// `DepositOutcome`.
+pub struct DepositExact<'a> {
+    pub accounts: DepositAccounts<'a>,
+    pub instruction_data: DepositExactInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for DepositExact<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositExactInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero
+        if instruction_data.amount_x == 0
+            || instruction_data.amount_y == 0
+            || instruction_data.min_lp == 0
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositExact<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Mint as much LP as both amounts cover at the current reserves,
+        //    net of the mints' transfer fees
+        let epoch = Clock::get()?.epoch;
+        let amount_x = self.instruction_data.amount_x;
+        let amount_y = self.instruction_data.amount_y;
+        let lp_amount = {
+            let config = Config::load(self.accounts.config)?;
+            let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, epoch)?;
+            let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, epoch)?;
+            let mint_lp =
+                MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, epoch)?;
+            lp_for_amounts(
+                config.reserve_x(),
+                config.reserve_y(),
+                mint_lp.supply,
+                amount_x - mint_x.fee_for(amount_x),
+                amount_y - mint_y.fee_for(amount_y),
+            )?
+        };
+        if lp_amount < self.instruction_data.min_lp {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 2. Deposit for that much LP, paying at most the given amounts
+        Deposit {
+            accounts: DepositAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                hook_accounts: self.accounts.hook_accounts,
+            },
+            instruction_data: DepositInstructionData {
+                amount: lp_amount,
+                max_x: amount_x,
+                max_y: amount_y,
+                expiration: self.instruction_data.expiration,
+            },
+            native: false,
+        }
+        .process()
+    }
+}
@@ -0,0 +1,147 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    expiration::check_expiration, quote::zap_swap_amount, Config, DepositAccounts, DepositExact,
+    DepositExactInstructionData, PoolOperation, SwapLeg,
+};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct DepositSingleInstructionData {
+    pub is_x: u8, // bool as u8 for packed struct
+    pub amount: u64,
+    pub min_lp: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for DepositSingleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+impl DepositSingleInstructionData {
+    #[inline]
+    pub fn is_x(&self) -> bool {
+        self.is_x != 0
+    }
+}
+
+// ==================== DepositSingle Instruction ====================
+
+/// Deposits `amount` of X (`is_x`) or Y alone: swaps part of it through the
+/// pool for the other token, then deposits both sides, minting at least
+/// `min_lp`. Takes `Deposit`'s accounts; what the deposit can't use stays
+/// with the user. Returns the deposit's `DepositOutcome`.
+pub struct DepositSingle<'a> {
+    pub accounts: DepositAccounts<'a>,
+    pub instruction_data: DepositSingleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for DepositSingle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositSingleInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero
+        if instruction_data.amount == 0 || instruction_data.min_lp == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositSingle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
+        let clock = Clock::get()?;
+        check_expiration(self.instruction_data.expiration, &clock)?;
+
+        // 2. Work out how much to swap; the pool must already hold liquidity
+        let is_x = self.instruction_data.is_x();
+        let amount = self.instruction_data.amount;
+        let swap_amount = {
+            let config = Config::load(self.accounts.config)?;
+            config.check_state(PoolOperation::Deposit)?;
+            let reserve_in = match is_x {
+                true => config.reserve_x(),
+                false => config.reserve_y(),
+            };
+            zap_swap_amount(reserve_in, amount, config.fee())?
+        };
+        if swap_amount == 0 || swap_amount >= amount {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 3. Swap it for the other token; `min_lp` covers slippage
+        let leg = SwapLeg {
+            config: self.accounts.config,
+            vault_x: self.accounts.vault_x,
+            vault_y: self.accounts.vault_y,
+            mint_x: self.accounts.mint_x,
+            mint_y: self.accounts.mint_y,
+            user_x_ata: self.accounts.user_x_ata,
+            user_y_ata: self.accounts.user_y_ata,
+            referrer: None,
+            hook_accounts: self.accounts.hook_accounts,
+        };
+        let swapped = leg
+            .swap(
+                self.accounts.user,
+                self.accounts.token_program,
+                is_x,
+                swap_amount,
+                1,
+                &clock,
+            )?
+            .amount_out;
+        let (max_x, max_y) = match is_x {
+            true => (amount - swap_amount, swapped),
+            false => (swapped, amount - swap_amount),
+        };
+
+        // 4. Deposit both sides for as much LP as they cover
+        DepositExact {
+            accounts: DepositAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                hook_accounts: self.accounts.hook_accounts,
+            },
+            instruction_data: DepositExactInstructionData {
+                amount_x: max_x,
+                amount_y: max_y,
+                min_lp: self.instruction_data.min_lp,
+                expiration: self.instruction_data.expiration,
+            },
+        }
+        .process()
+    }
+}
@@ -0,0 +1,121 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    quote::{max_with_slippage, MAX_SLIPPAGE_BPS},
+    token::MintInfo,
+    Config, Deposit, DepositAccounts, DepositInstructionData,
+};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct DepositWithSlippageInstructionData {
+    pub amount: u64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub slippage_bps: u16,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for DepositWithSlippageInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== DepositWithSlippage Instruction ====================
+
+/// `Deposit` with its limits given the way frontends show them: `amount_x`
+/// and `amount_y` are what `amount` LP cost at the reserves the user was
+/// quoted, and each may come in up to `slippage_bps` higher. The first
+/// deposit has no ratio to drift from and takes the quoted amounts as they
+/// are. Takes `Deposit`'s accounts and returns its `DepositOutcome`.
+pub struct DepositWithSlippage<'a> {
+    pub accounts: DepositAccounts<'a>,
+    pub instruction_data: DepositWithSlippageInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for DepositWithSlippage<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositWithSlippageInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero and the tolerance is sane
+        if instruction_data.amount == 0
+            || instruction_data.amount_x == 0
+            || instruction_data.amount_y == 0
+            || instruction_data.slippage_bps > MAX_SLIPPAGE_BPS
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositWithSlippage<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &36;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Turn the tolerance into absolute limits, unless this is the
+        //    first deposit
+        let amount_x = self.instruction_data.amount_x;
+        let amount_y = self.instruction_data.amount_y;
+        let slippage_bps = self.instruction_data.slippage_bps;
+        let first = {
+            let config = Config::load(self.accounts.config)?;
+            let epoch = Clock::get()?.epoch;
+            let mint_lp =
+                MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, epoch)?;
+            mint_lp.supply == 0 && config.reserve_x() == 0 && config.reserve_y() == 0
+        };
+        let (max_x, max_y) = match first {
+            true => (amount_x, amount_y),
+            false => (
+                max_with_slippage(amount_x, slippage_bps),
+                max_with_slippage(amount_y, slippage_bps),
+            ),
+        };
+
+        // 2. Deposit for `amount` LP within those limits
+        Deposit {
+            accounts: DepositAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                hook_accounts: self.accounts.hook_accounts,
+            },
+            instruction_data: DepositInstructionData {
+                amount: self.instruction_data.amount,
+                max_x,
+                max_y,
+                expiration: self.instruction_data.expiration,
+            },
+            native: false,
+        }
+        .process()
+    }
+}
@@ -0,0 +1,187 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    events::{DonateEvent, Event},
+    quote::donate_amount_y,
+    token::{check_token_program, MintInfo, TransferChecked},
+    Config, PoolOperation,
+};
+
+// ==================== Accounts ====================
+
+pub struct DonateAccounts<'a> {
+    pub donor: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub donor_x_ata: &'a AccountView,
+    pub donor_y_ata: &'a AccountView,
+    pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    /// Extra accounts for Token-2022 transfer hooks on either mint
+    pub hook_accounts: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for DonateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [donor, vault_x, vault_y, donor_x_ata, donor_y_ata, config, token_program, mint_x, mint_y, hook_accounts @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            donor,
+            vault_x,
+            vault_y,
+            donor_x_ata,
+            donor_y_ata,
+            config,
+            token_program,
+            mint_x,
+            mint_y,
+            hook_accounts,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct DonateInstructionData {
+    pub amount_x: u64,
+    pub max_y: u64,
+}
+
+impl TryFrom<&[u8]> for DonateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== Donate Instruction ====================
+
+/// Adds `amount_x` of X and the matching amount of Y (at most `max_y`) to the
+/// reserves without minting LP tokens: the pool's price is unchanged and the
+/// value accrues to the existing LPs
+pub struct Donate<'a> {
+    pub accounts: DonateAccounts<'a>,
+    pub instruction_data: DonateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Donate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DonateAccounts::try_from(accounts)?;
+        let instruction_data = DonateInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero
+        if instruction_data.amount_x == 0 || instruction_data.max_y == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Donate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load and validate config; donations go where deposits can
+        let config = Config::load(self.accounts.config)?;
+        config.check_state(PoolOperation::Deposit)?;
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Verify the token program and mints, and read the mints' transfer fees
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let clock = Clock::get()?;
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, clock.epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, clock.epoch)?;
+
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
+        drop(config);
+
+        // 4. Match what reaches vault_x with Y at the pool's ratio, grossed up
+        //    by Y's transfer fee
+        let x = self.instruction_data.amount_x;
+        let net_x = x - mint_x.fee_for(x);
+        let net_y = donate_amount_y(reserve_x, reserve_y, net_x)?;
+        let y = mint_y.amount_with_fee(net_y)?;
+        if y > self.instruction_data.max_y {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 5. Transfer both tokens from the donor to the vaults
+        TransferChecked {
+            from: self.accounts.donor_x_ata,
+            mint: self.accounts.mint_x,
+            to: self.accounts.vault_x,
+            authority: self.accounts.donor,
+            amount: x,
+            decimals: mint_x.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_with_hook_accounts(self.accounts.hook_accounts)?;
+
+        TransferChecked {
+            from: self.accounts.donor_y_ata,
+            mint: self.accounts.mint_y,
+            to: self.accounts.vault_y,
+            authority: self.accounts.donor,
+            amount: y,
+            decimals: mint_y.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_with_hook_accounts(self.accounts.hook_accounts)?;
+
+        // 6. Close the oracle period at the old price, then add the donation
+        //    to the reserves
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp);
+        let reserve_x = reserve_x
+            .checked_add(net_x)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_y = reserve_y
+            .checked_add(net_y)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        config.set_reserves(reserve_x, reserve_y);
+
+        DonateEvent {
+            pool: *self.accounts.config.address(),
+            donor: *self.accounts.donor.address(),
+            amount_x: net_x,
+            amount_y: net_y,
+            reserve_x,
+            reserve_y,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use pinocchio::{
+    AccountView,
+    cpi::{set_return_data, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    events::{Event, RemoveLiquidityEvent},
+    quote::withdraw_amounts,
+    token::{check_token_program, Burn, MintInfo, TransferChecked},
+    AmmState, Config, WithdrawAccounts, WithdrawOutcome,
+};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct EmergencyWithdrawInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for EmergencyWithdrawInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== EmergencyWithdraw Instruction ====================
+
+/// LPs' way out of a Disabled pool, which `Withdraw` rejects: burns `amount`
+/// LP tokens for the same share of both reserves. Takes `Withdraw`'s
+/// accounts; with swaps stopped the share can't move, so there is no
+/// slippage limit or expiration.
+pub struct EmergencyWithdraw<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instruction_data: EmergencyWithdrawInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for EmergencyWithdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instruction_data = EmergencyWithdrawInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero
+        if instruction_data.amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> EmergencyWithdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &34;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config; only a Disabled pool outside a flash loan qualifies
+        let config = Config::load(self.accounts.config)?;
+        if config.state() != AmmState::Disabled as u8 || config.flash_loan_active() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Verify the token program and mints, and read the mints
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let epoch = Clock::get()?.epoch;
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, epoch)?;
+        let mint_lp = MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, epoch)?;
+
+        // 4. The LP tokens' share of the reserves
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
+        let (x, y) = withdraw_amounts(
+            reserve_x,
+            reserve_y,
+            mint_lp.supply,
+            self.instruction_data.amount,
+        )?;
+
+        // 5. Pay out both sides (config PDA signs), then burn the LP tokens
+        let config_seeds = config.signer_seeds();
+        let config_signer = [Signer::from(&config_seeds)];
+
+        TransferChecked {
+            from: self.accounts.vault_x,
+            mint: self.accounts.mint_x,
+            to: self.accounts.user_x_ata,
+            authority: self.accounts.config,
+            amount: x,
+            decimals: mint_x.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_signed_with_hook_accounts(&config_signer, self.accounts.hook_accounts)?;
+
+        TransferChecked {
+            from: self.accounts.vault_y,
+            mint: self.accounts.mint_y,
+            to: self.accounts.user_y_ata,
+            authority: self.accounts.config,
+            amount: y,
+            decimals: mint_y.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_signed_with_hook_accounts(&config_signer, self.accounts.hook_accounts)?;
+
+        Burn {
+            mint: self.accounts.mint_lp,
+            account: self.accounts.user_lp_ata,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke()?;
+
+        // 6. Take what left the vaults out of the reserves
+        // (config is re-borrowed mutably once the CPIs are done)
+        drop(config);
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let reserve_x = reserve_x.checked_sub(x).ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_y = reserve_y.checked_sub(y).ok_or(ProgramError::ArithmeticOverflow)?;
+        config.set_reserves(reserve_x, reserve_y);
+
+        let outcome = WithdrawOutcome {
+            amount_x: x - mint_x.fee_for(x),
+            amount_y: y - mint_y.fee_for(y),
+            lp_burned: self.instruction_data.amount,
+        };
+        // Log the withdrawal for indexers
+        RemoveLiquidityEvent {
+            pool: *self.accounts.config.address(),
+            user: *self.accounts.user.address(),
+            amount_x: outcome.amount_x,
+            amount_y: outcome.amount_y,
+            lp_burned: outcome.lp_burned,
+            reserve_x,
+            reserve_y,
+        }
+        .emit();
+
+        // 7. Report the amounts to CPI callers and simulators
+        set_return_data(&outcome.to_bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use pinocchio::{
+    AccountView,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{clock::Clock, instructions::Instructions, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    events::{Event, FlashLoanEvent},
+    token::{check_token_program, token_amount, MintInfo, TransferChecked},
+    Config, FlashRepay, PoolOperation,
+};
+
+// ==================== Accounts ====================
+
+pub struct FlashLoanAccounts<'a> {
+    pub borrower: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub borrower_x_ata: &'a AccountView,
+    pub borrower_y_ata: &'a AccountView,
+    pub instructions: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FlashLoanAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [borrower, config, vault_x, vault_y, borrower_x_ata, borrower_y_ata, instructions, token_program, mint_x, mint_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !borrower.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            borrower,
+            config,
+            vault_x,
+            vault_y,
+            borrower_x_ata,
+            borrower_y_ata,
+            instructions,
+            token_program,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct FlashLoanInstructionData {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl TryFrom<&[u8]> for FlashLoanInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== FlashLoan Instruction ====================
+
+pub struct FlashLoan<'a> {
+    pub accounts: FlashLoanAccounts<'a>,
+    pub instruction_data: FlashLoanInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for FlashLoan<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FlashLoanAccounts::try_from(accounts)?;
+        let instruction_data = FlashLoanInstructionData::try_from(data)?;
+
+        // Validate something is borrowed
+        if instruction_data.amount_x == 0 && instruction_data.amount_y == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> FlashLoan<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let amount_x = self.instruction_data.amount_x;
+        let amount_y = self.instruction_data.amount_y;
+
+        // 1. Verify a FlashRepay for this pool runs later in the transaction
+        self.check_repay_follows()?;
+
+        // 2. Load config and lock the pool until the vaults are refilled with the fee.
+        //    The fee is booked now, the protocol's share as protocol fees and the
+        //    rest into the reserves: if it isn't paid the whole transaction fails
+        //    anyway. Config is released before the CPIs.
+        {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            // Verify pool state allows flash loans (Initialized, no loan already out)
+            config.check_state(PoolOperation::FlashLoan)?;
+
+            // 3. Verify the vaults are the ones recorded at initialization
+            config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+            // 4. Record the balances the vaults must be back at: current + fee
+            check_token_program(self.accounts.token_program)?;
+            let vault_x_amount = token_amount(self.accounts.vault_x, self.accounts.token_program)?;
+            let vault_y_amount = token_amount(self.accounts.vault_y, self.accounts.token_program)?;
+
+            let fee_x = config.flash_fee_for(amount_x)?;
+            let fee_y = config.flash_fee_for(amount_y)?;
+            let repay_x = vault_x_amount
+                .checked_add(fee_x)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let repay_y = vault_y_amount
+                .checked_add(fee_y)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            config.start_flash_loan(repay_x, repay_y);
+            let share_x = config.accrue_protocol_fee(true, fee_x)?;
+            let share_y = config.accrue_protocol_fee(false, fee_y)?;
+
+            let reserve_x = config
+                .reserve_x()
+                .checked_add(fee_x - share_x)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let reserve_y = config
+                .reserve_y()
+                .checked_add(fee_y - share_y)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            config.set_reserves(reserve_x, reserve_y);
+
+            FlashLoanEvent {
+                pool: *self.accounts.config.address(),
+                borrower: *self.accounts.borrower.address(),
+                amount_x,
+                amount_y,
+                fee_x,
+                fee_y,
+                reserve_x,
+                reserve_y,
+            }
+            .emit();
+        }
+
+        // 5. Verify the mints and prepare config PDA signer for vault transfers
+        let config = Config::load(self.accounts.config)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let epoch = Clock::get()?.epoch;
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, epoch)?;
+
+        let config_seeds = config.signer_seeds();
+
+        // 6. Lend token X
+        if amount_x > 0 {
+            let config_signer = Signer::from(&config_seeds);
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.borrower_x_ata,
+                authority: self.accounts.config,
+                amount: amount_x,
+                decimals: mint_x.decimals,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[config_signer])?;
+        }
+
+        // 7. Lend token Y
+        if amount_y > 0 {
+            let config_signer = Signer::from(&config_seeds);
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.borrower_y_ata,
+                authority: self.accounts.config,
+                amount: amount_y,
+                decimals: mint_y.decimals,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[config_signer])?;
+        }
+
+        Ok(())
+    }
+
+    /// Find a top-level `FlashRepay` for this config after the current instruction
+    fn check_repay_follows(&self) -> ProgramResult {
+        let instructions = Instructions::try_from(self.accounts.instructions)?;
+        let current = instructions.load_current_index() as usize;
+
+        for index in current + 1..instructions.num_instructions() {
+            let instruction = instructions.load_instruction_at(index)?;
+            if instruction.get_program_id() != &crate::ID {
+                continue;
+            }
+            if instruction.get_instruction_data().first() != Some(FlashRepay::DISCRIMINATOR) {
+                continue;
+            }
+            // FlashRepay's first account is the config it releases
+            if let Ok(meta) = instruction.get_account_meta_at(0) {
+                if &meta.key == self.accounts.config.address() {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
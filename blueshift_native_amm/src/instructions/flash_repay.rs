@@ -0,0 +1,81 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{
+    token::{check_token_program, token_amount},
+    Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct FlashRepayAccounts<'a> {
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FlashRepayAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, vault_x, vault_y, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            config,
+            vault_x,
+            vault_y,
+            token_program,
+        })
+    }
+}
+
+// ==================== FlashRepay Instruction ====================
+
+/// Closes a `FlashLoan`. The borrower transfers principal + fee back into the
+/// vaults beforehand; this only checks the balances and unlocks the pool.
+pub struct FlashRepay<'a> {
+    pub accounts: FlashRepayAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FlashRepay<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = FlashRepayAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> FlashRepay<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify a loan is out
+        let mut config = Config::load_mut(self.accounts.config)?;
+        if !config.flash_loan_active() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Verify principal + fee are back in both vaults
+        check_token_program(self.accounts.token_program)?;
+        let vault_x_amount = token_amount(self.accounts.vault_x, self.accounts.token_program)?;
+        let vault_y_amount = token_amount(self.accounts.vault_y, self.accounts.token_program)?;
+        if vault_x_amount < config.flash_repay_x() || vault_y_amount < config.flash_repay_y() {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // 4. Unlock the pool
+        config.end_flash_loan();
+
+        Ok(())
+    }
+}
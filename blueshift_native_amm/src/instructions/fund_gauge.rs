@@ -0,0 +1,142 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    token::{check_token_program, token_amount, MintInfo, TransferChecked},
+    Config, Gauge,
+};
+
+// ==================== Accounts ====================
+
+pub struct FundGaugeAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub gauge: &'a AccountView,
+    pub authority_reward_ata: &'a AccountView,
+    pub reward_vault: &'a AccountView,
+    pub reward_mint: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FundGaugeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config, gauge, authority_reward_ata, reward_vault, reward_mint, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority,
+            config,
+            gauge,
+            authority_reward_ata,
+            reward_vault,
+            reward_mint,
+            token_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct FundGaugeInstructionData {
+    pub amount: u64,
+    /// Seconds to stream `amount` (and any unstreamed rewards) over
+    pub duration: u64,
+}
+
+impl TryFrom<&[u8]> for FundGaugeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== FundGauge Instruction ====================
+
+/// Moves `amount` reward tokens from the pool authority into the gauge and
+/// restarts the stream to end `duration` seconds from now
+pub struct FundGauge<'a> {
+    pub accounts: FundGaugeAccounts<'a>,
+    pub instruction_data: FundGaugeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for FundGauge<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FundGaugeAccounts::try_from(accounts)?;
+        let instruction_data = FundGaugeInstructionData::try_from(data)?;
+
+        // Validate amount and duration are greater than zero
+        if instruction_data.amount == 0 || instruction_data.duration == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> FundGauge<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Verify the pool authority signed
+        let config = Config::load(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+        drop(config);
+
+        // 2. Verify the gauge belongs to the pool and the reward vault and mint
+        //    are the recorded ones
+        let gauge = Gauge::load(self.accounts.gauge)?;
+        if gauge.config().as_slice() != self.accounts.config.address().as_ref() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        gauge.check_reward_vault(self.accounts.reward_vault, self.accounts.reward_mint)?;
+        drop(gauge);
+
+        // 3. Transfer the rewards in; only what arrives (net of any transfer
+        //    fee) is streamed
+        check_token_program(self.accounts.token_program)?;
+        let clock = Clock::get()?;
+        let reward_mint =
+            MintInfo::load(self.accounts.reward_mint, self.accounts.token_program, clock.epoch)?;
+        let before = token_amount(self.accounts.reward_vault, self.accounts.token_program)?;
+
+        TransferChecked {
+            from: self.accounts.authority_reward_ata,
+            mint: self.accounts.reward_mint,
+            to: self.accounts.reward_vault,
+            authority: self.accounts.authority,
+            amount: self.instruction_data.amount,
+            decimals: reward_mint.decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke()?;
+
+        let received = token_amount(self.accounts.reward_vault, self.accounts.token_program)?
+            .checked_sub(before)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // 4. Restart the stream with the new rewards plus what was left
+        let mut gauge = Gauge::load_mut(self.accounts.gauge)?;
+        gauge.notify_reward(received, self.instruction_data.duration, clock.unix_timestamp)?;
+
+        Ok(())
+    }
+}
@@ -7,9 +7,16 @@ use pinocchio::{
     ProgramResult,
 };
 use pinocchio_system::create_account_with_minimum_balance_signed;
-use pinocchio_token::instructions::InitializeMint2;
+use solana_challenge_common::seeds::{MINT_LP_SEED, POOL_CONFIG_SEED};
 
-use crate::Config;
+use crate::{
+    events::{Event, PoolCreated},
+    pda::{check_protocol_config, vault_addresses},
+    quote::MAX_LP_DECIMALS,
+    registry::check_fee_tier,
+    token::{check_token_program, InitializeMint2, MintInfo, MINT_LEN},
+    Config, PoolRegistry, ProtocolConfig,
+};
 
 // ==================== Accounts ====================
 
@@ -17,20 +24,44 @@ pub struct InitializeAccounts<'a> {
     pub initializer: &'a AccountView,
     pub mint_lp: &'a AccountView,
     pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    pub registry: &'a AccountView,
+    pub protocol: &'a AccountView,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [initializer, mint_lp, config, _system_program, _token_program] = accounts else {
+        let [initializer, mint_lp, config, _system_program, token_program, mint_x, mint_y, registry, protocol] =
+            accounts
+        else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // The pool's mints, vaults and LP mint all live under this program
+        check_token_program(token_program)?;
+
+        // Pool mints must be initialized mints of that program, and neither
+        // can be the LP mint created below
+        for mint in [mint_x, mint_y] {
+            MintInfo::load(mint, token_program, 0)?;
+            if mint.address() == mint_lp.address() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
         Ok(Self {
             initializer,
             mint_lp,
             config,
+            token_program,
+            mint_x,
+            mint_y,
+            registry,
+            protocol,
         })
     }
 }
@@ -45,6 +76,9 @@ pub struct InitializeInstructionData {
     pub mint_y: [u8; 32],
     pub config_bump: [u8; 1],
     pub lp_bump: [u8; 1],
+    pub registry_bump: [u8; 1],
+    /// Decimals of the LP mint, at most `MAX_LP_DECIMALS`
+    pub lp_decimals: u8,
     pub authority: [u8; 32],
 }
 
@@ -93,6 +127,27 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Initialize<'a> {
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = InitializeAccounts::try_from(accounts)?;
         let instruction_data = InitializeInstructionData::try_from(data)?;
+
+        // Mints must be in canonical (byte) order, so a pair maps to one
+        // pool per seed and X/Y vs Y/X duplicates can't be created
+        if instruction_data.mint_x >= instruction_data.mint_y {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Only sanctioned fee tiers, each registered once per pair below
+        check_fee_tier(instruction_data.fee)?;
+
+        if instruction_data.lp_decimals > MAX_LP_DECIMALS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // The mints written into Config must be the accounts just validated
+        if accounts.mint_x.address().as_ref() != instruction_data.mint_x
+            || accounts.mint_y.address().as_ref() != instruction_data.mint_y
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
         Ok(Self {
             accounts,
             instruction_data,
@@ -104,10 +159,19 @@ impl<'a> Initialize<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
     pub fn process(&mut self) -> ProgramResult {
-        // 1. Create Config account
+        // 1. The fee must be within the deployment's bounds, and the pool
+        //    starts with its default protocol share
+        let protocol_fee = {
+            check_protocol_config(self.accounts.protocol.address())?;
+            let protocol = ProtocolConfig::load(self.accounts.protocol)?;
+            protocol.check_fee(self.instruction_data.fee)?;
+            protocol.protocol_fee()
+        };
+
+        // 2. Create Config account
         let seed_binding = self.instruction_data.seed.to_le_bytes();
         let config_seeds = [
-            Seed::from(b"config"),
+            Seed::from(POOL_CONFIG_SEED),
             Seed::from(&seed_binding),
             Seed::from(&self.instruction_data.mint_x),
             Seed::from(&self.instruction_data.mint_y),
@@ -124,7 +188,7 @@ impl<'a> Initialize<'a> {
             &[config_signer],
         )?;
 
-        // 2. Fill Config data
+        // 3. Fill Config data
         let config = unsafe { Config::load_mut_unchecked(self.accounts.config)? };
         config.set_inner(
             self.instruction_data.seed,
@@ -134,37 +198,85 @@ impl<'a> Initialize<'a> {
             self.instruction_data.fee,
             self.instruction_data.config_bump,
         )?;
+        config.set_lp_decimals(self.instruction_data.lp_decimals);
+        config.set_protocol_fee(protocol_fee)?;
+
+        // Record the vault ATAs once, so later instructions check them with a
+        // comparison instead of deriving them
+        let [vault_x, vault_y] = vault_addresses(
+            self.accounts.config.address(),
+            self.accounts.token_program.address(),
+            [self.accounts.mint_x.address(), self.accounts.mint_y.address()],
+        );
+        config.set_vaults(vault_x, vault_y);
 
-        // 3. Create mint_lp account
+        // 4. Create mint_lp account
         let mint_lp_seeds = [
-            Seed::from(b"mint_lp"),
+            Seed::from(MINT_LP_SEED),
             Seed::from(self.accounts.config.address().as_ref()),
             Seed::from(&self.instruction_data.lp_bump),
         ];
         let mint_lp_signer = Signer::from(&mint_lp_seeds);
 
-        // Mint account size is 82 bytes
-        const MINT_SIZE: usize = 82;
-
         create_account_with_minimum_balance_signed(
             self.accounts.mint_lp,
-            MINT_SIZE,
-            &pinocchio_token::ID,
+            MINT_LEN,
+            self.accounts.token_program.address(),
             self.accounts.initializer,
             None,  // rent_sysvar - use syscall
             &[mint_lp_signer],
         )?;
 
-        // 4. Initialize mint_lp with config as mint_authority
-        // LP token has 6 decimals (standard for LP tokens)
+        // 5. Initialize mint_lp with config as mint_authority
         InitializeMint2 {
             mint: self.accounts.mint_lp,
-            decimals: 6,
+            decimals: self.instruction_data.lp_decimals,
             mint_authority: self.accounts.config.address(),
-            freeze_authority: None,
+            token_program: self.accounts.token_program.address(),
         }
         .invoke()?;
 
+        // 6. Register the pool under its pair and fee tier; a second pool
+        //    at the same tier fails here, as the registry already exists
+        let fee_binding = self.instruction_data.fee.to_le_bytes();
+        let registry_seeds = [
+            Seed::from(b"registry"),
+            Seed::from(&self.instruction_data.mint_x),
+            Seed::from(&self.instruction_data.mint_y),
+            Seed::from(&fee_binding),
+            Seed::from(&self.instruction_data.registry_bump),
+        ];
+        let registry_signer = Signer::from(&registry_seeds);
+
+        create_account_with_minimum_balance_signed(
+            self.accounts.registry,
+            PoolRegistry::LEN,
+            &crate::ID,
+            self.accounts.initializer,
+            None,  // rent_sysvar - use syscall
+            &[registry_signer],
+        )?;
+
+        let registry = unsafe { PoolRegistry::load_mut_unchecked(self.accounts.registry)? };
+        registry.set_inner(
+            self.accounts.config.address().to_bytes(),
+            self.instruction_data.seed,
+            self.instruction_data.fee,
+            self.instruction_data.registry_bump,
+        );
+
+        // 7. Log the new pool for indexers
+        PoolCreated {
+            pool: *self.accounts.config.address(),
+            mint_x: *self.accounts.mint_x.address(),
+            mint_y: *self.accounts.mint_y.address(),
+            mint_lp: *self.accounts.mint_lp.address(),
+            authority: Address::new_from_array(self.instruction_data.authority),
+            seed: self.instruction_data.seed,
+            fee: self.instruction_data.fee,
+        }
+        .emit();
+
         Ok(())
     }
 }
@@ -0,0 +1,109 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    ProgramResult,
+};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::{ProtocolConfig, PROTOCOL_SEED};
+
+// ==================== Accounts ====================
+
+pub struct InitializeProtocolAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub protocol: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for InitializeProtocolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, protocol, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority,
+            protocol,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct InitializeProtocolInstructionData {
+    pub min_fee: u16,
+    pub max_fee: u16,
+    pub protocol_fee: u16,
+    pub bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for InitializeProtocolInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== InitializeProtocol Instruction ====================
+
+/// Creates the deployment's protocol config, with the signer (who pays for
+/// it) as its authority. There is one per deployment, so whoever deploys the
+/// program should create it straight away; `Initialize` fails until then.
+pub struct InitializeProtocol<'a> {
+    pub accounts: InitializeProtocolAccounts<'a>,
+    pub instruction_data: InitializeProtocolInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for InitializeProtocol<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitializeProtocolAccounts::try_from(accounts)?;
+        let instruction_data = InitializeProtocolInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> InitializeProtocol<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &38;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Create the protocol config; a second one fails here, as the
+        //    account already exists
+        let protocol_seeds = [
+            Seed::from(PROTOCOL_SEED),
+            Seed::from(&self.instruction_data.bump),
+        ];
+        let protocol_signer = Signer::from(&protocol_seeds);
+
+        create_account_with_minimum_balance_signed(
+            self.accounts.protocol,
+            ProtocolConfig::LEN,
+            &crate::ID,
+            self.accounts.authority,
+            None,  // rent_sysvar - use syscall
+            &[protocol_signer],
+        )?;
+
+        // 2. Fill it in (the fee bounds are validated here)
+        let protocol = unsafe { ProtocolConfig::load_mut_unchecked(self.accounts.protocol)? };
+        protocol.set_inner(
+            self.accounts.authority.address().to_bytes(),
+            self.instruction_data.min_fee,
+            self.instruction_data.max_fee,
+            self.instruction_data.protocol_fee,
+            self.instruction_data.bump,
+        )
+    }
+}
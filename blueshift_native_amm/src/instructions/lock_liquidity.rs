@@ -0,0 +1,175 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::{
+    pda::check_mint_lp,
+    token::{check_token_program, token_owner, TransferChecked},
+    Config, LpLock,
+};
+
+// ==================== Accounts ====================
+
+pub struct LockLiquidityAccounts<'a> {
+    pub owner: &'a AccountView,
+    pub config: &'a AccountView,
+    pub lock: &'a AccountView,
+    pub owner_lp_ata: &'a AccountView,
+    pub lock_vault: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for LockLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, config, lock, owner_lp_ata, lock_vault, mint_lp, _system_program, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            owner,
+            config,
+            lock,
+            owner_lp_ata,
+            lock_vault,
+            mint_lp,
+            token_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct LockLiquidityInstructionData {
+    /// Tells the owner's locks in a pool apart
+    pub id: u64,
+    pub amount: u64,
+    pub unlock_at: i64,
+    pub linear: u8, // bool as u8 for packed struct
+    /// Bump of the lock PDA
+    pub lock_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for LockLiquidityInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== LockLiquidity Instruction ====================
+
+/// Locks `amount` of the owner's LP tokens in a new lock until `unlock_at`,
+/// vesting linearly until then if `linear` is set. The tokens go to the
+/// lock's ATA, created by the client.
+pub struct LockLiquidity<'a> {
+    pub accounts: LockLiquidityAccounts<'a>,
+    pub instruction_data: LockLiquidityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for LockLiquidity<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = LockLiquidityAccounts::try_from(accounts)?;
+        let instruction_data = LockLiquidityInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero
+        if instruction_data.amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> LockLiquidity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. The lock must end in the future
+        let now = Clock::get()?.unix_timestamp;
+        if self.instruction_data.unlock_at <= now {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 2. Verify the config is a pool and the LP mint is its own
+        let lp_decimals = Config::load(self.accounts.config)?.lp_decimals();
+        check_mint_lp(self.accounts.config.address(), self.accounts.mint_lp.address())?;
+
+        // 3. The vault must be a token account of the lock, so only the lock
+        //    can move what's in it
+        check_token_program(self.accounts.token_program)?;
+        if token_owner(self.accounts.lock_vault, self.accounts.token_program)?.as_slice()
+            != self.accounts.lock.address().as_ref()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 4. Create the lock account
+        let id_binding = self.instruction_data.id.to_le_bytes();
+        let lock_seeds = [
+            Seed::from(b"lock"),
+            Seed::from(self.accounts.config.address().as_ref()),
+            Seed::from(self.accounts.owner.address().as_ref()),
+            Seed::from(&id_binding),
+            Seed::from(&self.instruction_data.lock_bump),
+        ];
+        let lock_signer = Signer::from(&lock_seeds);
+
+        create_account_with_minimum_balance_signed(
+            self.accounts.lock,
+            LpLock::LEN,
+            &crate::ID,
+            self.accounts.owner,
+            None,  // rent_sysvar - use syscall
+            &[lock_signer],
+        )?;
+
+        LpLock::load_mut(self.accounts.lock)?.set_inner(
+            self.accounts.config.address().to_bytes(),
+            self.accounts.owner.address().to_bytes(),
+            self.accounts.lock_vault.address().to_bytes(),
+            self.instruction_data.id,
+            self.instruction_data.amount,
+            now,
+            self.instruction_data.unlock_at,
+            self.instruction_data.linear != 0,
+            self.instruction_data.lock_bump,
+        );
+
+        // 5. Move the LP tokens into the lock; the LP mint has no transfer
+        //    fee, so the full amount arrives
+        TransferChecked {
+            from: self.accounts.owner_lp_ata,
+            mint: self.accounts.mint_lp,
+            to: self.accounts.lock_vault,
+            authority: self.accounts.owner,
+            amount: self.instruction_data.amount,
+            decimals: lp_decimals,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke()
+    }
+}
@@ -0,0 +1,136 @@
+use pinocchio::{
+    AccountView,
+    Address,
+    error::ProgramError,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::{
+    pda::{check_vault_addresses, vault_addresses},
+    quote::DEFAULT_LP_DECIMALS,
+    token::{check_token_program, token_amount},
+    AmmError, Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct MigrateConfigAccounts<'a> {
+    pub payer: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for MigrateConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [payer, config, vault_x, vault_y, token_program, _system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !payer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_token_program(token_program)?;
+
+        Ok(Self {
+            payer,
+            config,
+            vault_x,
+            vault_y,
+            token_program,
+        })
+    }
+}
+
+// ==================== MigrateConfig Instruction ====================
+
+/// Grows a config written by an older program version to the current layout
+/// and stamps `Config::VERSION`. Permissionless: the new fields start out
+/// zeroed, at what every older pool had, or (for version 0 pools, which
+/// predate reserve tracking) read from the pool's own vaults, so the result
+/// doesn't depend on who migrates; the payer only funds the extra rent.
+pub struct MigrateConfig<'a> {
+    pub accounts: MigrateConfigAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for MigrateConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = MigrateConfigAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> MigrateConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Read the config's layout version from its length (and version byte)
+        if !self.accounts.config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let version = {
+            let data = self.accounts.config.try_borrow()?;
+            let version_byte = data.get(Config::VERSION_OFFSET).copied().unwrap_or(0);
+            Config::layout_version(data.len(), version_byte)
+                .ok_or(ProgramError::InvalidAccountData)?
+        };
+        if version >= Config::VERSION {
+            return Err(AmmError::ConfigAlreadyMigrated.into());
+        }
+
+        // 2. Top up rent for the larger account from the payer
+        let required = Rent::get()?.try_minimum_balance(Config::LEN)?;
+        let shortfall = required.saturating_sub(self.accounts.config.lamports());
+        if shortfall > 0 {
+            Transfer {
+                from: self.accounts.payer,
+                to: self.accounts.config,
+                lamports: shortfall,
+            }
+            .invoke()?;
+        }
+
+        // 3. Grow to the current layout; the added bytes are zero-initialized
+        self.accounts.config.resize(Config::LEN)?;
+
+        // 4. Stamp the current version. Every other field added so far
+        //    defaults to zero (e.g. no fee recipient or protocol fee), except:
+        //    - version 0 pools didn't record their vaults or track reserves,
+        //      so record the config's vault ATAs and take the reserves to be
+        //      everything they hold;
+        //    - LP decimals became configurable in version 6 and were fixed before.
+        let mut config = Config::load_mut(self.accounts.config)?;
+        if version == 0 {
+            let [vault_x, vault_y] = vault_addresses(
+                self.accounts.config.address(),
+                self.accounts.token_program.address(),
+                [
+                    &Address::new_from_array(*config.mint_x()),
+                    &Address::new_from_array(*config.mint_y()),
+                ],
+            );
+            check_vault_addresses(
+                [self.accounts.vault_x.address(), self.accounts.vault_y.address()],
+                [&vault_x, &vault_y],
+            )?;
+            config.set_vaults(vault_x, vault_y);
+            config.set_reserves(
+                token_amount(self.accounts.vault_x, self.accounts.token_program)?,
+                token_amount(self.accounts.vault_y, self.accounts.token_program)?,
+            );
+        }
+        if version < 6 {
+            config.set_lp_decimals(DEFAULT_LP_DECIMALS);
+        }
+        config.set_version(Config::VERSION);
+
+        Ok(())
+    }
+}
@@ -2,8 +2,80 @@ pub mod initialize;
 pub mod deposit;
 pub mod withdraw;
 pub mod swap;
+pub mod update_fee;
+pub mod set_state;
+pub mod propose_authority;
+pub mod accept_authority;
+pub mod claim_fees;
+pub mod read_oracle;
+pub mod flash_loan;
+pub mod flash_repay;
+pub mod route;
+pub mod sync;
+pub mod skim;
+pub mod quote;
+pub mod create_gauge;
+pub mod fund_gauge;
+pub mod stake;
+pub mod unstake;
+pub mod claim_rewards;
+pub mod set_price_impact_limit;
+pub mod migrate_config;
+pub mod donate;
+pub mod set_fee_recipient;
+pub mod set_oracle;
+pub mod deposit_single;
+pub mod withdraw_single;
+pub mod deposit_exact;
+pub mod close_pool;
+pub mod batch_swap;
+pub mod create_lp_metadata;
+pub mod lock_liquidity;
+pub mod unlock_liquidity;
+pub mod emergency_withdraw;
+pub mod set_swap_throttle;
+pub mod deposit_with_slippage;
+pub mod withdraw_with_slippage;
+pub mod initialize_protocol;
+pub mod set_protocol_config;
 
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use swap::*;
+pub use update_fee::*;
+pub use set_state::*;
+pub use propose_authority::*;
+pub use accept_authority::*;
+pub use claim_fees::*;
+pub use read_oracle::*;
+pub use flash_loan::*;
+pub use flash_repay::*;
+pub use route::*;
+pub use sync::*;
+pub use skim::*;
+pub use quote::*;
+pub use create_gauge::*;
+pub use fund_gauge::*;
+pub use stake::*;
+pub use unstake::*;
+pub use claim_rewards::*;
+pub use set_price_impact_limit::*;
+pub use migrate_config::*;
+pub use donate::*;
+pub use set_fee_recipient::*;
+pub use set_oracle::*;
+pub use deposit_single::*;
+pub use withdraw_single::*;
+pub use deposit_exact::*;
+pub use close_pool::*;
+pub use batch_swap::*;
+pub use create_lp_metadata::*;
+pub use lock_liquidity::*;
+pub use unlock_liquidity::*;
+pub use emergency_withdraw::*;
+pub use set_swap_throttle::*;
+pub use deposit_with_slippage::*;
+pub use withdraw_with_slippage::*;
+pub use initialize_protocol::*;
+pub use set_protocol_config::*;
@@ -0,0 +1,87 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct ProposeAuthorityAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ProposeAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct ProposeAuthorityInstructionData {
+    pub new_authority: [u8; 32],
+}
+
+impl TryFrom<&[u8]> for ProposeAuthorityInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== ProposeAuthority Instruction ====================
+
+pub struct ProposeAuthority<'a> {
+    pub accounts: ProposeAuthorityAccounts<'a>,
+    pub instruction_data: ProposeAuthorityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ProposeAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ProposeAuthorityAccounts::try_from(accounts)?;
+        let instruction_data = ProposeAuthorityInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ProposeAuthority<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        let new_authority = self.instruction_data.new_authority;
+
+        // 2. Proposing the zero authority renounces it right away: nobody can
+        //    sign for it, and the pool becomes immutable
+        if new_authority == [0; 32] {
+            config.set_authority([0; 32]);
+            config.set_pending_authority([0; 32]);
+            return Ok(());
+        }
+
+        // 3. Otherwise record the proposal (replacing any earlier one) until
+        //    the new authority accepts it
+        config.set_pending_authority(new_authority);
+
+        Ok(())
+    }
+}
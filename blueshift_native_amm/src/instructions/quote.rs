@@ -0,0 +1,133 @@
+use pinocchio::{
+    AccountView,
+    cpi::set_return_data,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    quote::Pool,
+    token::{check_token_program, MintInfo},
+    Config, PoolOperation,
+};
+
+// ==================== Accounts ====================
+
+pub struct QuoteAccounts<'a> {
+    pub config: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuoteAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, token_program, mint_x, mint_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            config,
+            token_program,
+            mint_x,
+            mint_y,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct QuoteInstructionData {
+    pub is_x: u8, // bool as u8 for packed struct
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for QuoteInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+impl QuoteInstructionData {
+    #[inline]
+    pub fn is_x(&self) -> bool {
+        self.is_x != 0
+    }
+}
+
+// ==================== Quote Instruction ====================
+
+/// Read-only: prices a `Swap` of `amount` against the current reserves and
+/// returns the `SwapOutcome` it would produce, without moving tokens
+pub struct Quote<'a> {
+    pub accounts: QuoteAccounts<'a>,
+    pub instruction_data: QuoteInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Quote<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = QuoteAccounts::try_from(accounts)?;
+        let instruction_data = QuoteInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero
+        if instruction_data.amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Quote<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config; a pool that can't swap can't be quoted
+        let config = Config::load(self.accounts.config)?;
+        config.check_state(PoolOperation::Swap)?;
+
+        // 2. Verify the token program and mints, and read the mints' transfer fees
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let epoch = Clock::get()?.epoch;
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, epoch)?;
+
+        // 3. Price the swap with the same math `Swap` runs
+        let pool = Pool {
+            reserve_x: config.reserve_x(),
+            reserve_y: config.reserve_y(),
+            fee: config.fee(),
+            lp_supply: 0, // not used for swaps
+        };
+        let outcome = pool.swap(
+            self.instruction_data.is_x(),
+            self.instruction_data.amount,
+            &mint_x,
+            &mint_y,
+        )?;
+
+        // 4. Return what would reach the user
+        set_return_data(&outcome.to_bytes());
+
+        Ok(())
+    }
+}
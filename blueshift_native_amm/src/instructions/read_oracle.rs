@@ -0,0 +1,91 @@
+use pinocchio::{
+    AccountView,
+    cpi::set_return_data,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct ReadOracleAccounts<'a> {
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ReadOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { config })
+    }
+}
+
+// ==================== Return Data ====================
+
+/// Returned by `ReadOracle`: the cumulative prices brought up to `timestamp`.
+/// Two reads `t` seconds apart give the TWAP as the wrapping difference / `t`.
+#[repr(C, packed)]
+pub struct OracleObservation {
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub timestamp: i64,
+}
+
+impl OracleObservation {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..16].copy_from_slice(&{ self.price_x_cumulative }.to_le_bytes());
+        bytes[16..32].copy_from_slice(&{ self.price_y_cumulative }.to_le_bytes());
+        bytes[32..].copy_from_slice(&{ self.timestamp }.to_le_bytes());
+        bytes
+    }
+}
+
+// ==================== ReadOracle Instruction ====================
+
+pub struct ReadOracle<'a> {
+    pub accounts: ReadOracleAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ReadOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = ReadOracleAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ReadOracle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config
+        let config = Config::load(self.accounts.config)?;
+
+        // 2. Extend the accumulators to now without writing them back
+        let now = Clock::get()?.unix_timestamp;
+        let (price_x_cumulative, price_y_cumulative) =
+            config.cumulative_prices(config.reserve_x(), config.reserve_y(), now);
+
+        set_return_data(
+            &OracleObservation {
+                price_x_cumulative,
+                price_y_cumulative,
+                timestamp: now,
+            }
+            .to_bytes(),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{expiration::check_expiration, SwapLeg};
+
+/// Accounts per hop: config, vault_x, vault_y, mint_x, mint_y, user_x_ata, user_y_ata
+pub(crate) const LEG_ACCOUNTS: usize = 7;
+
+// ==================== Accounts ====================
+
+pub struct RouteAccounts<'a> {
+    pub user: &'a AccountView,
+    pub token_program: &'a AccountView,
+    /// `LEG_ACCOUNTS` accounts per hop, in swap order
+    pub legs: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RouteAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, token_program, legs @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            user,
+            token_program,
+            legs,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+/// Fixed part of the data; one direction byte per hop follows (non-zero = X in)
+#[repr(C, packed)]
+pub struct RouteInstructionData {
+    pub amount: u64,
+    pub min: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for RouteInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== Route Instruction ====================
+
+/// Multi-hop swap (X -> Y -> Z ...) through pools sharing one token program.
+/// Each hop's output lands in the user's token account that the next hop
+/// spends from; `min` is checked once, on what the last hop delivers.
+pub struct Route<'a> {
+    pub accounts: RouteAccounts<'a>,
+    pub instruction_data: RouteInstructionData,
+    pub directions: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Route<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RouteAccounts::try_from(accounts)?;
+
+        let header = core::mem::size_of::<RouteInstructionData>();
+        if data.len() < header {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (data, directions) = data.split_at(header);
+        let instruction_data = RouteInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero
+        if instruction_data.amount == 0 || instruction_data.min == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Validate hop count and that every hop has its accounts
+        if !(1..=Self::MAX_HOPS).contains(&directions.len()) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if accounts.legs.len() != directions.len() * LEG_ACCOUNTS {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            directions,
+        })
+    }
+}
+
+impl<'a> Route<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    /// Most pools a single route may go through
+    pub const MAX_HOPS: usize = 4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
+        let clock = Clock::get()?;
+        check_expiration(self.instruction_data.expiration, &clock)?;
+
+        // 2. Swap through each pool, feeding what arrived into the next hop
+        let hops = self.directions.len();
+        let mut amount = self.instruction_data.amount;
+        let mut previous_out: Option<&AccountView> = None;
+
+        for (hop, (accounts, direction)) in self
+            .accounts
+            .legs
+            .chunks_exact(LEG_ACCOUNTS)
+            .zip(self.directions)
+            .enumerate()
+        {
+            let [config, vault_x, vault_y, mint_x, mint_y, user_x_ata, user_y_ata] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let leg = SwapLeg {
+                config,
+                vault_x,
+                vault_y,
+                mint_x,
+                mint_y,
+                user_x_ata,
+                user_y_ata,
+                referrer: None,
+                // No tail accounts: pools with transfer hooks or an oracle
+                // can't be routed through
+                hook_accounts: &[],
+            };
+
+            let is_x = *direction != 0;
+            let (user_in, user_out) = match is_x {
+                true => (user_x_ata, user_y_ata),
+                false => (user_y_ata, user_x_ata),
+            };
+
+            // Each hop must spend from the account the previous hop paid into
+            if let Some(previous_out) = previous_out {
+                if previous_out.address() != user_in.address() {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+
+            // Intermediate hops only need to produce something; the caller's
+            // minimum applies to the final output
+            let min = match hop + 1 == hops {
+                true => self.instruction_data.min,
+                false => 1,
+            };
+
+            amount = leg.swap(
+                self.accounts.user,
+                self.accounts.token_program,
+                is_x,
+                amount,
+                min,
+                &clock,
+            )?
+            .amount_out;
+            previous_out = Some(user_out);
+        }
+
+        Ok(())
+    }
+}
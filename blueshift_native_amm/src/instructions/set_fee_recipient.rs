@@ -0,0 +1,78 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct SetFeeRecipientAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetFeeRecipientAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetFeeRecipientInstructionData {
+    pub fee_recipient: [u8; 32], // All zeroes sends fees back to the authority
+}
+
+impl TryFrom<&[u8]> for SetFeeRecipientInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetFeeRecipient Instruction ====================
+
+/// Sets who may receive protocol fees and skimmed tokens, e.g. a DAO treasury;
+/// ClaimFees and Skim then only pay out to token accounts it owns
+pub struct SetFeeRecipient<'a> {
+    pub accounts: SetFeeRecipientAccounts<'a>,
+    pub instruction_data: SetFeeRecipientInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetFeeRecipient<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetFeeRecipientAccounts::try_from(accounts)?;
+        let instruction_data = SetFeeRecipientInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetFeeRecipient<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Set the recipient
+        config.set_fee_recipient(self.instruction_data.fee_recipient);
+
+        Ok(())
+    }
+}
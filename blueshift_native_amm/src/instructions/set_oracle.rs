@@ -0,0 +1,80 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct SetOracleAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetOracleInstructionData {
+    pub oracle: [u8; 32],
+    pub band_bps: u16, // 0 (with a zero oracle) removes the guard
+}
+
+impl TryFrom<&[u8]> for SetOracleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetOracle Instruction ====================
+
+/// Sets the Pyth price account (X priced in Y) swaps are checked against, and
+/// how far their execution price may stray from it before failing with
+/// `AmmError::OraclePriceOutOfBand`
+pub struct SetOracle<'a> {
+    pub accounts: SetOracleAccounts<'a>,
+    pub instruction_data: SetOracleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetOracleAccounts::try_from(accounts)?;
+        let instruction_data = SetOracleInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetOracle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Set the oracle and band (at most 10_000 basis points)
+        config.set_oracle_band(self.instruction_data.oracle, self.instruction_data.band_bps)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct SetPriceImpactLimitAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetPriceImpactLimitAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetPriceImpactLimitInstructionData {
+    pub max_price_impact_bps: u16, // 0 removes the limit
+}
+
+impl TryFrom<&[u8]> for SetPriceImpactLimitInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetPriceImpactLimit Instruction ====================
+
+/// Sets how far a single swap may move the pool's price before it fails with
+/// `AmmError::PriceImpactTooHigh`
+pub struct SetPriceImpactLimit<'a> {
+    pub accounts: SetPriceImpactLimitAccounts<'a>,
+    pub instruction_data: SetPriceImpactLimitInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetPriceImpactLimit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetPriceImpactLimitAccounts::try_from(accounts)?;
+        let instruction_data = SetPriceImpactLimitInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetPriceImpactLimit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Set the limit (at most 10_000 basis points)
+        config.set_max_price_impact_bps(self.instruction_data.max_price_impact_bps)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::ProtocolConfig;
+
+// ==================== Accounts ====================
+
+pub struct SetProtocolConfigAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub protocol: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetProtocolConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, protocol] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            authority,
+            protocol,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetProtocolConfigInstructionData {
+    pub min_fee: u16,
+    pub max_fee: u16,
+    pub protocol_fee: u16,
+}
+
+impl TryFrom<&[u8]> for SetProtocolConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetProtocolConfig Instruction ====================
+
+/// Replaces the pool fee bounds and the protocol share new pools start with.
+/// Existing pools are left as they are.
+pub struct SetProtocolConfig<'a> {
+    pub accounts: SetProtocolConfigAccounts<'a>,
+    pub instruction_data: SetProtocolConfigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetProtocolConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetProtocolConfigAccounts::try_from(accounts)?;
+        let instruction_data = SetProtocolConfigInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetProtocolConfig<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &39;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load the protocol config and verify its authority signed
+        let mut protocol = ProtocolConfig::load_mut(self.accounts.protocol)?;
+        protocol.check_authority(self.accounts.authority)?;
+
+        // 2. Set the new bounds and default share
+        protocol.set_fees(
+            self.instruction_data.min_fee,
+            self.instruction_data.max_fee,
+            self.instruction_data.protocol_fee,
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::{AmmState, Config};
+
+// ==================== Accounts ====================
+
+pub struct SetStateAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetStateInstructionData {
+    pub state: u8,
+}
+
+impl TryFrom<&[u8]> for SetStateInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetState Instruction ====================
+
+pub struct SetState<'a> {
+    pub accounts: SetStateAccounts<'a>,
+    pub instruction_data: SetStateInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetStateAccounts::try_from(accounts)?;
+        let instruction_data = SetStateInstructionData::try_from(data)?;
+
+        // A live pool can't go back to Uninitialized
+        if instruction_data.state == AmmState::Uninitialized as u8 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Move the pool to Initialized, Disabled or WithdrawOnly
+        config
+            .set_state(self.instruction_data.state)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(())
+    }
+}
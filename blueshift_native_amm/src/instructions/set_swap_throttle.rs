@@ -0,0 +1,80 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct SetSwapThrottleAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SetSwapThrottleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct SetSwapThrottleInstructionData {
+    pub enabled: u8, // bool as u8 for packed struct
+}
+
+impl TryFrom<&[u8]> for SetSwapThrottleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== SetSwapThrottle Instruction ====================
+
+/// Turns the pool's swap throttle on or off. While on, a signer's swap fails
+/// with `AmmError::SwapThrottled` if the same signer swapped the other way
+/// earlier in the slot, the easiest sandwich to pull off on a small pool.
+/// Costs every swap an extra config write.
+pub struct SetSwapThrottle<'a> {
+    pub accounts: SetSwapThrottleAccounts<'a>,
+    pub instruction_data: SetSwapThrottleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SetSwapThrottle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SetSwapThrottleAccounts::try_from(accounts)?;
+        let instruction_data = SetSwapThrottleInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SetSwapThrottle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &35;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Switch the throttle
+        config.set_swap_throttle(self.instruction_data.enabled != 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::create_account_with_minimum_balance_signed;
+
+use crate::{
+    token::{check_token_program, mint_decimals, TransferChecked},
+    Gauge, StakePosition,
+};
+
+// ==================== Accounts ====================
+
+pub struct StakeAccounts<'a> {
+    pub owner: &'a AccountView,
+    pub gauge: &'a AccountView,
+    pub position: &'a AccountView,
+    pub owner_lp_ata: &'a AccountView,
+    pub lp_vault: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for StakeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, gauge, position, owner_lp_ata, lp_vault, mint_lp, _system_program, token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            owner,
+            gauge,
+            position,
+            owner_lp_ata,
+            lp_vault,
+            mint_lp,
+            token_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct StakeInstructionData {
+    pub amount: u64,
+    /// Bump of the position PDA, used when it is created
+    pub position_bump: [u8; 1],
+}
+
+impl TryFrom<&[u8]> for StakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== Stake Instruction ====================
+
+/// Stakes `amount` LP tokens into the gauge, creating the owner's position on
+/// first use
+pub struct Stake<'a> {
+    pub accounts: StakeAccounts<'a>,
+    pub instruction_data: StakeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Stake<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = StakeAccounts::try_from(accounts)?;
+        let instruction_data = StakeInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero
+        if instruction_data.amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Stake<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &18;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Verify the LP vault is the gauge's
+        Gauge::load(self.accounts.gauge)?.check_lp_vault(self.accounts.lp_vault)?;
+
+        // 2. Create the position on first stake, otherwise verify it is the owner's
+        if self.accounts.position.data_len() == 0 {
+            let position_seeds = [
+                Seed::from(b"stake"),
+                Seed::from(self.accounts.gauge.address().as_ref()),
+                Seed::from(self.accounts.owner.address().as_ref()),
+                Seed::from(&self.instruction_data.position_bump),
+            ];
+            let position_signer = Signer::from(&position_seeds);
+
+            create_account_with_minimum_balance_signed(
+                self.accounts.position,
+                StakePosition::LEN,
+                &crate::ID,
+                self.accounts.owner,
+                None,  // rent_sysvar - use syscall
+                &[position_signer],
+            )?;
+
+            StakePosition::load_mut(self.accounts.position)?.set_inner(
+                self.accounts.gauge.address().to_bytes(),
+                self.accounts.owner.address().to_bytes(),
+                self.instruction_data.position_bump,
+            );
+        } else {
+            StakePosition::load_mut(self.accounts.position)?
+                .check(self.accounts.gauge, self.accounts.owner)?;
+        }
+
+        // 3. Transfer the LP tokens into the gauge; the LP mint has no
+        //    transfer fee, so the full amount arrives
+        check_token_program(self.accounts.token_program)?;
+        TransferChecked {
+            from: self.accounts.owner_lp_ata,
+            mint: self.accounts.mint_lp,
+            to: self.accounts.lp_vault,
+            authority: self.accounts.owner,
+            amount: self.instruction_data.amount,
+            decimals: mint_decimals(self.accounts.mint_lp, self.accounts.token_program)?,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke()?;
+
+        // 4. Settle rewards earned so far, then grow the stake
+        let now = Clock::get()?.unix_timestamp;
+        let mut gauge = Gauge::load_mut(self.accounts.gauge)?;
+        let mut position = StakePosition::load_mut(self.accounts.position)?;
+        position.settle(gauge.update(now)?)?;
+        position.add_stake(self.instruction_data.amount)?;
+        gauge.add_stake(self.instruction_data.amount)?;
+
+        Ok(())
+    }
+}
@@ -1,22 +1,25 @@
-use constant_product_curve::{ConstantProduct, LiquidityPair};
 use pinocchio::{
     AccountView,
-    Address,
-    cpi::{Seed, Signer},
+    cpi::{set_return_data, Signer},
     error::ProgramError,
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::Transfer,
-    state::TokenAccount,
+use crate::{
+    events::{Event, SwapEvent},
+    curve::check_product,
+    expiration::check_expiration,
+    native::{native_side, split_native_flag, unwrap, wrap},
+    pyth::PythPrice,
+    quote::{curve_swap, price_impact_bps},
+    token::{check_token_program, token_amount, token_owner, MintInfo, TransferChecked},
+    Config, PoolOperation,
 };
 
-use crate::{AmmState, Config};
-
 // ==================== Accounts ====================
 
 pub struct SwapAccounts<'a> {
+    /// Owner of the input token account, or a delegate approved on it
     pub user: &'a AccountView,
     pub user_x_ata: &'a AccountView,
     pub user_y_ata: &'a AccountView,
@@ -24,17 +27,35 @@ pub struct SwapAccounts<'a> {
     pub vault_y: &'a AccountView,
     pub config: &'a AccountView,
     pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    /// Optional token account (in the input token) paid the referral share of the fee
+    pub referrer: Option<&'a AccountView>,
+    /// Extra accounts for Token-2022 transfer hooks on either mint, and the
+    /// pool's Pyth price account if it has one
+    pub hook_accounts: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program] = accounts
+        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program, mint_x, mint_y, rest @ ..] =
+            accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Tail: [referrer, hook accounts and oracle..]; this program's ID in
+        // the referrer slot stands for no referrer
+        let (referrer, hook_accounts) = match rest.split_first() {
+            Some((referrer, hook_accounts)) => (
+                (referrer.address() != &crate::ID).then_some(referrer),
+                hook_accounts,
+            ),
+            None => (None, rest),
+        };
+
         Ok(Self {
             user,
             user_x_ata,
@@ -43,6 +64,10 @@ impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
             vault_y,
             config,
             token_program,
+            mint_x,
+            mint_y,
+            referrer,
+            hook_accounts,
         })
     }
 }
@@ -75,11 +100,37 @@ impl SwapInstructionData {
     }
 }
 
+// ==================== Return Data ====================
+
+/// Returned by `Swap` and `Quote`: what the vault received, what reached the user after
+/// the output mint's transfer fee, and the pool fee taken from the input
+#[repr(C, packed)]
+pub struct SwapOutcome {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+impl SwapOutcome {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&{ self.amount_in }.to_le_bytes());
+        bytes[8..16].copy_from_slice(&{ self.amount_out }.to_le_bytes());
+        bytes[16..].copy_from_slice(&{ self.fee }.to_le_bytes());
+        bytes
+    }
+}
+
 // ==================== Swap Instruction ====================
 
 pub struct Swap<'a> {
     pub accounts: SwapAccounts<'a>,
     pub instruction_data: SwapInstructionData,
+    /// Pay the pool's native-mint side in or out as lamports
+    pub native: bool,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Swap<'a> {
@@ -87,6 +138,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Swap<'a> {
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = SwapAccounts::try_from(accounts)?;
+        let (data, native) = split_native_flag::<SwapInstructionData>(data);
         let instruction_data = SwapInstructionData::try_from(data)?;
 
         // Validate amounts are greater than zero
@@ -97,6 +149,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Swap<'a> {
         Ok(Self {
             accounts,
             instruction_data,
+            native,
         })
     }
 }
@@ -105,136 +158,322 @@ impl<'a> Swap<'a> {
     pub const DISCRIMINATOR: &'a u8 = &3;
 
     pub fn process(&mut self) -> ProgramResult {
-        // 1. Check expiration using Clock sysvar
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
         let clock = Clock::get()?;
-        if clock.unix_timestamp >= self.instruction_data.expiration {
-            return Err(ProgramError::Custom(1)); // Order expired
+        check_expiration(self.instruction_data.expiration, &clock)?;
+
+        // 2. Wrap the input from the user's lamports if it is native SOL
+        let is_x = self.instruction_data.is_x();
+        let native_x = match self.native {
+            true => Some(native_side(self.accounts.mint_x, self.accounts.mint_y)?),
+            false => None,
+        };
+        if native_x == Some(is_x) {
+            // Fail fast on a pool that can't swap before paying for the CPIs
+            Config::load(self.accounts.config)?.check_state(PoolOperation::Swap)?;
+            wrap(
+                self.accounts.user,
+                match is_x {
+                    true => self.accounts.user_x_ata,
+                    false => self.accounts.user_y_ata,
+                },
+                self.instruction_data.amount,
+                self.accounts.token_program,
+                self.accounts.hook_accounts,
+            )?;
         }
 
-        // 2. Load and validate config
-        let config = Config::load(self.accounts.config)?;
+        // 3. Swap through the pool
+        let leg = SwapLeg {
+            config: self.accounts.config,
+            vault_x: self.accounts.vault_x,
+            vault_y: self.accounts.vault_y,
+            mint_x: self.accounts.mint_x,
+            mint_y: self.accounts.mint_y,
+            user_x_ata: self.accounts.user_x_ata,
+            user_y_ata: self.accounts.user_y_ata,
+            referrer: self.accounts.referrer,
+            hook_accounts: self.accounts.hook_accounts,
+        };
+        let outcome = leg.swap(
+            self.accounts.user,
+            self.accounts.token_program,
+            is_x,
+            self.instruction_data.amount,
+            self.instruction_data.min,
+            &clock,
+        )?;
+
+        // 4. Unwrap the native side back into the user's wallet
+        if let Some(native_x) = native_x {
+            unwrap(
+                match native_x {
+                    true => self.accounts.user_x_ata,
+                    false => self.accounts.user_y_ata,
+                },
+                self.accounts.user,
+                self.accounts.token_program,
+            )?;
+        }
+
+        // 5. Report the amounts to CPI callers and simulators
+        set_return_data(&outcome.to_bytes());
+
+        Ok(())
+    }
+}
+
+// ==================== Swap Leg ====================
+
+/// One pool a swap goes through, with the user's token accounts on both sides,
+/// the referrer paid a share of the fee and the mints' transfer hook accounts
+/// (plus the pool's oracle), if any. Shared by `Swap` and each hop of `Route`.
+pub struct SwapLeg<'a> {
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    pub user_x_ata: &'a AccountView,
+    pub user_y_ata: &'a AccountView,
+    pub referrer: Option<&'a AccountView>,
+    pub hook_accounts: &'a [AccountView],
+}
 
-        // Verify pool state allows swaps (must be initialized)
-        if config.state() != AmmState::Initialized as u8 {
+impl SwapLeg<'_> {
+    /// Swap `amount` of X (`is_x`) or Y for the other token, failing if less
+    /// than `min` reaches the user
+    pub fn swap(
+        &self,
+        user: &AccountView,
+        token_program: &AccountView,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+        clock: &Clock,
+    ) -> Result<SwapOutcome, ProgramError> {
+        // 1. Load and validate config
+        let config = Config::load(self.config)?;
+
+        // Verify pool state allows swaps (Initialized only)
+        config.check_state(PoolOperation::Swap)?;
+
+        // With the throttle on, a signer can't swap both ways in one slot
+        config.check_swap_throttle(user.address(), is_x, clock.slot)?;
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.vault_x, self.vault_y)?;
+
+        // 3. Verify the token program and mints, and read the mints' transfer fees
+        check_token_program(token_program)?;
+        if self.mint_x.address().as_ref() != config.mint_x()
+            || self.mint_y.address().as_ref() != config.mint_y()
+        {
             return Err(ProgramError::InvalidAccountData);
         }
+        let mint_x = MintInfo::load(self.mint_x, token_program, clock.epoch)?;
+        let mint_y = MintInfo::load(self.mint_y, token_program, clock.epoch)?;
+
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
 
-        // 3. Verify vault_x is valid ATA (only on-chain)
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+        // 4. Pick the accounts for the swap direction
+        let (user_in, user_out, vault_in, vault_out, mint_in, mint_out) = match is_x {
+            // User sends X, receives Y
+            true => (
+                self.user_x_ata,
+                self.user_y_ata,
+                self.vault_x,
+                self.vault_y,
+                (self.mint_x, mint_x),
+                (self.mint_y, mint_y),
+            ),
+            // User sends Y, receives X
+            false => (
+                self.user_y_ata,
+                self.user_x_ata,
+                self.vault_y,
+                self.vault_x,
+                (self.mint_y, mint_y),
+                (self.mint_x, mint_x),
+            ),
+        };
+
+        // A delegate of the input account may sign in the owner's place (e.g.
+        // a session key with a bounded allowance), but the output must go
+        // back to the owner
+        let owner = token_owner(user_in, token_program)?;
+        if user.address().as_ref() != owner.as_slice()
+            && token_owner(user_out, token_program)? != owner
         {
-            let (vault_x_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_x(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_x_addr.ne(self.accounts.vault_x.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
+            return Err(ProgramError::InvalidAccountData);
         }
 
-        // 4. Verify vault_y is valid ATA
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
-        {
-            let (vault_y_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_y(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_y_addr.ne(self.accounts.vault_y.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
+        // 5. Take the input first (user signs): with a transfer fee the vault
+        // gets less than `amount`, and only what arrived is priced
+        let vault_in_amount = token_amount(vault_in, token_program)?;
+        let vault_out_amount = token_amount(vault_out, token_program)?;
+        TransferChecked {
+            from: user_in,
+            mint: mint_in.0,
+            to: vault_in,
+            authority: user,
+            amount,
+            decimals: mint_in.1.decimals,
+            token_program: token_program.address(),
         }
+        .invoke_with_hook_accounts(self.hook_accounts)?;
 
-        // 5. Deserialize the token accounts
-        let vault_x_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
+        let received = token_amount(vault_in, token_program)?
+            .checked_sub(vault_in_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
         // 6. Calculate swap using constant product curve
-        let mut curve = ConstantProduct::init(
-            vault_x_account.amount(),
-            vault_y_account.amount(),
-            vault_x_account.amount(), // l parameter (not used for swap)
-            config.fee(),
-            None,
-        )
-        .map_err(|_| ProgramError::Custom(1))?;
-
-        let pair = match self.instruction_data.is_x() {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
-        };
+        let swap_result = curve_swap(reserve_x, reserve_y, config.fee(), is_x, received, min)?;
 
-        let swap_result = curve
-            .swap(pair, self.instruction_data.amount, self.instruction_data.min)
-            .map_err(|_| ProgramError::Custom(1))?;
-
-        // 7. Validate swap result
-        if swap_result.deposit == 0 || swap_result.withdraw == 0 {
+        // 7. `min` applies to what reaches the user after the output mint's
+        // transfer fee
+        let delivered = swap_result.withdraw - mint_out.1.fee_for(swap_result.withdraw);
+        if delivered < min {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // 8. Prepare config PDA signer for vault transfers
-        let seed_binding = config.seed().to_le_bytes();
-        let bump_binding = config.config_bump();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(config.mint_x()),
-            Seed::from(config.mint_y()),
-            Seed::from(&bump_binding),
-        ];
-
-        // 9. Execute transfers based on swap direction
-        if self.instruction_data.is_x() {
-            // User sends X, receives Y
-            // Transfer X from user to vault_x (user signs)
-            Transfer {
-                from: self.accounts.user_x_ata,
-                to: self.accounts.vault_x,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
-            }
-            .invoke()?;
-
-            // Transfer Y from vault_y to user (config PDA signs)
-            let config_signer = Signer::from(&config_seeds);
-            Transfer {
-                from: self.accounts.vault_y,
-                to: self.accounts.user_y_ata,
-                authority: self.accounts.config,
-                amount: swap_result.withdraw,
-            }
-            .invoke_signed(&[config_signer])?;
-        } else {
-            // User sends Y, receives X
-            // Transfer Y from user to vault_y (user signs)
-            Transfer {
-                from: self.accounts.user_y_ata,
-                to: self.accounts.vault_y,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
-            }
-            .invoke()?;
-
-            // Transfer X from vault_x to user (config PDA signs)
-            let config_signer = Signer::from(&config_seeds);
-            Transfer {
-                from: self.accounts.vault_x,
-                to: self.accounts.user_x_ata,
-                authority: self.accounts.config,
-                amount: swap_result.withdraw,
+        // Refuse trades that would move the price past the pool's limit
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+        config.check_price_impact(price_impact_bps(
+            reserve_in,
+            reserve_out,
+            received,
+            swap_result.withdraw,
+        )?)?;
+
+        // Refuse trades priced too far from the pool's oracle, if it has one;
+        // the price account is looked up among the tail accounts
+        if let Some(oracle) = config.oracle() {
+            let oracle = self
+                .hook_accounts
+                .iter()
+                .find(|account| account.address().as_ref() == oracle.as_slice())
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let (amount_x, amount_y) = match is_x {
+                true => (received, swap_result.withdraw),
+                false => (swap_result.withdraw, received),
+            };
+            PythPrice::load(oracle, clock.slot)?.check_band(
+                amount_x,
+                amount_y,
+                mint_x.decimals,
+                mint_y.decimals,
+                config.oracle_band_bps(),
+            )?;
+        }
+
+        // 8. Prepare config PDA signer for vault transfers, once for both
+        let config_seeds = config.signer_seeds();
+        let config_signer = [Signer::from(&config_seeds)];
+
+        // 9. Pay out from the other vault (config PDA signs)
+        TransferChecked {
+            from: vault_out,
+            mint: mint_out.0,
+            to: user_out,
+            authority: self.config,
+            amount: swap_result.withdraw,
+            decimals: mint_out.1.decimals,
+            token_program: token_program.address(),
+        }
+        .invoke_signed_with_hook_accounts(&config_signer, self.hook_accounts)?;
+
+        // 10. Pay the referrer its share of the fee, in the deposited token.
+        // Without a referrer that share stays with the LPs.
+        let referral_share = match self.referrer {
+            Some(referrer) => {
+                if referrer.address() == vault_in.address() {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let referral_share = config.referral_share(swap_result.fee);
+                if referral_share > 0 {
+                    TransferChecked {
+                        from: vault_in,
+                        mint: mint_in.0,
+                        to: referrer,
+                        authority: self.config,
+                        amount: referral_share,
+                        decimals: mint_in.1.decimals,
+                        token_program: token_program.address(),
+                    }
+                    .invoke_signed_with_hook_accounts(&config_signer, self.hook_accounts)?;
+                }
+                referral_share
             }
-            .invoke_signed(&[config_signer])?;
+            None => 0,
+        };
+
+        // 11. Record the pre-swap price for the TWAP oracle, add the trade to
+        // the pool's stats (and the throttle) and set aside the protocol's
+        // share of the fee, which was paid in the deposited token
+        // (config is re-borrowed mutably once the CPIs are done)
+        drop(config);
+        let mut config = Config::load_mut(self.config)?;
+        config.update_oracle(reserve_x, reserve_y, clock.unix_timestamp);
+        config.record_swap(is_x, received, swap_result.withdraw, swap_result.fee);
+        config.record_swap_slot(user.address(), is_x, clock.slot);
+        let protocol_share = config.accrue_protocol_fee(is_x, swap_result.fee)?;
+
+        // 12. Last line of defence against math or transfer fee surprises:
+        // what the vaults hold for the pool, less protocol fees, must not
+        // multiply to less than before
+        let (fees_in, fees_out) = match is_x {
+            true => (config.protocol_fees_x(), config.protocol_fees_y()),
+            false => (config.protocol_fees_y(), config.protocol_fees_x()),
+        };
+        let pool_balance = |amount: u64, fees: u64| {
+            amount.checked_sub(fees).ok_or(ProgramError::ArithmeticOverflow)
+        };
+        check_product(
+            (
+                pool_balance(vault_in_amount, fees_in - protocol_share)?,
+                pool_balance(vault_out_amount, fees_out)?,
+            ),
+            (
+                pool_balance(token_amount(vault_in, token_program)?, fees_in)?,
+                pool_balance(token_amount(vault_out, token_program)?, fees_out)?,
+            ),
+        )?;
+
+        // Move the rest of the trade (less the referral) into the reserves
+        let reserve_in = reserve_in
+            .checked_add(received - protocol_share - referral_share)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_out = reserve_out
+            .checked_sub(swap_result.withdraw)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let (reserve_x, reserve_y) = match is_x {
+            true => (reserve_in, reserve_out),
+            false => (reserve_out, reserve_in),
+        };
+        config.set_reserves(reserve_x, reserve_y);
+
+        // 13. Log the trade for indexers
+        SwapEvent {
+            pool: *self.config.address(),
+            user: *user.address(),
+            is_x: is_x as u8,
+            amount_in: received,
+            amount_out: delivered,
+            fee: swap_result.fee,
+            reserve_x,
+            reserve_y,
         }
+        .emit();
 
-        Ok(())
+        Ok(SwapOutcome {
+            amount_in: received,
+            amount_out: delivered,
+            fee: swap_result.fee,
+        })
     }
 }
@@ -0,0 +1,94 @@
+use pinocchio::{
+    AccountView,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    events::{Event, SyncEvent},
+    token::{check_token_program, token_amount},
+    Config,
+};
+
+// ==================== Accounts ====================
+
+pub struct SyncAccounts<'a> {
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for SyncAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, vault_x, vault_y, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            config,
+            vault_x,
+            vault_y,
+            token_program,
+        })
+    }
+}
+
+// ==================== Sync Instruction ====================
+
+/// Permissionless: folds tokens sent straight to the vaults into the reserves,
+/// so they are priced and shared by the LPs
+pub struct Sync<'a> {
+    pub accounts: SyncAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for Sync<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = SyncAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Sync<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config; the vaults are short while a flash loan is out
+        let mut config = Config::load_mut(self.accounts.config)?;
+        if config.flash_loan_active() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 2. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
+
+        // 3. Read the excess in each vault
+        check_token_program(self.accounts.token_program)?;
+        let excess_x =
+            config.excess_x(token_amount(self.accounts.vault_x, self.accounts.token_program)?)?;
+        let excess_y =
+            config.excess_y(token_amount(self.accounts.vault_y, self.accounts.token_program)?)?;
+
+        // 4. Close the oracle period at the old price, then add the excess
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
+        config.update_oracle(reserve_x, reserve_y, Clock::get()?.unix_timestamp);
+        let reserve_x = reserve_x + excess_x;
+        let reserve_y = reserve_y + excess_y;
+        config.set_reserves(reserve_x, reserve_y);
+
+        SyncEvent {
+            pool: *self.accounts.config.address(),
+            reserve_x,
+            reserve_y,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+use solana_challenge_common::lamports;
+
+use crate::{
+    token::{check_token_program, mint_decimals, CloseAccount, TransferChecked},
+    AmmError, LpLock,
+};
+
+// ==================== Accounts ====================
+
+pub struct UnlockLiquidityAccounts<'a> {
+    pub owner: &'a AccountView,
+    pub lock: &'a AccountView,
+    pub owner_lp_ata: &'a AccountView,
+    pub lock_vault: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UnlockLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, lock, owner_lp_ata, lock_vault, mint_lp, token_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            owner,
+            lock,
+            owner_lp_ata,
+            lock_vault,
+            mint_lp,
+            token_program,
+        })
+    }
+}
+
+// ==================== UnlockLiquidity Instruction ====================
+
+/// Returns what has vested of a lock to its owner. Once everything is
+/// released, the lock and its token account are closed and their rent goes
+/// to the owner.
+pub struct UnlockLiquidity<'a> {
+    pub accounts: UnlockLiquidityAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UnlockLiquidity<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let accounts = UnlockLiquidityAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> UnlockLiquidity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Record the vested, unreleased amount as released. Scoped so the
+        //    lock isn't borrowed when it signs below.
+        let (amount, done, config, id, bump) = {
+            let mut lock = LpLock::load_mut(self.accounts.lock)?;
+            lock.check(self.accounts.owner, self.accounts.lock_vault)?;
+
+            let amount = lock.releasable(Clock::get()?.unix_timestamp);
+            if amount == 0 {
+                return Err(AmmError::LiquidityLocked.into());
+            }
+            lock.release(amount)?;
+            (
+                amount,
+                lock.released() == lock.amount(),
+                *lock.config(),
+                lock.id().to_le_bytes(),
+                lock.bump(),
+            )
+        };
+
+        // 2. Return the LP tokens, signed by the lock
+        check_token_program(self.accounts.token_program)?;
+        let lock_seeds = [
+            Seed::from(b"lock"),
+            Seed::from(&config),
+            Seed::from(self.accounts.owner.address().as_ref()),
+            Seed::from(&id),
+            Seed::from(&bump),
+        ];
+
+        TransferChecked {
+            from: self.accounts.lock_vault,
+            mint: self.accounts.mint_lp,
+            to: self.accounts.owner_lp_ata,
+            authority: self.accounts.lock,
+            amount,
+            decimals: mint_decimals(self.accounts.mint_lp, self.accounts.token_program)?,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_signed(&[Signer::from(&lock_seeds)])?;
+
+        // 3. Once fully released, close the vault and the lock into the owner
+        if done {
+            CloseAccount {
+                account: self.accounts.lock_vault,
+                destination: self.accounts.owner,
+                authority: self.accounts.lock,
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&[Signer::from(&lock_seeds)])?;
+
+            let balance =
+                lamports::sweep(self.accounts.lock.lamports(), self.accounts.owner.lamports())
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.accounts.owner.set_lamports(balance);
+            self.accounts.lock.close()?;
+        }
+
+        Ok(())
+    }
+}
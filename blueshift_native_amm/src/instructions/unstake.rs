@@ -0,0 +1,138 @@
+use pinocchio::{
+    AccountView,
+    cpi::{Seed, Signer},
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    token::{check_token_program, mint_decimals, TransferChecked},
+    Gauge, StakePosition,
+};
+
+// ==================== Accounts ====================
+
+pub struct UnstakeAccounts<'a> {
+    pub owner: &'a AccountView,
+    pub gauge: &'a AccountView,
+    pub position: &'a AccountView,
+    pub owner_lp_ata: &'a AccountView,
+    pub lp_vault: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UnstakeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [owner, gauge, position, owner_lp_ata, lp_vault, mint_lp, token_program] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            owner,
+            gauge,
+            position,
+            owner_lp_ata,
+            lp_vault,
+            mint_lp,
+            token_program,
+        })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct UnstakeInstructionData {
+    pub amount: u64,
+}
+
+impl TryFrom<&[u8]> for UnstakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== Unstake Instruction ====================
+
+/// Returns `amount` staked LP tokens to the owner; earned rewards stay owed
+/// until claimed
+pub struct Unstake<'a> {
+    pub accounts: UnstakeAccounts<'a>,
+    pub instruction_data: UnstakeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Unstake<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UnstakeAccounts::try_from(accounts)?;
+        let instruction_data = UnstakeInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero
+        if instruction_data.amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Unstake<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Settle rewards earned so far, then shrink the stake. Scoped so the
+        //    gauge isn't borrowed when it signs below.
+        let (config, bump) = {
+            let mut gauge = Gauge::load_mut(self.accounts.gauge)?;
+            gauge.check_lp_vault(self.accounts.lp_vault)?;
+            let mut position = StakePosition::load_mut(self.accounts.position)?;
+            position.check(self.accounts.gauge, self.accounts.owner)?;
+
+            let now = Clock::get()?.unix_timestamp;
+            position.settle(gauge.update(now)?)?;
+            position.remove_stake(self.instruction_data.amount)?;
+            gauge.remove_stake(self.instruction_data.amount)?;
+            (*gauge.config(), gauge.bump())
+        };
+
+        // 2. Return the LP tokens, signed by the gauge
+        check_token_program(self.accounts.token_program)?;
+        let gauge_seeds = [
+            Seed::from(b"gauge"),
+            Seed::from(&config),
+            Seed::from(&bump),
+        ];
+        let gauge_signer = Signer::from(&gauge_seeds);
+
+        TransferChecked {
+            from: self.accounts.lp_vault,
+            mint: self.accounts.mint_lp,
+            to: self.accounts.owner_lp_ata,
+            authority: self.accounts.gauge,
+            amount: self.instruction_data.amount,
+            decimals: mint_decimals(self.accounts.mint_lp, self.accounts.token_program)?,
+            token_program: self.accounts.token_program.address(),
+        }
+        .invoke_signed(&[gauge_signer])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::Config;
+
+// ==================== Accounts ====================
+
+pub struct UpdateFeeAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UpdateFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self { authority, config })
+    }
+}
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct UpdateFeeInstructionData {
+    pub fee: u16,
+    pub protocol_fee: u16, // optional, KEEP_PROTOCOL_FEE when omitted
+    pub referral_fee: u16, // optional, KEEP_REFERRAL_FEE when omitted
+}
+
+impl UpdateFeeInstructionData {
+    /// Filled in when the data carries only `fee`, leaving the protocol share as is
+    pub const KEEP_PROTOCOL_FEE: u16 = u16::MAX;
+    /// Filled in when the data stops before `referral_fee`, leaving the referral share as is
+    pub const KEEP_REFERRAL_FEE: u16 = u16::MAX;
+
+    #[inline]
+    pub fn protocol_fee(&self) -> Option<u16> {
+        match self.protocol_fee {
+            Self::KEEP_PROTOCOL_FEE => None,
+            protocol_fee => Some(protocol_fee),
+        }
+    }
+
+    #[inline]
+    pub fn referral_fee(&self) -> Option<u16> {
+        match self.referral_fee {
+            Self::KEEP_REFERRAL_FEE => None,
+            referral_fee => Some(referral_fee),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for UpdateFeeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        const UPDATE_FEE_DATA_LEN_WITH_REFERRAL_FEE: usize =
+            core::mem::size_of::<UpdateFeeInstructionData>();
+        const UPDATE_FEE_DATA_LEN_WITH_PROTOCOL_FEE: usize = 2 * core::mem::size_of::<u16>();
+        const UPDATE_FEE_DATA_LEN: usize = core::mem::size_of::<u16>();
+
+        match data.len() {
+            UPDATE_FEE_DATA_LEN_WITH_REFERRAL_FEE => {
+                let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+                // The sentinels are not valid shares, so they can't be sent explicitly
+                if instruction_data.protocol_fee().is_none()
+                    || instruction_data.referral_fee().is_none()
+                {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Ok(instruction_data)
+            }
+            // Fee and protocol share - keep the current referral share
+            UPDATE_FEE_DATA_LEN_WITH_PROTOCOL_FEE => {
+                let protocol_fee = u16::from_le_bytes([data[2], data[3]]);
+                if protocol_fee == Self::KEEP_PROTOCOL_FEE {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Ok(Self {
+                    fee: u16::from_le_bytes([data[0], data[1]]),
+                    protocol_fee,
+                    referral_fee: Self::KEEP_REFERRAL_FEE,
+                })
+            }
+            // Fee only - keep the current protocol and referral shares
+            UPDATE_FEE_DATA_LEN => Ok(Self {
+                fee: u16::from_le_bytes([data[0], data[1]]),
+                protocol_fee: Self::KEEP_PROTOCOL_FEE,
+                referral_fee: Self::KEEP_REFERRAL_FEE,
+            }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+// ==================== UpdateFee Instruction ====================
+
+pub struct UpdateFee<'a> {
+    pub accounts: UpdateFeeAccounts<'a>,
+    pub instruction_data: UpdateFeeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for UpdateFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateFeeAccounts::try_from(accounts)?;
+        let instruction_data = UpdateFeeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Load config and verify the pool authority signed
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.check_authority(self.accounts.authority)?;
+
+        // 2. Set the new fee (must stay below 10_000 basis points)
+        config.set_fee(self.instruction_data.fee)?;
+
+        // 3. Optionally set the protocol's and the referrer's shares of the
+        //    fee (together at most 10_000 basis points). A new referral share
+        //    replaces the old one, so it doesn't count against the protocol's.
+        let referral_fee = self.instruction_data.referral_fee();
+        if referral_fee.is_some() {
+            config.set_referral_fee(0)?;
+        }
+        if let Some(protocol_fee) = self.instruction_data.protocol_fee() {
+            config.set_protocol_fee(protocol_fee)?;
+        }
+        if let Some(referral_fee) = referral_fee {
+            config.set_referral_fee(referral_fee)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,18 +1,19 @@
-use constant_product_curve::ConstantProduct;
 use pinocchio::{
     AccountView,
-    Address,
-    cpi::{Seed, Signer},
+    cpi::{set_return_data, Signer},
     error::ProgramError,
     sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::{Burn, Transfer},
-    state::{Mint, TokenAccount},
-};
 
-use crate::{AmmState, Config};
+use crate::{
+    events::{Event, RemoveLiquidityEvent},
+    expiration::check_expiration,
+    native::{native_side, split_native_flag, unwrap},
+    quote::withdraw_amounts,
+    token::{check_token_program, Burn, MintInfo, TransferChecked},
+    Config, PoolOperation,
+};
 
 // ==================== Accounts ====================
 
@@ -26,13 +27,17 @@ pub struct WithdrawAccounts<'a> {
     pub user_lp_ata: &'a AccountView,
     pub config: &'a AccountView,
     pub token_program: &'a AccountView,
+    pub mint_x: &'a AccountView,
+    pub mint_y: &'a AccountView,
+    /// Extra accounts for Token-2022 transfer hooks on either mint
+    pub hook_accounts: &'a [AccountView],
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program, mint_x, mint_y, hook_accounts @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -48,6 +53,9 @@ impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
             user_lp_ata,
             config,
             token_program,
+            mint_x,
+            mint_y,
+            hook_accounts,
         })
     }
 }
@@ -73,11 +81,37 @@ impl TryFrom<&[u8]> for WithdrawInstructionData {
     }
 }
 
+// ==================== Return Data ====================
+
+/// Returned by `Withdraw`: what reached the user after the mints' transfer
+/// fees and the LP tokens burned
+#[repr(C, packed)]
+pub struct WithdrawOutcome {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_burned: u64,
+}
+
+impl WithdrawOutcome {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0u8; Self::LEN];
+        bytes[..8].copy_from_slice(&{ self.amount_x }.to_le_bytes());
+        bytes[8..16].copy_from_slice(&{ self.amount_y }.to_le_bytes());
+        bytes[16..].copy_from_slice(&{ self.lp_burned }.to_le_bytes());
+        bytes
+    }
+}
+
 // ==================== Withdraw Instruction ====================
 
 pub struct Withdraw<'a> {
     pub accounts: WithdrawAccounts<'a>,
     pub instruction_data: WithdrawInstructionData,
+    /// Pay the pool's native-mint side out as lamports
+    pub native: bool,
 }
 
 impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Withdraw<'a> {
@@ -85,6 +119,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Withdraw<'a> {
 
     fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
         let accounts = WithdrawAccounts::try_from(accounts)?;
+        let (data, native) = split_native_flag::<WithdrawInstructionData>(data);
         let instruction_data = WithdrawInstructionData::try_from(data)?;
 
         // Validate amounts are greater than zero
@@ -95,6 +130,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Withdraw<'a> {
         Ok(Self {
             accounts,
             instruction_data,
+            native,
         })
     }
 }
@@ -103,123 +139,136 @@ impl<'a> Withdraw<'a> {
     pub const DISCRIMINATOR: &'a u8 = &2;
 
     pub fn process(&mut self) -> ProgramResult {
-        // 1. Check expiration using Clock sysvar
-        let clock = Clock::get()?;
-        if clock.unix_timestamp >= self.instruction_data.expiration {
-            return Err(ProgramError::Custom(1)); // Order expired
+        let outcome = self.withdraw()?;
+
+        // Unwrap the native side into the user's wallet
+        if self.native {
+            unwrap(
+                match native_side(self.accounts.mint_x, self.accounts.mint_y)? {
+                    true => self.accounts.user_x_ata,
+                    false => self.accounts.user_y_ata,
+                },
+                self.accounts.user,
+                self.accounts.token_program,
+            )?;
         }
 
+        // Report the amounts to CPI callers and simulators
+        set_return_data(&outcome.to_bytes());
+
+        Ok(())
+    }
+
+    /// Burn the LP tokens and pay out both sides; shared with `WithdrawSingle`
+    pub fn withdraw(&self) -> Result<WithdrawOutcome, ProgramError> {
+        // 1. Check expiration (a timestamp or slot) using Clock sysvar
+        let clock = Clock::get()?;
+        check_expiration(self.instruction_data.expiration, &clock)?;
+
         // 2. Load and validate config
         let config = Config::load(self.accounts.config)?;
 
-        // Verify pool state is not disabled (allows withdrawals even when not initialized)
-        if config.state() == AmmState::Disabled as u8 {
-            return Err(ProgramError::InvalidAccountData);
-        }
+        // Verify pool state allows withdrawals (Initialized or WithdrawOnly)
+        config.check_state(PoolOperation::Withdraw)?;
 
-        // 3. Verify vault_x is valid ATA (only on-chain, syscall not available off-chain)
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
-        {
-            let (vault_x_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_x(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_x_addr.ne(self.accounts.vault_x.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
-        }
+        // 3. Verify the vaults are the ones recorded at initialization
+        config.check_vaults(self.accounts.vault_x, self.accounts.vault_y)?;
 
-        // 4. Verify vault_y is valid ATA
-        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+        // 4. Verify the token program and mints, and read the mints
+        check_token_program(self.accounts.token_program)?;
+        if self.accounts.mint_x.address().as_ref() != config.mint_x()
+            || self.accounts.mint_y.address().as_ref() != config.mint_y()
         {
-            let (vault_y_addr, _) = Address::find_program_address(
-                &[
-                    self.accounts.config.address().as_ref(),
-                    self.accounts.token_program.address().as_ref(),
-                    config.mint_y(),
-                ],
-                &pinocchio_associated_token_account::ID,
-            );
-            if vault_y_addr.ne(self.accounts.vault_y.address()) {
-                return Err(ProgramError::InvalidAccountData);
-            }
+            return Err(ProgramError::InvalidAccountData);
         }
+        let mint_x = MintInfo::load(self.accounts.mint_x, self.accounts.token_program, clock.epoch)?;
+        let mint_y = MintInfo::load(self.accounts.mint_y, self.accounts.token_program, clock.epoch)?;
+        let mint_lp =
+            MintInfo::load(self.accounts.mint_lp, self.accounts.token_program, clock.epoch)?;
 
-        // 5. Deserialize the token accounts
-        let mint_lp = unsafe { Mint::from_account_view_unchecked(self.accounts.mint_lp)? };
-        let vault_x_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y_account =
-            unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
-
-        // 6. Calculate withdraw amounts
-        let (x, y) = match mint_lp.supply() == self.instruction_data.amount {
-            // If withdrawing all LP tokens, get all remaining tokens
-            true => (vault_x_account.amount(), vault_y_account.amount()),
-            // Otherwise calculate proportional amounts
-            false => {
-                let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-                    vault_x_account.amount(),
-                    vault_y_account.amount(),
-                    mint_lp.supply(),
-                    self.instruction_data.amount,
-                    6, // LP token decimals
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                (amounts.x, amounts.y)
-            }
-        };
+        let reserve_x = config.reserve_x();
+        let reserve_y = config.reserve_y();
 
-        // 7. Check for slippage (ensure user gets at least min amounts)
-        if !(x >= self.instruction_data.min_x && y >= self.instruction_data.min_y) {
+        // 5. Calculate withdraw amounts
+        // (all remaining reserves when withdrawing all LP tokens)
+        let (x, y) = withdraw_amounts(
+            reserve_x,
+            reserve_y,
+            mint_lp.supply,
+            self.instruction_data.amount,
+        )?;
+
+        // 6. Check for slippage (ensure user gets at least min amounts after
+        //    the mints' transfer fees)
+        if !(x - mint_x.fee_for(x) >= self.instruction_data.min_x
+            && y - mint_y.fee_for(y) >= self.instruction_data.min_y)
+        {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // 8. Prepare config PDA signer for vault transfers
-        let seed_binding = config.seed().to_le_bytes();
-        let bump_binding = config.config_bump();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(config.mint_x()),
-            Seed::from(config.mint_y()),
-            Seed::from(&bump_binding),
-        ];
-        let config_signer = Signer::from(&config_seeds);
-
-        // 9. Transfer token X from vault to user
-        Transfer {
+        // 7. Prepare config PDA signer for vault transfers
+        let config_seeds = config.signer_seeds();
+        let config_signer = [Signer::from(&config_seeds)];
+
+        // 8. Transfer token X from vault to user
+        TransferChecked {
             from: self.accounts.vault_x,
+            mint: self.accounts.mint_x,
             to: self.accounts.user_x_ata,
             authority: self.accounts.config,
             amount: x,
+            decimals: mint_x.decimals,
+            token_program: self.accounts.token_program.address(),
         }
-        .invoke_signed(&[config_signer])?;
+        .invoke_signed_with_hook_accounts(&config_signer, self.accounts.hook_accounts)?;
 
-        // 10. Transfer token Y from vault to user
-        // Need to recreate signer due to move
-        let config_signer2 = Signer::from(&config_seeds);
-        Transfer {
+        // 9. Transfer token Y from vault to user
+        TransferChecked {
             from: self.accounts.vault_y,
+            mint: self.accounts.mint_y,
             to: self.accounts.user_y_ata,
             authority: self.accounts.config,
             amount: y,
+            decimals: mint_y.decimals,
+            token_program: self.accounts.token_program.address(),
         }
-        .invoke_signed(&[config_signer2])?;
+        .invoke_signed_with_hook_accounts(&config_signer, self.accounts.hook_accounts)?;
 
-        // 11. Burn LP tokens from user's account
+        // 10. Burn LP tokens from user's account
         Burn {
             mint: self.accounts.mint_lp,
             account: self.accounts.user_lp_ata,
             authority: self.accounts.user,
             amount: self.instruction_data.amount,
+            token_program: self.accounts.token_program.address(),
         }
         .invoke()?;
 
-        Ok(())
+        // 11. Take what left the vaults out of the reserves
+        // (config is re-borrowed mutably once the CPIs are done)
+        drop(config);
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let reserve_x = reserve_x.checked_sub(x).ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_y = reserve_y.checked_sub(y).ok_or(ProgramError::ArithmeticOverflow)?;
+        config.set_reserves(reserve_x, reserve_y);
+
+        let outcome = WithdrawOutcome {
+            amount_x: x - mint_x.fee_for(x),
+            amount_y: y - mint_y.fee_for(y),
+            lp_burned: self.instruction_data.amount,
+        };
+        // Log the withdrawal for indexers
+        RemoveLiquidityEvent {
+            pool: *self.accounts.config.address(),
+            user: *self.accounts.user.address(),
+            amount_x: outcome.amount_x,
+            amount_y: outcome.amount_y,
+            lp_burned: outcome.lp_burned,
+            reserve_x,
+            reserve_y,
+        }
+        .emit();
+
+        Ok(outcome)
     }
 }
@@ -0,0 +1,155 @@
+use pinocchio::{
+    AccountView,
+    cpi::set_return_data,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{SwapLeg, Withdraw, WithdrawAccounts, WithdrawInstructionData, WithdrawOutcome};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct WithdrawSingleInstructionData {
+    pub is_x: u8, // bool as u8 for packed struct; receive X (or Y)
+    pub amount: u64,
+    pub min_out: u64,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for WithdrawSingleInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+impl WithdrawSingleInstructionData {
+    #[inline]
+    pub fn is_x(&self) -> bool {
+        self.is_x != 0
+    }
+}
+
+// ==================== WithdrawSingle Instruction ====================
+
+/// Burns `amount` LP tokens for both sides, then swaps the other side through
+/// the pool so the user ends up with X (`is_x`) or Y alone, at least
+/// `min_out` of it. Takes `Withdraw`'s accounts; the pool must allow swaps.
+/// Returns a `WithdrawOutcome` with everything on the side received.
+pub struct WithdrawSingle<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instruction_data: WithdrawSingleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for WithdrawSingle<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawSingleInstructionData::try_from(data)?;
+
+        // Validate amounts are greater than zero
+        if instruction_data.amount == 0 || instruction_data.min_out == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawSingle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Withdraw both sides; `min_out` below covers slippage
+        let withdrawn = Withdraw {
+            accounts: WithdrawAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                hook_accounts: self.accounts.hook_accounts,
+            },
+            instruction_data: WithdrawInstructionData {
+                amount: self.instruction_data.amount,
+                min_x: 0,
+                min_y: 0,
+                expiration: self.instruction_data.expiration,
+            },
+            native: false,
+        }
+        .withdraw()?;
+
+        // 2. Swap what arrived of the other token into the one kept
+        let is_x = self.instruction_data.is_x();
+        let (kept, other) = match is_x {
+            true => (withdrawn.amount_x, withdrawn.amount_y),
+            false => (withdrawn.amount_y, withdrawn.amount_x),
+        };
+        let swapped = match other {
+            0 => 0,
+            other => {
+                let leg = SwapLeg {
+                    config: self.accounts.config,
+                    vault_x: self.accounts.vault_x,
+                    vault_y: self.accounts.vault_y,
+                    mint_x: self.accounts.mint_x,
+                    mint_y: self.accounts.mint_y,
+                    user_x_ata: self.accounts.user_x_ata,
+                    user_y_ata: self.accounts.user_y_ata,
+                    referrer: None,
+                    hook_accounts: self.accounts.hook_accounts,
+                };
+                leg.swap(
+                    self.accounts.user,
+                    self.accounts.token_program,
+                    !is_x,
+                    other,
+                    1,
+                    &Clock::get()?,
+                )?
+                .amount_out
+            }
+        };
+
+        // 3. Check for slippage on the total received
+        let amount_out = kept
+            .checked_add(swapped)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if amount_out < self.instruction_data.min_out {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 4. Report the amounts to CPI callers and simulators
+        let (amount_x, amount_y) = match is_x {
+            true => (amount_out, 0),
+            false => (0, amount_out),
+        };
+        set_return_data(
+            &WithdrawOutcome {
+                amount_x,
+                amount_y,
+                lp_burned: withdrawn.lp_burned,
+            }
+            .to_bytes(),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use pinocchio::{AccountView, error::ProgramError, ProgramResult};
+
+use crate::{
+    quote::{min_with_slippage, MAX_SLIPPAGE_BPS},
+    Withdraw, WithdrawAccounts, WithdrawInstructionData,
+};
+
+// ==================== Instruction Data ====================
+
+#[repr(C, packed)]
+pub struct WithdrawWithSlippageInstructionData {
+    pub amount: u64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub slippage_bps: u16,
+    pub expiration: i64,
+}
+
+impl TryFrom<&[u8]> for WithdrawWithSlippageInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+// ==================== WithdrawWithSlippage Instruction ====================
+
+/// `Withdraw` with its limits given the way frontends show them: `amount_x`
+/// and `amount_y` are what burning `amount` LP paid out at the reserves the
+/// user was quoted, and each may come in up to `slippage_bps` lower. Takes
+/// `Withdraw`'s accounts and returns its `WithdrawOutcome`.
+pub struct WithdrawWithSlippage<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instruction_data: WithdrawWithSlippageInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for WithdrawWithSlippage<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawWithSlippageInstructionData::try_from(data)?;
+
+        // Validate amount is greater than zero and the tolerance is sane
+        if instruction_data.amount == 0 || instruction_data.slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawWithSlippage<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &37;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 1. Turn the tolerance into absolute limits
+        let slippage_bps = self.instruction_data.slippage_bps;
+        let min_x = min_with_slippage(self.instruction_data.amount_x, slippage_bps);
+        let min_y = min_with_slippage(self.instruction_data.amount_y, slippage_bps);
+
+        // 2. Burn `amount` LP for at least those amounts
+        Withdraw {
+            accounts: WithdrawAccounts {
+                user: self.accounts.user,
+                mint_lp: self.accounts.mint_lp,
+                vault_x: self.accounts.vault_x,
+                vault_y: self.accounts.vault_y,
+                user_x_ata: self.accounts.user_x_ata,
+                user_y_ata: self.accounts.user_y_ata,
+                user_lp_ata: self.accounts.user_lp_ata,
+                config: self.accounts.config,
+                token_program: self.accounts.token_program,
+                mint_x: self.accounts.mint_x,
+                mint_y: self.accounts.mint_y,
+                hook_accounts: self.accounts.hook_accounts,
+            },
+            instruction_data: WithdrawInstructionData {
+                amount: self.instruction_data.amount,
+                min_x,
+                min_y,
+                expiration: self.instruction_data.expiration,
+            },
+            native: false,
+        }
+        .process()
+    }
+}
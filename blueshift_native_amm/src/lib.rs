@@ -1,11 +1,12 @@
 #![no_std]
 
 use pinocchio::{
-    AccountView, Address, entrypoint, ProgramResult,
+    AccountView, Address, ProgramResult,
     error::ProgramError,
 };
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 pub mod instructions;
 pub use instructions::*;
@@ -13,13 +14,60 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+pub mod gauge;
+pub use gauge::*;
+
+pub mod registry;
+pub use registry::*;
+
+pub mod protocol;
+pub use protocol::*;
+
+pub mod lock;
+pub use lock::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod math;
+
+pub mod curve;
+
+pub mod events;
+
+pub mod expiration;
+
+pub mod quote;
+
+pub mod pyth;
+
+pub mod native;
+
+pub mod pda;
+
+pub mod metadata;
+
+#[cfg(feature = "client")]
+extern crate std;
+#[cfg(feature = "client")]
+pub mod client;
+
+pub mod token;
+
 // Program ID: 22222222222222222222222222222222
+#[cfg(not(feature = "standalone-id"))]
 pub const ID: Address = Address::new_from_array([
     0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
     0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07, 0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
 ]);
 
-fn process_instruction(
+// Program ID of the `standalone-id` build, for deploying next to a program
+// that holds the challenge ID (anchor_escrow's `take_via_amm` tests)
+#[cfg(feature = "standalone-id")]
+pub const ID: Address =
+    Address::new_from_array(solana_challenge_common::ids::AMM_STANDALONE_PROGRAM_ID);
+
+pub fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
@@ -31,6 +79,72 @@ fn process_instruction(
         Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
         Some((Withdraw::DISCRIMINATOR, data)) => Withdraw::try_from((data, accounts))?.process(),
         Some((Swap::DISCRIMINATOR, data)) => Swap::try_from((data, accounts))?.process(),
+        Some((UpdateFee::DISCRIMINATOR, data)) => UpdateFee::try_from((data, accounts))?.process(),
+        Some((SetState::DISCRIMINATOR, data)) => SetState::try_from((data, accounts))?.process(),
+        Some((ProposeAuthority::DISCRIMINATOR, data)) => {
+            ProposeAuthority::try_from((data, accounts))?.process()
+        }
+        Some((AcceptAuthority::DISCRIMINATOR, _)) => AcceptAuthority::try_from(accounts)?.process(),
+        Some((ClaimFees::DISCRIMINATOR, _)) => ClaimFees::try_from(accounts)?.process(),
+        Some((ReadOracle::DISCRIMINATOR, _)) => ReadOracle::try_from(accounts)?.process(),
+        Some((FlashLoan::DISCRIMINATOR, data)) => FlashLoan::try_from((data, accounts))?.process(),
+        Some((FlashRepay::DISCRIMINATOR, _)) => FlashRepay::try_from(accounts)?.process(),
+        Some((Route::DISCRIMINATOR, data)) => Route::try_from((data, accounts))?.process(),
+        Some((Sync::DISCRIMINATOR, _)) => Sync::try_from(accounts)?.process(),
+        Some((Skim::DISCRIMINATOR, _)) => Skim::try_from(accounts)?.process(),
+        Some((Quote::DISCRIMINATOR, data)) => Quote::try_from((data, accounts))?.process(),
+        Some((CreateGauge::DISCRIMINATOR, data)) => {
+            CreateGauge::try_from((data, accounts))?.process()
+        }
+        Some((FundGauge::DISCRIMINATOR, data)) => FundGauge::try_from((data, accounts))?.process(),
+        Some((Stake::DISCRIMINATOR, data)) => Stake::try_from((data, accounts))?.process(),
+        Some((Unstake::DISCRIMINATOR, data)) => Unstake::try_from((data, accounts))?.process(),
+        Some((ClaimRewards::DISCRIMINATOR, _)) => ClaimRewards::try_from(accounts)?.process(),
+        Some((SetPriceImpactLimit::DISCRIMINATOR, data)) => {
+            SetPriceImpactLimit::try_from((data, accounts))?.process()
+        }
+        Some((MigrateConfig::DISCRIMINATOR, _)) => MigrateConfig::try_from(accounts)?.process(),
+        Some((Donate::DISCRIMINATOR, data)) => Donate::try_from((data, accounts))?.process(),
+        Some((SetFeeRecipient::DISCRIMINATOR, data)) => {
+            SetFeeRecipient::try_from((data, accounts))?.process()
+        }
+        Some((SetOracle::DISCRIMINATOR, data)) => SetOracle::try_from((data, accounts))?.process(),
+        Some((DepositSingle::DISCRIMINATOR, data)) => {
+            DepositSingle::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawSingle::DISCRIMINATOR, data)) => {
+            WithdrawSingle::try_from((data, accounts))?.process()
+        }
+        Some((DepositExact::DISCRIMINATOR, data)) => {
+            DepositExact::try_from((data, accounts))?.process()
+        }
+        Some((ClosePool::DISCRIMINATOR, _)) => ClosePool::try_from(accounts)?.process(),
+        Some((BatchSwap::DISCRIMINATOR, data)) => BatchSwap::try_from((data, accounts))?.process(),
+        Some((CreateLpMetadata::DISCRIMINATOR, data)) => {
+            CreateLpMetadata::try_from((data, accounts))?.process()
+        }
+        Some((LockLiquidity::DISCRIMINATOR, data)) => {
+            LockLiquidity::try_from((data, accounts))?.process()
+        }
+        Some((UnlockLiquidity::DISCRIMINATOR, _)) => UnlockLiquidity::try_from(accounts)?.process(),
+        Some((EmergencyWithdraw::DISCRIMINATOR, data)) => {
+            EmergencyWithdraw::try_from((data, accounts))?.process()
+        }
+        Some((SetSwapThrottle::DISCRIMINATOR, data)) => {
+            SetSwapThrottle::try_from((data, accounts))?.process()
+        }
+        Some((DepositWithSlippage::DISCRIMINATOR, data)) => {
+            DepositWithSlippage::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawWithSlippage::DISCRIMINATOR, data)) => {
+            WithdrawWithSlippage::try_from((data, accounts))?.process()
+        }
+        Some((InitializeProtocol::DISCRIMINATOR, data)) => {
+            InitializeProtocol::try_from((data, accounts))?.process()
+        }
+        Some((SetProtocolConfig::DISCRIMINATOR, data)) => {
+            SetProtocolConfig::try_from((data, accounts))?.process()
+        }
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
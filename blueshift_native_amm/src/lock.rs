@@ -0,0 +1,244 @@
+//! LP token locks: a PDA per (pool, owner, id) holding LP tokens in its own
+//! token account until `unlock_at`, all at once or vesting linearly from the
+//! moment of locking.
+//!
+//! The lock account stays readable for as long as anything is locked, so
+//! anyone can check how much of a pool's LP supply is locked and until when.
+
+use core::mem::size_of;
+use pinocchio::{
+    AccountView,
+    account::{Ref, RefMut},
+    error::ProgramError,
+};
+
+// ==================== LpLock ====================
+
+/// PDA: ["lock", config, owner, id (u64 LE)]
+#[repr(C)]
+pub struct LpLock {
+    config: [u8; 32],
+    owner: [u8; 32],
+    vault: [u8; 32],
+    id: [u8; 8],
+    amount: [u8; 8],
+    released: [u8; 8],
+    start: [u8; 8],
+    unlock_at: [u8; 8],
+    linear: u8,
+    bump: [u8; 1],
+}
+
+impl LpLock {
+    pub const LEN: usize = size_of::<LpLock>();
+
+    // ==================== Read Helpers ====================
+
+    #[inline(always)]
+    pub fn load(account_view: &AccountView) -> Result<Ref<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    /// Return an `LpLock` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `LpLock`.
+    /// `LpLock` has an alignment of 1 byte. This method does not perform a length validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const LpLock)
+    }
+
+    /// Return a mutable `LpLock` reference from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `LpLock`.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut LpLock)
+    }
+
+    /// The pool whose LP tokens are locked
+    #[inline(always)]
+    pub fn config(&self) -> &[u8; 32] {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &[u8; 32] {
+        &self.owner
+    }
+
+    /// The lock's LP token account (its ATA), holding what is still locked
+    #[inline(always)]
+    pub fn vault(&self) -> &[u8; 32] {
+        &self.vault
+    }
+
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        u64::from_le_bytes(self.id)
+    }
+
+    /// LP tokens locked in total
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// LP tokens already returned to the owner
+    #[inline(always)]
+    pub fn released(&self) -> u64 {
+        u64::from_le_bytes(self.released)
+    }
+
+    /// Unix timestamp the tokens were locked at
+    #[inline(always)]
+    pub fn start(&self) -> i64 {
+        i64::from_le_bytes(self.start)
+    }
+
+    /// Unix timestamp everything is unlocked by
+    #[inline(always)]
+    pub fn unlock_at(&self) -> i64 {
+        i64::from_le_bytes(self.unlock_at)
+    }
+
+    /// Whether the tokens vest linearly from `start`, rather than all at `unlock_at`
+    #[inline(always)]
+    pub fn linear(&self) -> bool {
+        self.linear != 0
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// LP tokens unlocked by `now`, released or not
+    #[inline(always)]
+    pub fn vested(&self, now: i64) -> u64 {
+        let (start, unlock_at) = (self.start(), self.unlock_at());
+        if now >= unlock_at {
+            self.amount()
+        } else if self.linear() && now > start {
+            // now - start < unlock_at - start, so this stays below amount
+            (self.amount() as u128 * (now - start) as u128 / (unlock_at - start) as u128) as u64
+        } else {
+            0
+        }
+    }
+
+    /// LP tokens the owner can take out at `now`
+    #[inline(always)]
+    pub fn releasable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.released())
+    }
+
+    /// Check that this is `owner`'s lock and `vault` its token account
+    #[inline(always)]
+    pub fn check(&self, owner: &AccountView, vault: &AccountView) -> Result<(), ProgramError> {
+        if owner.address().as_ref() != self.owner.as_slice() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        if vault.address().as_ref() != self.vault.as_slice() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // ==================== Write Helpers ====================
+
+    #[inline(always)]
+    pub fn load_mut(account_view: &AccountView) -> Result<RefMut<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(
+            account_view.try_borrow_mut()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        config: [u8; 32],
+        owner: [u8; 32],
+        vault: [u8; 32],
+        id: u64,
+        amount: u64,
+        start: i64,
+        unlock_at: i64,
+        linear: bool,
+        bump: [u8; 1],
+    ) {
+        self.config = config;
+        self.owner = owner;
+        self.vault = vault;
+        self.id = id.to_le_bytes();
+        self.amount = amount.to_le_bytes();
+        self.released = [0; 8];
+        self.start = start.to_le_bytes();
+        self.unlock_at = unlock_at.to_le_bytes();
+        self.linear = linear as u8;
+        self.bump = bump;
+    }
+
+    /// Record `amount` more returned to the owner
+    #[inline(always)]
+    pub fn release(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let released = self.released().checked_add(amount);
+        self.released = released.ok_or(ProgramError::ArithmeticOverflow)?.to_le_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_lock(linear: bool, check: impl FnOnce(&mut LpLock)) {
+        let mut bytes = [0u8; LpLock::LEN];
+        let lock = unsafe { LpLock::from_bytes_unchecked_mut(&mut bytes) };
+        lock.set_inner([1; 32], [2; 32], [3; 32], 0, 1_000, 100, 200, linear, [255]);
+        check(lock);
+    }
+
+    #[test]
+    fn cliff_lock_releases_everything_at_unlock_at() {
+        with_lock(false, |lock| {
+            assert_eq!(lock.releasable(100), 0);
+            assert_eq!(lock.releasable(199), 0);
+            assert_eq!(lock.releasable(200), 1_000);
+            assert_eq!(lock.releasable(i64::MAX), 1_000);
+        });
+    }
+
+    #[test]
+    fn linear_lock_vests_from_start_and_counts_releases() {
+        with_lock(true, |lock| {
+            assert_eq!(lock.releasable(50), 0);
+            assert_eq!(lock.releasable(100), 0);
+            assert_eq!(lock.releasable(125), 250);
+            lock.release(250).unwrap();
+            assert_eq!(lock.releasable(125), 0);
+            assert_eq!(lock.releasable(199), 740); // rounds down
+            assert_eq!(lock.releasable(300), 750);
+        });
+    }
+}
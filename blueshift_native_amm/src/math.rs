@@ -0,0 +1,90 @@
+//! Fixed-point helpers shared by the curves, quotes and gauges.
+
+use pinocchio::error::ProgramError;
+
+const LO: u128 = u64::MAX as u128;
+
+/// `a * b` as a 256-bit `(hi, lo)` pair
+#[inline(always)]
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    let (a1, a0) = (a >> 64, a & LO);
+    let (b1, b0) = (b >> 64, b & LO);
+
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+
+    // Each term is < 2^64, so the sum fits in u128
+    let mid = (p00 >> 64) + (p01 & LO) + (p10 & LO);
+    let lo = (p00 & LO) | ((mid & LO) << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// `a * b / denominator` with a 256-bit intermediate, rounded down or up.
+/// Errors when the denominator is zero or the result doesn't fit in u128.
+pub fn mul_div(a: u128, b: u128, denominator: u128, round_up: bool) -> Result<u128, ProgramError> {
+    if denominator == 0 {
+        return Err(ProgramError::ArithmeticOverflow);
+    }
+
+    let (hi, lo) = full_mul(a, b);
+    if hi == 0 {
+        let quotient = lo / denominator;
+        return match round_up && lo % denominator != 0 {
+            true => quotient.checked_add(1).ok_or(ProgramError::ArithmeticOverflow),
+            false => Ok(quotient),
+        };
+    }
+    if hi >= denominator {
+        return Err(ProgramError::ArithmeticOverflow);
+    }
+
+    // Shift-subtract long division of (hi, lo) by the denominator; the running
+    // remainder stays below 2 * denominator, so a carried-out bit means it's >= denominator
+    let mut quotient = 0u128;
+    let mut remainder = hi;
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+
+    match round_up && remainder != 0 {
+        true => quotient.checked_add(1).ok_or(ProgramError::ArithmeticOverflow),
+        false => Ok(quotient),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_plain_math_when_it_fits() {
+        assert_eq!(mul_div(6, 7, 4, false), Ok(10));
+        assert_eq!(mul_div(6, 7, 4, true), Ok(11));
+        assert_eq!(mul_div(6, 7, 0, false), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn handles_256_bit_intermediates() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX, false), Ok(u128::MAX));
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90, false), Ok(1 << 110));
+        assert_eq!(mul_div(u128::MAX, 3, 2, false), Err(ProgramError::ArithmeticOverflow));
+        // (2^128 - 1) * 2 / 3 = 226854911280625642308916404954512140970.0
+        assert_eq!(
+            mul_div(u128::MAX, 2, 3, false),
+            Ok(226854911280625642308916404954512140970)
+        );
+        assert_eq!(
+            mul_div(u128::MAX, 2, 3, true),
+            Ok(226854911280625642308916404954512140970)
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Metaplex token metadata CPI for the LP mint.
+//!
+//! Works for LP mints of either token program, so wallets show the pool's
+//! name and symbol instead of an unknown token.
+
+use pinocchio::{
+    AccountView,
+    Address,
+    cpi::{invoke_signed, Signer},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+    ProgramResult,
+};
+
+/// metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s
+pub const METADATA_PROGRAM_ID: Address = Address::new_from_array([
+    0x0b, 0x70, 0x65, 0xb1, 0xe3, 0xd1, 0x7c, 0x45, 0x38, 0x9d, 0x52, 0x7f, 0x6b, 0x04, 0xc3, 0xcd,
+    0x58, 0xb8, 0x6c, 0x73, 0x1a, 0xa0, 0xfd, 0xb5, 0x49, 0xb6, 0xd1, 0xbc, 0x03, 0xf8, 0x29, 0x46,
+]);
+
+/// Longest name, symbol and URI the metadata program accepts
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
+
+/// `CreateMetadataAccountV3` instruction data with the longest strings
+const MAX_DATA_LEN: usize = 1 + 4 + MAX_NAME_LEN + 4 + MAX_SYMBOL_LEN + 4 + MAX_URI_LEN + 2 + 5;
+
+/// `CreateMetadataAccountV3` without creators, collection or uses
+pub struct CreateMetadataAccountV3<'a> {
+    pub metadata: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub mint_authority: &'a AccountView,
+    pub payer: &'a AccountView,
+    pub update_authority: &'a AccountView,
+    pub system_program: &'a AccountView,
+    pub name: &'a [u8],
+    pub symbol: &'a [u8],
+    pub uri: &'a [u8],
+}
+
+impl CreateMetadataAccountV3<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let accounts = [
+            InstructionAccount::writable(self.metadata.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::readonly_signer(self.mint_authority.address()),
+            InstructionAccount::writable_signer(self.payer.address()),
+            InstructionAccount::readonly_signer(self.update_authority.address()),
+            InstructionAccount::readonly(self.system_program.address()),
+        ];
+
+        let (data, len) = metadata_data(self.name, self.symbol, self.uri)?;
+
+        invoke_signed(
+            &InstructionView {
+                program_id: &METADATA_PROGRAM_ID,
+                accounts: &accounts,
+                data: &data[..len],
+            },
+            &[
+                self.metadata,
+                self.mint,
+                self.mint_authority,
+                self.payer,
+                self.update_authority,
+                self.system_program,
+            ],
+            signers,
+        )
+    }
+}
+
+/// `CreateMetadataAccountV3` data: [discriminator, name, symbol, uri,
+/// seller_fee_basis_points: 0, creators / collection / uses: None,
+/// is_mutable: true, collection_details: None], strings Borsh-encoded
+fn metadata_data(
+    name: &[u8],
+    symbol: &[u8],
+    uri: &[u8],
+) -> Result<([u8; MAX_DATA_LEN], usize), ProgramError> {
+    if name.len() > MAX_NAME_LEN || symbol.len() > MAX_SYMBOL_LEN || uri.len() > MAX_URI_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut data = [0u8; MAX_DATA_LEN];
+    data[0] = 33;
+    let mut len = 1;
+    for string in [name, symbol, uri] {
+        data[len..len + 4].copy_from_slice(&(string.len() as u32).to_le_bytes());
+        data[len + 4..len + 4 + string.len()].copy_from_slice(string);
+        len += 4 + string.len();
+    }
+    // Seller fee and the three empty options stay zero
+    len += 2 + 3;
+    data[len] = 1; // is_mutable
+    len += 2; // collection_details: None
+
+    Ok((data, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_is_borsh_encoded_and_strings_are_bounded() {
+        let (data, len) = metadata_data(b"X-Y LP", b"XYLP", b"").unwrap();
+        assert_eq!(len, 30);
+        assert_eq!(data[..5], [33, 6, 0, 0, 0]);
+        assert_eq!(&data[5..11], b"X-Y LP");
+        assert_eq!(data[11..15], [4, 0, 0, 0]);
+        assert_eq!(&data[15..19], b"XYLP");
+        assert_eq!(data[19..30], [0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]);
+
+        let symbol = [b'S'; MAX_SYMBOL_LEN + 1];
+        assert_eq!(
+            metadata_data(b"X-Y LP", &symbol, b"").unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+}
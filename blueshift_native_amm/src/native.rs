@@ -0,0 +1,94 @@
+//! Native SOL pools.
+//!
+//! A pool side whose mint is the native mint (wSOL) can be paid in and out as
+//! lamports: with the native flag set, the user's wSOL token account is funded
+//! from their wallet right before the pool takes from it, and closed back into
+//! the wallet at the end, so it only lives for the transaction. Clients create
+//! it (idempotently) ahead of the instruction; any wSOL it already held is
+//! unwrapped with the rest.
+
+use core::mem::size_of;
+use pinocchio::{AccountView, Address, error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::Transfer;
+use solana_challenge_common::ids;
+
+use crate::token::{check_token_program, CloseAccount, SyncNative};
+
+/// 9pan9bMn5HatX4EJdBwg9VgCa7Uz5HL8N1m5D3NdXejP, Token-2022's native mint
+pub const NATIVE_MINT_2022: Address = Address::new_from_array([
+    0x83, 0x0d, 0xfc, 0x9f, 0xde, 0x5f, 0xe6, 0xb8, 0xaa, 0x7c, 0x04, 0xa4, 0x76, 0xe9, 0x1e, 0x8a,
+    0xc6, 0xbb, 0x26, 0x4a, 0xad, 0x90, 0xfa, 0x19, 0xc9, 0xdf, 0x49, 0xd8, 0x5c, 0x3e, 0x5b, 0x5e,
+]);
+
+#[inline(always)]
+pub fn is_native_mint(mint: &AccountView) -> bool {
+    mint.address() == &Address::new_from_array(ids::NATIVE_MINT)
+        || mint.address() == &NATIVE_MINT_2022
+}
+
+/// Split instruction data for a `T` from the optional native flag byte after
+/// it; data without the byte leaves the flag off
+#[inline(always)]
+pub fn split_native_flag<T>(data: &[u8]) -> (&[u8], bool) {
+    match data.split_last() {
+        Some((flag, data)) if data.len() == size_of::<T>() => (data, *flag != 0),
+        _ => (data, false),
+    }
+}
+
+/// Whether X (or Y) is the native side of a pool with the given mints; fails
+/// if neither is
+#[inline(always)]
+pub fn native_side(mint_x: &AccountView, mint_y: &AccountView) -> Result<bool, ProgramError> {
+    match (is_native_mint(mint_x), is_native_mint(mint_y)) {
+        (true, _) => Ok(true),
+        (false, true) => Ok(false),
+        (false, false) => Err(ProgramError::InvalidArgument),
+    }
+}
+
+/// Move `lamports` from `user` into their wSOL `account` and sync its token
+/// balance; the system program must be among `tail`
+pub fn wrap(
+    user: &AccountView,
+    account: &AccountView,
+    lamports: u64,
+    token_program: &AccountView,
+    tail: &[AccountView],
+) -> ProgramResult {
+    check_token_program(token_program)?;
+    if !tail
+        .iter()
+        .any(|program| program.address() == &pinocchio_system::ID)
+    {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    Transfer {
+        from: user,
+        to: account,
+        lamports,
+    }
+    .invoke()?;
+
+    SyncNative {
+        account,
+        token_program: token_program.address(),
+    }
+    .invoke()
+}
+
+/// Close the user's wSOL `account`, unwrapping its balance and rent into `user`
+pub fn unwrap(
+    account: &AccountView,
+    user: &AccountView,
+    token_program: &AccountView,
+) -> ProgramResult {
+    CloseAccount {
+        account,
+        destination: user,
+        authority: user,
+        token_program: token_program.address(),
+    }
+    .invoke_signed(&[])
+}
@@ -0,0 +1,172 @@
+//! Addresses the program derives: the protocol config, pools' LP mints and the
+//! vault ATAs of pools and gauges.
+//!
+//! Deriving takes the find-program-address syscall on-chain and the curve25519
+//! backend off-chain, which the `client` feature and the unit tests bring in.
+//! The vaults are derived once, when a pool or gauge is created, and recorded
+//! in its account; later instructions compare against the record with plain
+//! byte checks. Builds that can't derive (the bare host library) record
+//! all-zero vaults and skip the protocol config and LP mint checks.
+
+use pinocchio::{error::ProgramError, Address};
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+use solana_challenge_common::seeds::MINT_LP_SEED;
+
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+use crate::PROTOCOL_SEED;
+
+/// `wallet`'s associated token account for `mint` under `token_program`
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+pub fn associated_token_address(
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+) -> Address {
+    Address::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &pinocchio_associated_token_account::ID,
+    )
+    .0
+}
+
+/// LP mint of the pool at `config`, and its bump
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+pub fn mint_lp_address(config: &Address) -> (Address, u8) {
+    Address::find_program_address(&[MINT_LP_SEED, config.as_ref()], &crate::ID)
+}
+
+/// The deployment's protocol config, and its bump
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+pub fn protocol_config_address() -> (Address, u8) {
+    Address::find_program_address(&[PROTOCOL_SEED], &crate::ID)
+}
+
+/// `owner`'s vaults for `mints` under `token_program`, to record at creation
+#[inline(always)]
+pub fn vault_addresses(
+    owner: &Address,
+    token_program: &Address,
+    mints: [&Address; 2],
+) -> [[u8; 32]; 2] {
+    #[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+    {
+        mints.map(|mint| associated_token_address(owner, mint, token_program).to_bytes())
+    }
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf", feature = "client", test)))]
+    {
+        let _ = (owner, token_program, mints);
+        [[0; 32]; 2]
+    }
+}
+
+/// Check that `vaults` are the ones recorded at creation
+#[inline(always)]
+pub fn check_vault_addresses(
+    vaults: [&Address; 2],
+    recorded: [&[u8; 32]; 2],
+) -> Result<(), ProgramError> {
+    if vaults[0].as_ref() != recorded[0].as_slice()
+        || vaults[1].as_ref() != recorded[1].as_slice()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Check that `protocol` is the deployment's protocol config
+#[inline(always)]
+pub fn check_protocol_config(protocol: &Address) -> Result<(), ProgramError> {
+    #[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+    {
+        if protocol != &protocol_config_address().0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf", feature = "client", test)))]
+    {
+        let _ = protocol;
+    }
+    Ok(())
+}
+
+/// Check that `mint_lp` is the LP mint of the pool at `config`
+#[inline(always)]
+pub fn check_mint_lp(config: &Address, mint_lp: &Address) -> Result<(), ProgramError> {
+    #[cfg(any(target_os = "solana", target_arch = "bpf", feature = "client", test))]
+    {
+        if mint_lp != &mint_lp_address(config).0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+    #[cfg(not(any(target_os = "solana", target_arch = "bpf", feature = "client", test)))]
+    {
+        let _ = (config, mint_lp);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_PROGRAM: Address = Address::new_from_array([6; 32]);
+    const MINT_X: Address = Address::new_from_array([4; 32]);
+    const MINT_Y: Address = Address::new_from_array([5; 32]);
+
+    fn pool(n: u8) -> Address {
+        Address::new_from_array([n; 32])
+    }
+
+    #[test]
+    fn only_the_recorded_vaults_pass() {
+        let [vault_x, vault_y] = vault_addresses(&pool(1), &TOKEN_PROGRAM, [&MINT_X, &MINT_Y]);
+        let (x, y) = (Address::new_from_array(vault_x), Address::new_from_array(vault_y));
+        assert_eq!(check_vault_addresses([&x, &y], [&vault_x, &vault_y]), Ok(()));
+
+        // Swapped, another pool's, or a wallet's own token accounts
+        let [other_x, other_y] = vault_addresses(&pool(2), &TOKEN_PROGRAM, [&MINT_X, &MINT_Y]);
+        let other = (Address::new_from_array(other_x), Address::new_from_array(other_y));
+        let wallet_x = associated_token_address(&pool(3), &MINT_X, &TOKEN_PROGRAM);
+        for vaults in [[&y, &x], [&other.0, &other.1], [&wallet_x, &y]] {
+            assert_eq!(
+                check_vault_addresses(vaults, [&vault_x, &vault_y]),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+    }
+
+    #[test]
+    fn vaults_are_the_owners_associated_token_accounts() {
+        let [vault_x, vault_y] = vault_addresses(&pool(1), &TOKEN_PROGRAM, [&MINT_X, &MINT_Y]);
+        assert_ne!(vault_x, vault_y);
+        let ata_x = associated_token_address(&pool(1), &MINT_X, &TOKEN_PROGRAM);
+        assert_eq!(vault_x, ata_x.to_bytes());
+
+        // Under another token program the ATAs are others
+        let [other_x, _] = vault_addresses(&pool(1), &pool(9), [&MINT_X, &MINT_Y]);
+        assert_ne!(vault_x, other_x);
+    }
+
+    #[test]
+    fn only_the_pools_own_lp_mint_passes() {
+        let (mint_lp, _) = mint_lp_address(&pool(1));
+        assert_eq!(check_mint_lp(&pool(1), &mint_lp), Ok(()));
+        assert_eq!(
+            check_mint_lp(&pool(2), &mint_lp),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            check_mint_lp(&pool(1), &MINT_X),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn only_the_protocol_config_pda_passes() {
+        assert_eq!(check_protocol_config(&protocol_config_address().0), Ok(()));
+        assert_eq!(
+            check_protocol_config(&mint_lp_address(&pool(1)).0),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Protocol config: one PDA per deployment, set up by `InitializeProtocol`
+//! and consulted by `Initialize`.
+//!
+//! It bounds the swap fee a pool may be created with, on top of `FEE_TIERS`,
+//! and gives new pools their protocol share of the fee. Pools keep what they
+//! were created with when it changes; their own authority moves them from
+//! there with `UpdateFee`.
+
+use core::mem::size_of;
+use pinocchio::{
+    AccountView,
+    account::{Ref, RefMut},
+    error::ProgramError,
+};
+
+use crate::AmmError;
+
+/// Seed of the protocol config PDA
+pub const PROTOCOL_SEED: &[u8] = b"protocol";
+
+// ==================== ProtocolConfig ====================
+
+/// PDA: ["protocol"]
+#[repr(C)]
+pub struct ProtocolConfig {
+    authority: [u8; 32],
+    min_fee: [u8; 2],
+    max_fee: [u8; 2],
+    protocol_fee: [u8; 2],
+    bump: [u8; 1],
+}
+
+impl ProtocolConfig {
+    pub const LEN: usize = size_of::<ProtocolConfig>();
+
+    // ==================== Read Helpers ====================
+
+    #[inline(always)]
+    pub fn load(account_view: &AccountView) -> Result<Ref<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    /// Return a `ProtocolConfig` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `ProtocolConfig`.
+    /// `ProtocolConfig` has an alignment of 1 byte. This method does not perform a length
+    /// validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const ProtocolConfig)
+    }
+
+    /// Return a mutable `ProtocolConfig` reference from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `ProtocolConfig`.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut ProtocolConfig)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &[u8; 32] {
+        &self.authority
+    }
+
+    /// Lowest swap fee (basis points) a pool may be created with
+    #[inline(always)]
+    pub fn min_fee(&self) -> u16 {
+        u16::from_le_bytes(self.min_fee)
+    }
+
+    /// Highest swap fee (basis points) a pool may be created with
+    #[inline(always)]
+    pub fn max_fee(&self) -> u16 {
+        u16::from_le_bytes(self.max_fee)
+    }
+
+    /// Protocol share of the swap fee new pools start with, in basis points
+    /// of the fee
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    /// Check that a pool may be created with `fee`
+    #[inline(always)]
+    pub fn check_fee(&self, fee: u16) -> Result<(), ProgramError> {
+        match (self.min_fee()..=self.max_fee()).contains(&fee) {
+            true => Ok(()),
+            false => Err(AmmError::FeeOutOfBounds.into()),
+        }
+    }
+
+    /// Check that `authority` signed and is the protocol authority
+    #[inline(always)]
+    pub fn check_authority(&self, authority: &AccountView) -> Result<(), ProgramError> {
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if authority.address().as_ref() != self.authority.as_slice() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        Ok(())
+    }
+
+    // ==================== Write Helpers ====================
+
+    #[inline(always)]
+    pub fn load_mut(account_view: &AccountView) -> Result<RefMut<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(
+            account_view.try_borrow_mut()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn load_mut_unchecked(account_view: &AccountView) -> Result<&mut Self, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self::from_bytes_unchecked_mut(
+            account_view.borrow_unchecked_mut(),
+        ))
+    }
+
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        authority: [u8; 32],
+        min_fee: u16,
+        max_fee: u16,
+        protocol_fee: u16,
+        bump: [u8; 1],
+    ) -> Result<(), ProgramError> {
+        self.authority = authority;
+        self.bump = bump;
+        self.set_fees(min_fee, max_fee, protocol_fee)
+    }
+
+    /// Set the pool fee bounds and the default protocol share. Pools charge
+    /// less than 10_000 basis points, and the share is at most the whole fee.
+    #[inline(always)]
+    pub fn set_fees(
+        &mut self,
+        min_fee: u16,
+        max_fee: u16,
+        protocol_fee: u16,
+    ) -> Result<(), ProgramError> {
+        if min_fee > max_fee || max_fee >= 10_000 || protocol_fee > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.min_fee = min_fee.to_le_bytes();
+        self.max_fee = max_fee.to_le_bytes();
+        self.protocol_fee = protocol_fee.to_le_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protocol(min_fee: u16, max_fee: u16) -> [u8; ProtocolConfig::LEN] {
+        let mut bytes = [0u8; ProtocolConfig::LEN];
+        let protocol = unsafe { ProtocolConfig::from_bytes_unchecked_mut(&mut bytes) };
+        protocol.set_inner([1; 32], min_fee, max_fee, 0, [255]).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn only_fees_within_the_bounds_are_accepted() {
+        let bytes = protocol(5, 30);
+        let protocol = unsafe { ProtocolConfig::from_bytes_unchecked(&bytes) };
+        for fee in [5, 6, 29, 30] {
+            assert_eq!(protocol.check_fee(fee), Ok(()));
+        }
+        for fee in [0, 4, 31, 100] {
+            assert_eq!(protocol.check_fee(fee), Err(AmmError::FeeOutOfBounds.into()));
+        }
+    }
+
+    #[test]
+    fn fee_bounds_must_be_ordered_and_below_the_whole_swap() {
+        let mut bytes = protocol(0, 0);
+        let protocol = unsafe { ProtocolConfig::from_bytes_unchecked_mut(&mut bytes) };
+        for (min_fee, max_fee, protocol_fee) in [(31, 30, 0), (0, 10_000, 0), (0, 30, 10_001)] {
+            assert_eq!(
+                protocol.set_fees(min_fee, max_fee, protocol_fee),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+        assert_eq!(protocol.set_fees(1, 9_999, 10_000), Ok(()));
+        assert_eq!(protocol.min_fee(), 1);
+        assert_eq!(protocol.max_fee(), 9_999);
+        assert_eq!(protocol.protocol_fee(), 10_000);
+    }
+}
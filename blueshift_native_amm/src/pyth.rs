@@ -0,0 +1,180 @@
+use pinocchio::{AccountView, error::ProgramError};
+
+use crate::AmmError;
+
+// Pyth (v2) price account layout, as read by pyth-sdk-solana
+const MAGIC: u32 = 0xa1b2_c3d4;
+const VERSION_2: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// Aggregate status of a price being actively published
+const STATUS_TRADING: u32 = 1;
+
+/// Oldest aggregate price (in slots) a swap is checked against
+pub const MAX_PRICE_AGE_SLOTS: u64 = 25;
+
+/// Largest power of ten `expected_y` scales by
+const MAX_EXPONENT: u32 = 38;
+
+/// The aggregate price of a Pyth price account: X priced in Y, in whole
+/// tokens, as `price * 10^expo`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub expo: i32,
+}
+
+impl PythPrice {
+    /// Read the aggregate price of `account`, which must be trading and
+    /// published within `MAX_PRICE_AGE_SLOTS` of `slot`
+    #[inline(always)]
+    pub fn load(account: &AccountView, slot: u64) -> Result<Self, ProgramError> {
+        Self::from_bytes(&account.try_borrow()?, slot)
+    }
+
+    pub fn from_bytes(data: &[u8], slot: u64) -> Result<Self, ProgramError> {
+        if data.len() < PRICE_ACCOUNT_MIN_LEN
+            || read_u32(data, 0) != MAGIC
+            || read_u32(data, 4) != VERSION_2
+            || read_u32(data, 8) != ACCOUNT_TYPE_PRICE
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let price = i64::from_le_bytes(
+            data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].try_into().unwrap(),
+        );
+        let pub_slot = u64::from_le_bytes(
+            data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8].try_into().unwrap(),
+        );
+        if read_u32(data, AGG_STATUS_OFFSET) != STATUS_TRADING
+            || price <= 0
+            || slot.saturating_sub(pub_slot) > MAX_PRICE_AGE_SLOTS
+        {
+            return Err(AmmError::OraclePriceUnavailable.into());
+        }
+
+        Ok(Self {
+            price,
+            expo: read_u32(data, EXPO_OFFSET) as i32,
+        })
+    }
+
+    /// Atoms of Y that `amount_x` atoms of X are worth at this price
+    pub fn expected_y(
+        &self,
+        amount_x: u64,
+        decimals_x: u8,
+        decimals_y: u8,
+    ) -> Result<u128, ProgramError> {
+        let value = amount_x as u128 * self.price as u128;
+        let exponent = self.expo + decimals_y as i32 - decimals_x as i32;
+        if exponent.unsigned_abs() > MAX_EXPONENT {
+            return Err(ProgramError::ArithmeticOverflow);
+        }
+        let scale = 10u128.pow(exponent.unsigned_abs());
+        if exponent >= 0 {
+            value.checked_mul(scale).ok_or(ProgramError::ArithmeticOverflow)
+        } else {
+            Ok(value / scale)
+        }
+    }
+
+    /// Fail with `OraclePriceOutOfBand` if trading `amount_x` for `amount_y`
+    /// (either way) is more than `band_bps` off this price
+    pub fn check_band(
+        &self,
+        amount_x: u64,
+        amount_y: u64,
+        decimals_x: u8,
+        decimals_y: u8,
+        band_bps: u16,
+    ) -> Result<(), ProgramError> {
+        let expected = self.expected_y(amount_x, decimals_x, decimals_y)?;
+        let deviation = expected.abs_diff(amount_y as u128);
+        let deviation = deviation
+            .checked_mul(10_000)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let band = expected
+            .checked_mul(band_bps as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if deviation > band {
+            return Err(AmmError::OraclePriceOutOfBand.into());
+        }
+        Ok(())
+    }
+}
+
+#[inline(always)]
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_account(
+        price: i64,
+        expo: i32,
+        status: u32,
+        pub_slot: u64,
+    ) -> [u8; PRICE_ACCOUNT_MIN_LEN] {
+        let mut data = [0u8; PRICE_ACCOUNT_MIN_LEN];
+        data[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&VERSION_2.to_le_bytes());
+        data[8..12].copy_from_slice(&ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8].copy_from_slice(&pub_slot.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_fresh_trading_prices_only() {
+        let data = price_account(150_000_000, -6, STATUS_TRADING, 100);
+        assert_eq!(
+            PythPrice::from_bytes(&data, 125),
+            Ok(PythPrice { price: 150_000_000, expo: -6 })
+        );
+
+        let unavailable = Err(AmmError::OraclePriceUnavailable.into());
+        assert_eq!(PythPrice::from_bytes(&data, 126), unavailable);
+        let halted = price_account(150_000_000, -6, 2, 100);
+        assert_eq!(PythPrice::from_bytes(&halted, 100), unavailable);
+        let negative = price_account(-1, -6, STATUS_TRADING, 100);
+        assert_eq!(PythPrice::from_bytes(&negative, 100), unavailable);
+
+        let mut not_a_price = data;
+        not_a_price[8] = 2;
+        assert_eq!(
+            PythPrice::from_bytes(&not_a_price, 100),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn band_compares_the_trade_with_the_oracle_price() {
+        // 150 Y (6 decimals) per X (9 decimals): 1 X = 150_000_000 atoms of Y
+        let price = PythPrice { price: 15_000, expo: -2 };
+        assert_eq!(price.expected_y(1_000_000_000, 9, 6), Ok(150_000_000));
+
+        // 1% band: 148.5..=151.5 Y per X passes
+        assert_eq!(price.check_band(1_000_000_000, 148_500_000, 9, 6, 100), Ok(()));
+        assert_eq!(price.check_band(1_000_000_000, 151_500_000, 9, 6, 100), Ok(()));
+        assert_eq!(
+            price.check_band(1_000_000_000, 148_499_999, 9, 6, 100),
+            Err(AmmError::OraclePriceOutOfBand.into())
+        );
+        assert_eq!(
+            price.check_band(1_000_000_000, 151_500_001, 9, 6, 100),
+            Err(AmmError::OraclePriceOutOfBand.into())
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Swap, deposit and withdraw math, shared by the instructions and off-chain
+//! quoters.
+//!
+//! Everything here is pure: it works from reserve numbers (or raw `Config`
+//! account bytes) and mint data, so bots and UIs can link this crate and get
+//! the exact amounts the program will compute, rounding and transfer fees
+//! included.
+
+use pinocchio::error::ProgramError;
+
+use crate::{
+    curve::{self, SwapResult},
+    math::mul_div,
+    token::MintInfo,
+    Config, DepositOutcome, SwapOutcome, WithdrawOutcome,
+};
+
+/// LP mint decimals unless `Initialize` is given others; every pool created
+/// before they were configurable has these
+pub const DEFAULT_LP_DECIMALS: u8 = 6;
+
+/// Most LP mint decimals `Initialize` accepts
+pub const MAX_LP_DECIMALS: u8 = 9;
+
+/// Most slippage tolerance the `*WithSlippage` instructions accept: 100%
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+/// Price `amount` of X (`is_x`) or Y arriving in a pool with the given
+/// reserves and fee (bps). Fails if nothing would come out, or less than `min`
+/// before the output mint's transfer fee.
+pub fn curve_swap(
+    reserve_x: u64,
+    reserve_y: u64,
+    fee: u16,
+    is_x: bool,
+    amount: u64,
+    min: u64,
+) -> Result<SwapResult, ProgramError> {
+    let swap_result = match is_x {
+        true => curve::swap(reserve_x, reserve_y, fee, amount)?,
+        false => curve::swap(reserve_y, reserve_x, fee, amount)?,
+    };
+
+    if swap_result.deposit == 0 || swap_result.withdraw < min.max(1) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(swap_result)
+}
+
+/// How far (basis points) a swap moves the price of the token going in: the
+/// pool goes from `reserve_in` / `reserve_out` to `reserve_in + amount_in` /
+/// `reserve_out - amount_out`. Rounded up.
+pub fn price_impact_bps(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    amount_out: u64,
+) -> Result<u64, ProgramError> {
+    let new_in = reserve_in
+        .checked_add(amount_in)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let new_out = reserve_out
+        .checked_sub(amount_out)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // New price over old price: (new_out / new_in) / (reserve_out / reserve_in)
+    let price_ratio_bps = mul_div(
+        new_out as u128 * reserve_in as u128,
+        10_000,
+        new_in as u128 * reserve_out as u128,
+        false,
+    )?;
+    Ok(10_000u64.saturating_sub(price_ratio_bps as u64))
+}
+
+/// Amounts of X and Y that must reach the vaults to mint `lp_amount`
+/// (pools that already hold liquidity); rounded up
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    curve::deposit_amounts(reserve_x, reserve_y, lp_supply, lp_amount)
+}
+
+/// Amounts of X and Y leaving the vaults when `lp_amount` is burned; rounded
+/// down, and burning the whole supply empties the reserves
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    curve::withdraw_amounts(reserve_x, reserve_y, lp_supply, lp_amount)
+}
+
+/// Token Y that must reach the vault alongside `amount_x` of X for a donation
+/// to keep the pool's price; rounded up, in the pool's favour
+pub fn donate_amount_y(reserve_x: u64, reserve_y: u64, amount_x: u64) -> Result<u64, ProgramError> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let amount_y = mul_div(amount_x as u128, reserve_y as u128, reserve_x as u128, true)?;
+    u64::try_from(amount_y).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Part of `amount` to swap before a single-sided deposit, so that what is
+/// left and what the swap returns match the pool's new ratio. Solves
+/// `(amount - s) / (reserve_in + s) = out(s) / (reserve_out - out(s))` for
+/// the constant product curve with `fee` (bps); transfer fees are ignored.
+pub fn zap_swap_amount(reserve_in: u64, amount: u64, fee: u16) -> Result<u64, ProgramError> {
+    if reserve_in == 0 || fee >= 10_000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // s = (sqrt(r²g² + 4·k·a·r) - r·g) / 2k with k = 1 - fee and g = 2 - fee,
+    // computed as 2·a·r / (sqrt(r²g² + 4·k·a·r) + r·g) to stay within u128;
+    // below, k and g are scaled by 10_000
+    let r = reserve_in as u128;
+    let k = 10_000 - fee as u128;
+    let g = 20_000 - fee as u128;
+    let inner = g * g * r + 40_000 * k * amount as u128;
+    let root = match r.checked_mul(inner) {
+        Some(discriminant) => isqrt(discriminant),
+        // Only for reserves past ~2·10^10, where splitting the root is off by
+        // well under a basis point
+        None => isqrt(r) * isqrt(inner),
+    };
+    let swap = mul_div(20_000 * amount as u128, r, root + r * g, false)?;
+    u64::try_from(swap).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Most LP tokens `amount_x` and `amount_y` (reaching the vaults) can mint;
+/// rounded down. The first deposit mints the geometric mean of the two.
+pub fn lp_for_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_x: u64,
+    amount_y: u64,
+) -> Result<u64, ProgramError> {
+    if lp_supply == 0 && reserve_x == 0 && reserve_y == 0 {
+        let lp = isqrt(amount_x as u128 * amount_y as u128);
+        return u64::try_from(lp).map_err(|_| ProgramError::ArithmeticOverflow);
+    }
+    if reserve_x == 0 || reserve_y == 0 || lp_supply == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let lp_x = mul_div(amount_x as u128, lp_supply as u128, reserve_x as u128, false)?;
+    let lp_y = mul_div(amount_y as u128, lp_supply as u128, reserve_y as u128, false)?;
+    u64::try_from(lp_x.min(lp_y)).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Most a deposit quoted at `quote` may cost with `slippage_bps` of
+/// tolerance; rounded down, saturating
+pub fn max_with_slippage(quote: u64, slippage_bps: u16) -> u64 {
+    let max = quote as u128 * (10_000 + slippage_bps as u128) / 10_000;
+    u64::try_from(max).unwrap_or(u64::MAX)
+}
+
+/// Least a withdrawal quoted at `quote` may deliver with `slippage_bps` of
+/// tolerance (capped at `MAX_SLIPPAGE_BPS`); rounded up
+pub fn min_with_slippage(quote: u64, slippage_bps: u16) -> u64 {
+    let kept = MAX_SLIPPAGE_BPS.saturating_sub(slippage_bps) as u128;
+    (quote as u128 * kept).div_ceil(10_000) as u64
+}
+
+/// Integer square root, rounded down
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// ==================== Pool ====================
+
+/// The numbers a quote is computed from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    /// Swap fee in basis points
+    pub fee: u16,
+    pub lp_supply: u64,
+}
+
+impl Pool {
+    /// Read a pool from raw `Config` account data and the LP mint's supply
+    pub fn from_config(data: &[u8], lp_supply: u64) -> Result<Self, ProgramError> {
+        if data.len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let config = unsafe { Config::from_bytes_unchecked(data) };
+        Ok(Self {
+            reserve_x: config.reserve_x(),
+            reserve_y: config.reserve_y(),
+            fee: config.fee(),
+            lp_supply,
+        })
+    }
+
+    /// What `Swap` does with `amount` of X (`is_x`) or Y sent by the user
+    pub fn swap(
+        &self,
+        is_x: bool,
+        amount: u64,
+        mint_x: &MintInfo,
+        mint_y: &MintInfo,
+    ) -> Result<SwapOutcome, ProgramError> {
+        let (mint_in, mint_out) = match is_x {
+            true => (mint_x, mint_y),
+            false => (mint_y, mint_x),
+        };
+        let received = amount - mint_in.fee_for(amount);
+        let swap_result = curve_swap(self.reserve_x, self.reserve_y, self.fee, is_x, received, 0)?;
+
+        Ok(SwapOutcome {
+            amount_in: received,
+            amount_out: swap_result.withdraw - mint_out.fee_for(swap_result.withdraw),
+            fee: swap_result.fee,
+        })
+    }
+
+    /// What `Deposit` takes from the user to mint `lp_amount`; the first
+    /// deposit into an empty pool is priced by the depositor instead
+    pub fn deposit(
+        &self,
+        lp_amount: u64,
+        mint_x: &MintInfo,
+        mint_y: &MintInfo,
+    ) -> Result<DepositOutcome, ProgramError> {
+        let (x, y) = deposit_amounts(self.reserve_x, self.reserve_y, self.lp_supply, lp_amount)?;
+        Ok(DepositOutcome {
+            amount_x: mint_x.amount_with_fee(x)?,
+            amount_y: mint_y.amount_with_fee(y)?,
+            lp_minted: lp_amount,
+        })
+    }
+
+    /// What `Withdraw` delivers to the user for burning `lp_amount`
+    pub fn withdraw(
+        &self,
+        lp_amount: u64,
+        mint_x: &MintInfo,
+        mint_y: &MintInfo,
+    ) -> Result<WithdrawOutcome, ProgramError> {
+        let (x, y) = withdraw_amounts(self.reserve_x, self.reserve_y, self.lp_supply, lp_amount)?;
+        Ok(WithdrawOutcome {
+            amount_x: x - mint_x.fee_for(x),
+            amount_y: y - mint_y.fee_for(y),
+            lp_burned: lp_amount,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: MintInfo = MintInfo {
+        supply: 0,
+        decimals: 6,
+        transfer_fee: None,
+    };
+
+    const POOL: Pool = Pool {
+        reserve_x: 1_000_000,
+        reserve_y: 4_000_000,
+        fee: 30,
+        lp_supply: 2_000_000,
+    };
+
+    #[test]
+    fn reads_the_pool_from_config_bytes() {
+        let mut bytes = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut bytes) };
+        config.set_inner(1, [1; 32], [2; 32], [3; 32], 30, [255]).unwrap();
+        config.set_reserves(1_000_000, 4_000_000);
+
+        assert_eq!(Pool::from_config(&bytes, 2_000_000), Ok(POOL));
+        assert_eq!(
+            Pool::from_config(&bytes[1..], 2_000_000),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn swap_keeps_the_product_and_charges_the_fee() {
+        let outcome = POOL.swap(true, 10_000, &MINT, &MINT).unwrap();
+        assert_eq!({ outcome.amount_in }, 10_000);
+        assert!({ outcome.fee } > 0);
+
+        let before = POOL.reserve_x as u128 * POOL.reserve_y as u128;
+        let after = (POOL.reserve_x + outcome.amount_in) as u128
+            * (POOL.reserve_y - outcome.amount_out) as u128;
+        assert!(after >= before);
+    }
+
+    #[test]
+    fn price_impact_compares_the_price_after_to_before() {
+        // Doubling X and halving Y quarters X's price
+        assert_eq!(price_impact_bps(1_000, 1_000, 1_000, 500), Ok(7_500));
+        assert_eq!(price_impact_bps(1_000_000, 1_000_000, 1, 0), Ok(1));
+        assert_eq!(price_impact_bps(1_000, 1_000, 0, 0), Ok(0));
+        assert_eq!(
+            price_impact_bps(1_000, 1_000, 1, 1_001),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn donations_match_the_pool_ratio() {
+        assert_eq!(donate_amount_y(1_000, 4_000, 10), Ok(40));
+        assert_eq!(donate_amount_y(3, 1, 1), Ok(1)); // rounds up
+        assert_eq!(donate_amount_y(0, 0, 1), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn zaps_swap_what_leaves_the_rest_at_the_new_ratio() {
+        assert_eq!(zap_swap_amount(1_000_000_000, 1_000, 0), Ok(499));
+
+        for (reserve_in, reserve_out, amount, fee) in [
+            (1_000_000u64, 4_000_000u64, 500_000u64, 30u16),
+            (1_000_000, 1_000_000, 10_000_000, 100),
+            (1_000_000_000_000_000, 500_000_000_000_000, 1_000_000_000_000, 30),
+        ] {
+            let swap = zap_swap_amount(reserve_in, amount, fee).unwrap() as u128;
+            let in_after_fee = swap * (10_000 - fee as u128) / 10_000;
+            let out = in_after_fee * reserve_out as u128 / (reserve_in as u128 + in_after_fee);
+            let left = (amount as u128 - swap) * (reserve_out as u128 - out);
+            let ratio = out * (reserve_in as u128 + swap);
+            assert!(left.abs_diff(ratio) * 1_000 <= ratio); // within 0.1%
+        }
+    }
+
+    #[test]
+    fn lp_for_amounts_is_limited_by_the_scarcer_side() {
+        assert_eq!(lp_for_amounts(1_000, 4_000, 2_000, 100, 1_000), Ok(200));
+        assert_eq!(lp_for_amounts(1_000, 4_000, 2_000, 100, 200), Ok(100));
+        assert_eq!(lp_for_amounts(0, 0, 0, 100, 400), Ok(200));
+        assert_eq!(
+            lp_for_amounts(1_000, 4_000, 0, 100, 200),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn slippage_bounds_round_against_the_user() {
+        assert_eq!(max_with_slippage(1_000, 50), 1_005);
+        assert_eq!(max_with_slippage(999, 50), 1_003); // 1_003.995
+        assert_eq!(max_with_slippage(u64::MAX, 1), u64::MAX);
+        assert_eq!(min_with_slippage(1_000, 50), 995);
+        assert_eq!(min_with_slippage(999, 50), 995); // 994.005
+        assert_eq!(min_with_slippage(1_000, 10_000), 0);
+        assert_eq!(min_with_slippage(u64::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn withdrawing_the_whole_supply_empties_the_pool() {
+        let outcome = POOL.withdraw(POOL.lp_supply, &MINT, &MINT).unwrap();
+        assert_eq!(
+            (outcome.amount_x, outcome.amount_y),
+            (POOL.reserve_x, POOL.reserve_y)
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Pool registry: one PDA per (mint_x, mint_y, fee tier), created by
+//! `Initialize` and pointing at the pool's config.
+//!
+//! Pools may only be created at a sanctioned fee tier, and a pair gets at most
+//! one pool per tier, whatever `seed` it is created with. Clients find every
+//! pool of a pair by deriving the registry for each of `FEE_TIERS`.
+
+use core::mem::size_of;
+use pinocchio::{
+    AccountView,
+    account::Ref,
+    error::ProgramError,
+};
+
+use crate::AmmError;
+
+/// Swap fees (basis points) pools can be created with
+pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+
+/// Check that `fee` is one of `FEE_TIERS`
+#[inline(always)]
+pub fn check_fee_tier(fee: u16) -> Result<(), ProgramError> {
+    match FEE_TIERS.contains(&fee) {
+        true => Ok(()),
+        false => Err(AmmError::UnsupportedFeeTier.into()),
+    }
+}
+
+// ==================== PoolRegistry ====================
+
+/// PDA: ["registry", mint_x, mint_y, fee (u16 LE)]
+#[repr(C)]
+pub struct PoolRegistry {
+    config: [u8; 32],
+    seed: [u8; 8],
+    fee: [u8; 2],
+    bump: [u8; 1],
+}
+
+impl PoolRegistry {
+    pub const LEN: usize = size_of::<PoolRegistry>();
+
+    // ==================== Read Helpers ====================
+
+    #[inline(always)]
+    pub fn load(account_view: &AccountView) -> Result<Ref<Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    /// Return a `PoolRegistry` from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `PoolRegistry`.
+    /// `PoolRegistry` has an alignment of 1 byte. This method does not perform a length
+    /// validation.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const PoolRegistry)
+    }
+
+    /// Return a mutable `PoolRegistry` reference from the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `bytes` contains a valid representation of `PoolRegistry`.
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut PoolRegistry)
+    }
+
+    /// The registered pool's config
+    #[inline(always)]
+    pub fn config(&self) -> &[u8; 32] {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    /// Fee tier the pool was created at; `UpdateFee` may have moved it since
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        u16::from_le_bytes(self.fee)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    // ==================== Write Helpers ====================
+
+    #[inline(always)]
+    pub unsafe fn load_mut_unchecked(account_view: &AccountView) -> Result<&mut Self, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self::from_bytes_unchecked_mut(
+            account_view.borrow_unchecked_mut(),
+        ))
+    }
+
+    #[inline(always)]
+    pub fn set_inner(&mut self, config: [u8; 32], seed: u64, fee: u16, bump: [u8; 1]) {
+        self.config = config;
+        self.seed = seed.to_le_bytes();
+        self.fee = fee.to_le_bytes();
+        self.bump = bump;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_sanctioned_fee_tiers_are_accepted() {
+        for fee in FEE_TIERS {
+            assert_eq!(check_fee_tier(fee), Ok(()));
+        }
+        for fee in [0, 2, 29, 31, 10_000] {
+            assert_eq!(check_fee_tier(fee), Err(AmmError::UnsupportedFeeTier.into()));
+        }
+    }
+}
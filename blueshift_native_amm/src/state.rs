@@ -3,8 +3,12 @@ use pinocchio::{
     AccountView,
     Address,
     account::{Ref, RefMut},
+    cpi::Seed,
     error::ProgramError,
 };
+use solana_challenge_common::{layouts::amm_config, seeds::POOL_CONFIG_SEED};
+
+use crate::{pda::check_vault_addresses, quote::DEFAULT_LP_DECIMALS, AmmError};
 
 #[repr(C)]
 pub struct Config {
@@ -15,8 +19,48 @@ pub struct Config {
     mint_y: [u8; 32],
     fee: [u8; 2],
     config_bump: [u8; 1],
+    pending_authority: [u8; 32],
+    protocol_fee: [u8; 2],
+    protocol_fees_x: [u8; 8],
+    protocol_fees_y: [u8; 8],
+    price_x_cumulative: [u8; 16],
+    price_y_cumulative: [u8; 16],
+    last_observation: [u8; 8],
+    flash_loan: u8,
+    flash_repay_x: [u8; 8],
+    flash_repay_y: [u8; 8],
+    reserve_x: [u8; 8],
+    reserve_y: [u8; 8],
+    vault_x: [u8; 32],
+    vault_y: [u8; 32],
+    referral_fee: [u8; 2],
+    max_price_impact_bps: [u8; 2],
+    /// Layout version; fields added after this one must read as zero on a
+    /// freshly migrated config (or be filled in by `MigrateConfig`), bump
+    /// `VERSION` and update `solana_challenge_common::layouts::amm_config`
+    version: u8,
+    fee_recipient: [u8; 32],
+    oracle: [u8; 32],
+    oracle_band_bps: [u8; 2],
+    volume_x: [u8; 16],
+    volume_y: [u8; 16],
+    swap_fees_x: [u8; 16],
+    swap_fees_y: [u8; 16],
+    swap_count: [u8; 8],
+    swap_throttle: u8,
+    last_swap_slot: [u8; 8],
+    last_swap_signer: [u8; 32],
+    last_swap_x: u8,
+    lp_decimals: u8,
 }
 
+// anchor_escrow reads pools through the shared layout
+const _: () = assert!(
+    Config::LEN == amm_config::LEN
+        && Config::MINT_X_OFFSET == amm_config::MINT_X
+        && Config::MINT_Y_OFFSET == amm_config::MINT_Y
+);
+
 #[repr(u8)]
 pub enum AmmState {
     Uninitialized = 0u8,
@@ -25,9 +69,74 @@ pub enum AmmState {
     WithdrawOnly = 3u8,
 }
 
+/// Pool operations gated by the pool's `AmmState`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolOperation {
+    Deposit,
+    Swap,
+    Withdraw,
+    FlashLoan,
+}
+
+impl AmmState {
+    /// Whether a pool in `state` accepts `operation`:
+    /// Initialized allows everything, WithdrawOnly only lets LPs exit,
+    /// Disabled and Uninitialized allow nothing.
+    #[inline(always)]
+    pub const fn allows(state: u8, operation: PoolOperation) -> bool {
+        match state {
+            s if s == AmmState::Initialized as u8 => true,
+            s if s == AmmState::WithdrawOnly as u8 => {
+                matches!(operation, PoolOperation::Withdraw)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl Config {
     pub const LEN: usize = size_of::<Config>();
 
+    /// Layout version written by `Initialize` and `MigrateConfig`
+    pub const VERSION: u8 = 6;
+
+    /// Length of the original configs (version 0): no version byte, and none
+    /// of the fields from `pending_authority` on
+    pub const V0_LEN: usize = core::mem::offset_of!(Config, pending_authority);
+    /// Length of version 1 configs, before `fee_recipient`
+    pub const V1_LEN: usize = core::mem::offset_of!(Config, fee_recipient);
+    /// Length of version 2 configs, before `oracle`
+    pub const V2_LEN: usize = core::mem::offset_of!(Config, oracle);
+    /// Length of version 3 configs, before the swap statistics
+    pub const V3_LEN: usize = core::mem::offset_of!(Config, volume_x);
+    /// Length of version 4 configs, before the swap throttle
+    pub const V4_LEN: usize = core::mem::offset_of!(Config, swap_throttle);
+    /// Length of version 5 configs, before `lp_decimals`
+    pub const V5_LEN: usize = core::mem::offset_of!(Config, lp_decimals);
+
+    /// Offset of the version byte, in configs of version 1 and later
+    pub const VERSION_OFFSET: usize = core::mem::offset_of!(Config, version);
+
+    /// Offsets of the pool's mints, for programs reading the config raw
+    pub const MINT_X_OFFSET: usize = core::mem::offset_of!(Config, mint_x);
+    pub const MINT_Y_OFFSET: usize = core::mem::offset_of!(Config, mint_y);
+
+    /// Layout version of a config account `data_len` bytes long, whose
+    /// version byte (if it has one) is `version`
+    #[inline(always)]
+    pub const fn layout_version(data_len: usize, version: u8) -> Option<u8> {
+        match data_len {
+            Self::V0_LEN => Some(0),
+            Self::V1_LEN
+            | Self::V2_LEN
+            | Self::V3_LEN
+            | Self::V4_LEN
+            | Self::V5_LEN
+            | Self::LEN => Some(version),
+            _ => None,
+        }
+    }
+
     // ==================== Read Helpers ====================
 
     #[inline(always)]
@@ -85,11 +194,51 @@ impl Config {
         self.state
     }
 
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
     #[inline(always)]
     pub fn seed(&self) -> u64 {
         u64::from_le_bytes(self.seed)
     }
 
+    /// Check that the pool's state allows `operation` and no flash loan is out
+    /// (the vaults are short until it is repaid, so reserves can't be trusted)
+    #[inline(always)]
+    pub fn check_state(&self, operation: PoolOperation) -> Result<(), ProgramError> {
+        if !AmmState::allows(self.state, operation) || self.flash_loan_active() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// Whether a `FlashLoan` is waiting for its `FlashRepay`
+    #[inline(always)]
+    pub fn flash_loan_active(&self) -> bool {
+        self.flash_loan != 0
+    }
+
+    /// Vault X balance `FlashRepay` must see before releasing the pool
+    #[inline(always)]
+    pub fn flash_repay_x(&self) -> u64 {
+        u64::from_le_bytes(self.flash_repay_x)
+    }
+
+    /// Vault Y balance `FlashRepay` must see before releasing the pool
+    #[inline(always)]
+    pub fn flash_repay_y(&self) -> u64 {
+        u64::from_le_bytes(self.flash_repay_y)
+    }
+
+    /// Fee owed on a flash loan of `amount`: the swap fee, rounded up
+    #[inline(always)]
+    pub fn flash_fee_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        let fee = (amount as u128 * self.fee() as u128).div_ceil(10_000);
+        u64::try_from(fee).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
     #[inline(always)]
     pub fn authority(&self) -> &[u8; 32] {
         &self.authority
@@ -115,6 +264,249 @@ impl Config {
         self.config_bump
     }
 
+    /// Decimals of the pool's LP mint
+    #[inline(always)]
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+
+    /// Seeds for the config PDA to sign with, borrowed from the config itself
+    /// rather than copied out: ["config", seed, mint_x, mint_y, bump]
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(POOL_CONFIG_SEED),
+            Seed::from(&self.seed),
+            Seed::from(&self.mint_x),
+            Seed::from(&self.mint_y),
+            Seed::from(&self.config_bump),
+        ]
+    }
+
+    /// Authority proposed by `ProposeAuthority`, waiting to sign `AcceptAuthority`
+    /// (all-zero when no transfer is pending)
+    #[inline(always)]
+    pub fn pending_authority(&self) -> &[u8; 32] {
+        &self.pending_authority
+    }
+
+    /// Share of every swap fee kept for the protocol, in basis points of the fee
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee)
+    }
+
+    /// Share of the swap fee (basis points) paid to a swap's referrer, if any
+    #[inline(always)]
+    pub fn referral_fee(&self) -> u16 {
+        u16::from_le_bytes(self.referral_fee)
+    }
+
+    /// Largest price move (basis points) a single swap may cause; 0 means no limit
+    #[inline(always)]
+    pub fn max_price_impact_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_price_impact_bps)
+    }
+
+    /// Fail with `PriceImpactTooHigh` if a swap moving the price by
+    /// `price_impact_bps` is beyond the pool's limit
+    #[inline(always)]
+    pub fn check_price_impact(&self, price_impact_bps: u64) -> Result<(), ProgramError> {
+        let max_price_impact_bps = self.max_price_impact_bps();
+        if max_price_impact_bps != 0 && price_impact_bps > max_price_impact_bps as u64 {
+            return Err(AmmError::PriceImpactTooHigh.into());
+        }
+        Ok(())
+    }
+
+    /// Largest deviation (basis points) of a swap's execution price from the
+    /// oracle price
+    #[inline(always)]
+    pub fn oracle_band_bps(&self) -> u16 {
+        u16::from_le_bytes(self.oracle_band_bps)
+    }
+
+    /// Protocol fees in token X sitting in vault_x until `ClaimFees`
+    #[inline(always)]
+    pub fn protocol_fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_x)
+    }
+
+    /// Protocol fees in token Y sitting in vault_y until `ClaimFees`
+    #[inline(always)]
+    pub fn protocol_fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_y)
+    }
+
+    /// Running sum of the X price (in Y, UQ64.64) times seconds it held; wraps on overflow
+    #[inline(always)]
+    pub fn price_x_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_x_cumulative)
+    }
+
+    /// Running sum of the Y price (in X, UQ64.64) times seconds it held; wraps on overflow
+    #[inline(always)]
+    pub fn price_y_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_y_cumulative)
+    }
+
+    /// Unix timestamp the cumulative prices were last brought up to, 0 before the first swap
+    #[inline(always)]
+    pub fn last_observation(&self) -> i64 {
+        i64::from_le_bytes(self.last_observation)
+    }
+
+    /// Token X swapped through the pool, in and out, since the stats started
+    #[inline(always)]
+    pub fn volume_x(&self) -> u128 {
+        u128::from_le_bytes(self.volume_x)
+    }
+
+    /// Token Y swapped through the pool, in and out, since the stats started
+    #[inline(always)]
+    pub fn volume_y(&self) -> u128 {
+        u128::from_le_bytes(self.volume_y)
+    }
+
+    /// Swap fees charged in token X (protocol and referral shares included)
+    #[inline(always)]
+    pub fn swap_fees_x(&self) -> u128 {
+        u128::from_le_bytes(self.swap_fees_x)
+    }
+
+    /// Swap fees charged in token Y (protocol and referral shares included)
+    #[inline(always)]
+    pub fn swap_fees_y(&self) -> u128 {
+        u128::from_le_bytes(self.swap_fees_y)
+    }
+
+    /// Swaps executed against the pool
+    #[inline(always)]
+    pub fn swap_count(&self) -> u64 {
+        u64::from_le_bytes(self.swap_count)
+    }
+
+    /// Whether a signer may only swap one way per slot
+    #[inline(always)]
+    pub fn swap_throttle(&self) -> bool {
+        self.swap_throttle != 0
+    }
+
+    /// Slot of the last swap, as recorded while the throttle is on
+    #[inline(always)]
+    pub fn last_swap_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_swap_slot)
+    }
+
+    /// Signer of the last swap, as recorded while the throttle is on
+    #[inline(always)]
+    pub fn last_swap_signer(&self) -> &[u8; 32] {
+        &self.last_swap_signer
+    }
+
+    /// Whether the last swap sold X, as recorded while the throttle is on
+    #[inline(always)]
+    pub fn last_swap_x(&self) -> bool {
+        self.last_swap_x != 0
+    }
+
+    /// With the throttle on, fail with `SwapThrottled` if `signer` swapped the
+    /// other way earlier in `slot`, the front- and back-run of a sandwich
+    #[inline(always)]
+    pub fn check_swap_throttle(
+        &self,
+        signer: &Address,
+        is_x: bool,
+        slot: u64,
+    ) -> Result<(), ProgramError> {
+        if self.swap_throttle()
+            && self.last_swap_slot() == slot
+            && self.last_swap_x() != is_x
+            && signer.as_ref() == self.last_swap_signer.as_slice()
+        {
+            return Err(AmmError::SwapThrottled.into());
+        }
+        Ok(())
+    }
+
+    /// Cumulative prices as of `now`, extending the stored ones by the current
+    /// reserves' price over the time since the last observation.
+    /// TWAP over a window = (cumulative_end - cumulative_start) / seconds, wrapping.
+    #[inline(always)]
+    pub fn cumulative_prices(&self, reserve_x: u64, reserve_y: u64, now: i64) -> (u128, u128) {
+        let mut price_x_cumulative = self.price_x_cumulative();
+        let mut price_y_cumulative = self.price_y_cumulative();
+
+        let last_observation = self.last_observation();
+        if last_observation != 0 && now > last_observation && reserve_x != 0 && reserve_y != 0 {
+            let elapsed = (now - last_observation) as u128;
+            let price_x = ((reserve_y as u128) << 64) / reserve_x as u128;
+            let price_y = ((reserve_x as u128) << 64) / reserve_y as u128;
+            price_x_cumulative = price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed));
+            price_y_cumulative = price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed));
+        }
+
+        (price_x_cumulative, price_y_cumulative)
+    }
+
+    /// Token X backing LP shares, as accounted by the pool's own transfers
+    #[inline(always)]
+    pub fn reserve_x(&self) -> u64 {
+        u64::from_le_bytes(self.reserve_x)
+    }
+
+    /// Token Y backing LP shares, as accounted by the pool's own transfers
+    #[inline(always)]
+    pub fn reserve_y(&self) -> u64 {
+        u64::from_le_bytes(self.reserve_y)
+    }
+
+    /// The pool's token X account (config's ATA), recorded at initialization
+    #[inline(always)]
+    pub fn vault_x(&self) -> &[u8; 32] {
+        &self.vault_x
+    }
+
+    /// The pool's token Y account (config's ATA), recorded at initialization
+    #[inline(always)]
+    pub fn vault_y(&self) -> &[u8; 32] {
+        &self.vault_y
+    }
+
+    /// Check that `vault_x` / `vault_y` are this pool's vaults; a comparison
+    /// instead of deriving the ATAs on every instruction
+    #[inline(always)]
+    pub fn check_vaults(
+        &self,
+        vault_x: &AccountView,
+        vault_y: &AccountView,
+    ) -> Result<(), ProgramError> {
+        check_vault_addresses(
+            [vault_x.address(), vault_y.address()],
+            [&self.vault_x, &self.vault_y],
+        )
+    }
+
+    /// Token X in the vault beyond reserves and unclaimed protocol fees,
+    /// i.e. tokens sent to the vault directly
+    #[inline(always)]
+    pub fn excess_x(&self, vault_x_amount: u64) -> Result<u64, ProgramError> {
+        vault_x_amount
+            .checked_sub(self.protocol_fees_x())
+            .and_then(|amount| amount.checked_sub(self.reserve_x()))
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Token Y in the vault beyond reserves and unclaimed protocol fees,
+    /// i.e. tokens sent to the vault directly
+    #[inline(always)]
+    pub fn excess_y(&self, vault_y_amount: u64) -> Result<u64, ProgramError> {
+        vault_y_amount
+            .checked_sub(self.protocol_fees_y())
+            .and_then(|amount| amount.checked_sub(self.reserve_y()))
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
     // ==================== Write Helpers ====================
 
     #[inline(always)]
@@ -147,6 +539,34 @@ impl Config {
         ))
     }
 
+    #[inline(always)]
+    pub fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    /// All zeroes sends fees back to the authority
+    #[inline(always)]
+    pub fn set_fee_recipient(&mut self, fee_recipient: [u8; 32]) {
+        self.fee_recipient = fee_recipient;
+    }
+
+    /// Guard swaps with the Pyth price account `oracle` within `band_bps`
+    /// (1..=10_000), or turn the guard off with a zero oracle and band
+    #[inline(always)]
+    pub fn set_oracle_band(
+        &mut self,
+        oracle: [u8; 32],
+        band_bps: u16,
+    ) -> Result<(), ProgramError> {
+        let enabled = Self::non_zero(&oracle).is_some();
+        if band_bps > 10_000 || enabled != (band_bps != 0) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.oracle = oracle;
+        self.oracle_band_bps = band_bps.to_le_bytes();
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
         if state > AmmState::WithdrawOnly as u8 {
@@ -185,11 +605,168 @@ impl Config {
         Ok(())
     }
 
+    /// Record the LP mint's decimals; `Initialize` and `MigrateConfig` only,
+    /// as the mint can't change them
+    #[inline(always)]
+    pub fn set_lp_decimals(&mut self, lp_decimals: u8) {
+        self.lp_decimals = lp_decimals;
+    }
+
     #[inline(always)]
     pub fn set_config_bump(&mut self, config_bump: [u8; 1]) {
         self.config_bump = config_bump;
     }
 
+    #[inline(always)]
+    pub fn set_pending_authority(&mut self, pending_authority: [u8; 32]) {
+        self.pending_authority = pending_authority;
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<(), ProgramError> {
+        if protocol_fee as u32 + self.referral_fee() as u32 > 10_000 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.protocol_fee = protocol_fee.to_le_bytes();
+        Ok(())
+    }
+
+    /// The protocol and referral shares both come out of the swap fee, so
+    /// together they can't exceed it
+    #[inline(always)]
+    pub fn set_referral_fee(&mut self, referral_fee: u16) -> Result<(), ProgramError> {
+        if self.protocol_fee() as u32 + referral_fee as u32 > 10_000 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.referral_fee = referral_fee.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_max_price_impact_bps(
+        &mut self,
+        max_price_impact_bps: u16,
+    ) -> Result<(), ProgramError> {
+        if max_price_impact_bps > 10_000 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.max_price_impact_bps = max_price_impact_bps.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fees_x(&mut self, protocol_fees_x: u64) {
+        self.protocol_fees_x = protocol_fees_x.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fees_y(&mut self, protocol_fees_y: u64) {
+        self.protocol_fees_y = protocol_fees_y.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_reserves(&mut self, reserve_x: u64, reserve_y: u64) {
+        self.reserve_x = reserve_x.to_le_bytes();
+        self.reserve_y = reserve_y.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_vaults(&mut self, vault_x: [u8; 32], vault_y: [u8; 32]) {
+        self.vault_x = vault_x;
+        self.vault_y = vault_y;
+    }
+
+    /// Record the reserves' price up to `now`; call with the reserves from
+    /// before a swap moves them
+    #[inline(always)]
+    pub fn update_oracle(&mut self, reserve_x: u64, reserve_y: u64, now: i64) {
+        if now <= self.last_observation() {
+            return;
+        }
+        let (price_x_cumulative, price_y_cumulative) =
+            self.cumulative_prices(reserve_x, reserve_y, now);
+        self.price_x_cumulative = price_x_cumulative.to_le_bytes();
+        self.price_y_cumulative = price_y_cumulative.to_le_bytes();
+        self.last_observation = now.to_le_bytes();
+    }
+
+    /// Add a swap of `amount_in` X (`is_x`) or Y for `amount_out` of the other
+    /// token, charged `fee` in the input token, to the pool's statistics.
+    /// They saturate rather than fail a swap.
+    #[inline(always)]
+    pub fn record_swap(&mut self, is_x: bool, amount_in: u64, amount_out: u64, fee: u64) {
+        let (amount_x, amount_y) = match is_x {
+            true => (amount_in, amount_out),
+            false => (amount_out, amount_in),
+        };
+        self.volume_x = self.volume_x().saturating_add(amount_x as u128).to_le_bytes();
+        self.volume_y = self.volume_y().saturating_add(amount_y as u128).to_le_bytes();
+        if is_x {
+            self.swap_fees_x = self.swap_fees_x().saturating_add(fee as u128).to_le_bytes();
+        } else {
+            self.swap_fees_y = self.swap_fees_y().saturating_add(fee as u128).to_le_bytes();
+        }
+        self.swap_count = self.swap_count().saturating_add(1).to_le_bytes();
+    }
+
+    /// Turn the swap throttle on or off, forgetting the last swap
+    #[inline(always)]
+    pub fn set_swap_throttle(&mut self, swap_throttle: bool) {
+        self.swap_throttle = swap_throttle as u8;
+        self.last_swap_slot = [0; 8];
+        self.last_swap_signer = [0; 32];
+        self.last_swap_x = 0;
+    }
+
+    /// Remember `signer` swapping X (`is_x`) or Y in `slot` for
+    /// `check_swap_throttle`; nothing is written while the throttle is off
+    #[inline(always)]
+    pub fn record_swap_slot(&mut self, signer: &Address, is_x: bool, slot: u64) {
+        if self.swap_throttle() {
+            self.last_swap_slot = slot.to_le_bytes();
+            self.last_swap_signer = signer.to_bytes();
+            self.last_swap_x = is_x as u8;
+        }
+    }
+
+    /// Lock the pool until the vaults hold at least `repay_x` / `repay_y` again
+    #[inline(always)]
+    pub fn start_flash_loan(&mut self, repay_x: u64, repay_y: u64) {
+        self.flash_loan = 1;
+        self.flash_repay_x = repay_x.to_le_bytes();
+        self.flash_repay_y = repay_y.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn end_flash_loan(&mut self) {
+        self.flash_loan = 0;
+        self.flash_repay_x = [0; 8];
+        self.flash_repay_y = [0; 8];
+    }
+
+    /// Set aside the protocol's share of a swap `fee` paid in token X (`is_x`)
+    /// or token Y; the rest of the fee stays with the LPs. Returns the share.
+    #[inline(always)]
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, fee: u64) -> Result<u64, ProgramError> {
+        // fee * 10_000 fits in u128, and protocol_fee <= 10_000 keeps the share <= fee
+        let share = (fee as u128 * self.protocol_fee() as u128 / 10_000) as u64;
+        if is_x {
+            let fees = self.protocol_fees_x().checked_add(share);
+            self.set_protocol_fees_x(fees.ok_or(ProgramError::ArithmeticOverflow)?);
+        } else {
+            let fees = self.protocol_fees_y().checked_add(share);
+            self.set_protocol_fees_y(fees.ok_or(ProgramError::ArithmeticOverflow)?);
+        }
+        Ok(share)
+    }
+
+    /// The referrer's share of a swap `fee`
+    #[inline(always)]
+    pub fn referral_share(&self, fee: u64) -> u64 {
+        // Same bounds as the protocol share
+        (fee as u128 * self.referral_fee() as u128 / 10_000) as u64
+    }
+
     #[inline(always)]
     pub fn set_inner(
         &mut self,
@@ -207,18 +784,313 @@ impl Config {
         self.set_mint_y(mint_y);
         self.set_fee(fee)?;
         self.set_config_bump(config_bump);
+        self.set_pending_authority([0; 32]);
+        self.set_protocol_fee(0)?;
+        self.set_referral_fee(0)?;
+        self.set_max_price_impact_bps(0)?;
+        self.set_version(Self::VERSION);
+        self.set_fee_recipient([0; 32]);
+        self.set_oracle_band([0; 32], 0)?;
+        self.set_protocol_fees_x(0);
+        self.set_protocol_fees_y(0);
+        self.price_x_cumulative = [0; 16];
+        self.price_y_cumulative = [0; 16];
+        self.last_observation = [0; 8];
+        self.volume_x = [0; 16];
+        self.volume_y = [0; 16];
+        self.swap_fees_x = [0; 16];
+        self.swap_fees_y = [0; 16];
+        self.swap_count = [0; 8];
+        self.set_swap_throttle(false);
+        self.set_lp_decimals(DEFAULT_LP_DECIMALS);
+        self.end_flash_loan();
+        self.set_reserves(0, 0);
+        self.set_vaults([0; 32], [0; 32]);
+        Ok(())
+    }
+
+    /// Check that `authority` signed and is this pool's authority.
+    /// Immutable pools (all-zero authority) reject every admin instruction.
+    #[inline(always)]
+    pub fn check_authority(&self, authority: &AccountView) -> Result<(), ProgramError> {
+        let Some(expected) = self.has_authority() else {
+            return Err(ProgramError::Immutable);
+        };
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if authority.address().as_ref() != expected.as_slice() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
         Ok(())
     }
 
     /// Check if authority is set (non-zero means mutable, all-zero means immutable)
     #[inline(always)]
     pub fn has_authority(&self) -> Option<[u8; 32]> {
-        let bytes = self.authority();
-        let chunks: &[u64; 4] = unsafe { &*(bytes.as_ptr() as *const [u64; 4]) };
-        if chunks.iter().any(|&x| x != 0) {
-            Some(self.authority)
-        } else {
-            None
+        Self::non_zero(&self.authority)
+    }
+
+    /// Where protocol fees and skimmed tokens go, if not to the authority
+    #[inline(always)]
+    pub fn fee_recipient(&self) -> Option<[u8; 32]> {
+        Self::non_zero(&self.fee_recipient)
+    }
+
+    /// When a fee recipient is set, fee and skim transfers may only go to
+    /// token accounts it owns
+    #[inline(always)]
+    pub fn check_fee_destination(&self, owner: &[u8; 32]) -> Result<(), ProgramError> {
+        match self.fee_recipient() {
+            Some(recipient) if &recipient != owner => Err(ProgramError::InvalidAccountData),
+            _ => Ok(()),
+        }
+    }
+
+    /// Pyth price account swaps are checked against, if any
+    #[inline(always)]
+    pub fn oracle(&self) -> Option<[u8; 32]> {
+        Self::non_zero(&self.oracle)
+    }
+
+    #[inline(always)]
+    fn non_zero(address: &[u8; 32]) -> Option<[u8; 32]> {
+        (address != &[0; 32]).then_some(*address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATIONS: [PoolOperation; 4] = [
+        PoolOperation::Deposit,
+        PoolOperation::Swap,
+        PoolOperation::Withdraw,
+        PoolOperation::FlashLoan,
+    ];
+
+    fn allowed(state: AmmState) -> [bool; 4] {
+        let state = state as u8;
+        OPERATIONS.map(|operation| AmmState::allows(state, operation))
+    }
+
+    #[test]
+    fn uninitialized_allows_nothing() {
+        assert_eq!(allowed(AmmState::Uninitialized), [false, false, false, false]);
+    }
+
+    #[test]
+    fn initialized_allows_everything() {
+        assert_eq!(allowed(AmmState::Initialized), [true, true, true, true]);
+    }
+
+    #[test]
+    fn disabled_allows_nothing() {
+        assert_eq!(allowed(AmmState::Disabled), [false, false, false, false]);
+    }
+
+    #[test]
+    fn withdraw_only_allows_only_withdrawals() {
+        assert_eq!(allowed(AmmState::WithdrawOnly), [false, false, true, false]);
+    }
+
+    #[test]
+    fn unknown_state_allows_nothing() {
+        for operation in OPERATIONS {
+            assert!(!AmmState::allows(AmmState::WithdrawOnly as u8 + 1, operation));
         }
     }
+
+    fn with_config(protocol_fee: u16, check: impl FnOnce(&mut Config)) {
+        let mut bytes = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut bytes) };
+        config.set_inner(1, [1; 32], [2; 32], [3; 32], 30, [255]).unwrap();
+        config.set_protocol_fee(protocol_fee).unwrap();
+        check(config);
+    }
+
+    #[test]
+    fn protocol_fee_accrues_on_the_paid_side() {
+        with_config(2_500, |config| {
+            assert_eq!(config.accrue_protocol_fee(true, 1_000), Ok(250));
+            assert_eq!(config.accrue_protocol_fee(false, 400), Ok(100));
+            assert_eq!(config.accrue_protocol_fee(true, 3), Ok(0)); // rounds down
+            assert_eq!(config.protocol_fees_x(), 250);
+            assert_eq!(config.protocol_fees_y(), 100);
+        });
+    }
+
+    #[test]
+    fn excess_is_what_reserves_and_protocol_fees_do_not_account_for() {
+        with_config(2_500, |config| {
+            config.accrue_protocol_fee(true, 1_000).unwrap();
+            config.set_reserves(1_000, 500);
+            assert_eq!((config.reserve_x(), config.reserve_y()), (1_000, 500));
+            assert_eq!(config.excess_x(1_250), Ok(0));
+            assert_eq!(config.excess_x(1_300), Ok(50));
+            assert_eq!(config.excess_y(600), Ok(100));
+            assert_eq!(config.excess_y(499), Err(ProgramError::ArithmeticOverflow));
+        });
+    }
+
+    #[test]
+    fn protocol_fee_is_capped_at_the_whole_fee() {
+        with_config(10_000, |config| {
+            config.accrue_protocol_fee(false, u64::MAX).unwrap();
+            assert_eq!(config.protocol_fees_y(), u64::MAX);
+            assert_eq!(
+                config.accrue_protocol_fee(false, 1),
+                Err(ProgramError::ArithmeticOverflow)
+            );
+            assert_eq!(config.set_protocol_fee(10_001), Err(ProgramError::InvalidAccountData));
+        });
+    }
+
+    #[test]
+    fn protocol_and_referral_shares_fit_in_the_fee() {
+        with_config(2_500, |config| {
+            assert_eq!(config.set_referral_fee(7_501), Err(ProgramError::InvalidAccountData));
+            config.set_referral_fee(1_000).unwrap();
+            assert_eq!(config.referral_share(1_000), 100);
+            assert_eq!(config.set_protocol_fee(9_001), Err(ProgramError::InvalidAccountData));
+            assert_eq!(config.set_protocol_fee(9_000), Ok(()));
+        });
+    }
+
+    #[test]
+    fn price_impact_limit_is_off_at_zero() {
+        with_config(0, |config| {
+            assert_eq!(config.check_price_impact(10_000), Ok(()));
+            config.set_max_price_impact_bps(500).unwrap();
+            assert_eq!(config.check_price_impact(500), Ok(()));
+            assert_eq!(
+                config.check_price_impact(501),
+                Err(ProgramError::Custom(AmmError::PriceImpactTooHigh as u32))
+            );
+            assert_eq!(
+                config.set_max_price_impact_bps(10_001),
+                Err(ProgramError::InvalidAccountData)
+            );
+        });
+    }
+
+    #[test]
+    fn oracle_and_band_are_set_together() {
+        with_config(0, |config| {
+            assert_eq!(config.oracle(), None);
+            config.set_oracle_band([5; 32], 200).unwrap();
+            assert_eq!(config.oracle(), Some([5; 32]));
+            assert_eq!(config.oracle_band_bps(), 200);
+            for (oracle, band_bps) in [([5; 32], 0), ([0; 32], 200), ([5; 32], 10_001)] {
+                assert_eq!(
+                    config.set_oracle_band(oracle, band_bps),
+                    Err(ProgramError::InvalidAccountData)
+                );
+            }
+            config.set_oracle_band([0; 32], 0).unwrap();
+            assert_eq!(config.oracle(), None);
+        });
+    }
+
+    #[test]
+    fn layout_version_comes_from_the_length_then_the_version_byte() {
+        assert_eq!(Config::V0_LEN, 108);
+        assert_eq!(Config::VERSION_OFFSET + 1, Config::V1_LEN);
+        assert_eq!(Config::layout_version(Config::V0_LEN, 7), Some(0));
+        assert_eq!(Config::layout_version(Config::V1_LEN, 1), Some(1));
+        assert_eq!(Config::layout_version(Config::V2_LEN, 2), Some(2));
+        assert_eq!(Config::layout_version(Config::V3_LEN, 3), Some(3));
+        assert_eq!(Config::layout_version(Config::V4_LEN, 4), Some(4));
+        assert_eq!(Config::layout_version(Config::V5_LEN, 5), Some(5));
+        assert_eq!(Config::layout_version(Config::LEN, 6), Some(6));
+        assert_eq!(Config::layout_version(Config::LEN + 1, 1), None);
+        with_config(0, |config| {
+            assert_eq!(config.version(), Config::VERSION);
+            assert_eq!(config.lp_decimals(), DEFAULT_LP_DECIMALS);
+            assert_eq!(config.fee_recipient(), None);
+            config.set_fee_recipient([4; 32]);
+            assert_eq!(config.fee_recipient(), Some([4; 32]));
+        });
+    }
+
+    #[test]
+    fn oracle_accumulates_price_times_elapsed_seconds() {
+        with_config(0, |config| {
+            // The first observation only starts the clock
+            config.update_oracle(100, 200, 1_000);
+            assert_eq!((config.price_x_cumulative(), config.price_y_cumulative()), (0, 0));
+
+            // 10s at 2 Y per X, then 5s at 1 Y per X
+            config.update_oracle(100, 200, 1_010);
+            config.update_oracle(300, 300, 1_015);
+            assert_eq!(config.price_x_cumulative(), (2 * 10 + 5u128) << 64);
+            assert_eq!(config.price_y_cumulative(), (10u128 << 63) + (5u128 << 64));
+            assert_eq!(config.last_observation(), 1_015);
+
+            // Reads extend to now without writing; same-second updates are no-ops
+            assert_eq!(config.cumulative_prices(300, 300, 1_020).0, 30u128 << 64);
+            config.update_oracle(1, 1_000, 1_015);
+            assert_eq!(config.price_x_cumulative(), 25u128 << 64);
+        });
+    }
+
+    #[test]
+    fn swap_stats_count_both_sides_and_the_fee_in_the_input_token() {
+        with_config(0, |config| {
+            config.record_swap(true, 1_000, 450, 3);
+            config.record_swap(false, 200, 390, 1);
+            assert_eq!((config.volume_x(), config.volume_y()), (1_390, 650));
+            assert_eq!((config.swap_fees_x(), config.swap_fees_y()), (3, 1));
+            assert_eq!(config.swap_count(), 2);
+
+            // Saturates instead of failing the swap
+            config.volume_x = u128::MAX.to_le_bytes();
+            config.record_swap(true, 1, 1, 0);
+            assert_eq!(config.volume_x(), u128::MAX);
+        });
+    }
+
+    #[test]
+    fn swap_throttle_stops_a_signer_turning_around_within_a_slot() {
+        with_config(0, |config| {
+            let throttled = Err(ProgramError::Custom(AmmError::SwapThrottled as u32));
+            let signer = Address::new_from_array([7; 32]);
+            let other = Address::new_from_array([8; 32]);
+
+            // Off by default: nothing is recorded or checked
+            config.record_swap_slot(&signer, true, 10);
+            assert_eq!(config.check_swap_throttle(&signer, false, 10), Ok(()));
+
+            config.set_swap_throttle(true);
+            config.record_swap_slot(&signer, true, 10);
+            assert_eq!(config.check_swap_throttle(&signer, false, 10), throttled);
+            assert_eq!(config.check_swap_throttle(&signer, true, 10), Ok(()));
+            assert_eq!(config.check_swap_throttle(&other, false, 10), Ok(()));
+            assert_eq!(config.check_swap_throttle(&signer, false, 11), Ok(()));
+
+            // Turning it off and on again forgets the last swap
+            config.set_swap_throttle(false);
+            config.set_swap_throttle(true);
+            assert_eq!(config.check_swap_throttle(&signer, false, 10), Ok(()));
+        });
+    }
+
+    #[test]
+    fn flash_loan_locks_every_operation_until_repaid() {
+        with_config(0, |config| {
+            assert_eq!(config.flash_fee_for(10_000).unwrap(), 30);
+            assert_eq!(config.flash_fee_for(1).unwrap(), 1); // rounds up
+
+            config.start_flash_loan(1_030, 0);
+            for operation in OPERATIONS {
+                assert_eq!(config.check_state(operation), Err(ProgramError::InvalidAccountData));
+            }
+
+            config.end_flash_loan();
+            for operation in OPERATIONS {
+                assert_eq!(config.check_state(operation), Ok(()));
+            }
+        });
+    }
 }
@@ -0,0 +1,560 @@
+//! Token CPIs and account readers that work under both the Token and the
+//! Token-2022 program.
+//!
+//! `pinocchio_token` always targets the Token program and rejects accounts owned
+//! by Token-2022, so pool mints and vaults go through these instead. A pool runs
+//! entirely under one token program: X, Y, their vaults and the LP mint.
+
+use pinocchio::{
+    AccountView,
+    Address,
+    cpi::{invoke_signed, invoke_signed_with_bounds, Signer},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+    ProgramResult,
+};
+use solana_challenge_common::ids;
+
+/// TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+pub const TOKEN_2022_PROGRAM_ID: Address = Address::new_from_array(ids::TOKEN_2022_PROGRAM_ID);
+
+/// Size of a Mint without extensions
+pub const MINT_LEN: usize = ids::MINT_LEN;
+/// `is_initialized` flag in the base Mint layout
+const MINT_IS_INITIALIZED_OFFSET: usize = 45;
+/// Size of a token Account without extensions; Token-2022 extensions start after it
+const ACCOUNT_LEN: usize = ids::TOKEN_ACCOUNT_LEN;
+
+/// Token-2022 `AccountType::Mint`, stored right after the base account size
+const ACCOUNT_TYPE_MINT: u8 = 1;
+/// Token-2022 `ExtensionType::TransferFeeConfig`
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+/// TransferFeeConfig: two authorities and the withheld amount precede the fees
+const OLDER_TRANSFER_FEE_OFFSET: usize = 72;
+const NEWER_TRANSFER_FEE_OFFSET: usize = 90;
+const TRANSFER_FEE_CONFIG_LEN: usize = 108;
+
+/// Most extra accounts forwarded to a Token-2022 transfer hook
+pub const MAX_TRANSFER_HOOK_ACCOUNTS: usize = 16;
+const TRANSFER_CHECKED_MAX_ACCOUNTS: usize = 4 + MAX_TRANSFER_HOOK_ACCOUNTS;
+
+/// Check that `token_program` is the Token or the Token-2022 program
+#[inline(always)]
+pub fn check_token_program(token_program: &AccountView) -> ProgramResult {
+    let address = token_program.address();
+    if address != &pinocchio_token::ID && address != &TOKEN_2022_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Balance of a token account owned by `token_program`
+#[inline(always)]
+pub fn token_amount(account: &AccountView, token_program: &AccountView) -> Result<u64, ProgramError> {
+    if !account.owned_by(token_program.address()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = account.try_borrow()?;
+    if data.len() < ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(u64::from_le_bytes(data[64..72].try_into().unwrap()))
+}
+
+/// Owner of a token account owned by `token_program`
+#[inline(always)]
+pub fn token_owner(account: &AccountView, token_program: &AccountView) -> Result<[u8; 32], ProgramError> {
+    if !account.owned_by(token_program.address()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = account.try_borrow()?;
+    if data.len() < ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(data[32..64].try_into().unwrap())
+}
+
+/// Decimals of a mint owned by `token_program`, for `TransferChecked` where
+/// nothing else of the mint is needed
+#[inline(always)]
+pub fn mint_decimals(mint: &AccountView, token_program: &AccountView) -> Result<u8, ProgramError> {
+    if !mint.owned_by(token_program.address()) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = mint.try_borrow()?;
+    if data.len() < MINT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(data[44])
+}
+
+// ==================== Mint ====================
+
+/// One of the two fee schedules in a Token-2022 TransferFeeConfig
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            epoch: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            maximum_fee: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            basis_points: u16::from_le_bytes(bytes[16..18].try_into().unwrap()),
+        }
+    }
+
+    /// Fee withheld from a transfer of `amount`: rounded up, capped at `maximum_fee`
+    #[inline(always)]
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        (fee as u64).min(self.maximum_fee)
+    }
+
+    /// Amount to send so that `net` arrives after the fee
+    #[inline(always)]
+    pub fn amount_with_fee(&self, net: u64) -> Result<u64, ProgramError> {
+        if self.basis_points == 0 || net == 0 {
+            return Ok(net);
+        }
+        let capped = net
+            .checked_add(self.maximum_fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if self.basis_points >= 10_000 {
+            return Ok(capped);
+        }
+        let gross = (net as u128 * 10_000).div_ceil((10_000 - self.basis_points) as u128);
+        Ok(u64::try_from(gross).map_or(capped, |gross| gross.min(capped)))
+    }
+}
+
+/// What the pool needs from a mint: supply, decimals and the transfer fee in force
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintInfo {
+    pub supply: u64,
+    pub decimals: u8,
+    pub transfer_fee: Option<TransferFee>,
+}
+
+impl MintInfo {
+    /// Read a mint owned by `token_program` at `epoch`
+    pub fn load(
+        mint: &AccountView,
+        token_program: &AccountView,
+        epoch: u64,
+    ) -> Result<Self, ProgramError> {
+        if !mint.owned_by(token_program.address()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Self::from_bytes(&mint.try_borrow()?, epoch)
+    }
+
+    /// Parse mint data, walking the Token-2022 extension TLVs when present
+    pub fn from_bytes(data: &[u8], epoch: u64) -> Result<Self, ProgramError> {
+        if data.len() < MINT_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[MINT_IS_INITIALIZED_OFFSET] == 0 {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let mut mint = Self {
+            supply: u64::from_le_bytes(data[36..44].try_into().unwrap()),
+            decimals: data[44],
+            transfer_fee: None,
+        };
+
+        // Base mint only (Token, or Token-2022 without extensions)
+        if data.len() == MINT_LEN {
+            return Ok(mint);
+        }
+        if data.len() <= ACCOUNT_LEN || data[ACCOUNT_LEN] != ACCOUNT_TYPE_MINT {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut offset = ACCOUNT_LEN + 1;
+        while offset + 4 <= data.len() {
+            let extension = u16::from_le_bytes([data[offset], data[offset + 1]]);
+            // Zeroed space after the last extension
+            if extension == 0 {
+                break;
+            }
+            let len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            let value = data
+                .get(offset + 4..offset + 4 + len)
+                .ok_or(ProgramError::InvalidAccountData)?;
+
+            if extension == EXTENSION_TRANSFER_FEE_CONFIG {
+                if len != TRANSFER_FEE_CONFIG_LEN {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let older = TransferFee::from_bytes(&value[OLDER_TRANSFER_FEE_OFFSET..]);
+                let newer = TransferFee::from_bytes(&value[NEWER_TRANSFER_FEE_OFFSET..]);
+                mint.transfer_fee = Some(match epoch >= newer.epoch {
+                    true => newer,
+                    false => older,
+                });
+                break;
+            }
+            offset += 4 + len;
+        }
+
+        Ok(mint)
+    }
+
+    /// Fee withheld when `amount` of this mint is transferred
+    #[inline(always)]
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        self.transfer_fee.map_or(0, |fee| fee.fee_for(amount))
+    }
+
+    /// Amount of this mint to send so that `net` arrives
+    #[inline(always)]
+    pub fn amount_with_fee(&self, net: u64) -> Result<u64, ProgramError> {
+        self.transfer_fee.map_or(Ok(net), |fee| fee.amount_with_fee(net))
+    }
+}
+
+// ==================== CPIs ====================
+
+/// `TransferChecked`, required by Token-2022 for mints with a transfer fee or
+/// a transfer hook
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub to: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Address,
+}
+
+impl TransferChecked<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let accounts = [
+            InstructionAccount::writable(self.from.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::writable(self.to.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &self.data(),
+            },
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+
+    #[inline(always)]
+    pub fn invoke_with_hook_accounts(&self, hook_accounts: &[AccountView]) -> ProgramResult {
+        self.invoke_signed_with_hook_accounts(&[], hook_accounts)
+    }
+
+    /// Forward `hook_accounts` after the transfer's own accounts. Token-2022
+    /// picks the hook program, its validation account and extra accounts out
+    /// of them by address, so the same list serves transfers of either pool
+    /// mint; the Token program ignores them.
+    pub fn invoke_signed_with_hook_accounts(
+        &self,
+        signers: &[Signer],
+        hook_accounts: &[AccountView],
+    ) -> ProgramResult {
+        if hook_accounts.is_empty() {
+            return self.invoke_signed(signers);
+        }
+        if hook_accounts.len() > MAX_TRANSFER_HOOK_ACCOUNTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let base = [self.from, self.mint, self.to, self.authority];
+        let views: [&AccountView; TRANSFER_CHECKED_MAX_ACCOUNTS] =
+            core::array::from_fn(|i| match base.get(i) {
+                Some(account) => *account,
+                // Padding past the end is never passed on
+                None => hook_accounts.get(i - base.len()).unwrap_or(self.mint),
+            });
+        let accounts: [InstructionAccount; TRANSFER_CHECKED_MAX_ACCOUNTS] =
+            core::array::from_fn(|i| match i {
+                0 => InstructionAccount::writable(self.from.address()),
+                1 => InstructionAccount::readonly(self.mint.address()),
+                2 => InstructionAccount::writable(self.to.address()),
+                3 => InstructionAccount::readonly_signer(self.authority.address()),
+                // Hook accounts keep their writability but never sign
+                _ => InstructionAccount::new(views[i].address(), views[i].is_writable(), false),
+            });
+        let len = base.len() + hook_accounts.len();
+
+        invoke_signed_with_bounds::<TRANSFER_CHECKED_MAX_ACCOUNTS>(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts[..len],
+                data: &self.data(),
+            },
+            &views[..len],
+            signers,
+        )
+    }
+
+    /// [discriminator, amount, decimals]
+    #[inline(always)]
+    fn data(&self) -> [u8; 10] {
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+        data
+    }
+}
+
+/// `MintTo`
+pub struct MintTo<'a> {
+    pub mint: &'a AccountView,
+    pub account: &'a AccountView,
+    pub mint_authority: &'a AccountView,
+    pub amount: u64,
+    pub token_program: &'a Address,
+}
+
+impl MintTo<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let accounts = [
+            InstructionAccount::writable(self.mint.address()),
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::readonly_signer(self.mint_authority.address()),
+        ];
+
+        // [discriminator, amount]
+        let mut data = [0u8; 9];
+        data[0] = 7;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &data,
+            },
+            &[self.mint, self.account, self.mint_authority],
+            signers,
+        )
+    }
+}
+
+/// `Burn`
+pub struct Burn<'a> {
+    pub account: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub amount: u64,
+    pub token_program: &'a Address,
+}
+
+impl Burn<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let accounts = [
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::writable(self.mint.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        // [discriminator, amount]
+        let mut data = [0u8; 9];
+        data[0] = 8;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &data,
+            },
+            &[self.account, self.mint, self.authority],
+            &[],
+        )
+    }
+}
+
+/// `InitializeMint2` without a freeze authority
+pub struct InitializeMint2<'a> {
+    pub mint: &'a AccountView,
+    pub decimals: u8,
+    pub mint_authority: &'a Address,
+    pub token_program: &'a Address,
+}
+
+impl InitializeMint2<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let accounts = [InstructionAccount::writable(self.mint.address())];
+
+        // [discriminator, decimals, mint_authority, freeze_authority: None]
+        let mut data = [0u8; 35];
+        data[0] = 20;
+        data[1] = self.decimals;
+        data[2..34].copy_from_slice(self.mint_authority.as_ref());
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &data,
+            },
+            &[self.mint],
+            &[],
+        )
+    }
+}
+
+/// `CloseAccount` of an empty token account, sending its rent to `destination`
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountView,
+    pub destination: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a Address,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let accounts = [
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::writable(self.destination.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        // [discriminator]
+        let data = [9u8];
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &data,
+            },
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
+
+/// `SyncNative`: bring a native (wSOL) account's token balance in line with
+/// its lamports
+pub struct SyncNative<'a> {
+    pub account: &'a AccountView,
+    pub token_program: &'a Address,
+}
+
+impl SyncNative<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let accounts = [InstructionAccount::writable(self.account.address())];
+
+        // [discriminator]
+        let data = [17u8];
+
+        invoke_signed(
+            &InstructionView {
+                program_id: self.token_program,
+                accounts: &accounts,
+                data: &data,
+            },
+            &[self.account],
+            &[],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE: TransferFee = TransferFee {
+        epoch: 0,
+        maximum_fee: 50,
+        basis_points: 100,
+    };
+
+    #[test]
+    fn fee_rounds_up_and_caps() {
+        assert_eq!(FEE.fee_for(1), 1);
+        assert_eq!(FEE.fee_for(1_000), 10);
+        assert_eq!(FEE.fee_for(1_000_000), 50);
+    }
+
+    #[test]
+    fn amount_with_fee_delivers_the_net_amount() {
+        for net in [1, 99, 1_000, 4_950, 4_951, 1_000_000] {
+            let gross = FEE.amount_with_fee(net).unwrap();
+            assert_eq!(gross - FEE.fee_for(gross), net);
+        }
+    }
+
+    fn mint_data(extensions: &[(u16, &[u8])]) -> [u8; 512] {
+        let mut data = [0u8; 512];
+        data[36..44].copy_from_slice(&7u64.to_le_bytes());
+        data[44] = 6;
+        data[MINT_IS_INITIALIZED_OFFSET] = 1;
+        data[ACCOUNT_LEN] = ACCOUNT_TYPE_MINT;
+        let mut offset = ACCOUNT_LEN + 1;
+        for (extension, value) in extensions {
+            data[offset..offset + 2].copy_from_slice(&extension.to_le_bytes());
+            data[offset + 2..offset + 4].copy_from_slice(&(value.len() as u16).to_le_bytes());
+            data[offset + 4..offset + 4 + value.len()].copy_from_slice(value);
+            offset += 4 + value.len();
+        }
+        data
+    }
+
+    #[test]
+    fn reads_the_transfer_fee_in_force() {
+        let mut config = [0u8; TRANSFER_FEE_CONFIG_LEN];
+        // older: from epoch 0, newer: from epoch 10
+        config[OLDER_TRANSFER_FEE_OFFSET + 8..OLDER_TRANSFER_FEE_OFFSET + 16]
+            .copy_from_slice(&50u64.to_le_bytes());
+        config[OLDER_TRANSFER_FEE_OFFSET + 16..OLDER_TRANSFER_FEE_OFFSET + 18]
+            .copy_from_slice(&100u16.to_le_bytes());
+        config[NEWER_TRANSFER_FEE_OFFSET..NEWER_TRANSFER_FEE_OFFSET + 8]
+            .copy_from_slice(&10u64.to_le_bytes());
+        config[NEWER_TRANSFER_FEE_OFFSET + 16..NEWER_TRANSFER_FEE_OFFSET + 18]
+            .copy_from_slice(&200u16.to_le_bytes());
+
+        // Another extension (MintCloseAuthority) ahead of the fee config
+        let data = mint_data(&[(3, &[9; 32]), (EXTENSION_TRANSFER_FEE_CONFIG, &config)]);
+
+        let mint = MintInfo::from_bytes(&data, 9).unwrap();
+        assert_eq!((mint.supply, mint.decimals), (7, 6));
+        assert_eq!(mint.transfer_fee, Some(FEE));
+
+        let mint = MintInfo::from_bytes(&data, 10).unwrap();
+        assert_eq!(mint.transfer_fee.unwrap().basis_points, 200);
+    }
+
+    #[test]
+    fn plain_mints_have_no_fee() {
+        let data = mint_data(&[]);
+        assert_eq!(MintInfo::from_bytes(&data[..MINT_LEN], 0).unwrap().transfer_fee, None);
+        assert_eq!(MintInfo::from_bytes(&data, 0).unwrap().fee_for(1_000), 0);
+        assert_eq!(MintInfo::from_bytes(&data[..100], 0), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn rejects_uninitialized_mints() {
+        let mut data = mint_data(&[]);
+        data[MINT_IS_INITIALIZED_OFFSET] = 0;
+        assert_eq!(
+            MintInfo::from_bytes(&data[..MINT_LEN], 0),
+            Err(ProgramError::UninitializedAccount)
+        );
+    }
+}
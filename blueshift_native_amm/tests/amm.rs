@@ -0,0 +1,858 @@
+//! Initialize / Deposit / Swap / Withdraw run against the SBF build of the
+//! program in mollusk, so the vault and PDA checks unit-tested in `pda` run
+//! here against real instructions and accounts.
+//!
+//! Needs the program built first (`cargo build-sbf`), then
+//! `SBF_OUT_DIR=$PWD/target/deploy cargo test --features client`.
+
+use blueshift_native_amm::{
+    client::{self, PoolAddresses},
+    curve,
+    expiration::slot_expiration,
+    quote::{deposit_amounts, withdraw_amounts, DEFAULT_LP_DECIMALS, MAX_LP_DECIMALS},
+    token::MINT_LEN,
+    AmmError, AmmState, Config, PoolRegistry, FEE_TIERS,
+};
+use mollusk_svm::{program::keyed_account_for_system_program, result::InstructionResult, Mollusk};
+use pinocchio::Address;
+use solana_account::Account;
+use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+const SEED: u64 = 42;
+/// Swap fee in basis points
+const FEE: u16 = 30;
+/// What each user starts with of X and Y
+const BALANCE: u64 = 1_000_000_000;
+/// First deposit: LP minted and the X and Y paying for it (1 X : 4 Y)
+const LP: u64 = 1_000_000;
+const X: u64 = 1_000_000;
+const Y: u64 = 4_000_000;
+/// Far enough out that the default clock never expires an order
+const NEVER: i64 = i64::MAX;
+const LAMPORTS: u64 = 10_000_000_000;
+/// spl-token account size
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+fn key(address: &Address) -> Pubkey {
+    Pubkey::new_from_array(address.to_bytes())
+}
+
+struct Env {
+    mollusk: Mollusk,
+    accounts: Vec<(Pubkey, Account)>,
+    pool: PoolAddresses,
+    authority: Address,
+    user: Address,
+}
+
+impl Env {
+    /// A pool of two 6-decimal mints, not yet initialized, with its vaults
+    /// in place and a user holding `BALANCE` of each token. The protocol
+    /// config, which `authority` also runs, allows every fee tier.
+    fn new() -> Self {
+        let program_id = key(&blueshift_native_amm::ID);
+        let mut mollusk = Mollusk::new(&program_id, "blueshift_native_amm");
+        mollusk_svm_programs_token::token::add_program(&mut mollusk);
+
+        // Mints go in canonical order
+        let mut mints = [
+            Address::new_from_array(Pubkey::new_unique().to_bytes()),
+            Address::new_from_array(Pubkey::new_unique().to_bytes()),
+        ];
+        mints.sort_by_key(|mint| mint.to_bytes());
+        let [mint_x, mint_y] = mints;
+        let pool = PoolAddresses::find(SEED, mint_x, mint_y, pinocchio_token::ID);
+        let authority = Address::new_from_array(Pubkey::new_unique().to_bytes());
+        let user = Address::new_from_array(Pubkey::new_unique().to_bytes());
+
+        let mut env = Self {
+            mollusk,
+            accounts: vec![
+                keyed_account_for_system_program(),
+                mollusk_svm_programs_token::token::keyed_account(),
+            ],
+            pool: pool.clone(),
+            authority,
+            user,
+        };
+
+        env.set(&authority, env.wallet());
+        env.set(&user, env.wallet());
+        env.set(&mint_x, env.mint());
+        env.set(&mint_y, env.mint());
+        env.set(&pool.vault_x, env.token_account(&mint_x, &pool.config, 0));
+        env.set(&pool.vault_y, env.token_account(&mint_y, &pool.config, 0));
+        env.fund(&user);
+
+        let ix = client::initialize_protocol_ix(authority, FEE_TIERS[0], FEE_TIERS[3], 0);
+        assert_eq!(env.process(ix).raw_result, Ok(()));
+
+        env
+    }
+
+    /// An initialized pool holding the first deposit
+    fn with_liquidity() -> Self {
+        let mut env = Self::new();
+        env.initialize();
+        let result = env.process(client::deposit_ix(env.user, &env.pool, LP, X, Y, NEVER));
+        assert_eq!(result.raw_result, Ok(()));
+        env
+    }
+
+    fn set(&mut self, address: &Address, account: Account) {
+        let pubkey = key(address);
+        match self.accounts.iter_mut().find(|(k, _)| k == &pubkey) {
+            Some((_, existing)) => *existing = account,
+            None => self.accounts.push((pubkey, account)),
+        }
+    }
+
+    fn get(&self, address: &Address) -> &Account {
+        let pubkey = key(address);
+        &self.accounts.iter().find(|(k, _)| k == &pubkey).unwrap().1
+    }
+
+    /// Give `wallet` token accounts holding `BALANCE` X and Y, and an empty
+    /// LP token account
+    fn fund(&mut self, wallet: &Address) {
+        let [x_ata, y_ata, lp_ata] = self.pool.user_atas(wallet);
+        self.set(&x_ata, self.token_account(&self.pool.mint_x, wallet, BALANCE));
+        self.set(&y_ata, self.token_account(&self.pool.mint_y, wallet, BALANCE));
+        self.set(&lp_ata, self.token_account(&self.pool.mint_lp, wallet, 0));
+    }
+
+    /// Token balance of the token account at `address`
+    fn balance(&self, address: &Address) -> u64 {
+        u64::from_le_bytes(self.get(address).data[64..72].try_into().unwrap())
+    }
+
+    /// The user's X, Y and LP balances
+    fn user_balances(&self) -> [u64; 3] {
+        self.pool.user_atas(&self.user).map(|ata| self.balance(&ata))
+    }
+
+    /// Reserves recorded in the pool's config
+    fn reserves(&self) -> (u64, u64) {
+        let config = self.config();
+        (config.reserve_x(), config.reserve_y())
+    }
+
+    fn config(&self) -> &Config {
+        let data = &self.get(&self.pool.config).data;
+        assert_eq!(data.len(), Config::LEN);
+        // SAFETY: length checked above, and `Config` has an alignment of 1
+        unsafe { Config::from_bytes_unchecked(data) }
+    }
+
+    /// Put the pool in `state`, as `SetState` would
+    fn set_state(&mut self, state: AmmState) {
+        let mut config = self.get(&self.pool.config).clone();
+        // SAFETY: `Config` has an alignment of 1 and the account is a config
+        unsafe { Config::from_bytes_unchecked_mut(&mut config.data) }
+            .set_state(state as u8)
+            .unwrap();
+        let address = self.pool.config;
+        self.set(&address, config);
+    }
+
+    fn lp_supply(&self) -> u64 {
+        u64::from_le_bytes(self.get(&self.pool.mint_lp).data[36..44].try_into().unwrap())
+    }
+
+    fn wallet(&self) -> Account {
+        Account::new(LAMPORTS, 0, &Pubkey::default())
+    }
+
+    /// Initialized 6-decimal mint with no freeze authority
+    fn mint(&self) -> Account {
+        let mut data = vec![0; MINT_LEN];
+        data[0] = 1;
+        data[4..36].copy_from_slice(Pubkey::new_unique().as_ref());
+        data[36..44].copy_from_slice(&u64::MAX.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+
+        self.rent_exempt(data, &pinocchio_token::ID)
+    }
+
+    /// Initialized token account with no delegate or close authority
+    fn token_account(&self, mint: &Address, owner: &Address, amount: u64) -> Account {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+
+        self.rent_exempt(data, &pinocchio_token::ID)
+    }
+
+    fn rent_exempt(&self, data: Vec<u8>, owner: &Address) -> Account {
+        Account {
+            lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data,
+            owner: key(owner),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Run `instruction`, keeping the resulting accounts when it succeeds.
+    /// Accounts the instruction names that don't exist yet start out empty.
+    fn process(&mut self, instruction: client::Instruction) -> InstructionResult {
+        let instruction = Instruction {
+            program_id: key(&instruction.program_id),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: key(&meta.address),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data,
+        };
+        for meta in &instruction.accounts {
+            if self.accounts.iter().all(|(k, _)| k != &meta.pubkey) {
+                self.accounts.push((meta.pubkey, Account::default()));
+            }
+        }
+
+        let result = self.mollusk.process_instruction(&instruction, &self.accounts);
+        if result.raw_result.is_ok() {
+            for (pubkey, account) in &result.resulting_accounts {
+                self.accounts
+                    .iter_mut()
+                    .find(|(k, _)| k == pubkey)
+                    .unwrap()
+                    .1 = account.clone();
+            }
+        }
+        result
+    }
+
+    fn initialize(&mut self) {
+        let ix = client::initialize_ix(self.authority, &self.pool, SEED, FEE, Some(self.authority));
+        let result = self.process(ix);
+        assert_eq!(result.raw_result, Ok(()));
+    }
+
+    fn swap_ix(&self, is_x: bool, amount: u64, min: u64, expiration: i64) -> client::Instruction {
+        client::swap_ix(self.user, &self.pool, is_x, amount, min, expiration, None, None)
+    }
+}
+
+// ==================== Initialize ====================
+
+#[test]
+fn initialize_creates_the_pool() {
+    let mut env = Env::new();
+    env.initialize();
+
+    let config = env.config();
+    assert_eq!(config.seed(), SEED);
+    assert_eq!(config.fee(), FEE);
+    assert_eq!(config.version(), Config::VERSION);
+    assert_eq!(config.mint_x(), &env.pool.mint_x.to_bytes());
+    assert_eq!(config.mint_y(), &env.pool.mint_y.to_bytes());
+    assert_eq!(config.has_authority(), Some(env.authority.to_bytes()));
+    assert_eq!(config.reserve_x(), 0);
+    assert_eq!(config.reserve_y(), 0);
+
+    // The vault ATAs are recorded on-chain
+    assert_eq!(config.vault_x(), &env.pool.vault_x.to_bytes());
+    assert_eq!(config.vault_y(), &env.pool.vault_y.to_bytes());
+
+    // The LP mint is the config's to mint
+    let mint_lp = env.get(&env.pool.mint_lp);
+    assert_eq!(mint_lp.owner, key(&pinocchio_token::ID));
+    assert_eq!(mint_lp.data[4..36], env.pool.config.to_bytes());
+    assert_eq!(mint_lp.data[44], DEFAULT_LP_DECIMALS);
+    assert_eq!(config.lp_decimals(), DEFAULT_LP_DECIMALS);
+    assert_eq!(env.lp_supply(), 0);
+}
+
+#[test]
+fn initialize_with_other_lp_decimals() {
+    let mut env = Env::new();
+
+    let ix = client::initialize_ix(env.authority, &env.pool, SEED, FEE, None);
+    let too_many = client::with_lp_decimals(ix.clone(), MAX_LP_DECIMALS + 1);
+    assert_eq!(
+        env.process(too_many).raw_result,
+        Err(InstructionError::InvalidInstructionData)
+    );
+
+    let ix = client::with_lp_decimals(ix, 9);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.get(&env.pool.mint_lp).data[44], 9);
+    assert_eq!(env.config().lp_decimals(), 9);
+
+    // The LP decimals don't change what a deposit mints
+    let result = env.process(client::deposit_ix(env.user, &env.pool, LP, X, Y, NEVER));
+    assert_eq!(result.raw_result, Ok(()));
+    assert_eq!(env.lp_supply(), LP);
+}
+
+#[test]
+fn initialize_twice_fails() {
+    let mut env = Env::new();
+    env.initialize();
+
+    let ix = client::initialize_ix(env.authority, &env.pool, SEED, FEE, Some(env.authority));
+    assert!(env.process(ix).raw_result.is_err());
+}
+
+#[test]
+fn initialize_with_mints_out_of_order_fails() {
+    let mut env = Env::new();
+
+    let mut pool = env.pool.clone();
+    core::mem::swap(&mut pool.mint_x, &mut pool.mint_y);
+    let ix = client::initialize_ix(env.authority, &pool, SEED, FEE, None);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn initialize_registers_one_pool_per_pair_and_fee_tier() {
+    let mut env = Env::new();
+    env.initialize();
+
+    let (registry, _) = client::pool_registry_address(&env.pool.mint_x, &env.pool.mint_y, FEE);
+    let data = &env.get(&registry).data;
+    assert_eq!(data.len(), PoolRegistry::LEN);
+    // SAFETY: length checked above, and `PoolRegistry` has an alignment of 1
+    let registry = unsafe { PoolRegistry::from_bytes_unchecked(data) };
+    assert_eq!(registry.config(), &env.pool.config.to_bytes());
+    assert_eq!((registry.seed(), registry.fee()), (SEED, FEE));
+
+    // Another seed at the same tier is refused, another tier is not
+    let (mint_x, mint_y) = (env.pool.mint_x, env.pool.mint_y);
+    let pool = PoolAddresses::find(SEED + 1, mint_x, mint_y, pinocchio_token::ID);
+    let ix = client::initialize_ix(env.authority, &pool, SEED + 1, FEE, None);
+    assert!(env.process(ix).raw_result.is_err());
+    let ix = client::initialize_ix(env.authority, &pool, SEED + 1, 5, None);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+}
+
+#[test]
+fn initialize_outside_the_fee_tiers_fails() {
+    let mut env = Env::new();
+
+    let ix = client::initialize_ix(env.authority, &env.pool, SEED, 31, None);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::Custom(AmmError::UnsupportedFeeTier as u32))
+    );
+}
+
+#[test]
+fn initialize_outside_the_protocol_fee_bounds_fails() {
+    let mut env = Env::new();
+    let ix = client::set_protocol_config_ix(env.authority, 5, 30, 0);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    // A sanctioned tier, but above what the deployment allows
+    let ix = client::initialize_ix(env.authority, &env.pool, SEED, 100, None);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::Custom(AmmError::FeeOutOfBounds as u32))
+    );
+}
+
+#[test]
+fn initialize_takes_the_default_protocol_share() {
+    let mut env = Env::new();
+    let ix = client::set_protocol_config_ix(env.authority, FEE, FEE, 2_000);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    env.initialize();
+    assert_eq!(env.config().protocol_fee(), 2_000);
+}
+
+#[test]
+fn protocol_config_answers_only_to_its_authority() {
+    let mut env = Env::new();
+
+    let ix = client::set_protocol_config_ix(env.user, 0, 9_999, 0);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::IncorrectAuthority));
+    let ix = client::set_protocol_config_ix(env.authority, 31, 30, 0);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidInstructionData));
+
+    // There is one per deployment
+    let ix = client::initialize_protocol_ix(env.user, 0, 9_999, 0);
+    assert!(env.process(ix).raw_result.is_err());
+}
+
+// ==================== Deposit ====================
+
+#[test]
+fn first_deposit_sets_the_price() {
+    let env = Env::with_liquidity();
+
+    assert_eq!(env.reserves(), (X, Y));
+    assert_eq!(env.balance(&env.pool.vault_x), X);
+    assert_eq!(env.balance(&env.pool.vault_y), Y);
+    assert_eq!(env.lp_supply(), LP);
+    assert_eq!(env.user_balances(), [BALANCE - X, BALANCE - Y, LP]);
+}
+
+#[test]
+fn later_deposits_pay_the_pool_ratio() {
+    let mut env = Env::with_liquidity();
+
+    // Half the supply again costs half the reserves, whatever the maximums
+    let result = env.process(client::deposit_ix(env.user, &env.pool, LP / 2, X, Y, NEVER));
+    assert_eq!(result.raw_result, Ok(()));
+
+    assert_eq!(env.reserves(), (X + X / 2, Y + Y / 2));
+    assert_eq!(env.lp_supply(), LP + LP / 2);
+    assert_eq!(
+        env.user_balances(),
+        [BALANCE - X - X / 2, BALANCE - Y - Y / 2, LP + LP / 2]
+    );
+}
+
+#[test]
+fn deposit_beyond_max_fails() {
+    let mut env = Env::with_liquidity();
+
+    let ix = client::deposit_ix(env.user, &env.pool, LP / 2, X / 2 - 1, Y, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+    assert_eq!(env.reserves(), (X, Y));
+}
+
+#[test]
+fn expired_deposit_fails() {
+    let mut env = Env::new();
+    env.initialize();
+    env.mollusk.sysvars.clock.unix_timestamp = 1_000;
+
+    let ix = client::deposit_ix(env.user, &env.pool, LP, X, Y, 1_000);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::Custom(1)));
+}
+
+#[test]
+fn deposit_into_another_vault_fails() {
+    let mut env = Env::new();
+    env.initialize();
+
+    // An X token account the pool's config owns, but not its ATA
+    let decoy = Address::new_from_array(Pubkey::new_unique().to_bytes());
+    env.set(&decoy, env.token_account(&env.pool.mint_x, &env.pool.config, 0));
+
+    let mut ix = client::deposit_ix(env.user, &env.pool, LP, X, Y, NEVER);
+    ix.accounts[2].address = decoy;
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+}
+
+#[test]
+fn deposit_with_slippage_tolerates_a_moved_ratio() {
+    let mut env = Env::with_liquidity();
+
+    // A swap puts 0.1% more X in the pool than the quote assumed
+    assert_eq!(env.process(env.swap_ix(true, 1_000, 1, NEVER)).raw_result, Ok(()));
+    let (reserve_x, reserve_y) = env.reserves();
+
+    let ix = client::deposit_with_slippage_ix(env.user, &env.pool, LP / 2, X / 2, Y / 2, 0, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+
+    let (x, y) = deposit_amounts(reserve_x, reserve_y, LP, LP / 2).unwrap();
+    let ix = client::deposit_with_slippage_ix(env.user, &env.pool, LP / 2, X / 2, Y / 2, 50, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.reserves(), (reserve_x + x, reserve_y + y));
+    assert_eq!(env.lp_supply(), LP + LP / 2);
+}
+
+#[test]
+fn first_deposit_with_slippage_pays_the_quote() {
+    let mut env = Env::new();
+    env.initialize();
+
+    let ix = client::deposit_with_slippage_ix(env.user, &env.pool, LP, X, Y, 100, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.reserves(), (X, Y));
+    assert_eq!(env.user_balances(), [BALANCE - X, BALANCE - Y, LP]);
+}
+
+// ==================== Swap ====================
+
+#[test]
+fn swap_x_for_y() {
+    let mut env = Env::with_liquidity();
+    let expected = curve::swap(X, Y, FEE, 10_000).unwrap();
+
+    let result = env.process(env.swap_ix(true, 10_000, expected.withdraw, NEVER));
+    assert_eq!(result.raw_result, Ok(()));
+
+    assert_eq!(
+        env.user_balances(),
+        [BALANCE - X - 10_000, BALANCE - Y + expected.withdraw, LP]
+    );
+    assert_eq!(env.reserves(), (X + 10_000, Y - expected.withdraw));
+    assert_eq!(env.balance(&env.pool.vault_x), X + 10_000);
+    assert_eq!(env.balance(&env.pool.vault_y), Y - expected.withdraw);
+
+    let config = env.config();
+    assert_eq!(config.volume_x(), 10_000);
+    assert_eq!(config.volume_y(), expected.withdraw as u128);
+    assert_eq!((config.swap_fees_x(), config.swap_fees_y()), (expected.fee as u128, 0));
+    assert_eq!(config.swap_count(), 1);
+}
+
+#[test]
+fn swap_y_for_x() {
+    let mut env = Env::with_liquidity();
+    let expected = curve::swap(Y, X, FEE, 40_000).unwrap();
+
+    let result = env.process(env.swap_ix(false, 40_000, expected.withdraw, NEVER));
+    assert_eq!(result.raw_result, Ok(()));
+
+    assert_eq!(
+        env.user_balances(),
+        [BALANCE - X + expected.withdraw, BALANCE - Y - 40_000, LP]
+    );
+    assert_eq!(env.reserves(), (X - expected.withdraw, Y + 40_000));
+}
+
+#[test]
+fn swap_below_min_fails() {
+    let mut env = Env::with_liquidity();
+    let expected = curve::swap(X, Y, FEE, 10_000).unwrap();
+
+    let ix = env.swap_ix(true, 10_000, expected.withdraw + 1, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+    assert_eq!(env.reserves(), (X, Y));
+}
+
+#[test]
+fn expired_swap_fails() {
+    let mut env = Env::with_liquidity();
+    env.mollusk.sysvars.clock.unix_timestamp = 1_000;
+
+    let ix = env.swap_ix(true, 10_000, 1, 999);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::Custom(1)));
+}
+
+#[test]
+fn swap_past_its_slot_deadline_fails() {
+    let mut env = Env::with_liquidity();
+    env.mollusk.warp_to_slot(100);
+
+    let ix = env.swap_ix(true, 10_000, 1, slot_expiration(100));
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::Custom(1)));
+
+    let ix = env.swap_ix(true, 10_000, 1, slot_expiration(101));
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+}
+
+#[test]
+fn swap_out_of_another_vault_fails() {
+    let mut env = Env::with_liquidity();
+
+    // Y the config owns outside the pool's vault must not be paid out
+    let decoy = Address::new_from_array(Pubkey::new_unique().to_bytes());
+    env.set(&decoy, env.token_account(&env.pool.mint_y, &env.pool.config, Y));
+
+    let mut ix = env.swap_ix(true, 10_000, 1, NEVER);
+    ix.accounts[4].address = decoy;
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+    assert_eq!(env.balance(&decoy), Y);
+}
+
+#[test]
+fn delegate_swaps_within_its_allowance_for_the_owner() {
+    let mut env = Env::with_liquidity();
+    let expected = curve::swap(X, Y, FEE, 10_000).unwrap();
+
+    // The user approves a session key for 10_000 X
+    let delegate = Address::new_from_array(Pubkey::new_unique().to_bytes());
+    env.set(&delegate, env.wallet());
+    let [user_x_ata, user_y_ata, _] = env.pool.user_atas(&env.user);
+    let mut account = env.get(&user_x_ata).clone();
+    account.data[72] = 1;
+    account.data[76..108].copy_from_slice(delegate.as_ref());
+    account.data[121..129].copy_from_slice(&10_000u64.to_le_bytes());
+    env.set(&user_x_ata, account);
+
+    // Output sent anywhere but the owner is refused
+    let [_, delegate_y_ata, _] = env.pool.user_atas(&delegate);
+    env.set(&delegate_y_ata, env.token_account(&env.pool.mint_y, &delegate, 0));
+    let mut ix = client::signed_by_delegate(env.swap_ix(true, 10_000, 1, NEVER), delegate);
+    ix.accounts[2].address = delegate_y_ata;
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+
+    let ix = client::signed_by_delegate(env.swap_ix(true, 10_000, 1, NEVER), delegate);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.balance(&user_x_ata), BALANCE - X - 10_000);
+    assert_eq!(env.balance(&user_y_ata), BALANCE - Y + expected.withdraw);
+
+    // The allowance is used up
+    let ix = client::signed_by_delegate(env.swap_ix(true, 1_000, 1, NEVER), delegate);
+    assert!(env.process(ix).raw_result.is_err());
+}
+
+#[test]
+fn throttled_pool_refuses_a_round_trip_within_a_slot() {
+    let mut env = Env::with_liquidity();
+    let ix = client::set_swap_throttle_ix(env.authority, &env.pool, true);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    env.mollusk.warp_to_slot(100);
+
+    assert_eq!(env.process(env.swap_ix(true, 10_000, 1, NEVER)).raw_result, Ok(()));
+    assert_eq!(env.process(env.swap_ix(true, 10_000, 1, NEVER)).raw_result, Ok(()));
+    assert_eq!(
+        env.process(env.swap_ix(false, 10_000, 1, NEVER)).raw_result,
+        Err(InstructionError::Custom(AmmError::SwapThrottled as u32))
+    );
+
+    env.mollusk.warp_to_slot(101);
+    assert_eq!(env.process(env.swap_ix(false, 10_000, 1, NEVER)).raw_result, Ok(()));
+}
+
+// ==================== Withdraw ====================
+
+#[test]
+fn partial_withdraw_pays_a_share_of_the_reserves() {
+    let mut env = Env::with_liquidity();
+
+    let ix = client::withdraw_ix(env.user, &env.pool, LP / 4, X / 4, Y / 4, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    assert_eq!(env.reserves(), (X - X / 4, Y - Y / 4));
+    assert_eq!(env.lp_supply(), LP - LP / 4);
+    assert_eq!(
+        env.user_balances(),
+        [BALANCE - X + X / 4, BALANCE - Y + Y / 4, LP - LP / 4]
+    );
+}
+
+#[test]
+fn full_withdraw_empties_the_pool() {
+    let mut env = Env::with_liquidity();
+
+    // Fees from a round trip stay in the pool and go to the last LP
+    let out = curve::swap(X, Y, FEE, 10_000).unwrap().withdraw;
+    assert_eq!(env.process(env.swap_ix(true, 10_000, 1, NEVER)).raw_result, Ok(()));
+    let back = curve::swap(Y - out, X + 10_000, FEE, out).unwrap().withdraw;
+    assert_eq!(env.process(env.swap_ix(false, out, 1, NEVER)).raw_result, Ok(()));
+    assert_eq!(env.reserves(), (X + 10_000 - back, Y));
+
+    let ix = client::withdraw_ix(env.user, &env.pool, LP, X, Y, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    assert_eq!(env.reserves(), (0, 0));
+    assert_eq!(env.balance(&env.pool.vault_x), 0);
+    assert_eq!(env.balance(&env.pool.vault_y), 0);
+    assert_eq!(env.lp_supply(), 0);
+    assert_eq!(env.user_balances(), [BALANCE, BALANCE, 0]);
+}
+
+#[test]
+fn withdraw_below_min_fails() {
+    let mut env = Env::with_liquidity();
+
+    let ix = client::withdraw_ix(env.user, &env.pool, LP / 4, X / 4, Y / 4 + 1, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+    assert_eq!(env.lp_supply(), LP);
+}
+
+#[test]
+fn withdraw_with_slippage_tolerates_a_moved_ratio() {
+    let mut env = Env::with_liquidity();
+
+    // A swap takes about 0.1% of Y out of the pool after the quote
+    assert_eq!(env.process(env.swap_ix(true, 1_000, 1, NEVER)).raw_result, Ok(()));
+    let (reserve_x, reserve_y) = env.reserves();
+
+    let ix = client::withdraw_with_slippage_ix(env.user, &env.pool, LP / 4, X / 4, Y / 4, 0, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+
+    let (x, y) = withdraw_amounts(reserve_x, reserve_y, LP, LP / 4).unwrap();
+    let ix =
+        client::withdraw_with_slippage_ix(env.user, &env.pool, LP / 4, X / 4, Y / 4, 50, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.reserves(), (reserve_x - x, reserve_y - y));
+    assert_eq!(env.lp_supply(), LP - LP / 4);
+}
+
+#[test]
+fn expired_withdraw_fails() {
+    let mut env = Env::with_liquidity();
+    env.mollusk.sysvars.clock.unix_timestamp = 1_000;
+
+    let ix = client::withdraw_ix(env.user, &env.pool, LP, 0, 0, 1_000);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::Custom(1)));
+}
+
+#[test]
+fn withdraw_from_another_vault_fails() {
+    let mut env = Env::with_liquidity();
+
+    let decoy = Address::new_from_array(Pubkey::new_unique().to_bytes());
+    env.set(&decoy, env.token_account(&env.pool.mint_x, &env.pool.config, X));
+
+    let mut ix = client::withdraw_ix(env.user, &env.pool, LP, 0, 0, NEVER);
+    ix.accounts[2].address = decoy;
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+    assert_eq!(env.balance(&decoy), X);
+}
+
+#[test]
+fn emergency_withdraw_exits_a_disabled_pool() {
+    let mut env = Env::with_liquidity();
+    env.set_state(AmmState::Disabled);
+
+    let ix = client::withdraw_ix(env.user, &env.pool, LP / 4, 0, 0, NEVER);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+
+    let ix = client::emergency_withdraw_ix(env.user, &env.pool, LP / 4);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    assert_eq!(env.reserves(), (X - X / 4, Y - Y / 4));
+    assert_eq!(env.lp_supply(), LP - LP / 4);
+    assert_eq!(
+        env.user_balances(),
+        [BALANCE - X + X / 4, BALANCE - Y + Y / 4, LP - LP / 4]
+    );
+}
+
+#[test]
+fn emergency_withdraw_from_a_live_pool_fails() {
+    let mut env = Env::with_liquidity();
+
+    for state in [AmmState::Initialized, AmmState::WithdrawOnly] {
+        env.set_state(state);
+        let ix = client::emergency_withdraw_ix(env.user, &env.pool, LP);
+        assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidAccountData));
+    }
+    assert_eq!(env.lp_supply(), LP);
+}
+
+// ==================== ClosePool ====================
+
+#[test]
+fn close_pool_returns_the_rent() {
+    let mut env = Env::with_liquidity();
+    let ix = client::withdraw_ix(env.user, &env.pool, LP, 0, 0, NEVER);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    let rent = env.get(&env.pool.config).lamports
+        + env.get(&env.pool.vault_x).lamports
+        + env.get(&env.pool.vault_y).lamports;
+    let destination = Address::new_from_array(Pubkey::new_unique().to_bytes());
+
+    let ix = client::close_pool_ix(env.authority, &env.pool, destination);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    assert_eq!(env.get(&destination).lamports, rent);
+    for closed in [env.pool.config, env.pool.vault_x, env.pool.vault_y] {
+        assert_eq!(env.get(&closed).lamports, 0);
+        assert!(env.get(&closed).data.is_empty());
+    }
+}
+
+#[test]
+fn close_pool_with_liquidity_fails() {
+    let mut env = Env::with_liquidity();
+
+    let destination = Address::new_from_array(Pubkey::new_unique().to_bytes());
+    let ix = client::close_pool_ix(env.authority, &env.pool, destination);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::Custom(AmmError::PoolNotEmpty as u32))
+    );
+}
+
+// ==================== Lock ====================
+
+#[test]
+fn locked_liquidity_vests_linearly_then_closes() {
+    let mut env = Env::with_liquidity();
+    env.mollusk.sysvars.clock.unix_timestamp = 1_000;
+
+    // The lock's LP token account is created by the client
+    let (lock, _) = client::lock_address(&env.pool.config, &env.user, 0);
+    let [_, _, lock_vault] = env.pool.user_atas(&lock);
+    env.set(&lock_vault, env.token_account(&env.pool.mint_lp, &lock, 0));
+
+    let ix = client::lock_liquidity_ix(env.user, &env.pool, 0, LP / 2, 2_000, true);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.user_balances()[2], LP / 2);
+    assert_eq!(env.balance(&lock_vault), LP / 2);
+
+    // Nothing has vested yet
+    let ix = client::unlock_liquidity_ix(env.user, &env.pool, 0);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::Custom(AmmError::LiquidityLocked as u32))
+    );
+
+    // Halfway through, half of it
+    env.mollusk.sysvars.clock.unix_timestamp = 1_500;
+    let ix = client::unlock_liquidity_ix(env.user, &env.pool, 0);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.user_balances()[2], LP / 2 + LP / 4);
+
+    // The rest at the end, closing the lock
+    env.mollusk.sysvars.clock.unix_timestamp = 2_000;
+    let ix = client::unlock_liquidity_ix(env.user, &env.pool, 0);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+    assert_eq!(env.user_balances()[2], LP);
+    for closed in [lock, lock_vault] {
+        assert_eq!(env.get(&closed).lamports, 0);
+    }
+}
+
+#[test]
+fn lock_ending_in_the_past_fails() {
+    let mut env = Env::with_liquidity();
+    env.mollusk.sysvars.clock.unix_timestamp = 1_000;
+
+    let (lock, _) = client::lock_address(&env.pool.config, &env.user, 0);
+    let [_, _, lock_vault] = env.pool.user_atas(&lock);
+    env.set(&lock_vault, env.token_account(&env.pool.mint_lp, &lock, 0));
+
+    let ix = client::lock_liquidity_ix(env.user, &env.pool, 0, LP / 2, 1_000, false);
+    assert_eq!(env.process(ix).raw_result, Err(InstructionError::InvalidArgument));
+}
+
+// ==================== MigrateConfig ====================
+
+#[test]
+fn migrate_config_brings_an_original_pool_up_to_date() {
+    let mut env = Env::with_liquidity();
+
+    // Cut the config back to the original 108-byte layout, which tracked no
+    // reserves or vaults, as pools created before any migration have it
+    let mut config = env.get(&env.pool.config).clone();
+    config.data.truncate(Config::V0_LEN);
+    config.lamports = env.mollusk.sysvars.rent.minimum_balance(Config::V0_LEN);
+    let address = env.pool.config;
+    env.set(&address, config);
+
+    let ix = client::migrate_config_ix(env.user, &env.pool);
+    assert_eq!(env.process(ix).raw_result, Ok(()));
+
+    let config = env.config();
+    assert_eq!(config.version(), Config::VERSION);
+    assert_eq!(config.vault_x(), &env.pool.vault_x.to_bytes());
+    assert_eq!(config.vault_y(), &env.pool.vault_y.to_bytes());
+    assert_eq!(config.lp_decimals(), DEFAULT_LP_DECIMALS);
+    assert_eq!(env.reserves(), (X, Y));
+
+    // The migrated pool swaps against what its vaults hold
+    let out = curve::swap(X, Y, FEE, 10_000).unwrap().withdraw;
+    assert_eq!(env.process(env.swap_ix(true, 10_000, out, NEVER)).raw_result, Ok(()));
+    assert_eq!(env.reserves(), (X + 10_000, Y - out));
+
+    let ix = client::migrate_config_ix(env.user, &env.pool);
+    assert_eq!(
+        env.process(ix).raw_result,
+        Err(InstructionError::Custom(AmmError::ConfigAlreadyMigrated as u32))
+    );
+}
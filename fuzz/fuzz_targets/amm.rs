@@ -0,0 +1,101 @@
+//! blueshift_native_amm: every instruction over arbitrary data and accounts,
+//! with a pool and the protocol config to start from
+
+#![no_main]
+
+use blueshift_client::amm::{
+    address, lock_address, pool_registry_address, protocol_config_address, pubkey, Config,
+    PoolAddresses, ProtocolConfig,
+};
+use blueshift_fuzz::{
+    state_bytes, Harness, Input, Target, MINTS, PROGRAM_ID, TOKEN_PROGRAM_ID, WALLETS,
+};
+use blueshift_native_amm::FEE_TIERS;
+use libfuzzer_sys::fuzz_target;
+use solana_pubkey::Pubkey;
+
+/// One past the highest discriminator the program dispatches
+const INSTRUCTIONS: u8 = 40;
+/// Pool seeds over the two mints the fuzzer can pick
+const SEEDS: u64 = 2;
+
+thread_local! {
+    static HARNESS: Harness = Harness::new(target());
+}
+
+fn pool(seed: u64) -> PoolAddresses {
+    PoolAddresses::find(
+        seed,
+        address(&MINTS[0]),
+        address(&MINTS[1]),
+        address(&TOKEN_PROGRAM_ID),
+    )
+}
+
+fn target() -> Target {
+    let (protocol, protocol_bump) = protocol_config_address();
+    let (mint_x, mint_y) = (address(&MINTS[0]), address(&MINTS[1]));
+    let mut keys = vec![pubkey(&protocol)];
+    for fee in FEE_TIERS {
+        keys.push(pubkey(&pool_registry_address(&mint_x, &mint_y, fee).0));
+    }
+    for seed in 0..SEEDS {
+        let pool = pool(seed);
+        let config = pubkey(&pool.config);
+        let (gauge, _) = Pubkey::find_program_address(&[b"gauge", config.as_ref()], &PROGRAM_ID);
+        keys.extend([&pool.config, &pool.mint_lp, &pool.vault_x, &pool.vault_y].map(pubkey));
+        keys.push(gauge);
+        for wallet in &WALLETS {
+            let [_, _, lp] = pool.user_atas(&address(wallet));
+            keys.push(pubkey(&lp));
+            keys.push(pubkey(&lock_address(&pool.config, &address(wallet), 0).0));
+            keys.push(
+                Pubkey::find_program_address(
+                    &[b"stake", gauge.as_ref(), wallet.as_ref()],
+                    &PROGRAM_ID,
+                )
+                .0,
+            );
+        }
+    }
+
+    // Pool 0 at a 30 bps fee holding reserves, administered by the first
+    // wallet like the protocol config
+    let authority = WALLETS[0].to_bytes();
+    let pool = pool(0);
+    let config = state_bytes(Config::LEN, |data| {
+        // Safety: `data` is `Config::LEN` bytes and `Config` has an alignment of 1
+        let config = unsafe { Config::from_bytes_unchecked_mut(data) };
+        if config
+            .set_inner(
+                0,
+                authority,
+                pool.mint_x.to_bytes(),
+                pool.mint_y.to_bytes(),
+                30,
+                [pool.config_bump],
+            )
+            .is_ok()
+        {
+            config.set_reserves(1_000_000, 1_000_000);
+            config.set_vaults(pool.vault_x.to_bytes(), pool.vault_y.to_bytes());
+        }
+    });
+    let protocol = state_bytes(ProtocolConfig::LEN, |data| {
+        // Safety: `data` is `ProtocolConfig::LEN` bytes and `ProtocolConfig` has an
+        // alignment of 1
+        let protocol = unsafe { ProtocolConfig::from_bytes_unchecked_mut(data) };
+        let _ = protocol.set_inner(authority, 1, 100, 0, [protocol_bump]);
+    });
+
+    Target {
+        so: "blueshift_native_amm/target/deploy/blueshift_native_amm.so",
+        instructions: (0..INSTRUCTIONS)
+            .map(|discriminator| vec![discriminator])
+            .collect(),
+        keys,
+        states: vec![config, protocol],
+    }
+}
+
+fuzz_target!(|input: Input| HARNESS.with(|harness| harness.run(&input)));
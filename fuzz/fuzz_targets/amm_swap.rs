@@ -0,0 +1,95 @@
+//! blueshift_native_amm: swap instruction data and pool reserves thrown at the
+//! curve and quote math
+//!
+//! Runs natively, so it covers the parsing and pricing without an SBF build.
+//! Errors are fine; a panic, an output the reserves can't cover or a swap that
+//! shrinks the pool's product is a finding.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use blueshift_native_amm::{
+    curve,
+    quote::{curve_swap, price_impact_bps, zap_swap_amount, Pool},
+    token::{MintInfo, TransferFee},
+    SwapInstructionData,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    /// Raw `Swap` instruction data, after the discriminator
+    data: Vec<u8>,
+    reserve_x: u64,
+    reserve_y: u64,
+    fee: u16,
+    lp_supply: u64,
+    fee_x: Option<(u16, u64)>,
+    fee_y: Option<(u16, u64)>,
+}
+
+/// A mint charging `basis_points` (capped at `maximum_fee`) on transfers
+fn mint(transfer_fee: Option<(u16, u64)>) -> MintInfo {
+    MintInfo {
+        supply: 0,
+        decimals: 6,
+        transfer_fee: transfer_fee.map(|(basis_points, maximum_fee)| TransferFee {
+            epoch: 0,
+            maximum_fee,
+            basis_points: basis_points % 10_001,
+        }),
+    }
+}
+
+fuzz_target!(|input: Input| {
+    // The parser must reject bad data with an error, never panic
+    let Ok(data) = SwapInstructionData::try_from(input.data.as_slice()) else {
+        return;
+    };
+    let (is_x, amount, min) = (data.is_x(), data.amount, data.min);
+    let (reserve_in, reserve_out) = match is_x {
+        true => (input.reserve_x, input.reserve_y),
+        false => (input.reserve_y, input.reserve_x),
+    };
+
+    if let Ok(result) = curve_swap(input.reserve_x, input.reserve_y, input.fee, is_x, amount, min) {
+        // The output is covered by the reserves, never drains them and meets `min`
+        assert!(result.withdraw < reserve_out);
+        assert!(result.withdraw >= min.max(1));
+        assert_eq!(result.deposit, amount);
+        assert!(result.fee <= amount);
+
+        // The product doesn't shrink, even with the fee taken out of the pool
+        if let Some(new_in) = reserve_in.checked_add(amount - result.fee) {
+            assert_eq!(
+                curve::check_product(
+                    (reserve_in, reserve_out),
+                    (new_in, reserve_out - result.withdraw)
+                ),
+                Ok(())
+            );
+        }
+
+        if let Ok(impact) = price_impact_bps(reserve_in, reserve_out, amount, result.withdraw) {
+            assert!(impact <= 10_000);
+        }
+    }
+
+    // A zap never swaps more than it was given
+    if let Ok(swap) = zap_swap_amount(reserve_in, amount, input.fee) {
+        assert!(swap <= amount);
+    }
+
+    // Quotes net of transfer fees never deliver more than the curve pays out
+    let pool = Pool {
+        reserve_x: input.reserve_x,
+        reserve_y: input.reserve_y,
+        fee: input.fee,
+        lp_supply: input.lp_supply,
+    };
+    if let Ok(outcome) = pool.swap(is_x, amount, &mint(input.fee_x), &mint(input.fee_y)) {
+        assert!(outcome.amount_in <= amount);
+        assert!(outcome.amount_out < reserve_out);
+        assert!(outcome.fee <= outcome.amount_in);
+    }
+});
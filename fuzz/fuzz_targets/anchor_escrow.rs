@@ -0,0 +1,123 @@
+//! anchor_escrow: every instruction over arbitrary data and accounts, with an
+//! escrow, an auction, the stats and the config to start from
+
+#![no_main]
+
+use anchor_lang::AccountSerialize;
+use blueshift_client::{
+    anchor_escrow::{
+        auction_address, config_address, escrow_address, pair_stats_address, program_data_address,
+        stats_address, Auction, CancelFee, Config, Escrow, EscrowStats, PairStats,
+    },
+    associated_token_address,
+};
+use blueshift_fuzz::{
+    program_data, Harness, Input, Target, MINTS, PROGRAM_ID, TOKEN_PROGRAM_ID, WALLETS,
+};
+use libfuzzer_sys::fuzz_target;
+
+/// One past the highest discriminator the program dispatches
+const INSTRUCTIONS: u8 = 10;
+/// Escrow and auction seeds per wallet the fuzzer can pick
+const SEEDS: u64 = 2;
+
+thread_local! {
+    static HARNESS: Harness = Harness::new(target());
+}
+
+fn serialize(account: &impl AccountSerialize) -> Vec<u8> {
+    let mut data = Vec::new();
+    account
+        .try_serialize(&mut data)
+        .expect("accounts serialize into a Vec");
+    data
+}
+
+fn target() -> Target {
+    let mut keys = vec![
+        config_address().0,
+        stats_address().0,
+        program_data_address(),
+        pair_stats_address(&MINTS[0], &MINTS[1]).0,
+        pair_stats_address(&MINTS[1], &MINTS[0]).0,
+    ];
+    for wallet in &WALLETS {
+        for seed in 0..SEEDS {
+            for (holder, _) in [escrow_address(wallet, seed), auction_address(wallet, seed)] {
+                keys.push(holder);
+                for mint in &MINTS {
+                    keys.push(associated_token_address(&holder, mint, &TOKEN_PROGRAM_ID));
+                }
+            }
+        }
+    }
+
+    // The first wallet's escrow 0 and auction 0 selling mint 0 for mint 1,
+    // and the config it administers with the second wallet as treasury
+    let maker = WALLETS[0];
+    let escrow = serialize(&Escrow {
+        seed: 0,
+        maker,
+        mint_a: MINTS[0],
+        mint_b: MINTS[1],
+        receive: 500,
+        bump: escrow_address(&maker, 0).1,
+        version: Escrow::VERSION,
+        expiry: 0,
+        cancel_fee: CancelFee::None,
+        memo: [0; 32],
+    });
+    let auction = serialize(&Auction {
+        seed: 0,
+        maker,
+        mint_a: MINTS[0],
+        mint_b: MINTS[1],
+        reserve: 500,
+        min_increment: 1,
+        end_time: 0,
+        highest_bidder: maker,
+        highest_bid: 0,
+        bump: auction_address(&maker, 0).1,
+    });
+    let stats = serialize(&EscrowStats {
+        fills: 0,
+        refunds: 0,
+        open_escrows: 1,
+        bump: stats_address().1,
+    });
+    let pair_stats = serialize(&PairStats {
+        mint_a: MINTS[0],
+        mint_b: MINTS[1],
+        fills: 0,
+        volume_a: 0,
+        volume_b: 0,
+        bump: pair_stats_address(&MINTS[0], &MINTS[1]).1,
+    });
+    let config = serialize(&Config {
+        authority: maker,
+        treasury: WALLETS[1],
+        max_cancel_fee_bps: 500,
+        paused: false,
+        bump: config_address().1,
+        amm_program: PROGRAM_ID,
+        max_cancel_fee_lamports: 1_000_000,
+    });
+
+    Target {
+        so: "anchor_escrow/target/deploy/anchor_escrow.so",
+        instructions: (0..INSTRUCTIONS)
+            .map(|discriminator| vec![discriminator])
+            .collect(),
+        keys,
+        states: vec![
+            escrow,
+            auction,
+            stats,
+            pair_stats,
+            config,
+            program_data(&maker),
+        ],
+    }
+}
+
+fuzz_target!(|input: Input| HARNESS.with(|harness| harness.run(&input)));
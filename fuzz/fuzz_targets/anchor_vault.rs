@@ -0,0 +1,20 @@
+//! anchor_vault: deposit and withdraw over arbitrary data and accounts
+
+#![no_main]
+
+use anchor_lang::Discriminator;
+use blueshift_anchor_vault::instruction::{Deposit, Withdraw};
+use blueshift_client::anchor_vault::vault_address;
+use blueshift_fuzz::{Harness, Input, Target, WALLETS};
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static HARNESS: Harness = Harness::new(Target {
+        so: "blueshift_anchor_vault/target/deploy/blueshift_anchor_vault.so",
+        instructions: vec![Deposit::DISCRIMINATOR.to_vec(), Withdraw::DISCRIMINATOR.to_vec()],
+        keys: WALLETS.iter().map(|wallet| vault_address(wallet).0).collect(),
+        states: Vec::new(),
+    });
+}
+
+fuzz_target!(|input: Input| HARNESS.with(|harness| harness.run(&input)));
@@ -0,0 +1,83 @@
+//! pinocchio_escrow: every instruction over arbitrary data and accounts, with
+//! escrows, the config and a maker counter to start from
+
+#![no_main]
+
+use blueshift_client::{
+    associated_token_address,
+    pinocchio_escrow::{
+        config_address, escrow_address, maker_counter_address, program_data_address, vault_address,
+        Config, Escrow, MakerCounter, NATIVE_MINT, TOKEN_PROGRAM_ID,
+    },
+};
+use blueshift_fuzz::{program_data, state_bytes, Harness, Input, Target, MINTS, WALLETS};
+use libfuzzer_sys::fuzz_target;
+
+/// One past the highest discriminator the program dispatches
+const INSTRUCTIONS: u8 = 16;
+/// Escrow seeds per wallet the fuzzer can pick
+const SEEDS: u64 = 2;
+
+thread_local! {
+    static HARNESS: Harness = Harness::new(target());
+}
+
+fn target() -> Target {
+    let (config, config_bump) = config_address();
+    // Wrapped SOL as Token B takes its own payout path
+    let mut keys = vec![config, program_data_address(), NATIVE_MINT];
+    for mint in &MINTS {
+        keys.push(associated_token_address(&config, mint, &TOKEN_PROGRAM_ID));
+    }
+    for wallet in &WALLETS {
+        keys.push(maker_counter_address(wallet).0);
+        for seed in 0..SEEDS {
+            keys.push(escrow_address(wallet, seed).0);
+            for mint in &MINTS {
+                keys.push(vault_address(wallet, seed, mint, &TOKEN_PROGRAM_ID));
+            }
+        }
+    }
+
+    // The first wallet's escrow 0 trading mint 0 for mint 1, the config it
+    // administers and its maker counter listing the escrow
+    let maker = WALLETS[0];
+    let escrow = state_bytes(Escrow::LEN, |data| {
+        if let Ok(escrow) = Escrow::load_uninit_mut(data) {
+            escrow.set_inner(
+                0,
+                maker.to_bytes(),
+                MINTS[0].to_bytes(),
+                MINTS[1].to_bytes(),
+                500,
+                1_000,
+                0,
+                [0; 32],
+                [0; 32],
+                [escrow_address(&maker, 0).1],
+            );
+        }
+    });
+    let config = state_bytes(Config::LEN, |data| {
+        if let Ok(config) = Config::load_uninit_mut(data) {
+            config.set_inner(100, 0, maker.to_bytes(), [config_bump]);
+        }
+    });
+    let counter = state_bytes(MakerCounter::LEN, |data| {
+        if let Ok(counter) = MakerCounter::load_uninit_mut(data) {
+            counter.set_inner([maker_counter_address(&maker).1]);
+            counter.record_open(0);
+        }
+    });
+
+    Target {
+        so: "pinocchio_escrow/target/deploy/pinocchio_escrow.so",
+        instructions: (0..INSTRUCTIONS)
+            .map(|discriminator| vec![discriminator])
+            .collect(),
+        keys,
+        states: vec![escrow, config, counter, program_data(&maker)],
+    }
+}
+
+fuzz_target!(|input: Input| HARNESS.with(|harness| harness.run(&input)));
@@ -0,0 +1,117 @@
+//! pinocchio_escrow: instruction data parsers and the raw-pointer state
+//! loaders, fed arbitrary bytes
+//!
+//! Runs natively, so it covers the parsing and settlement math without an SBF build.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use pinocchio_escrow::{
+    state::{Config, Escrow, MakerCounter},
+    InitializeConfigInstructionData, MakeInstructionData, ReduceDepositInstructionData,
+    TakeInstructionData, TransferEscrowInstructionData, UpdateReceiveInstructionData,
+};
+
+#[derive(Arbitrary, Debug)]
+enum Input {
+    InstructionData {
+        discriminator: u8,
+        data: Vec<u8>,
+    },
+    Escrow {
+        data: Vec<u8>,
+        amount: u64,
+        now: i64,
+        taker: [u8; 32],
+    },
+    Config {
+        data: Vec<u8>,
+        amount: u64,
+    },
+    MakerCounter {
+        data: Vec<u8>,
+        ops: Vec<(bool, u64)>,
+    },
+}
+
+/// Copy `data` into 8-byte aligned storage, as the runtime lays out account data
+fn aligned(data: &[u8]) -> Vec<u64> {
+    let mut buf = vec![0u64; data.len().div_ceil(8)];
+    bytes_mut(&mut buf, data.len()).copy_from_slice(data);
+    buf
+}
+
+fn bytes_mut(buf: &mut [u64], len: usize) -> &mut [u8] {
+    assert!(len <= buf.len() * 8);
+    // Safety: `buf` owns at least `len` initialized bytes
+    unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, len) }
+}
+
+fuzz_target!(|input: Input| match input {
+    Input::InstructionData { discriminator, data } => {
+        // Parsers must reject bad data with an error, never panic
+        let _ = match discriminator % 6 {
+            0 => MakeInstructionData::try_from(data.as_slice()).map(|_| ()),
+            1 => TakeInstructionData::try_from(data.as_slice()).map(|_| ()),
+            2 => InitializeConfigInstructionData::try_from(data.as_slice()).map(|_| ()),
+            3 => UpdateReceiveInstructionData::try_from(data.as_slice()).map(|_| ()),
+            4 => ReduceDepositInstructionData::try_from(data.as_slice()).map(|_| ()),
+            _ => TransferEscrowInstructionData::try_from(data.as_slice()).map(|_| ()),
+        };
+    }
+    Input::Escrow {
+        data,
+        amount,
+        now,
+        taker,
+    } => {
+        let mut buf = aligned(&data);
+        let Ok(escrow) = Escrow::load_mut(bytes_mut(&mut buf, data.len())) else {
+            return;
+        };
+
+        let _ = escrow.is_expired(now);
+        let _ = escrow.can_take(&taker);
+
+        // A fill never owes more than the rest of the escrow
+        if let Ok(receive) = escrow.receive_for(amount) {
+            assert!(receive <= escrow.receive());
+            escrow.set_remaining(escrow.remaining() - amount);
+            escrow.set_receive(escrow.receive() - receive);
+        }
+
+        // Shrinking the deposit never raises receive or empties it
+        if let Ok(receive) = escrow.receive_after_reduce(amount) {
+            assert!(receive <= escrow.receive());
+            assert!(escrow.receive() == 0 || receive > 0);
+        }
+    }
+    Input::Config { data, amount } => {
+        let mut buf = aligned(&data);
+        let Ok(config) = Config::load(bytes_mut(&mut buf, data.len())) else {
+            return;
+        };
+
+        // The protocol fee is never more than the amount it is taken from,
+        // nor the referral more than the fee
+        let fee = config.fee_for(amount);
+        assert!(fee <= amount);
+        assert!(config.referral_for(fee) <= fee);
+    }
+    Input::MakerCounter { data, ops } => {
+        let mut buf = aligned(&data);
+        let Ok(counter) = MakerCounter::load_mut(bytes_mut(&mut buf, data.len())) else {
+            return;
+        };
+
+        for (open, seed) in ops {
+            if open {
+                counter.record_open(seed);
+            } else {
+                counter.record_close(seed);
+            }
+            assert!(counter.len as usize <= MakerCounter::MAX_SEEDS);
+        }
+    }
+});
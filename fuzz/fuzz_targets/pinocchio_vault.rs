@@ -0,0 +1,18 @@
+//! pinocchio_vault: deposit and withdraw over arbitrary data and accounts
+
+#![no_main]
+
+use blueshift_client::pinocchio_vault::vault_address;
+use blueshift_fuzz::{Harness, Input, Target, WALLETS};
+use libfuzzer_sys::fuzz_target;
+
+thread_local! {
+    static HARNESS: Harness = Harness::new(Target {
+        so: "pinocchio_vault/target/deploy/blueshift_vault.so",
+        instructions: (0..2).map(|discriminator| vec![discriminator]).collect(),
+        keys: WALLETS.iter().map(|wallet| vault_address(wallet).0).collect(),
+        states: Vec::new(),
+    });
+}
+
+fuzz_target!(|input: Input| HARNESS.with(|harness| harness.run(&input)));
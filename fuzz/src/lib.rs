@@ -0,0 +1,284 @@
+//! Arbitrary instructions over shuffled and repeated accounts, run through
+//! each program's SBF build in mollusk
+//!
+//! Every pinocchio program reads its packed instruction structs and account
+//! state through unchecked pointer casts, so each target throws arbitrary
+//! instruction data at the program's `process_instruction` with account lists
+//! in any order, with any account passed any number of times. Any error
+//! result is fine; a panic or memory violation (`ProgramFailedToComplete`) is
+//! a finding.
+//!
+//! A target is a [`Target`]: where the program's build puts its SBF binary,
+//! the data prefix selecting each instruction, the addresses the program
+//! derives and well-formed accounts of its own. The programs have to be built
+//! first, as for the benchmark.
+
+use arbitrary::Arbitrary;
+use blueshift_client::associated_token_address;
+use blueshift_test_support::{mint_data, token_account_data};
+use mollusk_svm::{
+    program::{keyed_account_for_system_program, loader_keys::LOADER_V3},
+    Mollusk,
+};
+use mollusk_svm_programs_token::{associated_token, token};
+use solana_account::Account;
+use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+pub use blueshift_test_support::{
+    program_data, ASSOCIATED_TOKEN_PROGRAM_ID, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+
+/// Wallets the fuzzer can pick as makers, users and authorities
+pub const WALLETS: [Pubkey; 3] = [
+    Pubkey::new_from_array([1; 32]),
+    Pubkey::new_from_array([2; 32]),
+    Pubkey::new_from_array([3; 32]),
+];
+
+/// Mints the fuzzer can pick, in the AMM's canonical order
+pub const MINTS: [Pubkey; 2] = [
+    Pubkey::new_from_array([4; 32]),
+    Pubkey::new_from_array([5; 32]),
+];
+
+/// One program under fuzzing
+pub struct Target {
+    /// SBF binary, relative to the repository root
+    pub so: &'static str,
+    /// Data prefix selecting each instruction
+    pub instructions: Vec<Vec<u8>>,
+    /// Addresses the program derives, on top of the wallets, mints, their
+    /// associated token accounts and the programs every target knows
+    pub keys: Vec<Pubkey>,
+    /// Well-formed accounts owned by the program
+    pub states: Vec<Vec<u8>>,
+}
+
+/// One instruction and the accounts it runs against
+#[derive(Arbitrary, Debug)]
+pub struct Input {
+    /// Picks one of the target's instructions, or `data` with no prefix
+    instruction: u8,
+    data: Vec<u8>,
+    accounts: Vec<FuzzAccount>,
+    /// The instruction's account list, as indices into `accounts`: any
+    /// order, any account any number of times
+    metas: Vec<FuzzMeta>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzAccount {
+    key: KeyChoice,
+    owner: OwnerChoice,
+    lamports: u64,
+    data: DataChoice,
+}
+
+#[derive(Arbitrary, Debug)]
+struct FuzzMeta {
+    account: u8,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// Addresses the target knows, so checks get past the PDA and associated
+/// token account comparisons, or any other
+#[derive(Arbitrary, Debug)]
+enum KeyChoice {
+    Known(u8),
+    Raw([u8; 32]),
+}
+
+#[derive(Arbitrary, Debug)]
+enum OwnerChoice {
+    Program,
+    System,
+    Token,
+    Loader,
+    Raw([u8; 32]),
+}
+
+/// Well-formed accounts with fuzzed fields, or arbitrary bytes
+#[derive(Arbitrary, Debug)]
+enum DataChoice {
+    Raw(Vec<u8>),
+    Mint {
+        supply: u64,
+        decimals: u8,
+        authority: KeyChoice,
+    },
+    TokenAccount {
+        mint: KeyChoice,
+        owner: KeyChoice,
+        amount: u64,
+    },
+    /// One of the target's states with single bytes overwritten
+    State {
+        index: u8,
+        patch: Vec<(u16, u8)>,
+    },
+}
+
+/// A target's program loaded into mollusk, and what the fuzzer picks from
+pub struct Harness {
+    mollusk: Mollusk,
+    instructions: Vec<Vec<u8>>,
+    keys: Vec<Pubkey>,
+    states: Vec<Vec<u8>>,
+}
+
+impl Harness {
+    /// Load `target`'s SBF binary at `PROGRAM_ID`, next to SPL Token and the
+    /// Associated Token program
+    pub fn new(target: Target) -> Self {
+        let path = format!("{}/../{}", env!("CARGO_MANIFEST_DIR"), target.so);
+        let elf =
+            std::fs::read(&path).unwrap_or_else(|e| panic!("{path}: {e}; build the program first"));
+
+        let mut mollusk = Mollusk::default();
+        mollusk.add_program_with_elf_and_loader(&PROGRAM_ID, &elf, &LOADER_V3);
+        token::add_program(&mut mollusk);
+        associated_token::add_program(&mut mollusk);
+
+        let mut keys = vec![
+            PROGRAM_ID,
+            SYSTEM_PROGRAM_ID,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+        ];
+        keys.extend(WALLETS);
+        keys.extend(MINTS);
+        for wallet in &WALLETS {
+            for mint in &MINTS {
+                keys.push(associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID));
+            }
+        }
+        keys.extend(target.keys);
+
+        Self {
+            mollusk,
+            instructions: target.instructions,
+            keys,
+            states: target.states,
+        }
+    }
+
+    /// Run `input` and fail on anything but a clean success or error
+    pub fn run(&self, input: &Input) {
+        let mut accounts: Vec<(Pubkey, Account)> = Vec::with_capacity(input.accounts.len() + 3);
+        for account in &input.accounts {
+            let key = self.key(&account.key);
+            // A key picked twice keeps its first account
+            if accounts.iter().all(|(k, _)| k != &key) {
+                accounts.push((
+                    key,
+                    Account {
+                        lamports: account.lamports,
+                        data: self.data(&account.data),
+                        owner: owner(&account.owner),
+                        executable: false,
+                        rent_epoch: 0,
+                    },
+                ));
+            }
+        }
+
+        let metas = if accounts.is_empty() {
+            Vec::new()
+        } else {
+            input
+                .metas
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: accounts[meta.account as usize % accounts.len()].0,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect()
+        };
+
+        // The programs have to be loadable for CPIs
+        for program in [
+            keyed_account_for_system_program(),
+            token::keyed_account(),
+            associated_token::keyed_account(),
+        ] {
+            if accounts.iter().all(|(k, _)| k != &program.0) {
+                accounts.push(program);
+            }
+        }
+
+        let choice = input.instruction as usize % (self.instructions.len() + 1);
+        let mut data = self.instructions.get(choice).cloned().unwrap_or_default();
+        data.extend_from_slice(&input.data);
+
+        let instruction = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: metas,
+            data,
+        };
+
+        let result = self.mollusk.process_instruction(&instruction, &accounts);
+        assert_ne!(
+            result.raw_result,
+            Err(InstructionError::ProgramFailedToComplete),
+            "program panicked or faulted on {input:?}"
+        );
+    }
+
+    fn key(&self, choice: &KeyChoice) -> Pubkey {
+        match *choice {
+            KeyChoice::Known(i) => self.keys[i as usize % self.keys.len()],
+            KeyChoice::Raw(bytes) => Pubkey::new_from_array(bytes),
+        }
+    }
+
+    fn data(&self, choice: &DataChoice) -> Vec<u8> {
+        match choice {
+            DataChoice::Raw(bytes) => bytes.clone(),
+            DataChoice::Mint {
+                supply,
+                decimals,
+                authority,
+            } => mint_data(&self.key(authority), *supply, *decimals),
+            DataChoice::TokenAccount {
+                mint,
+                owner,
+                amount,
+            } => token_account_data(&self.key(mint), &self.key(owner), *amount),
+            DataChoice::State { index, patch } => {
+                let Some(state) = self.states.get(*index as usize) else {
+                    return Vec::new();
+                };
+                let mut data = state.clone();
+                for &(offset, byte) in patch {
+                    if let Some(slot) = data.get_mut(offset as usize) {
+                        *slot = byte;
+                    }
+                }
+                data
+            }
+        }
+    }
+}
+
+fn owner(choice: &OwnerChoice) -> Pubkey {
+    match *choice {
+        OwnerChoice::Program => PROGRAM_ID,
+        OwnerChoice::System => SYSTEM_PROGRAM_ID,
+        OwnerChoice::Token => TOKEN_PROGRAM_ID,
+        OwnerChoice::Loader => LOADER_V3,
+        OwnerChoice::Raw(bytes) => Pubkey::new_from_array(bytes),
+    }
+}
+
+/// `init` applied to `len` zeroed bytes on 8-byte aligned storage like the
+/// runtime provides, for state built through a program's own setters
+pub fn state_bytes(len: usize, init: impl FnOnce(&mut [u8])) -> Vec<u8> {
+    let mut buf = vec![0u64; len.div_ceil(8)];
+    // Safety: `buf` owns at least `len` zeroed bytes
+    let bytes = unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, len) };
+    init(bytes);
+    bytes.to_vec()
+}
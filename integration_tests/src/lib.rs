@@ -0,0 +1,384 @@
+//! End-to-end harness running the challenge programs' SBF builds together in
+//! LiteSVM, for scenarios that cross program boundaries: lamports parked in a
+//! vault while an escrow is made, Token B bought on the AMM to take it, and so
+//! on. Per-program tests run one program against hand-built accounts; here
+//! each step's accounts are whatever the previous program left behind.
+//!
+//! All five programs are deployed at the challenge's program ID, so only one
+//! can be loaded at a time. [`Harness::send`] deploys the program a
+//! transaction is for at that address before sending it, leaving every
+//! account in place. Programs that share a PDA seed also share the PDA (the
+//! two escrows' `config`, the two vaults' `vault`), so a scenario sticks to
+//! one vault and one escrow.
+//!
+//! The exception is the AMM's `standalone-id` build, which
+//! [`Harness::load_standalone_amm`] deploys at its own address so
+//! `anchor_escrow`'s `take_via_amm` has a pool to route through.
+//!
+//! Build the programs first (`cargo build-sbf` in `pinocchio_vault`,
+//! `pinocchio_escrow` and `blueshift_native_amm`, plus `cargo build-sbf
+//! --features standalone-id --sbf-out-dir target/deploy/standalone` in
+//! `blueshift_native_amm`, `anchor build` in `blueshift_anchor_vault` and
+//! `anchor_escrow`), then `cargo test`.
+
+use std::path::{Path, PathBuf};
+
+use blueshift_client::{amm, associated_token_address, Instruction, Pubkey};
+use blueshift_test_support as fixtures;
+use litesvm::{types::TransactionResult, LiteSVM};
+use solana_account::Account;
+use solana_challenge_common::{ids, seeds::POOL_CONFIG_SEED};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+pub use blueshift_test_support::{LAMPORTS, PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+/// Where [`Harness::load_standalone_amm`] deploys the AMM's `standalone-id`
+/// build
+pub const AMM_STANDALONE_ID: Pubkey = Pubkey::new_from_array(ids::AMM_STANDALONE_PROGRAM_ID);
+
+/// The AMM's `standalone-id` build, relative to the repository root
+const AMM_STANDALONE_SO: &str =
+    "blueshift_native_amm/target/deploy/standalone/blueshift_native_amm.so";
+
+/// LiteSVM's fee for a transaction with one signature
+pub const SIGNATURE_FEE: u64 = 5_000;
+
+/// Decimals of every mint the harness creates
+pub const DECIMALS: u8 = 6;
+
+/// Seed of every pool [`Harness::create_pool`] creates
+pub const POOL_SEED: u64 = 1;
+
+/// Swap fee in basis points of every pool [`Harness::create_pool`] creates
+pub const POOL_FEE: u16 = 30;
+
+/// A challenge program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Program {
+    AnchorVault,
+    AnchorEscrow,
+    PinocchioVault,
+    PinocchioEscrow,
+    Amm,
+}
+
+impl Program {
+    /// Where the program's build puts its SBF binary
+    pub fn so_path(self) -> PathBuf {
+        let relative = match self {
+            Program::AnchorVault => {
+                "blueshift_anchor_vault/target/deploy/blueshift_anchor_vault.so"
+            }
+            Program::AnchorEscrow => "anchor_escrow/target/deploy/anchor_escrow.so",
+            Program::PinocchioVault => "pinocchio_vault/target/deploy/blueshift_vault.so",
+            Program::PinocchioEscrow => "pinocchio_escrow/target/deploy/pinocchio_escrow.so",
+            Program::Amm => "blueshift_native_amm/target/deploy/blueshift_native_amm.so",
+        };
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(relative)
+    }
+}
+
+/// A LiteSVM with the SPL Token and Associated Token programs, and at most
+/// one challenge program, loaded
+pub struct Harness {
+    pub svm: LiteSVM,
+    loaded: Option<Program>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self {
+            svm: LiteSVM::new(),
+            loaded: None,
+        }
+    }
+
+    /// Deploy `program` at the challenge's program ID, replacing whichever
+    /// program was there
+    pub fn load(&mut self, program: Program) {
+        if self.loaded == Some(program) {
+            return;
+        }
+
+        let path = program.so_path();
+        let bytes = std::fs::read(&path)
+            .unwrap_or_else(|e| panic!("{}: {e} (build {program:?} first)", path.display()));
+        self.svm.add_program(PROGRAM_ID, &bytes);
+        self.loaded = Some(program);
+    }
+
+    /// Deploy the AMM's `standalone-id` build at `AMM_STANDALONE_ID`, where it
+    /// stays next to whichever program is at the challenge's ID
+    pub fn load_standalone_amm(&mut self) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(AMM_STANDALONE_SO);
+        let bytes = std::fs::read(&path).unwrap_or_else(|e| {
+            panic!(
+                "{}: {e} (build blueshift_native_amm with --features standalone-id first)",
+                path.display()
+            )
+        });
+        self.svm.add_program(AMM_STANDALONE_ID, &bytes);
+    }
+
+    /// Send `instruction` to `program` in a transaction paid for by the first
+    /// of `signers`
+    pub fn send(
+        &mut self,
+        program: Program,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> TransactionResult {
+        self.load(program);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signers[0].pubkey()),
+            signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx);
+        // Let the same instruction be sent again as a new transaction
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// Send an AMM builder's `instruction` to the AMM
+    pub fn send_amm(
+        &mut self,
+        instruction: amm::Instruction,
+        signers: &[&Keypair],
+    ) -> TransactionResult {
+        self.send(Program::Amm, amm::instruction(instruction), signers)
+    }
+
+    // ==================== Accounts ====================
+
+    /// A new wallet holding `LAMPORTS`
+    pub fn wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.svm.airdrop(&wallet.pubkey(), LAMPORTS).unwrap();
+        wallet
+    }
+
+    pub fn set(&mut self, address: Pubkey, account: Account) {
+        self.svm.set_account(address, account).unwrap();
+    }
+
+    /// `data`, owned by `owner` and holding its rent-exempt minimum
+    pub fn rent_exempt(&self, data: Vec<u8>, owner: &Pubkey) -> Account {
+        fixtures::rent_exempt(data, owner)
+    }
+
+    /// Lamports held at `address`, 0 if there is no account
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm
+            .get_account(address)
+            .map_or(0, |account| account.lamports)
+    }
+
+    /// Data of the account at `address`
+    pub fn data(&self, address: &Pubkey) -> Vec<u8> {
+        self.svm.get_account(address).unwrap().data
+    }
+
+    /// A new initialized mint with no freeze authority
+    pub fn mint(&mut self) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        self.set(mint, fixtures::mint(DECIMALS));
+        mint
+    }
+
+    /// Put an initialized token account holding `amount` at `address`, with
+    /// no delegate or close authority
+    pub fn token_account(&mut self, address: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        self.set(address, fixtures::token_account(mint, owner, amount));
+    }
+
+    /// `wallet`'s associated token account for `mint`
+    pub fn ata(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Give `wallet` an associated token account for `mint` holding `amount`
+    pub fn fund(&mut self, wallet: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let ata = self.ata(wallet, mint);
+        self.token_account(ata, mint, wallet, amount);
+        ata
+    }
+
+    /// Token balance of the token account at `address`, 0 if there is none
+    pub fn balance(&self, address: &Pubkey) -> u64 {
+        self.svm
+            .get_account(address)
+            .map_or(0, |account| fixtures::token_balance(&account.data))
+    }
+
+    // ==================== AMM ====================
+
+    /// Create an immutable pool of `mint_a` and `mint_b` at `POOL_FEE`, and
+    /// have `provider` deposit `reserve` of each, for as much LP
+    ///
+    /// The first pool also creates the protocol config, with `provider` in
+    /// charge of it.
+    pub fn create_pool(
+        &mut self,
+        provider: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        reserve: u64,
+    ) -> amm::PoolAddresses {
+        // Pools take their mints in canonical order
+        let (mint_x, mint_y) = match mint_a < mint_b {
+            true => (mint_a, mint_b),
+            false => (mint_b, mint_a),
+        };
+        let pool = amm::PoolAddresses::find(
+            POOL_SEED,
+            amm::address(mint_x),
+            amm::address(mint_y),
+            amm::address(&TOKEN_PROGRAM_ID),
+        );
+        let config = amm::pubkey(&pool.config);
+        self.token_account(amm::pubkey(&pool.vault_x), mint_x, &config, 0);
+        self.token_account(amm::pubkey(&pool.vault_y), mint_y, &config, 0);
+
+        let owner = amm::address(&provider.pubkey());
+        if self
+            .svm
+            .get_account(&amm::pubkey(&amm::protocol_config_address().0))
+            .is_none()
+        {
+            let ix = amm::initialize_protocol_ix(owner, POOL_FEE, POOL_FEE, 0);
+            self.send_amm(ix, &[provider]).unwrap();
+        }
+        let ix = amm::initialize_ix(owner, &pool, POOL_SEED, POOL_FEE, None);
+        self.send_amm(ix, &[provider]).unwrap();
+
+        let [_, _, lp_ata] = pool.user_atas(&owner);
+        self.fund(&provider.pubkey(), mint_x, reserve);
+        self.fund(&provider.pubkey(), mint_y, reserve);
+        self.token_account(
+            amm::pubkey(&lp_ata),
+            &amm::pubkey(&pool.mint_lp),
+            &provider.pubkey(),
+            0,
+        );
+        let ix = amm::deposit_ix(owner, &pool, reserve, reserve, reserve, i64::MAX);
+        self.send_amm(ix, &[provider]).unwrap();
+
+        pool
+    }
+
+    /// Have `user` sell `amount` of `mint_in` on `pool` for at least `min` of
+    /// the other token, into their associated token accounts
+    pub fn swap(
+        &mut self,
+        user: &Keypair,
+        pool: &amm::PoolAddresses,
+        mint_in: &Pubkey,
+        amount: u64,
+        min: u64,
+    ) -> TransactionResult {
+        let is_x = amm::address(mint_in) == pool.mint_x;
+        let ix = amm::swap_ix(
+            amm::address(&user.pubkey()),
+            pool,
+            is_x,
+            amount,
+            min,
+            i64::MAX,
+            None,
+            None,
+        );
+        self.send_amm(ix, &[user])
+    }
+
+    /// Reserves the pool's config records
+    pub fn reserves(&self, pool: &amm::PoolAddresses) -> (u64, u64) {
+        let data = self.data(&amm::pubkey(&pool.config));
+        let config = amm::config(&data).unwrap();
+        (config.reserve_x(), config.reserve_y())
+    }
+
+    /// Put an immutable pool of `mint_a` and `mint_b` at `POOL_FEE` holding
+    /// `reserve` of each in place for the standalone AMM; returns its config
+    /// and its X and Y vaults
+    ///
+    /// The builders derive the pool under the challenge's ID, so the pool is
+    /// written as `Initialize` and `Deposit` would leave it instead.
+    pub fn standalone_pool(
+        &mut self,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        reserve: u64,
+    ) -> (Pubkey, Pubkey, Pubkey) {
+        let (mint_x, mint_y) = match mint_a < mint_b {
+            true => (mint_a, mint_b),
+            false => (mint_b, mint_a),
+        };
+        let (config, bump) = Pubkey::find_program_address(
+            &[
+                POOL_CONFIG_SEED,
+                &POOL_SEED.to_le_bytes(),
+                mint_x.as_ref(),
+                mint_y.as_ref(),
+            ],
+            &AMM_STANDALONE_ID,
+        );
+        let vault_x = self.ata(&config, mint_x);
+        let vault_y = self.ata(&config, mint_y);
+        self.token_account(vault_x, mint_x, &config, reserve);
+        self.token_account(vault_y, mint_y, &config, reserve);
+
+        let mut data = vec![0; amm::Config::LEN];
+        // Safety: `data` is `Config::LEN` bytes and `Config` has an alignment of 1
+        let pool = unsafe { amm::Config::from_bytes_unchecked_mut(&mut data) };
+        pool.set_inner(
+            POOL_SEED,
+            [0; 32],
+            mint_x.to_bytes(),
+            mint_y.to_bytes(),
+            POOL_FEE,
+            [bump],
+        )
+        .unwrap();
+        pool.set_reserves(reserve, reserve);
+        pool.set_vaults(vault_x.to_bytes(), vault_y.to_bytes());
+        let account = self.rent_exempt(data, &AMM_STANDALONE_ID);
+        self.set(config, account);
+
+        (config, vault_x, vault_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueshift_client::{anchor_escrow, anchor_vault, pinocchio_escrow, pinocchio_vault};
+
+    #[test]
+    fn programs_share_the_challenge_id() {
+        // If one gets its own ID, it can stay loaded next to the others
+        for id in [
+            anchor_vault::PROGRAM_ID,
+            anchor_escrow::PROGRAM_ID,
+            pinocchio_vault::PROGRAM_ID,
+            pinocchio_escrow::PROGRAM_ID,
+            amm::PROGRAM_ID,
+        ] {
+            assert_eq!(id, PROGRAM_ID);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Vault deposit → escrow make → AMM swap → take, once through the pinocchio
+//! programs and once through the Anchor ones, with the AMM in both; and an
+//! Anchor escrow taken through the AMM in one step
+//!
+//! Needs every program built first; see the crate docs.
+
+use anchor_lang::AccountSerialize;
+use blueshift_client::{
+    amm, anchor_escrow, anchor_vault, pinocchio_escrow, pinocchio_vault, Pubkey,
+};
+use blueshift_integration_tests::{Harness, Program, AMM_STANDALONE_ID, PROGRAM_ID, SIGNATURE_FEE};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+/// Lamports the maker parks in their vault for the whole scenario
+const VAULT_DEPOSIT: u64 = 1_000_000_000;
+const SEED: u64 = 7;
+/// Token A the maker deposits
+const DEPOSIT: u64 = 1_000;
+/// Token B the maker asks for the whole deposit
+const RECEIVE: u64 = 500;
+/// pinocchio_escrow's protocol fee on Token B
+const FEE_BPS: u16 = 100;
+/// X and Y in the pool, deep enough that a small swap trades near 1:1
+const RESERVE: u64 = 1_000_000_000;
+/// Token A the taker sells on the AMM for the Token B to take with
+const SWAP_IN: u64 = 1_000;
+
+/// Everyone in a scenario, and the two tokens
+struct Scenario {
+    harness: Harness,
+    maker: Keypair,
+    taker: Keypair,
+    provider: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+impl Scenario {
+    /// A maker holding `DEPOSIT` Token A and a taker holding `SWAP_IN` Token A
+    /// and an empty Token B account
+    fn new() -> Self {
+        let mut harness = Harness::new();
+        let (maker, taker, provider) = (harness.wallet(), harness.wallet(), harness.wallet());
+        let (mint_a, mint_b) = (harness.mint(), harness.mint());
+
+        harness.fund(&maker.pubkey(), &mint_a, DEPOSIT);
+        harness.fund(&taker.pubkey(), &mint_a, SWAP_IN);
+        harness.fund(&taker.pubkey(), &mint_b, 0);
+
+        Self {
+            harness,
+            maker,
+            taker,
+            provider,
+            mint_a,
+            mint_b,
+        }
+    }
+
+    fn balance(&self, wallet: &Keypair, mint: &Pubkey) -> u64 {
+        let h = &self.harness;
+        h.balance(&h.ata(&wallet.pubkey(), mint))
+    }
+
+    /// Put anchor_escrow's config in place directly, as for pinocchio_escrow,
+    /// with `amm_program` as the AMM `take_via_amm` routes through
+    fn anchor_escrow_config(&mut self, amm_program: Pubkey) {
+        let (config, bump) = anchor_escrow::config_address();
+        let mut data = Vec::with_capacity(anchor_escrow::Config::SPACE);
+        anchor_escrow::Config {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            max_cancel_fee_bps: 0,
+            paused: false,
+            bump,
+            amm_program,
+            max_cancel_fee_lamports: 0,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        data.resize(anchor_escrow::Config::SPACE, 0);
+        let account = self.harness.rent_exempt(data, &PROGRAM_ID);
+        self.harness.set(config, account);
+    }
+
+    /// Have the maker offer their whole `DEPOSIT` of Token A for `RECEIVE`
+    /// Token B on anchor_escrow; returns the escrow
+    fn anchor_make(&mut self) -> Pubkey {
+        let maker = self.maker.pubkey();
+        let args = anchor_escrow::MakeArgs {
+            seed: SEED,
+            receive: RECEIVE,
+            amount: DEPOSIT,
+            reject_freezable: false,
+            expiry: 0,
+            cancel_fee: anchor_escrow::CancelFee::None,
+            memo: [0; 32],
+        };
+        let ix = anchor_escrow::make_ix(&maker, &self.mint_a, &self.mint_b, &args);
+        self.harness
+            .send(Program::AnchorEscrow, ix, &[&self.maker])
+            .unwrap();
+        let (escrow, _) = anchor_escrow::escrow_address(&maker, SEED);
+        let data = self.harness.data(&escrow);
+        assert_eq!(anchor_escrow::escrow(&data).unwrap().receive, RECEIVE);
+        escrow
+    }
+
+    /// Have the taker buy Token B for all their Token A, asking for at least
+    /// `RECEIVE`, and check the pool's books; returns what they bought
+    fn buy_token_b(&mut self) -> u64 {
+        let h = &mut self.harness;
+        let pool = h.create_pool(&self.provider, &self.mint_a, &self.mint_b, RESERVE);
+        h.swap(&self.taker, &pool, &self.mint_a, SWAP_IN, RECEIVE)
+            .unwrap();
+
+        // The pool's recorded reserves are what its vaults hold
+        let vaults = (
+            h.balance(&amm::pubkey(&pool.vault_x)),
+            h.balance(&amm::pubkey(&pool.vault_y)),
+        );
+        assert_eq!(h.reserves(&pool), vaults);
+
+        assert_eq!(self.balance(&self.taker, &self.mint_a), 0);
+        let bought = self.balance(&self.taker, &self.mint_b);
+        assert!(bought >= RECEIVE);
+        bought
+    }
+}
+
+#[test]
+fn pinocchio_vault_escrow_amm_take() {
+    let mut s = Scenario::new();
+    let maker = s.maker.pubkey();
+    let taker = s.taker.pubkey();
+
+    // The maker parks lamports in their vault
+    let ix = pinocchio_vault::deposit_ix(&maker, VAULT_DEPOSIT);
+    s.harness
+        .send(Program::PinocchioVault, ix, &[&s.maker])
+        .unwrap();
+    let (vault, _) = pinocchio_vault::vault_address(&maker);
+    assert_eq!(s.harness.lamports(&vault), VAULT_DEPOSIT);
+
+    // ... and offers their Token A for Token B. `InitializeConfig` wants the
+    // upgrade authority, which a LiteSVM deployment has none of, so the
+    // config is put in place directly.
+    let (config, bump) = pinocchio_escrow::config_address();
+    let mut data = vec![0; pinocchio_escrow::Config::LEN];
+    pinocchio_escrow::Config::load_uninit_mut(&mut data)
+        .unwrap()
+        .set_inner(FEE_BPS, 0, Pubkey::new_unique().to_bytes(), [bump]);
+    let account = s.harness.rent_exempt(data, &PROGRAM_ID);
+    s.harness.set(config, account);
+
+    let args = pinocchio_escrow::MakeArgs {
+        seed: SEED,
+        receive: RECEIVE,
+        amount: DEPOSIT,
+        expiry: 0,
+        taker: None,
+        hashlock: None,
+        reject_freezable: false,
+    };
+    let token_program = pinocchio_escrow::TOKEN_PROGRAM_ID;
+    let ix = pinocchio_escrow::make_ix(&maker, &s.mint_a, &s.mint_b, &token_program, &args, None);
+    s.harness
+        .send(Program::PinocchioEscrow, ix, &[&s.maker])
+        .unwrap();
+    let (escrow, _) = pinocchio_escrow::escrow_address(&maker, SEED);
+    let data = s.harness.data(&escrow);
+    assert_eq!(
+        pinocchio_escrow::escrow(&data).unwrap().remaining(),
+        DEPOSIT
+    );
+
+    // The taker buys the Token B on the AMM and takes the whole escrow
+    let bought = s.buy_token_b();
+    let ix = pinocchio_escrow::take_ix(
+        &taker,
+        &maker,
+        &s.mint_a,
+        &s.mint_b,
+        &token_program,
+        &maker,
+        SEED,
+        DEPOSIT,
+        None,
+        None,
+        None,
+    );
+    s.harness
+        .send(Program::PinocchioEscrow, ix, &[&s.taker])
+        .unwrap();
+
+    let fee = RECEIVE * FEE_BPS as u64 / 10_000;
+    assert_eq!(s.balance(&s.taker, &s.mint_a), DEPOSIT);
+    assert_eq!(s.balance(&s.taker, &s.mint_b), bought - RECEIVE);
+    assert_eq!(s.balance(&s.maker, &s.mint_a), 0);
+    assert_eq!(s.balance(&s.maker, &s.mint_b), RECEIVE - fee);
+    assert_eq!(s.harness.balance(&s.harness.ata(&config, &s.mint_b)), fee);
+    assert_eq!(s.harness.lamports(&escrow), 0);
+
+    // The vault sat through the other programs untouched, and pays out in full
+    assert_eq!(s.harness.lamports(&vault), VAULT_DEPOSIT);
+    let before = s.harness.lamports(&maker);
+    let ix = pinocchio_vault::withdraw_ix(&maker);
+    s.harness
+        .send(Program::PinocchioVault, ix, &[&s.maker])
+        .unwrap();
+    assert_eq!(s.harness.lamports(&vault), 0);
+    assert_eq!(
+        s.harness.lamports(&maker),
+        before + VAULT_DEPOSIT - SIGNATURE_FEE
+    );
+}
+
+#[test]
+fn anchor_vault_escrow_amm_take() {
+    let mut s = Scenario::new();
+    let maker = s.maker.pubkey();
+    let taker = s.taker.pubkey();
+
+    // The maker parks lamports in their vault
+    let ix = anchor_vault::deposit_ix(&maker, VAULT_DEPOSIT);
+    s.harness
+        .send(Program::AnchorVault, ix, &[&s.maker])
+        .unwrap();
+    let (vault, _) = anchor_vault::vault_address(&maker);
+    assert_eq!(s.harness.lamports(&vault), VAULT_DEPOSIT);
+
+    // ... and offers their Token A for Token B
+    s.anchor_escrow_config(Pubkey::new_unique());
+    let escrow = s.anchor_make();
+
+    // The taker buys the Token B on the AMM and takes the escrow
+    let bought = s.buy_token_b();
+    let ix = anchor_escrow::take_ix(&taker, &maker, &s.mint_a, &s.mint_b, SEED, None);
+    s.harness
+        .send(Program::AnchorEscrow, ix, &[&s.taker])
+        .unwrap();
+
+    assert_eq!(s.balance(&s.taker, &s.mint_a), DEPOSIT);
+    assert_eq!(s.balance(&s.taker, &s.mint_b), bought - RECEIVE);
+    assert_eq!(s.balance(&s.maker, &s.mint_a), 0);
+    assert_eq!(s.balance(&s.maker, &s.mint_b), RECEIVE);
+    assert_eq!(s.harness.lamports(&escrow), 0);
+
+    let data = s.harness.data(&anchor_escrow::stats_address().0);
+    let stats = anchor_escrow::stats(&data).unwrap();
+    assert_eq!((stats.fills, stats.open_escrows), (1, 0));
+
+    // The vault sat through the other programs untouched, and pays out in full
+    assert_eq!(s.harness.lamports(&vault), VAULT_DEPOSIT);
+    let before = s.harness.lamports(&maker);
+    let ix = anchor_vault::withdraw_ix(&maker);
+    s.harness
+        .send(Program::AnchorVault, ix, &[&s.maker])
+        .unwrap();
+    assert_eq!(s.harness.lamports(&vault), 0);
+    assert_eq!(
+        s.harness.lamports(&maker),
+        before + VAULT_DEPOSIT - SIGNATURE_FEE
+    );
+}
+
+#[test]
+fn anchor_escrow_take_via_amm() {
+    let mut s = Scenario::new();
+    let maker = s.maker.pubkey();
+    let taker = s.taker.pubkey();
+
+    // A pool on the standalone AMM, which the config routes fills through
+    s.harness.load_standalone_amm();
+    let (pool, vault_x, vault_y) = s.harness.standalone_pool(&s.mint_a, &s.mint_b, RESERVE);
+    s.anchor_escrow_config(AMM_STANDALONE_ID);
+    let escrow = s.anchor_make();
+
+    let take_via = |amm_program| {
+        anchor_escrow::take_via_amm_ix(
+            &taker,
+            &maker,
+            &s.mint_a,
+            &s.mint_b,
+            SEED,
+            &pool,
+            &vault_x,
+            &vault_y,
+            amm_program,
+        )
+    };
+
+    // Any other program than the configured AMM is turned away
+    let ix = take_via(&PROGRAM_ID);
+    assert!(s
+        .harness
+        .send(Program::AnchorEscrow, ix, &[&s.taker])
+        .is_err());
+
+    // The taker fills the escrow with the deposit sold on the pool
+    let ix = take_via(&AMM_STANDALONE_ID);
+    s.harness
+        .send(Program::AnchorEscrow, ix, &[&s.taker])
+        .unwrap();
+
+    // What left the pool's Token B vault went to the maker, up to `RECEIVE`,
+    // and the rest to the taker
+    let vault_b = match s.mint_b < s.mint_a {
+        true => vault_x,
+        false => vault_y,
+    };
+    let out = RESERVE - s.harness.balance(&vault_b);
+    assert!(out > RECEIVE);
+    assert_eq!(s.balance(&s.maker, &s.mint_b), RECEIVE);
+    assert_eq!(s.balance(&s.taker, &s.mint_b), out - RECEIVE);
+    assert_eq!(s.balance(&s.taker, &s.mint_a), SWAP_IN);
+    assert_eq!(s.harness.lamports(&escrow), 0);
+    assert_eq!(s.harness.lamports(&s.harness.ata(&escrow, &s.mint_a)), 0);
+
+    let data = s.harness.data(&pool);
+    let config = amm::config(&data).unwrap();
+    let vaults = (s.harness.balance(&vault_x), s.harness.balance(&vault_y));
+    assert_eq!((config.reserve_x(), config.reserve_y()), vaults);
+}
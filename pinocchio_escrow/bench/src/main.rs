@@ -0,0 +1,430 @@
+//! Compute units and binary size of pinocchio_escrow against anchor_escrow
+//!
+//! Runs the same Make → Take and Make → Refund flows through both SBF builds
+//! in mollusk and prints what each instruction costs. Both programs have to be
+//! built first (`cargo build-sbf` here, `anchor build` in `anchor_escrow/`).
+//!
+//! The run fails when a pinocchio_escrow instruction uses more compute units
+//! than its anchor_escrow counterpart. With a `baseline.txt` next to this crate,
+//! it also fails when pinocchio_escrow uses more compute units or a bigger
+//! binary than the baseline allows. `cargo run --release -- --save` records the
+//! current numbers as the baseline.
+
+use std::{collections::HashMap, fs, process::ExitCode};
+
+use anchor_escrow::state::{CancelFee, Config as AnchorConfig, EscrowStats, PairStats};
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use blueshift_test_support::{self as fixtures, Accounts};
+use mollusk_svm::{
+    program::{keyed_account_for_system_program, loader_keys::LOADER_V3},
+    Mollusk,
+};
+use mollusk_svm_programs_token::{associated_token, token};
+use pinocchio_escrow::{
+    client::{
+        associated_token_address, config_address, make_ix, refund_ix, take_ix, MakeArgs,
+        ASSOCIATED_TOKEN_PROGRAM_ID, PROGRAM_ID, SYSTEM_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    state::Config,
+};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+const PINOCCHIO_SO: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../target/deploy/pinocchio_escrow.so"
+);
+const ANCHOR_SO: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../anchor_escrow/target/deploy/anchor_escrow.so"
+);
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/baseline.txt");
+
+/// Compute units a pinocchio instruction may grow by over the baseline, in percent
+const CU_TOLERANCE_PCT: u64 = 2;
+/// Bytes the pinocchio binary may grow by over the baseline
+const SIZE_TOLERANCE_BYTES: u64 = 1024;
+
+const SEED: u64 = 7;
+const DEPOSIT: u64 = 1_000;
+const RECEIVE: u64 = 500;
+
+/// One row of the report
+struct Measurement {
+    name: &'static str,
+    pinocchio: u64,
+    anchor: u64,
+}
+
+/// Accounts the instructions of one flow run against
+struct Ledger {
+    mollusk: Mollusk,
+    accounts: Accounts,
+    maker: Pubkey,
+    taker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+impl Ledger {
+    /// A maker holding `DEPOSIT` Token A and a taker holding `RECEIVE` Token B,
+    /// with `program_id` loaded from `elf`
+    fn new(program_id: &Pubkey, elf: &[u8]) -> Self {
+        let mut mollusk = Mollusk::default();
+        mollusk.add_program_with_elf_and_loader(program_id, elf, &LOADER_V3);
+        token::add_program(&mut mollusk);
+        associated_token::add_program(&mut mollusk);
+
+        let mut ledger = Self {
+            mollusk,
+            accounts: Accounts(vec![
+                keyed_account_for_system_program(),
+                token::keyed_account(),
+                associated_token::keyed_account(),
+            ]),
+            maker: Pubkey::new_unique(),
+            taker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+        };
+
+        ledger.set(ledger.maker, fixtures::wallet());
+        ledger.set(ledger.taker, fixtures::wallet());
+        ledger.set(ledger.mint_a, fixtures::mint(6));
+        ledger.set(ledger.mint_b, fixtures::mint(9));
+        ledger.set(
+            ledger.ata(&ledger.maker, &ledger.mint_a),
+            fixtures::token_account(&ledger.mint_a, &ledger.maker, DEPOSIT),
+        );
+        ledger.set(
+            ledger.ata(&ledger.taker, &ledger.mint_b),
+            fixtures::token_account(&ledger.mint_b, &ledger.taker, RECEIVE),
+        );
+
+        ledger
+    }
+
+    fn set(&mut self, key: Pubkey, account: Account) {
+        self.accounts.set(key, account);
+    }
+
+    fn ata(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID)
+    }
+
+    fn rent_exempt(&self, data: Vec<u8>, owner: &Pubkey) -> Account {
+        fixtures::rent_exempt(data, owner)
+    }
+
+    /// Run `instruction`, keep the resulting accounts and return the compute
+    /// units it used. Accounts that don't exist yet start out empty.
+    fn process(&mut self, name: &str, instruction: &Instruction) -> u64 {
+        self.accounts.add_missing(instruction);
+
+        let result = self.mollusk.process_instruction(instruction, &self.accounts.0);
+        if let Err(e) = result.raw_result {
+            panic!("{name} failed: {e:?}");
+        }
+        for (key, account) in result.resulting_accounts {
+            self.set(key, account);
+        }
+        result.compute_units_consumed
+    }
+}
+
+/// Make, Take and Refund compute units of pinocchio_escrow
+fn pinocchio(elf: &[u8]) -> [u64; 3] {
+    let setup = || {
+        let mut ledger = Ledger::new(&PROGRAM_ID, elf);
+
+        // Config as `InitializeConfig` would leave it, with a 1% protocol fee
+        let mut data = vec![0; Config::LEN];
+        Config::load_uninit_mut(&mut data).unwrap().set_inner(
+            100,
+            0,
+            Pubkey::new_unique().to_bytes(),
+            [config_address().1],
+        );
+        let config = ledger.rent_exempt(data, &PROGRAM_ID);
+        ledger.set(config_address().0, config);
+
+        let args = MakeArgs {
+            seed: SEED,
+            receive: RECEIVE,
+            amount: DEPOSIT,
+            expiry: 0,
+            taker: None,
+            hashlock: None,
+            reject_freezable: false,
+        };
+        let make = make_ix(
+            &ledger.maker,
+            &ledger.mint_a,
+            &ledger.mint_b,
+            &TOKEN_PROGRAM_ID,
+            &args,
+            None,
+        );
+        let make = ledger.process("pinocchio make", &make);
+        (ledger, make)
+    };
+
+    let (mut ledger, make) = setup();
+    let take = take_ix(
+        &ledger.taker,
+        &ledger.maker,
+        &ledger.mint_a,
+        &ledger.mint_b,
+        &TOKEN_PROGRAM_ID,
+        &ledger.maker,
+        SEED,
+        DEPOSIT,
+        None,
+        None,
+        None,
+    );
+    let take = ledger.process("pinocchio take", &take);
+
+    let (mut ledger, _) = setup();
+    let refund = refund_ix(
+        &ledger.maker,
+        &ledger.mint_a,
+        &TOKEN_PROGRAM_ID,
+        &ledger.maker,
+        SEED,
+        None,
+    );
+    let refund = ledger.process("pinocchio refund", &refund);
+
+    [make, take, refund]
+}
+
+/// Make, Take and Refund compute units of anchor_escrow
+fn anchor(elf: &[u8]) -> [u64; 3] {
+    let program_id = anchor_escrow::ID;
+    let (config, config_bump) = AnchorConfig::find_address();
+    let (stats, _) = EscrowStats::find_address();
+    let treasury = Pubkey::new_unique();
+
+    let setup = || {
+        let mut ledger = Ledger::new(&program_id, elf);
+        ledger.set(treasury, fixtures::wallet());
+
+        // Config as `initialize_config` would leave it, without cancellation fees
+        let mut data = Vec::with_capacity(AnchorConfig::SPACE);
+        AnchorConfig {
+            authority: Pubkey::new_unique(),
+            treasury,
+            max_cancel_fee_bps: 0,
+            paused: false,
+            bump: config_bump,
+            amm_program: Pubkey::new_unique(),
+            max_cancel_fee_lamports: 0,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        let account = ledger.rent_exempt(data, &program_id);
+        ledger.set(config, account);
+
+        let escrow = anchor_escrow::state::Escrow::find_address(&ledger.maker, SEED).0;
+        let make = Instruction {
+            program_id,
+            accounts: anchor_escrow::accounts::Make {
+                maker: ledger.maker,
+                escrow,
+                mint_a: ledger.mint_a,
+                mint_b: ledger.mint_b,
+                maker_ata_a: ledger.ata(&ledger.maker, &ledger.mint_a),
+                vault: ledger.ata(&escrow, &ledger.mint_a),
+                associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: SYSTEM_PROGRAM_ID,
+                stats,
+                config,
+            }
+            .to_account_metas(None),
+            data: anchor_escrow::instruction::Make {
+                seed: SEED,
+                receive: RECEIVE,
+                amount: DEPOSIT,
+                reject_freezable: false,
+                expiry: 0,
+                cancel_fee: CancelFee::None,
+                memo: [0; 32],
+            }
+            .data(),
+        };
+        let make = ledger.process("anchor make", &make);
+        (ledger, escrow, make)
+    };
+
+    let (mut ledger, escrow, make) = setup();
+    let take = Instruction {
+        program_id,
+        accounts: anchor_escrow::accounts::Take {
+            taker: ledger.taker,
+            maker: ledger.maker,
+            escrow,
+            mint_a: ledger.mint_a,
+            mint_b: ledger.mint_b,
+            vault: ledger.ata(&escrow, &ledger.mint_a),
+            taker_ata_a: ledger.ata(&ledger.taker, &ledger.mint_a),
+            taker_ata_b: ledger.ata(&ledger.taker, &ledger.mint_b),
+            maker_ata_b: ledger.ata(&ledger.maker, &ledger.mint_b),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats,
+            pair_stats: PairStats::find_address(&ledger.mint_a, &ledger.mint_b).0,
+            config,
+        }
+        .to_account_metas(None),
+        data: anchor_escrow::instruction::Take {}.data(),
+    };
+    let take = ledger.process("anchor take", &take);
+
+    let (mut ledger, escrow, _) = setup();
+    let refund = Instruction {
+        program_id,
+        accounts: anchor_escrow::accounts::Refund {
+            maker: ledger.maker,
+            escrow,
+            mint_a: ledger.mint_a,
+            vault: ledger.ata(&escrow, &ledger.mint_a),
+            maker_ata_a: ledger.ata(&ledger.maker, &ledger.mint_a),
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: SYSTEM_PROGRAM_ID,
+            stats,
+            config,
+            treasury,
+            treasury_ata_a: None,
+        }
+        .to_account_metas(None),
+        data: anchor_escrow::instruction::Refund {}.data(),
+    };
+    let refund = ledger.process("anchor refund", &refund);
+
+    [make, take, refund]
+}
+
+fn read_elf(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| panic!("{path}: {e}; build the program first"))
+}
+
+/// `<name> <value>` per line, as written by `--save`
+fn read_baseline() -> Option<HashMap<String, u64>> {
+    let text = fs::read_to_string(BASELINE).ok()?;
+    let baseline = text
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            Some((name.to_string(), value.trim().parse().ok()?))
+        })
+        .collect();
+    Some(baseline)
+}
+
+fn write_baseline(measurements: &[Measurement]) {
+    let text: String = measurements
+        .iter()
+        .map(|m| format!("{} {}\n", m.name, m.pinocchio))
+        .collect();
+    fs::write(BASELINE, text).unwrap_or_else(|e| panic!("{BASELINE}: {e}"));
+}
+
+/// Change from `anchor` to `pinocchio` in percent
+fn delta_pct(pinocchio: u64, anchor: u64) -> f64 {
+    (pinocchio as f64 - anchor as f64) / anchor as f64 * 100.0
+}
+
+fn main() -> ExitCode {
+    let save = std::env::args().any(|arg| arg == "--save");
+
+    let pinocchio_elf = read_elf(PINOCCHIO_SO);
+    let anchor_elf = read_elf(ANCHOR_SO);
+
+    let [p_make, p_take, p_refund] = pinocchio(&pinocchio_elf);
+    let [a_make, a_take, a_refund] = anchor(&anchor_elf);
+
+    let measurements = [
+        Measurement {
+            name: "make",
+            pinocchio: p_make,
+            anchor: a_make,
+        },
+        Measurement {
+            name: "take",
+            pinocchio: p_take,
+            anchor: a_take,
+        },
+        Measurement {
+            name: "refund",
+            pinocchio: p_refund,
+            anchor: a_refund,
+        },
+        Measurement {
+            name: "binary_size",
+            pinocchio: pinocchio_elf.len() as u64,
+            anchor: anchor_elf.len() as u64,
+        },
+    ];
+
+    println!("| | pinocchio | anchor | delta |");
+    println!("|---|---:|---:|---:|");
+    for m in &measurements {
+        println!(
+            "| {} | {} | {} | {:+.1}% |",
+            m.name,
+            m.pinocchio,
+            m.anchor,
+            delta_pct(m.pinocchio, m.anchor)
+        );
+    }
+
+    if save {
+        write_baseline(&measurements);
+        println!("\nsaved baseline to {BASELINE}");
+        return ExitCode::SUCCESS;
+    }
+
+    // The point of the port: no instruction may cost more than in Anchor
+    let mut regressed = false;
+    for m in measurements.iter().filter(|m| m.name != "binary_size") {
+        if m.pinocchio > m.anchor {
+            println!("\n{} costs more than anchor_escrow: {} > {}", m.name, m.pinocchio, m.anchor);
+            regressed = true;
+        }
+    }
+
+    let Some(baseline) = read_baseline() else {
+        println!("\nno baseline at {BASELINE}; run with --save to record one");
+        return if regressed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        };
+    };
+
+    for m in &measurements {
+        let Some(&base) = baseline.get(m.name) else {
+            continue;
+        };
+        let limit = if m.name == "binary_size" {
+            base + SIZE_TOLERANCE_BYTES
+        } else {
+            base + base * CU_TOLERANCE_PCT / 100
+        };
+        if m.pinocchio > limit {
+            println!("\n{} regressed: {} > {} (baseline {})", m.name, m.pinocchio, limit, base);
+            regressed = true;
+        }
+    }
+
+    if regressed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
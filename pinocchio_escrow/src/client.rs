@@ -0,0 +1,411 @@
+//! Off-chain instruction builders (`client` feature)
+//!
+//! Assembles the exact account order and little-endian data layout each
+//! instruction expects, so callers don't have to hand-build the 15-account `Take`.
+//!
+//! Existing escrows are addressed by `creator` and `seed`. The creator is the
+//! wallet that made the escrow and only differs from the current `maker`
+//! after a `TransferEscrow`.
+
+use solana_challenge_common::ids;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    BatchRefund, ClaimFees, InitializeConfig, Make, ReadEscrow, ReduceDeposit, Refund, Take,
+    TransferEscrow, UpdateReceive, CONFIG_SEED, ESCROW_SEED, MAKER_SEED,
+};
+
+/// Escrow program ID
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array(crate::ID);
+
+/// System Program ID
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::SYSTEM_PROGRAM_ID);
+
+/// Associated Token Account Program ID
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::new_from_array(ids::ASSOCIATED_TOKEN_PROGRAM_ID);
+
+/// SPL Token Program ID
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::TOKEN_PROGRAM_ID);
+
+/// SPL Token-2022 Program ID
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::TOKEN_2022_PROGRAM_ID);
+
+/// Wrapped SOL mint; use as `mint_b` to be paid in native SOL
+pub const NATIVE_MINT: Pubkey = Pubkey::new_from_array(ids::NATIVE_MINT);
+
+/// Derive the escrow PDA for `creator` and `seed`
+pub fn escrow_address(creator: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ESCROW_SEED, creator.as_ref(), &seed.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Derive the counter PDA listing the open escrows made by `creator`
+pub fn maker_counter_address(creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MAKER_SEED, creator.as_ref()], &PROGRAM_ID)
+}
+
+/// Derive the program config PDA
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &PROGRAM_ID)
+}
+
+/// Derive this program's ProgramData account (upgradeable loader)
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[PROGRAM_ID.as_ref()],
+        &Pubkey::new_from_array(ids::BPF_LOADER_UPGRADEABLE_ID),
+    )
+    .0
+}
+
+/// Derive the associated token account of `wallet` for `mint`
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Derive the vault (the escrow's Token A ATA) for `creator` and `seed`
+pub fn vault_address(
+    creator: &Pubkey,
+    seed: u64,
+    mint_a: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    let (escrow, _) = escrow_address(creator, seed);
+    associated_token_address(&escrow, mint_a, token_program)
+}
+
+/// Terms of a new escrow offer
+pub struct MakeArgs {
+    pub seed: u64,
+    /// Token B asked for the whole deposit
+    pub receive: u64,
+    /// Token A deposited into the vault
+    pub amount: u64,
+    /// Unix timestamp after which the offer can't be taken (0 = never)
+    pub expiry: i64,
+    /// Only this wallet may take the offer (`None` = anyone)
+    pub taker: Option<Pubkey>,
+    /// SHA-256 hash whose preimage Take must reveal (`None` = no hashlock)
+    pub hashlock: Option<[u8; 32]>,
+    /// Reject mints that have a freeze authority
+    pub reject_freezable: bool,
+}
+
+/// Build a `Make` instruction
+///
+/// With `payer` set, `payer` funds the new accounts' rent and the maker only
+/// signs for the Token A deposit.
+pub fn make_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+    args: &MakeArgs,
+    payer: Option<&Pubkey>,
+) -> Instruction {
+    let (escrow, _) = escrow_address(maker, args.seed);
+
+    let mut data = Vec::with_capacity(1 + 8 + 8 + 8 + 8 + 32 + 32 + 1);
+    data.push(*Make::DISCRIMINATOR);
+    data.extend_from_slice(&args.seed.to_le_bytes());
+    data.extend_from_slice(&args.receive.to_le_bytes());
+    data.extend_from_slice(&args.amount.to_le_bytes());
+    data.extend_from_slice(&args.expiry.to_le_bytes());
+    data.extend_from_slice(args.taker.unwrap_or_default().as_ref());
+    data.extend_from_slice(&args.hashlock.unwrap_or_default());
+    data.push(args.reject_freezable as u8);
+
+    let mut accounts = vec![
+        AccountMeta::new(*maker, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(*mint_a, false),
+        AccountMeta::new_readonly(*mint_b, false),
+        AccountMeta::new(associated_token_address(maker, mint_a, token_program), false),
+        AccountMeta::new(associated_token_address(&escrow, mint_a, token_program), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new(maker_counter_address(maker).0, false),
+    ];
+    if let Some(payer) = payer {
+        accounts.push(AccountMeta::new(*payer, true));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Build a `Take` instruction filling `amount` of the escrow's remaining Token A,
+/// revealing `preimage` for hashlocked escrows and paying the referral share of
+/// the fee to the `referrer` Token B account
+///
+/// Token B comes from the taker's ATA, or from `taker_ata_b` when set: any
+/// Token B account the taker owns or is an approved delegate of.
+#[allow(clippy::too_many_arguments)]
+pub fn take_ix(
+    taker: &Pubkey,
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    token_program: &Pubkey,
+    creator: &Pubkey,
+    seed: u64,
+    amount: u64,
+    preimage: Option<&[u8; 32]>,
+    referrer: Option<&Pubkey>,
+    taker_ata_b: Option<&Pubkey>,
+) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+    let (config, _) = config_address();
+
+    let mut data = Vec::with_capacity(1 + 8 + 32);
+    data.push(*Take::DISCRIMINATOR);
+    data.extend_from_slice(&amount.to_le_bytes());
+    if let Some(preimage) = preimage {
+        data.extend_from_slice(preimage);
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(*taker, true),
+        AccountMeta::new(*maker, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(*mint_a, false),
+        AccountMeta::new_readonly(*mint_b, false),
+        AccountMeta::new(associated_token_address(&escrow, mint_a, token_program), false),
+        AccountMeta::new(associated_token_address(taker, mint_a, token_program), false),
+        AccountMeta::new(
+            taker_ata_b
+                .copied()
+                .unwrap_or_else(|| associated_token_address(taker, mint_b, token_program)),
+            false,
+        ),
+        AccountMeta::new(associated_token_address(maker, mint_b, token_program), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(associated_token_address(&config, mint_b, token_program), false),
+        AccountMeta::new(maker_counter_address(creator).0, false),
+        AccountMeta::new(associated_token_address(maker, mint_a, token_program), false),
+    ];
+    if let Some(referrer) = referrer {
+        accounts.push(AccountMeta::new(*referrer, false));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Build a `Refund` instruction
+///
+/// With `payer` set, the maker doesn't sign and `payer` refunds the expired
+/// escrow on the maker's behalf.
+pub fn refund_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    token_program: &Pubkey,
+    creator: &Pubkey,
+    seed: u64,
+    payer: Option<&Pubkey>,
+) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+
+    let mut accounts = vec![
+        AccountMeta::new(*maker, payer.is_none()),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(*mint_a, false),
+        AccountMeta::new(associated_token_address(&escrow, mint_a, token_program), false),
+        AccountMeta::new(associated_token_address(maker, mint_a, token_program), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new(maker_counter_address(creator).0, false),
+    ];
+    if let Some(payer) = payer {
+        accounts.push(AccountMeta::new(*payer, true));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: vec![*Refund::DISCRIMINATOR],
+    }
+}
+
+/// Build an `InitializeConfig` instruction; `authority` must be the program's upgrade authority
+pub fn initialize_config_ix(authority: &Pubkey, fee_bps: u16, referral_bps: u16) -> Instruction {
+    let (config, _) = config_address();
+
+    let mut data = Vec::with_capacity(1 + 2 + 2);
+    data.push(*InitializeConfig::DISCRIMINATOR);
+    data.extend_from_slice(&fee_bps.to_le_bytes());
+    data.extend_from_slice(&referral_bps.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(program_data_address(), false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Build a `ClaimFees` instruction sweeping the `mint` fee vault into `destination`
+pub fn claim_fees_ix(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let (config, _) = config_address();
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(associated_token_address(&config, mint, token_program), false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![*ClaimFees::DISCRIMINATOR],
+    }
+}
+
+/// Build an `UpdateReceive` instruction repricing the rest of an open escrow
+pub fn update_receive_ix(
+    maker: &Pubkey,
+    creator: &Pubkey,
+    seed: u64,
+    receive: u64,
+) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+
+    let mut data = Vec::with_capacity(1 + 8);
+    data.push(*UpdateReceive::DISCRIMINATOR);
+    data.extend_from_slice(&receive.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(escrow, false),
+        ],
+        data,
+    }
+}
+
+/// Build a `BatchRefund` instruction cancelling the maker's escrows made by
+/// `creator` at `seeds`, all of which must deposit `mint_a`
+pub fn batch_refund_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    token_program: &Pubkey,
+    creator: &Pubkey,
+    seeds: &[u64],
+) -> Instruction {
+    let mut accounts = Vec::with_capacity(5 + 2 * seeds.len());
+    accounts.extend([
+        AccountMeta::new(*maker, true),
+        AccountMeta::new_readonly(*mint_a, false),
+        AccountMeta::new(associated_token_address(maker, mint_a, token_program), false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new(maker_counter_address(creator).0, false),
+    ]);
+    for seed in seeds {
+        let (escrow, _) = escrow_address(creator, *seed);
+        accounts.push(AccountMeta::new(escrow, false));
+        accounts.push(AccountMeta::new(
+            associated_token_address(&escrow, mint_a, token_program),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: vec![*BatchRefund::DISCRIMINATOR],
+    }
+}
+
+/// Build a `TransferEscrow` instruction handing an open escrow to `new_maker`
+pub fn transfer_escrow_ix(
+    maker: &Pubkey,
+    creator: &Pubkey,
+    seed: u64,
+    new_maker: &Pubkey,
+) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+
+    let mut data = Vec::with_capacity(1 + 32);
+    data.push(*TransferEscrow::DISCRIMINATOR);
+    data.extend_from_slice(new_maker.as_ref());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(escrow, false),
+        ],
+        data,
+    }
+}
+
+/// Build a `ReduceDeposit` instruction returning `amount` of the escrow's
+/// Token A to `maker`
+pub fn reduce_deposit_ix(
+    maker: &Pubkey,
+    mint_a: &Pubkey,
+    token_program: &Pubkey,
+    creator: &Pubkey,
+    seed: u64,
+    amount: u64,
+) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+
+    let mut data = Vec::with_capacity(1 + 8);
+    data.push(*ReduceDeposit::DISCRIMINATOR);
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*maker, true),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new(associated_token_address(&escrow, mint_a, token_program), false),
+            AccountMeta::new(associated_token_address(maker, mint_a, token_program), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    }
+}
+
+/// Build a `ReadEscrow` instruction; decode its return data with
+/// [`EscrowTerms::load`](crate::state::EscrowTerms::load)
+pub fn read_escrow_ix(creator: &Pubkey, seed: u64) -> Instruction {
+    let (escrow, _) = escrow_address(creator, seed);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(escrow, false)],
+        data: vec![*ReadEscrow::DISCRIMINATOR],
+    }
+}
@@ -0,0 +1,41 @@
+use pinocchio::program_error::ProgramError;
+
+/// Escrow program errors, reported as `ProgramError::Custom(code)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    /// The signer is not the taker the offer was made for
+    InvalidTaker,
+    /// The signer is not the config (or program upgrade) authority
+    InvalidAuthority,
+    /// The preimage does not hash to the escrow's hashlock
+    InvalidPreimage,
+    /// A mint has a freeze authority and the maker asked to reject those
+    FreezableMint,
+    /// The deposited Token A amount is zero, or a ReduceDeposit would withdraw
+    /// nothing or the whole remaining deposit
+    InvalidAmount,
+    /// The requested Token B amount is zero
+    InvalidReceive,
+    /// The deposit or the requested amount exceeds `MakeInstructionData::MAX_AMOUNT`
+    AmountTooLarge,
+    /// Token A and Token B are the same mint
+    SameMint,
+    /// The escrow was closed earlier in this transaction and only its tombstone is left
+    EscrowClosed,
+    /// The escrow account is not the PDA derived from its creator and seed
+    InvalidEscrowPda,
+    /// The maker account is not the escrow's current maker
+    MakerMismatch,
+    /// The escrow's expiry has passed and it can no longer be taken
+    EscrowExpired,
+    /// The vault is not an initialized, escrow-owned Token A account
+    InvalidVault,
+    /// The taker is neither the owner nor the delegate of the Token B account it pays from
+    InvalidTokenAuthority,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,151 @@
+use pinocchio::{log::sol_log_data, pubkey::Pubkey};
+
+/// Emitted by `Make` once the escrow is funded
+///
+/// Logged via `sol_log_data` as: discriminator, seed, maker, mint_a, mint_b,
+/// amount, receive (integers little-endian)
+pub struct EscrowMade<'a> {
+    pub seed: u64,
+    pub maker: &'a Pubkey,
+    pub mint_a: &'a Pubkey,
+    pub mint_b: &'a Pubkey,
+    pub amount: u64,
+    pub receive: u64,
+}
+
+impl EscrowMade<'_> {
+    /// Event discriminator
+    pub const DISCRIMINATOR: &'static [u8] = &[0];
+
+    pub fn emit(&self) {
+        sol_log_data(&[
+            Self::DISCRIMINATOR,
+            &self.seed.to_le_bytes(),
+            self.maker,
+            self.mint_a,
+            self.mint_b,
+            &self.amount.to_le_bytes(),
+            &self.receive.to_le_bytes(),
+        ]);
+    }
+}
+
+/// Emitted by `Take` for every fill, partial or final
+///
+/// Logged via `sol_log_data` as: discriminator, seed, maker, taker, mint_a,
+/// mint_b, amount (Token A filled), receive (Token B paid), fee (protocol's
+/// share of `receive`), remaining, referral (referrer's share of `fee`)
+pub struct EscrowTaken<'a> {
+    pub seed: u64,
+    pub maker: &'a Pubkey,
+    pub taker: &'a Pubkey,
+    pub mint_a: &'a Pubkey,
+    pub mint_b: &'a Pubkey,
+    pub amount: u64,
+    pub receive: u64,
+    pub fee: u64,
+    pub remaining: u64,
+    pub referral: u64,
+}
+
+impl EscrowTaken<'_> {
+    /// Event discriminator
+    pub const DISCRIMINATOR: &'static [u8] = &[1];
+
+    pub fn emit(&self) {
+        sol_log_data(&[
+            Self::DISCRIMINATOR,
+            &self.seed.to_le_bytes(),
+            self.maker,
+            self.taker,
+            self.mint_a,
+            self.mint_b,
+            &self.amount.to_le_bytes(),
+            &self.receive.to_le_bytes(),
+            &self.fee.to_le_bytes(),
+            &self.remaining.to_le_bytes(),
+            &self.referral.to_le_bytes(),
+        ]);
+    }
+}
+
+/// Emitted by `Refund` before the escrow is closed
+///
+/// Logged via `sol_log_data` as: discriminator, seed, maker, mint_a, amount
+pub struct EscrowRefunded<'a> {
+    pub seed: u64,
+    pub maker: &'a Pubkey,
+    pub mint_a: &'a Pubkey,
+    pub amount: u64,
+}
+
+impl EscrowRefunded<'_> {
+    /// Event discriminator
+    pub const DISCRIMINATOR: &'static [u8] = &[2];
+
+    pub fn emit(&self) {
+        sol_log_data(&[
+            Self::DISCRIMINATOR,
+            &self.seed.to_le_bytes(),
+            self.maker,
+            self.mint_a,
+            &self.amount.to_le_bytes(),
+        ]);
+    }
+}
+
+/// Emitted by `TransferEscrow` once the new maker is recorded
+///
+/// Logged via `sol_log_data` as: discriminator, seed, creator, old_maker,
+/// new_maker; the escrow PDA is derived from `creator` and `seed`
+pub struct EscrowTransferred<'a> {
+    pub seed: u64,
+    pub creator: &'a Pubkey,
+    pub old_maker: &'a Pubkey,
+    pub new_maker: &'a Pubkey,
+}
+
+impl EscrowTransferred<'_> {
+    /// Event discriminator
+    pub const DISCRIMINATOR: &'static [u8] = &[3];
+
+    pub fn emit(&self) {
+        sol_log_data(&[
+            Self::DISCRIMINATOR,
+            &self.seed.to_le_bytes(),
+            self.creator,
+            self.old_maker,
+            self.new_maker,
+        ]);
+    }
+}
+
+/// Emitted by `ReduceDeposit` after part of the vault goes back to the maker
+///
+/// Logged via `sol_log_data` as: discriminator, seed, maker, mint_a, amount
+/// (Token A withdrawn), remaining, receive (Token B now asked for `remaining`)
+pub struct EscrowReduced<'a> {
+    pub seed: u64,
+    pub maker: &'a Pubkey,
+    pub mint_a: &'a Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+    pub receive: u64,
+}
+
+impl EscrowReduced<'_> {
+    /// Event discriminator
+    pub const DISCRIMINATOR: &'static [u8] = &[4];
+
+    pub fn emit(&self) {
+        sol_log_data(&[
+            Self::DISCRIMINATOR,
+            &self.seed.to_le_bytes(),
+            self.maker,
+            self.mint_a,
+            &self.amount.to_le_bytes(),
+            &self.remaining.to_le_bytes(),
+            &self.receive.to_le_bytes(),
+        ]);
+    }
+}
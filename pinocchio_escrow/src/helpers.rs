@@ -1,31 +1,56 @@
 use pinocchio::{
     account_info::AccountInfo,
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
     program_error::ProgramError,
     pubkey::Pubkey,
     ProgramResult,
 };
-use pinocchio_token::instructions::InitializeAccount3;
+use pinocchio_associated_token_account::instructions::{Create, CreateIdempotent};
+use solana_challenge_common::{ids, lamports, seeds};
 
-use crate::ID;
+use crate::{state::CLOSED_ACCOUNT_DISCRIMINATOR, ID};
+
+pub use solana_challenge_common::ids::{
+    is_token_program, ASSOCIATED_TOKEN_PROGRAM_ID, BPF_LOADER_UPGRADEABLE_ID, NATIVE_MINT,
+    TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
 
 /// SPL Token Account size
-pub const TOKEN_ACCOUNT_SIZE: usize = 165;
-
-/// Associated Token Account Program ID
-pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = [
-    0x8c, 0x97, 0x25, 0x8f, 0x4e, 0x24, 0x89, 0xf1,
-    0xbb, 0x3d, 0x10, 0x29, 0x14, 0x8e, 0x0d, 0x83,
-    0x0b, 0x5a, 0x13, 0x99, 0xda, 0xff, 0x10, 0x84,
-    0x04, 0x8e, 0x7b, 0xd8, 0xdb, 0xe9, 0xf8, 0x59,
-];
-
-/// SPL Token Program ID
-pub const TOKEN_PROGRAM_ID: Pubkey = [
-    0x06, 0xdd, 0xf6, 0xe1, 0xd7, 0x65, 0xa1, 0x93,
-    0xd9, 0xcb, 0xe1, 0x46, 0xce, 0xeb, 0x79, 0xac,
-    0x1c, 0xb4, 0x85, 0xed, 0x5f, 0x5b, 0x37, 0x91,
-    0x3a, 0x8c, 0xf5, 0x85, 0x7e, 0xff, 0x00, 0xa9,
-];
+pub const TOKEN_ACCOUNT_SIZE: usize = ids::TOKEN_ACCOUNT_LEN;
+
+/// SPL Mint size
+pub const MINT_SIZE: usize = ids::MINT_LEN;
+
+/// SHA-256 of the concatenation of `vals`, via the `sol_sha256` syscall
+/// on-chain and `sha2` in host builds (tests, the client)
+#[inline(always)]
+pub fn sha256(vals: &[&[u8]]) -> [u8; 32] {
+    #[cfg(target_os = "solana")]
+    {
+        let mut hash = [0u8; 32];
+        // Safety: `&[u8]` has the (ptr, len) layout the syscall expects for each value
+        unsafe {
+            pinocchio::syscalls::sol_sha256(
+                vals.as_ptr() as *const u8,
+                vals.len() as u64,
+                hash.as_mut_ptr(),
+            );
+        }
+        hash
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for val in vals {
+            hasher.update(val);
+        }
+        hasher.finalize().into()
+    }
+}
 
 /// Signer account helper
 pub struct SignerAccount;
@@ -43,13 +68,190 @@ impl SignerAccount {
 pub struct MintInterface;
 
 impl MintInterface {
-    pub fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        // Check that account is owned by token program
-        if account.owner() != &TOKEN_PROGRAM_ID {
+    /// Check that the mint is owned by the token program passed to the instruction
+    pub fn check(account: &AccountInfo, token_program: &AccountInfo) -> Result<(), ProgramError> {
+        if !is_token_program(token_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if account.owner() != token_program.key() {
             return Err(ProgramError::InvalidAccountOwner);
         }
         Ok(())
     }
+
+    /// Whether the mint has a freeze authority that could lock token accounts
+    pub fn has_freeze_authority(account: &AccountInfo) -> Result<bool, ProgramError> {
+        // Mint: mint_authority (36) + supply (8) + decimals (1) + is_initialized (1)
+        // + freeze_authority (36)
+        let data = account.try_borrow_data()?;
+        if data.len() < MINT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(data[46..50] != [0; 4])
+    }
+
+    /// Read the mint's decimals (same offset in spl-token and Token-2022)
+    pub fn decimals(account: &AccountInfo) -> Result<u8, ProgramError> {
+        let data = account.try_borrow_data()?;
+        if data.len() < MINT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(data[44])
+    }
+}
+
+/// Token instructions issued against whichever token program owns the accounts.
+/// `pinocchio_token` CPIs always target spl-token, which rejects Token-2022 accounts.
+pub struct TokenInterface;
+
+impl TokenInterface {
+    /// Read the balance of a token account (same offset in spl-token and Token-2022)
+    pub fn amount(account: &AccountInfo) -> Result<u64, ProgramError> {
+        if !is_token_program(account.owner()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let data = account.try_borrow_data()?;
+        if data.len() < TOKEN_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(u64::from_le_bytes(data[64..72].try_into().unwrap()))
+    }
+
+    /// Check that `account` is an initialized token account for `mint` whose
+    /// owner field is `owner`, with no delegate or close authority that could
+    /// move funds behind the owner's back. Any such account qualifies, not only the ATA.
+    ///
+    /// This derives no address, so escrows don't cache a vault ATA bump for
+    /// `create_program_address` either: it would cost more than this check.
+    pub fn check_account(
+        account: &AccountInfo,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &AccountInfo,
+    ) -> Result<(), ProgramError> {
+        if !is_token_program(token_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if account.owner() != token_program.key() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // Token account: mint (32) + owner (32) + amount (8) + delegate (36) + state (1)
+        // + is_native (12) + delegated_amount (8) + close_authority (36)
+        let data = account.try_borrow_data()?;
+        if data.len() < TOKEN_ACCOUNT_SIZE || data[108] != 1 {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if &data[0..32] != mint.as_ref() || &data[32..64] != owner.as_ref() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[72..76] != [0; 4] || data[129..133] != [0; 4] {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    /// Check that `account` is an initialized token account for `mint` and
+    /// return whether `authority` can spend from it, as its owner or its
+    /// delegate. The token program enforces a delegate's allowance on transfer.
+    pub fn is_spender(
+        account: &AccountInfo,
+        authority: &Pubkey,
+        mint: &Pubkey,
+        token_program: &AccountInfo,
+    ) -> Result<bool, ProgramError> {
+        if !is_token_program(token_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if account.owner() != token_program.key() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // Token account: mint (32) + owner (32) + amount (8) + delegate (36) + state (1)
+        let data = account.try_borrow_data()?;
+        if data.len() < TOKEN_ACCOUNT_SIZE || data[108] != 1 {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if &data[0..32] != mint.as_ref() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let is_owner = &data[32..64] == authority.as_ref();
+        let is_delegate = data[72..76] == [1, 0, 0, 0] && &data[76..108] == authority.as_ref();
+        Ok(is_owner || is_delegate)
+    }
+
+    /// Transfer `amount` tokens of `mint` from `from` to `to`. The token program
+    /// rejects the transfer unless both accounts hold `mint` and `decimals` matches it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_checked(
+        from: &AccountInfo,
+        mint: &AccountInfo,
+        to: &AccountInfo,
+        authority: &AccountInfo,
+        amount: u64,
+        decimals: u8,
+        token_program: &AccountInfo,
+        signers: &[Signer],
+    ) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(from.key()),
+            AccountMeta::readonly(mint.key()),
+            AccountMeta::writable(to.key()),
+            AccountMeta::readonly_signer(authority.key()),
+        ];
+
+        // TransferChecked: discriminator (1) + amount (8) + decimals (1)
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&amount.to_le_bytes());
+        data[9] = decimals;
+
+        let instruction = Instruction {
+            program_id: token_program.key(),
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[from, mint, to, authority], signers)
+    }
+
+    /// Close `account`, sending its lamports to `destination`
+    pub fn close_account(
+        account: &AccountInfo,
+        destination: &AccountInfo,
+        authority: &AccountInfo,
+        token_program: &AccountInfo,
+        signers: &[Signer],
+    ) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(account.key()),
+            AccountMeta::writable(destination.key()),
+            AccountMeta::readonly_signer(authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: token_program.key(),
+            accounts: &account_metas,
+            data: &[9],
+        };
+
+        invoke_signed(&instruction, &[account, destination, authority], signers)
+    }
+
+    /// Bring a wSOL account's token balance in line with its lamports
+    pub fn sync_native(account: &AccountInfo, token_program: &AccountInfo) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(account.key())];
+
+        let instruction = Instruction {
+            program_id: token_program.key(),
+            accounts: &account_metas,
+            data: &[17],
+        };
+
+        invoke_signed(&instruction, &[account], &[])
+    }
 }
 
 /// Program account helper for PDAs
@@ -65,23 +267,25 @@ impl ProgramAccount {
     }
 
     /// Close a PDA account and transfer lamports to destination
+    ///
+    /// The account is shrunk to a 1-byte tombstone and stays owned by this
+    /// program, so if it is topped up again later in the same transaction it
+    /// can't be re-created or loaded as a live account. The runtime purges it
+    /// once the transaction ends with zero lamports.
     pub fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
         // Transfer all lamports
-        let account_lamports = account.lamports();
+        let destination_lamports = lamports::sweep(account.lamports(), destination.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
         unsafe {
             *account.borrow_mut_lamports_unchecked() = 0;
-            *destination.borrow_mut_lamports_unchecked() += account_lamports;
+            *destination.borrow_mut_lamports_unchecked() = destination_lamports;
         }
 
-        // Zero out data
+        // Leave the closed tombstone behind
+        account.resize(1)?;
         let data = unsafe { account.borrow_mut_data_unchecked() };
-        data.fill(0);
-
-        // Reassign to system program
-        unsafe {
-            account.assign(&pinocchio_system::ID);
-        }
+        data[0] = CLOSED_ACCOUNT_DISCRIMINATOR;
 
         Ok(())
     }
@@ -91,10 +295,10 @@ impl ProgramAccount {
 pub struct AssociatedTokenAccount;
 
 impl AssociatedTokenAccount {
-    /// Derive ATA address
-    pub fn get_address(wallet: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    /// Derive ATA address for the given token program
+    pub fn get_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> (Pubkey, u8) {
         pinocchio::pubkey::find_program_address(
-            &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+            &seeds::associated_token_account(wallet, token_program, mint),
             &ASSOCIATED_TOKEN_PROGRAM_ID,
         )
     }
@@ -104,15 +308,18 @@ impl AssociatedTokenAccount {
         ata: &AccountInfo,
         wallet: &AccountInfo,
         mint: &AccountInfo,
-        _token_program: &AccountInfo,
+        token_program: &AccountInfo,
     ) -> Result<(), ProgramError> {
-        // Verify owner is token program
-        if ata.owner() != &TOKEN_PROGRAM_ID {
+        // Verify owner is the token program passed in
+        if !is_token_program(token_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if ata.owner() != token_program.key() {
             return Err(ProgramError::InvalidAccountOwner);
         }
 
         // Verify ATA address
-        let (expected_ata, _) = Self::get_address(wallet.key(), mint.key());
+        let (expected_ata, _) = Self::get_address(wallet.key(), mint.key(), token_program.key());
         if ata.key() != &expected_ata {
             return Err(ProgramError::InvalidSeeds);
         }
@@ -120,58 +327,45 @@ impl AssociatedTokenAccount {
         Ok(())
     }
 
-    /// Initialize an ATA (assumes account is pre-created by test framework)
-    /// Only initializes if not already a token account
+    /// Create the ATA through the associated token program, funding rent from `payer`.
+    /// Fails if the account already exists.
     pub fn init<'a>(
         ata: &'a AccountInfo,
         mint: &'a AccountInfo,
-        _payer: &'a AccountInfo,
+        payer: &'a AccountInfo,
         owner: &'a AccountInfo,
-        _system_program: &'a AccountInfo,
-        _token_program: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        token_program: &'a AccountInfo,
     ) -> ProgramResult {
-        // If account is already owned by token program, assume it's initialized
-        if ata.owner() == &TOKEN_PROGRAM_ID {
-            return Ok(());
-        }
-
-        // Initialize as token account (account should be pre-created with lamports)
-        InitializeAccount3 {
+        Create {
+            funding_account: payer,
             account: ata,
+            wallet: owner,
             mint,
-            owner: owner.key(),
+            system_program,
+            token_program,
         }
-        .invoke()?;
-
-        Ok(())
+        .invoke()
     }
 
-    /// Initialize an ATA if it doesn't exist
+    /// Create the ATA through the associated token program if it doesn't exist yet,
+    /// funding rent from `payer`
     pub fn init_if_needed<'a>(
         ata: &'a AccountInfo,
         mint: &'a AccountInfo,
-        _payer: &'a AccountInfo,
+        payer: &'a AccountInfo,
         owner: &'a AccountInfo,
-        _system_program: &'a AccountInfo,
-        _token_program: &'a AccountInfo,
+        system_program: &'a AccountInfo,
+        token_program: &'a AccountInfo,
     ) -> ProgramResult {
-        // If already owned by token program, assume it's initialized
-        if ata.owner() == &TOKEN_PROGRAM_ID {
-            return Ok(());
-        }
-
-        // If account has lamports but not initialized, initialize it
-        if ata.lamports() > 0 {
-            InitializeAccount3 {
-                account: ata,
-                mint,
-                owner: owner.key(),
-            }
-            .invoke()?;
+        CreateIdempotent {
+            funding_account: payer,
+            account: ata,
+            wallet: owner,
+            mint,
+            system_program,
+            token_program,
         }
-        // If account has no lamports, assume test framework will handle it
-        // or it's already set up correctly
-
-        Ok(())
+        .invoke()
     }
 }
@@ -0,0 +1,191 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    seeds,
+    ProgramResult,
+};
+
+use crate::{
+    errors::EscrowError,
+    events::EscrowRefunded,
+    helpers::{
+        AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount, TokenInterface,
+    },
+    state::{Escrow, MakerCounter},
+    ESCROW_SEED, ID,
+};
+
+/// BatchRefund accounts structure
+///
+/// The fixed accounts are followed by one `(escrow, vault)` pair per escrow
+/// being refunded. Every escrow must belong to `maker`, deposit `mint_a` and
+/// be listed in `maker_counter` (i.e. share the creator the counter is for).
+pub struct BatchRefundAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub mint_a: &'a AccountInfo,
+    pub maker_ata_a: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub maker_counter: &'a AccountInfo,
+    pub escrows: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for BatchRefundAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, mint_a, maker_ata_a, token_program, maker_counter, escrows @ ..] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Expect at least one complete (escrow, vault) pair
+        if escrows.is_empty() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        if escrows.len() % 2 != 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Basic account checks
+        SignerAccount::check(maker)?;
+        ProgramAccount::check(maker_counter)?;
+        MintInterface::check(mint_a, token_program)?;
+        AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
+
+        Ok(Self {
+            maker,
+            mint_a,
+            maker_ata_a,
+            token_program,
+            maker_counter,
+            escrows,
+        })
+    }
+}
+
+/// BatchRefund instruction - cancels several of one maker's escrow offers at once
+pub struct BatchRefund<'a> {
+    pub accounts: BatchRefundAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for BatchRefund<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = BatchRefundAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> BatchRefund<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &12;
+
+    /// Process the batch refund instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let mut counter_data = self.accounts.maker_counter.try_borrow_mut_data()?;
+        let counter = MakerCounter::load_mut(counter_data.as_mut())?;
+        let decimals = MintInterface::decimals(self.accounts.mint_a)?;
+
+        for pair in self.accounts.escrows.chunks_exact(2) {
+            let [escrow_info, vault] = pair else {
+                unreachable!()
+            };
+
+            // A closed escrow is left as a tombstone, so passing the same
+            // escrow twice fails here
+            let escrow = Escrow::load_checked(escrow_info)?;
+
+            // Check if maker and mint match
+            if &escrow.maker != self.accounts.maker.key() {
+                return Err(EscrowError::MakerMismatch.into());
+            }
+            if &escrow.mint_a != self.accounts.mint_a.key() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // Check if the escrow is valid
+            let escrow_key = create_program_address(
+                &[
+                    ESCROW_SEED,
+                    &escrow.creator,
+                    &escrow.seed().to_le_bytes(),
+                    &escrow.bump,
+                ],
+                &ID,
+            )?;
+            if &escrow_key != escrow_info.key() {
+                return Err(EscrowError::InvalidEscrowPda.into());
+            }
+
+            // Check the vault is an escrow-owned Token A account
+            TokenInterface::check_account(
+                vault,
+                escrow_info.key(),
+                &escrow.mint_a,
+                self.accounts.token_program,
+            )
+            .map_err(|_| EscrowError::InvalidVault)?;
+
+            // Release the escrow before the CPIs it signs
+            let seed = escrow.seed();
+            let Escrow {
+                creator,
+                mint_a,
+                bump,
+                ..
+            } = *escrow;
+            drop(escrow);
+
+            // Prepare signer seeds
+            let seed_bytes = seed.to_le_bytes();
+            let signer_seeds = seeds!(
+                ESCROW_SEED,
+                creator.as_ref(),
+                seed_bytes.as_ref(),
+                bump.as_ref()
+            );
+            let signer = Signer::from(&signer_seeds);
+
+            // Return the vault balance to the maker and close the vault
+            let amount = TokenInterface::amount(vault)?;
+
+            TokenInterface::transfer_checked(
+                vault,
+                self.accounts.mint_a,
+                self.accounts.maker_ata_a,
+                escrow_info,
+                amount,
+                decimals,
+                self.accounts.token_program,
+                &[signer.clone()],
+            )?;
+
+            TokenInterface::close_account(
+                vault,
+                self.accounts.maker,
+                escrow_info,
+                self.accounts.token_program,
+                &[signer.clone()],
+            )?;
+
+            EscrowRefunded {
+                seed,
+                maker: self.accounts.maker.key(),
+                mint_a: &mint_a,
+                amount,
+            }
+            .emit();
+
+            counter.check_address(self.accounts.maker_counter.key(), &creator)?;
+            counter.record_close(seed);
+
+            // Close the escrow
+            ProgramAccount::close(escrow_info, self.accounts.maker)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    seeds,
+    ProgramResult,
+};
+
+use crate::{
+    errors::EscrowError,
+    helpers::{
+        AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount, TokenInterface,
+    },
+    state::Config,
+    CONFIG_SEED, ID,
+};
+
+/// ClaimFees accounts structure
+pub struct ClaimFeesAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub fee_vault: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, mint, fee_vault, destination, token_program, _remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        SignerAccount::check(authority)?;
+        ProgramAccount::check(config)?;
+        MintInterface::check(mint, token_program)?;
+        AssociatedTokenAccount::check(fee_vault, config, mint, token_program)?;
+
+        Ok(Self {
+            authority,
+            config,
+            mint,
+            fee_vault,
+            destination,
+            token_program,
+        })
+    }
+}
+
+/// ClaimFees instruction - withdraws accrued protocol fees for one mint
+pub struct ClaimFees<'a> {
+    pub accounts: ClaimFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ClaimFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ClaimFeesAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimFees<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &10;
+
+    /// Process the claim fees instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let data = self.accounts.config.try_borrow_data()?;
+        let config = Config::load(&data)?;
+
+        // Check if the config is valid
+        let config_key = create_program_address(&[CONFIG_SEED, &config.bump], &ID)?;
+        if &config_key != self.accounts.config.key() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // Check if the signer may claim
+        if &config.authority != self.accounts.authority.key() {
+            return Err(EscrowError::InvalidAuthority.into());
+        }
+
+        // Prepare signer seeds
+        let bump_bytes = config.bump;
+        let signer_seeds = seeds!(CONFIG_SEED, bump_bytes.as_ref());
+        let signer = Signer::from(&signer_seeds);
+
+        // Sweep the whole fee vault to the destination
+        let amount = TokenInterface::amount(self.accounts.fee_vault)?;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        TokenInterface::transfer_checked(
+            self.accounts.fee_vault,
+            self.accounts.mint,
+            self.accounts.destination,
+            self.accounts.config,
+            amount,
+            MintInterface::decimals(self.accounts.mint)?,
+            self.accounts.token_program,
+            &[signer],
+        )?;
+
+        Ok(())
+    }
+}
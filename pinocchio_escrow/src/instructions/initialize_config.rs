@@ -0,0 +1,155 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    seeds,
+    sysvars::Sysvar,
+    ProgramResult,
+};
+use pinocchio_system::instructions::CreateAccount;
+use shank::ShankType;
+
+use crate::{
+    errors::EscrowError,
+    helpers::{SignerAccount, BPF_LOADER_UPGRADEABLE_ID},
+    state::Config,
+    CONFIG_SEED, ID,
+};
+
+/// InitializeConfig accounts structure
+pub struct InitializeConfigAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub program_data: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, program_data, system_program, _remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        SignerAccount::check(authority)?;
+
+        // Only the program's upgrade authority may set up the config, so nobody
+        // can front-run the deployment and claim the fees
+        let (program_data_key, _) = find_program_address(&[ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID);
+        if program_data.key() != &program_data_key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // ProgramData: tag (4) + slot (8) + Option<upgrade authority> (1 + 32)
+        let data = program_data.try_borrow_data()?;
+        if data.len() < 45 || data[12] != 1 || &data[13..45] != authority.key().as_ref() {
+            return Err(EscrowError::InvalidAuthority.into());
+        }
+
+        Ok(Self {
+            authority,
+            config,
+            program_data,
+            system_program,
+        })
+    }
+}
+
+/// InitializeConfig instruction data
+#[derive(ShankType)]
+pub struct InitializeConfigInstructionData {
+    /// Protocol fee in basis points of the Token B leg of each fill
+    pub fee_bps: u16,
+    /// Share of the protocol fee, in basis points, paid to a Take's referrer
+    pub referral_bps: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitializeConfigInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_bps = u16::from_le_bytes(data[0..2].try_into().unwrap());
+        let referral_bps = u16::from_le_bytes(data[2..4].try_into().unwrap());
+
+        // Instruction checks
+        if fee_bps > Config::MAX_FEE_BPS || referral_bps > Config::MAX_REFERRAL_BPS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            fee_bps,
+            referral_bps,
+        })
+    }
+}
+
+/// InitializeConfig instruction - creates the program config PDA
+pub struct InitializeConfig<'a> {
+    pub accounts: InitializeConfigAccounts<'a>,
+    pub instruction_data: InitializeConfigInstructionData,
+    pub bump: u8,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for InitializeConfig<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = InitializeConfigAccounts::try_from(accounts)?;
+        let instruction_data = InitializeConfigInstructionData::try_from(data)?;
+
+        // Derive config PDA and get bump
+        let (_, bump) = find_program_address(&[CONFIG_SEED], &ID);
+
+        let bump_bytes = [bump];
+        let signer_seeds = seeds!(CONFIG_SEED, bump_bytes.as_ref());
+        let signer = Signer::from(&signer_seeds);
+
+        // Get rent
+        let rent = pinocchio::sysvars::rent::Rent::get()?;
+
+        // Initialize the config account
+        CreateAccount {
+            from: accounts.authority,
+            to: accounts.config,
+            lamports: rent.minimum_balance(Config::LEN),
+            space: Config::LEN as u64,
+            owner: &ID,
+        }
+        .invoke_signed(&[signer])?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            bump,
+        })
+    }
+}
+
+impl<'a> InitializeConfig<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &8;
+
+    /// Process the initialize config instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let mut data = self.accounts.config.try_borrow_mut_data()?;
+        let config = Config::load_uninit_mut(data.as_mut())?;
+
+        config.set_inner(
+            self.instruction_data.fee_bps,
+            self.instruction_data.referral_bps,
+            *self.accounts.authority.key(),
+            [self.bump],
+        );
+
+        Ok(())
+    }
+}
@@ -4,24 +4,31 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::Signer,
     program_error::ProgramError,
-    pubkey::find_program_address,
+    pubkey::{find_program_address, Pubkey},
     seeds,
-    sysvars::Sysvar,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_associated_token_account::instructions::Create;
 use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::instructions::Transfer;
+use shank::ShankType;
 
 use crate::{
-    helpers::{AssociatedTokenAccount, MintInterface, SignerAccount},
-    state::Escrow,
-    ESCROW_SEED, ID,
+    errors::EscrowError,
+    events::EscrowMade,
+    helpers::{AssociatedTokenAccount, MintInterface, SignerAccount, TokenInterface},
+    state::{Escrow, MakerCounter},
+    ESCROW_SEED, ID, MAKER_SEED,
 };
 
 /// Make accounts structure
+///
+/// Rent for the escrow, the vault and the maker counter comes from the maker
+/// unless a separate signer is passed after `maker_counter`; the maker then
+/// only authorizes the Token A transfer. Rent still goes back to the maker when
+/// the escrow closes.
 pub struct MakeAccounts<'a> {
     pub maker: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
     pub escrow: &'a AccountInfo,
     pub mint_a: &'a AccountInfo,
     pub mint_b: &'a AccountInfo,
@@ -30,26 +37,35 @@ pub struct MakeAccounts<'a> {
     pub system_program: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
     pub associated_token_program: &'a AccountInfo,
+    pub maker_counter: &'a AccountInfo,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for MakeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [maker, escrow, mint_a, mint_b, maker_ata_a, vault, system_program, token_program, associated_token_program, _remaining @ ..] =
+        let [maker, escrow, mint_a, mint_b, maker_ata_a, vault, system_program, token_program, associated_token_program, maker_counter, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // A relayer may sponsor the rent; otherwise the maker pays
+        let payer = match remaining {
+            [payer, ..] => payer,
+            [] => maker,
+        };
+
         // Basic account checks
         SignerAccount::check(maker)?;
-        MintInterface::check(mint_a)?;
-        MintInterface::check(mint_b)?;
+        SignerAccount::check(payer)?;
+        MintInterface::check(mint_a, token_program)?;
+        MintInterface::check(mint_b, token_program)?;
         AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
 
         Ok(Self {
             maker,
+            payer,
             escrow,
             mint_a,
             mint_b,
@@ -58,31 +74,48 @@ impl<'a> TryFrom<&'a [AccountInfo]> for MakeAccounts<'a> {
             system_program,
             token_program,
             associated_token_program,
+            maker_counter,
         })
     }
 }
 
 /// Make instruction data
+#[derive(ShankType)]
 pub struct MakeInstructionData {
     pub seed: u64,
     pub receive: u64,
     pub amount: u64,
+    pub expiry: i64,
+    /// Designated taker, or all zeros to let anyone take the offer
+    pub taker: Pubkey,
+    /// SHA-256 hash Take must supply the preimage of, or all zeros for no hashlock
+    pub hashlock: [u8; 32],
+    /// Reject mints with a freeze authority (1) or allow them (0)
+    pub reject_freezable: bool,
 }
 
 impl<'a> TryFrom<&'a [u8]> for MakeInstructionData {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() != size_of::<u64>() * 3 {
+        if data.len() != size_of::<u64>() * 3 + size_of::<i64>() + size_of::<Pubkey>() + 32 + 1 {
             return Err(ProgramError::InvalidInstructionData);
         }
 
         let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
         let receive = u64::from_le_bytes(data[8..16].try_into().unwrap());
         let amount = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let expiry = i64::from_le_bytes(data[24..32].try_into().unwrap());
+        let taker: Pubkey = data[32..64].try_into().unwrap();
+        let hashlock: [u8; 32] = data[64..96].try_into().unwrap();
+        let reject_freezable = match data[96] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
 
         // Instruction checks
-        if amount == 0 {
+        if expiry != 0 && expiry <= Clock::get()?.unix_timestamp {
             return Err(ProgramError::InvalidInstructionData);
         }
 
@@ -90,10 +123,37 @@ impl<'a> TryFrom<&'a [u8]> for MakeInstructionData {
             seed,
             receive,
             amount,
+            expiry,
+            taker,
+            hashlock,
+            reject_freezable,
         })
     }
 }
 
+impl MakeInstructionData {
+    /// Largest `amount` or `receive` accepted, leaving headroom below
+    /// `u64::MAX` for the fee and pro-rata math at Take
+    pub const MAX_AMOUNT: u64 = i64::MAX as u64;
+
+    /// Reject terms that would create an escrow Take can't settle sensibly
+    pub fn validate(&self, mint_a: &Pubkey, mint_b: &Pubkey) -> Result<(), ProgramError> {
+        if self.amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+        if self.receive == 0 {
+            return Err(EscrowError::InvalidReceive.into());
+        }
+        if self.amount > Self::MAX_AMOUNT || self.receive > Self::MAX_AMOUNT {
+            return Err(EscrowError::AmountTooLarge.into());
+        }
+        if mint_a == mint_b {
+            return Err(EscrowError::SameMint.into());
+        }
+        Ok(())
+    }
+}
+
 /// Make instruction - creates an escrow offer
 pub struct Make<'a> {
     pub accounts: MakeAccounts<'a>,
@@ -107,19 +167,27 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Make<'a> {
     fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
         let accounts = MakeAccounts::try_from(accounts)?;
         let instruction_data = MakeInstructionData::try_from(data)?;
+        instruction_data.validate(accounts.mint_a.key(), accounts.mint_b.key())?;
+
+        // Optionally refuse mints whose freeze authority could lock the vault
+        // or the taker's tokens
+        if instruction_data.reject_freezable
+            && (MintInterface::has_freeze_authority(accounts.mint_a)?
+                || MintInterface::has_freeze_authority(accounts.mint_b)?)
+        {
+            return Err(EscrowError::FreezableMint.into());
+        }
 
         // Derive escrow PDA and get bump
-        let (_, bump) = find_program_address(
-            &[
-                ESCROW_SEED,
-                accounts.maker.key().as_ref(),
-                &instruction_data.seed.to_le_bytes(),
-            ],
-            &ID,
-        );
+        let seed_bytes = instruction_data.seed.to_le_bytes();
+        let escrow_seeds =
+            solana_challenge_common::seeds::escrow(accounts.maker.key(), &seed_bytes);
+        let (escrow_key, bump) = find_program_address(&escrow_seeds, &ID);
+        if &escrow_key != accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
 
         // Prepare seeds for PDA initialization
-        let seed_bytes = instruction_data.seed.to_le_bytes();
         let bump_bytes = [bump];
         let signer_seeds = seeds!(
             ESCROW_SEED,
@@ -134,7 +202,7 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Make<'a> {
 
         // Initialize the escrow account
         CreateAccount {
-            from: accounts.maker,
+            from: accounts.payer,
             to: accounts.escrow,
             lamports: rent.minimum_balance(Escrow::LEN),
             space: Escrow::LEN as u64,
@@ -142,16 +210,55 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Make<'a> {
         }
         .invoke_signed(&[signer])?;
 
-        // Initialize the vault via ATA program CPI
-        Create {
-            funding_account: accounts.maker,
-            account: accounts.vault,
-            wallet: accounts.escrow,
-            mint: accounts.mint_a,
-            system_program: accounts.system_program,
-            token_program: accounts.token_program,
+        // An existing token account the escrow PDA already owns (e.g. a
+        // Token-2022 account with extensions) serves as the vault as is;
+        // otherwise create the escrow's ATA via ATA program CPI
+        if accounts.vault.owner() == accounts.token_program.key() {
+            TokenInterface::check_account(
+                accounts.vault,
+                accounts.escrow.key(),
+                accounts.mint_a.key(),
+                accounts.token_program,
+            )
+            .map_err(|_| EscrowError::InvalidVault)?;
+        } else {
+            AssociatedTokenAccount::init(
+                accounts.vault,
+                accounts.mint_a,
+                accounts.payer,
+                accounts.escrow,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
+        }
+
+        // Create the maker's counter on their first escrow
+        if accounts.maker_counter.owner() != &ID {
+            let (counter_key, counter_bump) =
+                find_program_address(&[MAKER_SEED, accounts.maker.key().as_ref()], &ID);
+            if &counter_key != accounts.maker_counter.key() {
+                return Err(ProgramError::InvalidSeeds);
+            }
+
+            let counter_bump_bytes = [counter_bump];
+            let counter_seeds = seeds!(
+                MAKER_SEED,
+                accounts.maker.key().as_ref(),
+                counter_bump_bytes.as_ref()
+            );
+
+            CreateAccount {
+                from: accounts.payer,
+                to: accounts.maker_counter,
+                lamports: rent.minimum_balance(MakerCounter::LEN),
+                space: MakerCounter::LEN as u64,
+                owner: &ID,
+            }
+            .invoke_signed(&[Signer::from(&counter_seeds)])?;
+
+            let mut data = accounts.maker_counter.try_borrow_mut_data()?;
+            MakerCounter::load_uninit_mut(data.as_mut())?.set_inner(counter_bump_bytes);
         }
-        .invoke()?;
 
         Ok(Self {
             accounts,
@@ -169,7 +276,7 @@ impl<'a> Make<'a> {
     pub fn process(&mut self) -> ProgramResult {
         // Populate the escrow account
         let mut data = self.accounts.escrow.try_borrow_mut_data()?;
-        let escrow = Escrow::load_mut(data.as_mut())?;
+        let escrow = Escrow::load_uninit_mut(data.as_mut())?;
 
         escrow.set_inner(
             self.instruction_data.seed,
@@ -177,17 +284,40 @@ impl<'a> Make<'a> {
             *self.accounts.mint_a.key(),
             *self.accounts.mint_b.key(),
             self.instruction_data.receive,
+            self.instruction_data.amount,
+            self.instruction_data.expiry,
+            self.instruction_data.taker,
+            self.instruction_data.hashlock,
             [self.bump],
         );
 
         // Transfer tokens to vault
-        Transfer {
-            from: self.accounts.maker_ata_a,
-            to: self.accounts.vault,
-            authority: self.accounts.maker,
+        TokenInterface::transfer_checked(
+            self.accounts.maker_ata_a,
+            self.accounts.mint_a,
+            self.accounts.vault,
+            self.accounts.maker,
+            self.instruction_data.amount,
+            MintInterface::decimals(self.accounts.mint_a)?,
+            self.accounts.token_program,
+            &[],
+        )?;
+
+        // List the escrow under its maker
+        let mut counter_data = self.accounts.maker_counter.try_borrow_mut_data()?;
+        let counter = MakerCounter::load_mut(counter_data.as_mut())?;
+        counter.check_address(self.accounts.maker_counter.key(), self.accounts.maker.key())?;
+        counter.record_open(self.instruction_data.seed);
+
+        EscrowMade {
+            seed: self.instruction_data.seed,
+            maker: self.accounts.maker.key(),
+            mint_a: self.accounts.mint_a.key(),
+            mint_b: self.accounts.mint_b.key(),
             amount: self.instruction_data.amount,
+            receive: self.instruction_data.receive,
         }
-        .invoke()?;
+        .emit();
 
         Ok(())
     }
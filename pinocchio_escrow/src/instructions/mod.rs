@@ -1,7 +1,143 @@
+use shank::ShankInstruction;
+
+mod batch_refund;
+mod claim_fees;
+mod initialize_config;
 mod make;
+mod read_escrow;
+mod reduce_deposit;
 mod refund;
 mod take;
+mod transfer_escrow;
+mod update_receive;
 
-pub use make::Make;
+pub use batch_refund::BatchRefund;
+pub use claim_fees::ClaimFees;
+pub use initialize_config::{InitializeConfig, InitializeConfigInstructionData};
+pub use make::{Make, MakeInstructionData};
+pub use read_escrow::ReadEscrow;
+pub use reduce_deposit::{ReduceDeposit, ReduceDepositInstructionData};
 pub use refund::Refund;
-pub use take::Take;
+pub use take::{Take, TakeInstructionData};
+pub use transfer_escrow::{TransferEscrow, TransferEscrowInstructionData};
+pub use update_receive::{UpdateReceive, UpdateReceiveInstructionData};
+
+/// Instruction layout for shank IDL generation (`shank idl`). Variant order
+/// matches the discriminators; dispatch itself happens in `process_instruction`.
+///
+/// Discriminators follow anchor_escrow's: 0-2 and 8 are the same instructions,
+/// the ones only anchor_escrow has are reserved, and the ones only this
+/// program has start at 10.
+#[derive(ShankInstruction)]
+#[rustfmt::skip]
+pub enum EscrowInstruction {
+    /// Create an escrow offer
+    #[account(0, writable, signer, name = "maker", desc = "Maker funding the offer")]
+    #[account(1, writable, name = "escrow", desc = "Escrow PDA [\"escrow\", maker, seed]; the maker is recorded as its creator")]
+    #[account(2, name = "mint_a", desc = "Mint of the deposited token")]
+    #[account(3, name = "mint_b", desc = "Mint of the requested token")]
+    #[account(4, writable, name = "maker_ata_a", desc = "Maker's Token A ATA")]
+    #[account(5, writable, name = "vault", desc = "Escrow's Token A ATA")]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, name = "token_program", desc = "Token or Token-2022 program")]
+    #[account(8, name = "associated_token_program", desc = "Associated token program")]
+    #[account(9, writable, name = "maker_counter", desc = "Maker counter PDA [\"maker\", maker]; created on first use")]
+    #[account(10, optional, writable, signer, name = "payer", desc = "Funds the escrow, vault and counter rent instead of the maker")]
+    Make(MakeInstructionData),
+
+    /// Fill an escrow offer, fully or in part
+    #[account(0, writable, signer, name = "taker", desc = "Taker filling the offer")]
+    #[account(1, writable, name = "maker", desc = "Maker of the offer")]
+    #[account(2, writable, name = "escrow", desc = "Escrow PDA")]
+    #[account(3, name = "mint_a", desc = "Mint of the deposited token")]
+    #[account(4, name = "mint_b", desc = "Mint of the requested token")]
+    #[account(5, writable, name = "vault", desc = "Escrow's Token A ATA")]
+    #[account(6, writable, name = "taker_ata_a", desc = "Taker's Token A ATA")]
+    #[account(7, writable, name = "taker_ata_b", desc = "Token B account the taker owns or is the delegate of; unused when Token B is native SOL")]
+    #[account(8, writable, name = "maker_ata_b", desc = "Maker's Token B ATA; unused when Token B is native SOL")]
+    #[account(9, name = "system_program", desc = "System program")]
+    #[account(10, name = "token_program", desc = "Token or Token-2022 program")]
+    #[account(11, name = "associated_token_program", desc = "Associated token program")]
+    #[account(12, name = "config", desc = "Program config PDA [\"config\"]")]
+    #[account(13, writable, name = "fee_vault", desc = "Config's Token B ATA collecting the protocol fee")]
+    #[account(14, writable, name = "maker_counter", desc = "Maker counter PDA [\"maker\", escrow.creator]")]
+    #[account(15, optional, writable, name = "referrer", desc = "Token B (wSOL for native SOL) account paid the referral share of the fee")]
+    Take(TakeInstructionData),
+
+    /// Cancel an escrow offer
+    #[account(0, writable, name = "maker", desc = "Maker of the offer; signs unless refunding after expiry")]
+    #[account(1, writable, name = "escrow", desc = "Escrow PDA")]
+    #[account(2, name = "mint_a", desc = "Mint of the deposited token")]
+    #[account(3, writable, name = "vault", desc = "Escrow's Token A ATA")]
+    #[account(4, writable, name = "maker_ata_a", desc = "Maker's Token A ATA")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(6, name = "token_program", desc = "Token or Token-2022 program")]
+    #[account(7, writable, name = "maker_counter", desc = "Maker counter PDA [\"maker\", escrow.creator]")]
+    #[account(8, optional, writable, signer, name = "payer", desc = "Third party refunding an expired escrow")]
+    Refund,
+
+    /// Reserved: anchor_escrow's `migrate`, not implemented here
+    Migrate,
+
+    /// Reserved: anchor_escrow's `take_via_amm`, not implemented here
+    TakeViaAmm,
+
+    /// Reserved: anchor_escrow's `create_auction`, not implemented here
+    CreateAuction,
+
+    /// Reserved: anchor_escrow's `bid`, not implemented here
+    Bid,
+
+    /// Reserved: anchor_escrow's `settle_auction`, not implemented here
+    SettleAuction,
+
+    /// Create the program config; only the upgrade authority may call it
+    #[account(0, writable, signer, name = "authority", desc = "Program upgrade authority, becomes the config authority")]
+    #[account(1, writable, name = "config", desc = "Program config PDA [\"config\"]")]
+    #[account(2, name = "program_data", desc = "This program's ProgramData account")]
+    #[account(3, name = "system_program", desc = "System program")]
+    InitializeConfig(InitializeConfigInstructionData),
+
+    /// Reserved: anchor_escrow's `update_config`, not implemented here
+    UpdateConfig,
+
+    /// Withdraw accrued protocol fees for one mint
+    #[account(0, signer, name = "authority", desc = "Config authority")]
+    #[account(1, name = "config", desc = "Program config PDA")]
+    #[account(2, name = "mint", desc = "Mint of the fees being claimed")]
+    #[account(3, writable, name = "fee_vault", desc = "Config's ATA for the mint")]
+    #[account(4, writable, name = "destination", desc = "Token account receiving the fees")]
+    #[account(5, name = "token_program", desc = "Token or Token-2022 program")]
+    ClaimFees,
+
+    /// Change the Token B asked for the rest of an open escrow
+    #[account(0, signer, name = "maker", desc = "Maker of the offer")]
+    #[account(1, writable, name = "escrow", desc = "Escrow PDA")]
+    UpdateReceive(UpdateReceiveInstructionData),
+
+    /// Refund several escrows of one maker; followed by (escrow, vault) pairs
+    #[account(0, writable, signer, name = "maker", desc = "Maker of the offers")]
+    #[account(1, name = "mint_a", desc = "Mint deposited by every refunded escrow")]
+    #[account(2, writable, name = "maker_ata_a", desc = "Maker's Token A ATA")]
+    #[account(3, name = "token_program", desc = "Token or Token-2022 program")]
+    #[account(4, writable, name = "maker_counter", desc = "Maker counter PDA [\"maker\", creator] shared by every refunded escrow")]
+    BatchRefund,
+
+    /// Hand an open escrow to a new maker
+    #[account(0, signer, name = "maker", desc = "Current maker of the offer")]
+    #[account(1, writable, name = "escrow", desc = "Escrow PDA [\"escrow\", escrow.creator, seed]")]
+    TransferEscrow(TransferEscrowInstructionData),
+
+    /// Withdraw part of an open escrow's deposit, lowering receive pro rata
+    #[account(0, signer, name = "maker", desc = "Maker of the offer")]
+    #[account(1, writable, name = "escrow", desc = "Escrow PDA")]
+    #[account(2, name = "mint_a", desc = "Mint of the deposited token")]
+    #[account(3, writable, name = "vault", desc = "Escrow's Token A ATA")]
+    #[account(4, writable, name = "maker_ata_a", desc = "Maker's Token A ATA")]
+    #[account(5, name = "token_program", desc = "Token or Token-2022 program")]
+    ReduceDeposit(ReduceDepositInstructionData),
+
+    /// Return an open escrow's terms as `EscrowTerms` return data
+    #[account(0, name = "escrow", desc = "Escrow PDA")]
+    ReadEscrow,
+}
@@ -0,0 +1,78 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::set_return_data,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+
+use crate::{
+    errors::EscrowError,
+    helpers::ProgramAccount,
+    state::Escrow,
+    ESCROW_SEED, ID,
+};
+
+/// ReadEscrow accounts structure
+pub struct ReadEscrowAccounts<'a> {
+    pub escrow: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReadEscrowAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [escrow, _remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        ProgramAccount::check(escrow)?;
+
+        Ok(Self { escrow })
+    }
+}
+
+/// ReadEscrow instruction - returns an escrow's terms as [`EscrowTerms`] return data
+///
+/// [`EscrowTerms`]: crate::state::EscrowTerms
+pub struct ReadEscrow<'a> {
+    pub accounts: ReadEscrowAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReadEscrow<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = ReadEscrowAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ReadEscrow<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &15;
+
+    /// Process the read escrow instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let escrow = Escrow::load_checked(self.accounts.escrow)?;
+
+        // Check if the escrow is valid
+        let escrow_key = create_program_address(
+            &[
+                ESCROW_SEED,
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump,
+            ],
+            &ID,
+        )?;
+        if &escrow_key != self.accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        set_return_data(escrow.terms().as_bytes());
+
+        Ok(())
+    }
+}
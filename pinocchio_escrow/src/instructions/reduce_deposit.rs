@@ -0,0 +1,192 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    seeds,
+    ProgramResult,
+};
+use shank::ShankType;
+
+use crate::{
+    errors::EscrowError,
+    events::EscrowReduced,
+    helpers::{
+        AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount, TokenInterface,
+    },
+    state::Escrow,
+    ESCROW_SEED, ID,
+};
+
+/// ReduceDeposit accounts structure
+pub struct ReduceDepositAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+    pub mint_a: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub maker_ata_a: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReduceDepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, escrow, mint_a, vault, maker_ata_a, token_program, _remaining @ ..] = accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        SignerAccount::check(maker)?;
+        ProgramAccount::check(escrow)?;
+        MintInterface::check(mint_a, token_program)?;
+        AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
+
+        Ok(Self {
+            maker,
+            escrow,
+            mint_a,
+            vault,
+            maker_ata_a,
+            token_program,
+        })
+    }
+}
+
+/// ReduceDeposit instruction data
+#[derive(ShankType)]
+pub struct ReduceDepositInstructionData {
+    /// Token A to withdraw from the vault; must leave some of the offer open
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ReduceDepositInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        // Instruction checks
+        if amount == 0 {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        Ok(Self { amount })
+    }
+}
+
+/// ReduceDeposit instruction - shrinks an open escrow offer, keeping its price
+pub struct ReduceDeposit<'a> {
+    pub accounts: ReduceDepositAccounts<'a>,
+    pub instruction_data: ReduceDepositInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ReduceDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ReduceDepositAccounts::try_from(accounts)?;
+        let instruction_data = ReduceDepositInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ReduceDeposit<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &14;
+
+    /// Process the reduce deposit instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let mut escrow = Escrow::load_checked_mut(self.accounts.escrow)?;
+
+        // Check if maker matches
+        if &escrow.maker != self.accounts.maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
+        }
+
+        // Check if the escrow is valid
+        let escrow_key = create_program_address(
+            &[
+                ESCROW_SEED,
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump,
+            ],
+            &ID,
+        )?;
+        if &escrow_key != self.accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        // Check the maker withdraws the deposited mint
+        if &escrow.mint_a != self.accounts.mint_a.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the vault is an escrow-owned Token A account
+        TokenInterface::check_account(
+            self.accounts.vault,
+            self.accounts.escrow.key(),
+            &escrow.mint_a,
+            self.accounts.token_program,
+        )
+        .map_err(|_| EscrowError::InvalidVault)?;
+
+        // Scale receive down with the deposit; withdrawing everything is a Refund
+        let amount = self.instruction_data.amount;
+        let receive = escrow.receive_after_reduce(amount)?;
+        let remaining = escrow.remaining() - amount;
+        escrow.set_receive(receive);
+        escrow.set_remaining(remaining);
+
+        // Release the escrow before the CPI it signs
+        let seed = escrow.seed();
+        let Escrow { creator, bump, .. } = *escrow;
+        drop(escrow);
+
+        // Prepare signer seeds
+        let seed_bytes = seed.to_le_bytes();
+        let signer_seeds = seeds!(
+            ESCROW_SEED,
+            creator.as_ref(),
+            seed_bytes.as_ref(),
+            bump.as_ref()
+        );
+        let signer = Signer::from(&signer_seeds);
+
+        // Transfer the withdrawn Token A back to the maker
+        TokenInterface::transfer_checked(
+            self.accounts.vault,
+            self.accounts.mint_a,
+            self.accounts.maker_ata_a,
+            self.accounts.escrow,
+            amount,
+            MintInterface::decimals(self.accounts.mint_a)?,
+            self.accounts.token_program,
+            &[signer],
+        )?;
+
+        EscrowReduced {
+            seed,
+            maker: self.accounts.maker.key(),
+            mint_a: self.accounts.mint_a.key(),
+            amount,
+            remaining,
+            receive,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
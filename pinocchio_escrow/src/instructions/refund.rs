@@ -4,69 +4,86 @@ use pinocchio::{
     program_error::ProgramError,
     pubkey::create_program_address,
     seeds,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_associated_token_account::instructions::CreateIdempotent;
-use pinocchio_token::{
-    instructions::{CloseAccount, Transfer},
-    state::TokenAccount,
-};
 
 use crate::{
-    helpers::{AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount},
-    state::Escrow,
+    errors::EscrowError,
+    events::EscrowRefunded,
+    helpers::{
+        AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount, TokenInterface,
+    },
+    state::{Escrow, MakerCounter},
     ESCROW_SEED, ID,
 };
 
 /// Refund accounts structure
+///
+/// The maker normally signs. Once the escrow has expired anyone may refund on
+/// the maker's behalf by passing themselves as an extra signer after
+/// `maker_counter`; they then fund the maker's ATA if it is missing.
 pub struct RefundAccounts<'a> {
     pub maker: &'a AccountInfo,
+    pub payer: &'a AccountInfo,
     pub escrow: &'a AccountInfo,
     pub mint_a: &'a AccountInfo,
     pub vault: &'a AccountInfo,
     pub maker_ata_a: &'a AccountInfo,
     pub system_program: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
+    pub maker_counter: &'a AccountInfo,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for RefundAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, _remaining @ ..] =
+        let [maker, escrow, mint_a, vault, maker_ata_a, system_program, token_program, maker_counter, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
+        // Either the maker signs, or a third party refunding an expired escrow does
+        let payer = if maker.is_signer() {
+            maker
+        } else {
+            let [payer, ..] = remaining else {
+                return Err(ProgramError::MissingRequiredSignature);
+            };
+            payer
+        };
+
         // Basic account checks
-        SignerAccount::check(maker)?;
+        SignerAccount::check(payer)?;
         ProgramAccount::check(escrow)?;
-        MintInterface::check(mint_a)?;
-        AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+        ProgramAccount::check(maker_counter)?;
+        MintInterface::check(mint_a, token_program)?;
 
         // 确保 maker 的 ATA 存在（不存在时自动创建）
-        CreateIdempotent {
-            funding_account: maker,
-            account: maker_ata_a,
-            wallet: maker,
-            mint: mint_a,
+        AssociatedTokenAccount::init_if_needed(
+            maker_ata_a,
+            mint_a,
+            payer,
+            maker,
             system_program,
             token_program,
-        }
-        .invoke()?;
+        )?;
 
         // 再次校验 maker ATA 的归属与派生地址
         AssociatedTokenAccount::check(maker_ata_a, maker, mint_a, token_program)?;
 
         Ok(Self {
             maker,
+            payer,
             escrow,
             mint_a,
             vault,
             maker_ata_a,
             system_program,
             token_program,
+            maker_counter,
         })
     }
 }
@@ -91,61 +108,100 @@ impl<'a> Refund<'a> {
 
     /// Process the refund instruction
     pub fn process(&mut self) -> ProgramResult {
-        let data = self.accounts.escrow.try_borrow_data()?;
-        let escrow = Escrow::load(&data)?;
+        let escrow = Escrow::load_checked(self.accounts.escrow)?;
 
         // Check if maker matches
         if &escrow.maker != self.accounts.maker.key() {
-            return Err(ProgramError::IllegalOwner);
+            return Err(EscrowError::MakerMismatch.into());
         }
 
         // Check if the escrow is valid
         let escrow_key = create_program_address(
             &[
                 ESCROW_SEED,
-                self.accounts.maker.key(),
-                &escrow.seed.to_le_bytes(),
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
                 &escrow.bump,
             ],
             &ID,
         )?;
         if &escrow_key != self.accounts.escrow.key() {
-            return Err(ProgramError::InvalidAccountOwner);
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        // Check the vault is an escrow-owned Token A account
+        TokenInterface::check_account(
+            self.accounts.vault,
+            self.accounts.escrow.key(),
+            &escrow.mint_a,
+            self.accounts.token_program,
+        )
+        .map_err(|_| EscrowError::InvalidVault)?;
+
+        // Only the maker may refund before expiry
+        if !self.accounts.maker.is_signer() && !escrow.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // Release the escrow before the CPIs it signs
+        let seed = escrow.seed();
+        let Escrow {
+            creator,
+            mint_a,
+            bump,
+            ..
+        } = *escrow;
+        drop(escrow);
+
         // Prepare signer seeds
-        let seed_bytes = escrow.seed.to_le_bytes();
-        let bump_bytes = escrow.bump;
+        let seed_bytes = seed.to_le_bytes();
         let signer_seeds = seeds!(
             ESCROW_SEED,
-            self.accounts.maker.key().as_ref(),
+            creator.as_ref(),
             seed_bytes.as_ref(),
-            bump_bytes.as_ref()
+            bump.as_ref()
         );
         let signer = Signer::from(&signer_seeds);
 
         // Get vault balance
-        let amount = TokenAccount::from_account_info(self.accounts.vault)?.amount();
+        let amount = TokenInterface::amount(self.accounts.vault)?;
 
         // Transfer from vault back to maker
-        Transfer {
-            from: self.accounts.vault,
-            to: self.accounts.maker_ata_a,
-            authority: self.accounts.escrow,
+        TokenInterface::transfer_checked(
+            self.accounts.vault,
+            self.accounts.mint_a,
+            self.accounts.maker_ata_a,
+            self.accounts.escrow,
             amount,
-        }
-        .invoke_signed(&[signer.clone()])?;
+            MintInterface::decimals(self.accounts.mint_a)?,
+            self.accounts.token_program,
+            &[signer.clone()],
+        )?;
 
         // Close the vault
-        CloseAccount {
-            account: self.accounts.vault,
-            destination: self.accounts.maker,
-            authority: self.accounts.escrow,
+        TokenInterface::close_account(
+            self.accounts.vault,
+            self.accounts.maker,
+            self.accounts.escrow,
+            self.accounts.token_program,
+            &[signer.clone()],
+        )?;
+
+        EscrowRefunded {
+            seed,
+            maker: self.accounts.maker.key(),
+            mint_a: &mint_a,
+            amount,
         }
-        .invoke_signed(&[signer.clone()])?;
+        .emit();
+
+        // Drop the escrow from its maker's list
+        let mut counter_data = self.accounts.maker_counter.try_borrow_mut_data()?;
+        let counter = MakerCounter::load_mut(counter_data.as_mut())?;
+        counter.check_address(self.accounts.maker_counter.key(), &creator)?;
+        counter.record_close(seed);
 
         // Close the escrow
-        drop(data);
         ProgramAccount::close(self.accounts.escrow, self.accounts.maker)?;
 
         Ok(())
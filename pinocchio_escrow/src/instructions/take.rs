@@ -4,21 +4,35 @@ use pinocchio::{
     program_error::ProgramError,
     pubkey::create_program_address,
     seeds,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_associated_token_account::instructions::CreateIdempotent;
-use pinocchio_token::{
-    instructions::{CloseAccount, Transfer},
-    state::TokenAccount,
-};
+use pinocchio_system::instructions::Transfer;
+use shank::ShankType;
 
 use crate::{
-    helpers::{AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount},
-    state::Escrow,
-    ESCROW_SEED, ID,
+    errors::EscrowError,
+    events::EscrowTaken,
+    helpers::{
+        AssociatedTokenAccount, MintInterface, ProgramAccount, SignerAccount, TokenInterface,
+        NATIVE_MINT,
+    },
+    state::{Config, Escrow, MakerCounter},
+    CONFIG_SEED, ESCROW_SEED, ID,
 };
 
 /// Take accounts structure
+///
+/// `taker_ata_b` may be any Token B account the taker owns or is the delegate
+/// of, so a bot can fill offers from a limited allowance it was approved for.
+///
+/// `maker_ata_a` receives whatever is left in the vault after the final fill
+/// (tokens sent to it directly, or a balance a reused vault already held),
+/// and is only created when there is such a surplus.
+///
+/// A frontend can pass its own Token B account after `maker_ata_a` to be
+/// paid `Config::referral_bps` of the protocol fee. For native SOL this must be
+/// a wSOL account, as the share is wrapped like the fee.
 pub struct TakeAccounts<'a> {
     pub taker: &'a AccountInfo,
     pub maker: &'a AccountInfo,
@@ -32,13 +46,18 @@ pub struct TakeAccounts<'a> {
     pub system_program: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
     pub associated_token_program: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub fee_vault: &'a AccountInfo,
+    pub maker_counter: &'a AccountInfo,
+    pub maker_ata_a: &'a AccountInfo,
+    pub referrer: Option<&'a AccountInfo>,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for TakeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [taker, maker, escrow, mint_a, mint_b, vault, taker_ata_a, taker_ata_b, maker_ata_b, system_program, token_program, associated_token_program, _remaining @ ..] =
+        let [taker, maker, escrow, mint_a, mint_b, vault, taker_ata_a, taker_ata_b, maker_ata_b, system_program, token_program, associated_token_program, config, fee_vault, maker_counter, maker_ata_a, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -47,10 +66,17 @@ impl<'a> TryFrom<&'a [AccountInfo]> for TakeAccounts<'a> {
         // Basic account checks
         SignerAccount::check(taker)?;
         ProgramAccount::check(escrow)?;
-        MintInterface::check(mint_a)?;
-        MintInterface::check(mint_b)?;
-        AssociatedTokenAccount::check(taker_ata_b, taker, mint_b, token_program)?;
-        AssociatedTokenAccount::check(vault, escrow, mint_a, token_program)?;
+        ProgramAccount::check(config)?;
+        ProgramAccount::check(maker_counter)?;
+        MintInterface::check(mint_a, token_program)?;
+        MintInterface::check(mint_b, token_program)?;
+
+        // A native SOL leg is paid in lamports, so the taker needs no wSOL
+        if mint_b.key() != &NATIVE_MINT
+            && !TokenInterface::is_spender(taker_ata_b, taker.key(), mint_b.key(), token_program)?
+        {
+            return Err(EscrowError::InvalidTokenAuthority.into());
+        }
 
         Ok(Self {
             taker,
@@ -65,44 +91,94 @@ impl<'a> TryFrom<&'a [AccountInfo]> for TakeAccounts<'a> {
             system_program,
             token_program,
             associated_token_program,
+            config,
+            fee_vault,
+            maker_counter,
+            maker_ata_a,
+            referrer: remaining.first(),
         })
     }
 }
 
-/// Take instruction - accepts an escrow offer
+/// Take instruction data
+#[derive(ShankType)]
+pub struct TakeInstructionData {
+    /// Amount of Token A to fill (at most the escrow's remaining amount)
+    pub amount: u64,
+    /// Preimage of the escrow's hashlock; may be omitted (zeros) for unlocked escrows
+    pub preimage: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for TakeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let preimage: [u8; 32] = match data.len() {
+            8 => [0; 32],
+            40 => data[8..40].try_into().unwrap(),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        // Instruction checks
+        if amount == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { amount, preimage })
+    }
+}
+
+/// Take instruction - fills an escrow offer, fully or in part
 pub struct Take<'a> {
     pub accounts: TakeAccounts<'a>,
+    pub instruction_data: TakeInstructionData,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo]> for Take<'a> {
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Take<'a> {
     type Error = ProgramError;
 
-    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
         let accounts = TakeAccounts::try_from(accounts)?;
+        let instruction_data = TakeInstructionData::try_from(data)?;
 
         // Initialize taker's Token A account if needed
-        CreateIdempotent {
-            funding_account: accounts.taker,
-            account: accounts.taker_ata_a,
-            wallet: accounts.taker,
-            mint: accounts.mint_a,
-            system_program: accounts.system_program,
-            token_program: accounts.token_program,
-        }
-        .invoke()?;
+        AssociatedTokenAccount::init_if_needed(
+            accounts.taker_ata_a,
+            accounts.mint_a,
+            accounts.taker,
+            accounts.taker,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
 
         // Initialize maker's Token B account if needed
-        CreateIdempotent {
-            funding_account: accounts.taker,
-            account: accounts.maker_ata_b,
-            wallet: accounts.maker,
-            mint: accounts.mint_b,
-            system_program: accounts.system_program,
-            token_program: accounts.token_program,
+        if accounts.mint_b.key() != &NATIVE_MINT {
+            AssociatedTokenAccount::init_if_needed(
+                accounts.maker_ata_b,
+                accounts.mint_b,
+                accounts.taker,
+                accounts.maker,
+                accounts.system_program,
+                accounts.token_program,
+            )?;
         }
-        .invoke()?;
 
-        Ok(Self { accounts })
+        // Initialize the config's Token B fee vault if needed
+        AssociatedTokenAccount::init_if_needed(
+            accounts.fee_vault,
+            accounts.mint_b,
+            accounts.taker,
+            accounts.config,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
     }
 }
 
@@ -112,67 +188,273 @@ impl<'a> Take<'a> {
 
     /// Process the take instruction
     pub fn process(&mut self) -> ProgramResult {
-        let data = self.accounts.escrow.try_borrow_data()?;
-        let escrow = Escrow::load(&data)?;
+        let mut escrow = Escrow::load_checked_mut(self.accounts.escrow)?;
+
+        // Check if maker matches
+        if &escrow.maker != self.accounts.maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
+        }
 
         // Check if the escrow is valid
         let escrow_key = create_program_address(
             &[
                 ESCROW_SEED,
-                self.accounts.maker.key(),
-                &escrow.seed.to_le_bytes(),
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
                 &escrow.bump,
             ],
             &ID,
         )?;
         if &escrow_key != self.accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        // Check the taker pays in the requested mint
+        if &escrow.mint_b != self.accounts.mint_b.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Check the vault is an escrow-owned Token A account
+        TokenInterface::check_account(
+            self.accounts.vault,
+            self.accounts.escrow.key(),
+            &escrow.mint_a,
+            self.accounts.token_program,
+        )
+        .map_err(|_| EscrowError::InvalidVault)?;
+
+        // Check if the offer is reserved for another taker
+        if !escrow.can_take(self.accounts.taker.key()) {
+            return Err(EscrowError::InvalidTaker.into());
+        }
+
+        // Check if the taker knows the hashlock preimage
+        if !escrow.unlocks(&self.instruction_data.preimage) {
+            return Err(EscrowError::InvalidPreimage.into());
+        }
+
+        // Check if the offer is still open
+        if escrow.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(EscrowError::EscrowExpired.into());
+        }
+
+        // Check if the config is valid
+        let config_data = self.accounts.config.try_borrow_data()?;
+        let config = Config::load(&config_data)?;
+        let config_key = create_program_address(&[CONFIG_SEED, &config.bump], &ID)?;
+        if &config_key != self.accounts.config.key() {
             return Err(ProgramError::InvalidAccountOwner);
         }
 
+        // Work out the Token B owed for this fill at the escrow's price,
+        // the protocol's cut of it and the referrer's share of that cut
+        let amount = self.instruction_data.amount;
+        let receive = escrow.receive_for(amount)?;
+        let fee = config.fee_for(receive);
+        let referral = match self.accounts.referrer {
+            Some(_) => config.referral_for(fee),
+            None => 0,
+        };
+        drop(config_data);
+
+        // Record the fill; the price stays the same for the rest
+        let remaining = escrow.remaining() - amount;
+        escrow.set_remaining(remaining);
+        escrow.set_receive(escrow.receive() - receive);
+
+        // Release the escrow before the CPIs it signs
+        let seed = escrow.seed();
+        let Escrow {
+            creator,
+            mint_a,
+            mint_b,
+            bump,
+            ..
+        } = *escrow;
+        drop(escrow);
+
         // Prepare signer seeds
-        let seed_bytes = escrow.seed.to_le_bytes();
-        let bump_bytes = escrow.bump;
+        let seed_bytes = seed.to_le_bytes();
         let signer_seeds = seeds!(
             ESCROW_SEED,
-            self.accounts.maker.key().as_ref(),
+            creator.as_ref(),
             seed_bytes.as_ref(),
-            bump_bytes.as_ref()
+            bump.as_ref()
         );
         let signer = Signer::from(&signer_seeds);
 
-        // Get vault balance
-        let amount = TokenAccount::from_account_info(self.accounts.vault)?.amount();
-
         // Transfer from the Vault to the Taker
-        Transfer {
-            from: self.accounts.vault,
-            to: self.accounts.taker_ata_a,
-            authority: self.accounts.escrow,
+        let decimals = MintInterface::decimals(self.accounts.mint_a)?;
+        TokenInterface::transfer_checked(
+            self.accounts.vault,
+            self.accounts.mint_a,
+            self.accounts.taker_ata_a,
+            self.accounts.escrow,
             amount,
+            decimals,
+            self.accounts.token_program,
+            &[signer.clone()],
+        )?;
+
+        // Pay the Maker, the protocol fee and the referrer
+        self.pay(receive, fee, referral)?;
+
+        EscrowTaken {
+            seed,
+            maker: self.accounts.maker.key(),
+            taker: self.accounts.taker.key(),
+            mint_a: &mint_a,
+            mint_b: &mint_b,
+            amount,
+            receive,
+            fee,
+            remaining,
+            referral,
         }
-        .invoke_signed(&[signer.clone()])?;
+        .emit();
 
-        // Close the Vault
-        CloseAccount {
-            account: self.accounts.vault,
-            destination: self.accounts.maker,
-            authority: self.accounts.escrow,
+        // Keep the offer open until it is fully filled
+        if remaining > 0 {
+            return Ok(());
         }
-        .invoke_signed(&[signer.clone()])?;
 
-        // Transfer from the Taker to the Maker
-        Transfer {
-            from: self.accounts.taker_ata_b,
-            to: self.accounts.maker_ata_b,
-            authority: self.accounts.taker,
-            amount: escrow.receive,
+        // Return anything else in the Vault to the Maker, or it cannot be closed
+        let surplus = TokenInterface::amount(self.accounts.vault)?;
+        if surplus > 0 {
+            AssociatedTokenAccount::init_if_needed(
+                self.accounts.maker_ata_a,
+                self.accounts.mint_a,
+                self.accounts.taker,
+                self.accounts.maker,
+                self.accounts.system_program,
+                self.accounts.token_program,
+            )?;
+            AssociatedTokenAccount::check(
+                self.accounts.maker_ata_a,
+                self.accounts.maker,
+                self.accounts.mint_a,
+                self.accounts.token_program,
+            )?;
+            TokenInterface::transfer_checked(
+                self.accounts.vault,
+                self.accounts.mint_a,
+                self.accounts.maker_ata_a,
+                self.accounts.escrow,
+                surplus,
+                decimals,
+                self.accounts.token_program,
+                &[signer.clone()],
+            )?;
         }
-        .invoke()?;
+
+        // Close the Vault
+        TokenInterface::close_account(
+            self.accounts.vault,
+            self.accounts.maker,
+            self.accounts.escrow,
+            self.accounts.token_program,
+            &[signer.clone()],
+        )?;
+
+        // Drop the escrow from its maker's list
+        let mut counter_data = self.accounts.maker_counter.try_borrow_mut_data()?;
+        let counter = MakerCounter::load_mut(counter_data.as_mut())?;
+        counter.check_address(self.accounts.maker_counter.key(), &creator)?;
+        counter.record_close(seed);
 
         // Close the Escrow
-        drop(data);
         ProgramAccount::close(self.accounts.escrow, self.accounts.taker)?;
 
         Ok(())
     }
+
+    /// Move `receive` Token B from the taker: `receive - fee` to the maker,
+    /// `referral` of the fee to the referrer and the rest to the fee vault.
+    /// Native SOL is sent as lamports, with the fee and referral wrapped into
+    /// wSOL accounts so they can be swept like any token.
+    fn pay(&self, receive: u64, fee: u64, referral: u64) -> ProgramResult {
+        if fee > referral {
+            AssociatedTokenAccount::check(
+                self.accounts.fee_vault,
+                self.accounts.config,
+                self.accounts.mint_b,
+                self.accounts.token_program,
+            )?;
+        }
+        let referrer = self.accounts.referrer.filter(|_| referral > 0);
+
+        if self.accounts.mint_b.key() == &NATIVE_MINT {
+            Transfer {
+                from: self.accounts.taker,
+                to: self.accounts.maker,
+                lamports: receive - fee,
+            }
+            .invoke()?;
+
+            if fee > referral {
+                Transfer {
+                    from: self.accounts.taker,
+                    to: self.accounts.fee_vault,
+                    lamports: fee - referral,
+                }
+                .invoke()?;
+                TokenInterface::sync_native(self.accounts.fee_vault, self.accounts.token_program)?;
+            }
+
+            if let Some(referrer) = referrer {
+                Transfer {
+                    from: self.accounts.taker,
+                    to: referrer,
+                    lamports: referral,
+                }
+                .invoke()?;
+                TokenInterface::sync_native(referrer, self.accounts.token_program)?;
+            }
+
+            return Ok(());
+        }
+
+        // Transfer from the Taker to the Maker
+        let decimals = MintInterface::decimals(self.accounts.mint_b)?;
+        TokenInterface::transfer_checked(
+            self.accounts.taker_ata_b,
+            self.accounts.mint_b,
+            self.accounts.maker_ata_b,
+            self.accounts.taker,
+            receive - fee,
+            decimals,
+            self.accounts.token_program,
+            &[],
+        )?;
+
+        // Transfer the protocol's part of the fee from the Taker to the fee vault
+        if fee > referral {
+            TokenInterface::transfer_checked(
+                self.accounts.taker_ata_b,
+                self.accounts.mint_b,
+                self.accounts.fee_vault,
+                self.accounts.taker,
+                fee - referral,
+                decimals,
+                self.accounts.token_program,
+                &[],
+            )?;
+        }
+
+        // Transfer the referrer's part of the fee
+        if let Some(referrer) = referrer {
+            TokenInterface::transfer_checked(
+                self.accounts.taker_ata_b,
+                self.accounts.mint_b,
+                referrer,
+                self.accounts.taker,
+                referral,
+                decimals,
+                self.accounts.token_program,
+                &[],
+            )?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,127 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+    ProgramResult,
+};
+use shank::ShankType;
+
+use crate::{
+    errors::EscrowError,
+    events::EscrowTransferred,
+    helpers::{ProgramAccount, SignerAccount},
+    state::Escrow,
+    ESCROW_SEED, ID,
+};
+
+/// TransferEscrow accounts structure
+pub struct TransferEscrowAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for TransferEscrowAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, escrow, _remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        SignerAccount::check(maker)?;
+        ProgramAccount::check(escrow)?;
+
+        Ok(Self { maker, escrow })
+    }
+}
+
+/// TransferEscrow instruction data
+#[derive(ShankType)]
+pub struct TransferEscrowInstructionData {
+    /// Wallet taking over refund rights and the Token B proceeds
+    pub new_maker: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for TransferEscrowInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<Pubkey>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let new_maker: Pubkey = data[0..32].try_into().unwrap();
+
+        // Instruction checks
+        if new_maker == Pubkey::default() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { new_maker })
+    }
+}
+
+/// TransferEscrow instruction - hands an open escrow offer to a new maker
+pub struct TransferEscrow<'a> {
+    pub accounts: TransferEscrowAccounts<'a>,
+    pub instruction_data: TransferEscrowInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for TransferEscrow<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = TransferEscrowAccounts::try_from(accounts)?;
+        let instruction_data = TransferEscrowInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> TransferEscrow<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &13;
+
+    /// Process the transfer escrow instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let mut escrow = Escrow::load_checked_mut(self.accounts.escrow)?;
+
+        // Check if maker matches
+        if &escrow.maker != self.accounts.maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
+        }
+
+        // Check if the escrow is valid
+        let escrow_key = create_program_address(
+            &[
+                ESCROW_SEED,
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump,
+            ],
+            &ID,
+        )?;
+        if &escrow_key != self.accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        // The PDA stays derived from the creator; only the owner changes
+        escrow.maker = self.instruction_data.new_maker;
+
+        EscrowTransferred {
+            seed: escrow.seed(),
+            creator: &escrow.creator,
+            old_maker: self.accounts.maker.key(),
+            new_maker: &escrow.maker,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
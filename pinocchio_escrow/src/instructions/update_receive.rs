@@ -0,0 +1,117 @@
+use core::mem::size_of;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::create_program_address,
+    ProgramResult,
+};
+use shank::ShankType;
+
+use crate::{
+    errors::EscrowError,
+    helpers::{ProgramAccount, SignerAccount},
+    state::Escrow,
+    ESCROW_SEED, ID,
+};
+
+/// UpdateReceive accounts structure
+pub struct UpdateReceiveAccounts<'a> {
+    pub maker: &'a AccountInfo,
+    pub escrow: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateReceiveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [maker, escrow, _remaining @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Basic account checks
+        SignerAccount::check(maker)?;
+        ProgramAccount::check(escrow)?;
+
+        Ok(Self { maker, escrow })
+    }
+}
+
+/// UpdateReceive instruction data
+#[derive(ShankType)]
+pub struct UpdateReceiveInstructionData {
+    /// New amount of Token B asked for the Token A still in the vault
+    pub receive: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateReceiveInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let receive = u64::from_le_bytes(data[0..8].try_into().unwrap());
+
+        // Instruction checks
+        if receive == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { receive })
+    }
+}
+
+/// UpdateReceive instruction - reprices an open escrow offer
+pub struct UpdateReceive<'a> {
+    pub accounts: UpdateReceiveAccounts<'a>,
+    pub instruction_data: UpdateReceiveInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for UpdateReceive<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = UpdateReceiveAccounts::try_from(accounts)?;
+        let instruction_data = UpdateReceiveInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateReceive<'a> {
+    /// Instruction discriminator
+    pub const DISCRIMINATOR: &'static u8 = &11;
+
+    /// Process the update receive instruction
+    pub fn process(&mut self) -> ProgramResult {
+        let mut escrow = Escrow::load_checked_mut(self.accounts.escrow)?;
+
+        // Check if maker matches
+        if &escrow.maker != self.accounts.maker.key() {
+            return Err(EscrowError::MakerMismatch.into());
+        }
+
+        // Check if the escrow is valid
+        let escrow_key = create_program_address(
+            &[
+                ESCROW_SEED,
+                &escrow.creator,
+                &escrow.seed().to_le_bytes(),
+                &escrow.bump,
+            ],
+            &ID,
+        )?;
+        if &escrow_key != self.accounts.escrow.key() {
+            return Err(EscrowError::InvalidEscrowPda.into());
+        }
+
+        escrow.set_receive(self.instruction_data.receive);
+
+        Ok(())
+    }
+}
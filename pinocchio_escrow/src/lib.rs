@@ -1,13 +1,18 @@
-#![no_std]
+#![cfg_attr(not(feature = "client"), no_std)]
 
 use pinocchio::{
-    account_info::AccountInfo, entrypoint, nostd_panic_handler,
-    program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
 
-entrypoint!(process_instruction);
-nostd_panic_handler!();
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::nostd_panic_handler!();
 
+#[cfg(feature = "client")]
+pub mod client;
+pub mod errors;
+pub mod events;
 pub mod helpers;
 pub mod instructions;
 pub mod state;
@@ -22,16 +27,30 @@ pub const ID: Pubkey = [
     0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
 ];
 
-/// Escrow PDA seed prefix
-pub const ESCROW_SEED: &[u8] = b"escrow";
+/// Escrow PDA seed prefix, and config PDA seed
+pub use solana_challenge_common::seeds::{ESCROW_CONFIG_SEED as CONFIG_SEED, ESCROW_SEED};
+
+/// Maker counter PDA seed prefix
+pub const MAKER_SEED: &[u8] = b"maker";
 
 /// Process program instruction
 ///
 /// Instruction discriminators:
 /// - 0: Make - Create an escrow offer
-/// - 1: Take - Accept an escrow offer
+/// - 1: Take - Fill an escrow offer, fully or in part
 /// - 2: Refund - Cancel an escrow offer
-fn process_instruction(
+/// - 3-7: reserved (anchor_escrow's migrate and AMM/auction instructions)
+/// - 8: InitializeConfig - Set the protocol fee and its authority
+/// - 9: reserved (anchor_escrow's update_config)
+/// - 10: ClaimFees - Withdraw accrued protocol fees
+/// - 11: UpdateReceive - Reprice an open escrow offer
+/// - 12: BatchRefund - Cancel several escrow offers of one maker
+/// - 13: TransferEscrow - Hand an escrow offer to a new maker
+/// - 14: ReduceDeposit - Withdraw part of an escrow offer's deposit
+/// - 15: ReadEscrow - Return an escrow offer's terms as return data
+///
+/// 0-2 and 8 share their discriminators with anchor_escrow.
+pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
@@ -40,12 +59,33 @@ fn process_instruction(
         Some((Make::DISCRIMINATOR, data)) => {
             Make::try_from((data, accounts))?.process()
         }
-        Some((Take::DISCRIMINATOR, _)) => {
-            Take::try_from(accounts)?.process()
+        Some((Take::DISCRIMINATOR, data)) => {
+            Take::try_from((data, accounts))?.process()
         }
         Some((Refund::DISCRIMINATOR, _)) => {
             Refund::try_from(accounts)?.process()
         }
+        Some((InitializeConfig::DISCRIMINATOR, data)) => {
+            InitializeConfig::try_from((data, accounts))?.process()
+        }
+        Some((ClaimFees::DISCRIMINATOR, _)) => {
+            ClaimFees::try_from(accounts)?.process()
+        }
+        Some((UpdateReceive::DISCRIMINATOR, data)) => {
+            UpdateReceive::try_from((data, accounts))?.process()
+        }
+        Some((BatchRefund::DISCRIMINATOR, _)) => {
+            BatchRefund::try_from(accounts)?.process()
+        }
+        Some((TransferEscrow::DISCRIMINATOR, data)) => {
+            TransferEscrow::try_from((data, accounts))?.process()
+        }
+        Some((ReduceDeposit::DISCRIMINATOR, data)) => {
+            ReduceDeposit::try_from((data, accounts))?.process()
+        }
+        Some((ReadEscrow::DISCRIMINATOR, _)) => {
+            ReadEscrow::try_from(accounts)?.process()
+        }
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -1,60 +1,130 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+};
+use shank::ShankAccount;
+
+use crate::{errors::EscrowError, helpers::sha256, MAKER_SEED};
+
+/// Tag left at offset 0 of a closed account's 1-byte tombstone
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: u8 = 0xff;
+
+/// Whether `data` is the tombstone `ProgramAccount::close` leaves behind
+#[inline(always)]
+pub fn is_closed(data: &[u8]) -> bool {
+    data.first() == Some(&CLOSED_ACCOUNT_DISCRIMINATOR)
+}
 
 /// Escrow account state - stores all transaction terms
 /// Memory layout: #[repr(C)] ensures predictable field ordering
+///
+/// The first 156 bytes are byte-for-byte what anchor_escrow's Borsh-encoded
+/// `Escrow` holds (discriminator 1, seed, maker, mint_a, mint_b, receive,
+/// bump, version, expiry, `CancelFee::None`, memo), so one decoder reads
+/// escrows of either program. Fields this program adds follow after. Every
+/// field is a byte array, so the struct has alignment 1 and no padding.
 #[repr(C)]
+#[derive(ShankAccount)]
 pub struct Escrow {
-    /// Random identifier allowing multiple escrows per token pair
-    pub seed: u64,
-    /// Creator's wallet address
+    /// Account type tag, always `Escrow::DISCRIMINATOR` once initialized
+    pub discriminator: u8,
+    /// Random identifier allowing multiple escrows per token pair (little-endian u64)
+    seed: [u8; 8],
+    /// Current owner: holds refund rights and receives Token B
     pub maker: Pubkey,
     /// Deposited token's mint (Token A)
     pub mint_a: Pubkey,
     /// Requested token's mint (Token B)
     pub mint_b: Pubkey,
-    /// Desired amount of Token B
-    pub receive: u64,
+    /// Amount of Token B still owed for the remaining Token A (little-endian u64)
+    receive: [u8; 8],
     /// PDA derivation bump seed (stored as array for easy use in signer seeds)
     pub bump: [u8; 1],
+    /// Layout version, always `Escrow::VERSION` once initialized
+    pub version: u8,
+    /// Unix timestamp after which the offer can no longer be taken (0 = never
+    /// expires, little-endian i64)
+    expiry: [u8; 8],
+    /// anchor_escrow's `CancelFee` Borsh tag; always 0 (`CancelFee::None`)
+    cancel_fee: u8,
+    /// anchor_escrow's off-chain reference; always zeroed
+    memo: [u8; 32],
+    /// Amount of Token A still left in the vault to be filled (little-endian u64)
+    remaining: [u8; 8],
+    /// Wallet the escrow PDA is derived from; unchanged by `TransferEscrow`
+    pub creator: Pubkey,
+    /// Only this wallet may take the offer (all zeros = anyone)
+    pub taker: Pubkey,
+    /// SHA-256 hash a taker's preimage must match (all zeros = no hashlock)
+    pub hashlock: [u8; 32],
 }
 
 impl Escrow {
+    /// Account type tag stored at offset 0, same as anchor_escrow's
+    pub const DISCRIMINATOR: u8 = 1;
+
+    /// Current layout version
+    pub const VERSION: u8 = 3;
+
+    /// Offset of `version`, after the fields shared with anchor_escrow
+    pub const VERSION_OFFSET: usize = 1 + 8 + 32 + 32 + 32 + 8 + 1;
+
     /// Size of the Escrow account in bytes
-    /// 8 (seed) + 32 (maker) + 32 (mint_a) + 32 (mint_b) + 8 (receive) + 1 (bump) = 113
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+    /// 1 (discriminator) + 8 (seed) + 32 (maker) + 32 (mint_a) + 32 (mint_b) + 8 (receive)
+    /// + 1 (bump) + 1 (version) + 8 (expiry) + 1 (cancel_fee) + 32 (memo) + 8 (remaining)
+    /// + 32 (creator) + 32 (taker) + 32 (hashlock) = 260
+    pub const LEN: usize = 1 + 8 + 32 + 32 + 32 + 8 + 1 + 1 + 8 + 1 + 32 + 8 + 32 + 32 + 32;
 
-    /// Safely load Escrow from account data
+    /// Check the exact length and the discriminator/version header of escrow data
     #[inline(always)]
-    pub fn from_account_info(account: &AccountInfo) -> Result<&Self, ProgramError> {
-        // Verify account data length
-        if account.data_len() < Self::LEN {
+    fn check(data: &[u8]) -> Result<(), ProgramError> {
+        if is_closed(data) {
+            return Err(EscrowError::EscrowClosed.into());
+        }
+        if data.len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
-
-        // Safety: We verified the data length above
-        // The account data is properly aligned for our struct
-        unsafe {
-            let ptr = account.borrow_data_unchecked().as_ptr() as *const Self;
-            Ok(&*ptr)
+        if data[0] != Self::DISCRIMINATOR || data[Self::VERSION_OFFSET] != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
         }
+        Ok(())
     }
 
-    /// Safely load mutable Escrow from account data
+    /// Borrow and load an escrow account, checking it is owned by this program
+    /// and holds a live escrow of the current layout
+    ///
+    /// Instructions should load escrows through this (or [`Self::load_checked_mut`])
+    /// rather than casting raw account data. The borrow is released when the
+    /// returned `Ref` is dropped, which must happen before a CPI signs with the escrow.
     #[inline(always)]
-    pub fn from_account_info_mut(account: &AccountInfo) -> Result<&mut Self, ProgramError> {
-        // Verify account data length
-        if account.data_len() < Self::LEN {
-            return Err(ProgramError::InvalidAccountData);
+    pub fn load_checked(account: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account.owner() != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner);
         }
+        let data = account.try_borrow_data()?;
+        Self::check(&data)?;
 
-        // Safety: We verified the data length above
-        unsafe {
-            let ptr = account.borrow_mut_data_unchecked().as_mut_ptr() as *mut Self;
-            Ok(&mut *ptr)
+        // Safety: We verified the data length and header above
+        Ok(Ref::map(data, |data| unsafe { &*(data.as_ptr() as *const Self) }))
+    }
+
+    /// Mutable counterpart of [`Self::load_checked`]
+    #[inline(always)]
+    pub fn load_checked_mut(account: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account.owner() != &crate::ID {
+            return Err(ProgramError::InvalidAccountOwner);
         }
+        let data = account.try_borrow_mut_data()?;
+        Self::check(&data)?;
+
+        // Safety: We verified the data length and header above
+        Ok(RefMut::map(data, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut Self)
+        }))
     }
 
-    /// Initialize escrow with all fields
+    /// Initialize escrow with all fields; `maker` is also recorded as the creator
     #[inline(always)]
     pub fn init(
         &mut self,
@@ -63,17 +133,30 @@ impl Escrow {
         mint_a: Pubkey,
         mint_b: Pubkey,
         receive: u64,
+        remaining: u64,
+        expiry: i64,
+        taker: Pubkey,
+        hashlock: [u8; 32],
         bump: u8,
     ) {
-        self.seed = seed;
+        self.discriminator = Self::DISCRIMINATOR;
+        self.version = Self::VERSION;
+        self.seed = seed.to_le_bytes();
         self.maker = maker;
+        self.creator = maker;
         self.mint_a = mint_a;
         self.mint_b = mint_b;
-        self.receive = receive;
+        self.receive = receive.to_le_bytes();
+        self.remaining = remaining.to_le_bytes();
+        self.expiry = expiry.to_le_bytes();
+        self.cancel_fee = 0;
+        self.memo = [0; 32];
+        self.taker = taker;
+        self.hashlock = hashlock;
         self.bump = [bump];
     }
 
-    /// Set inner values (alias for init, matches reference code)
+    /// Set inner values (alias for init, matches reference code); `maker` is also recorded as the creator
     #[inline(always)]
     pub fn set_inner(
         &mut self,
@@ -82,37 +165,552 @@ impl Escrow {
         mint_a: Pubkey,
         mint_b: Pubkey,
         receive: u64,
+        remaining: u64,
+        expiry: i64,
+        taker: Pubkey,
+        hashlock: [u8; 32],
         bump: [u8; 1],
     ) {
-        self.seed = seed;
+        self.discriminator = Self::DISCRIMINATOR;
+        self.version = Self::VERSION;
+        self.seed = seed.to_le_bytes();
         self.maker = maker;
+        self.creator = maker;
         self.mint_a = mint_a;
         self.mint_b = mint_b;
-        self.receive = receive;
+        self.receive = receive.to_le_bytes();
+        self.remaining = remaining.to_le_bytes();
+        self.expiry = expiry.to_le_bytes();
+        self.cancel_fee = 0;
+        self.memo = [0; 32];
+        self.taker = taker;
+        self.hashlock = hashlock;
         self.bump = bump;
     }
 
     /// Load escrow from raw data slice
     #[inline(always)]
     pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::check(data)?;
+        unsafe {
+            let ptr = data.as_ptr() as *const Self;
+            Ok(&*ptr)
+        }
+    }
+
+    /// Load mutable escrow from raw data slice
+    #[inline(always)]
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::check(data)?;
+        unsafe {
+            let ptr = data.as_mut_ptr() as *mut Self;
+            Ok(&mut *ptr)
+        }
+    }
+
+    /// Load a freshly created, still zeroed escrow for initialization
+    #[inline(always)]
+    pub fn load_uninit_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if is_closed(data) {
+            return Err(EscrowError::EscrowClosed.into());
+        }
         if data.len() < Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
+        if data[0] != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        unsafe {
+            let ptr = data.as_mut_ptr() as *mut Self;
+            Ok(&mut *ptr)
+        }
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
+    }
+
+    #[inline(always)]
+    pub fn set_receive(&mut self, receive: u64) {
+        self.receive = receive.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn remaining(&self) -> u64 {
+        u64::from_le_bytes(self.remaining)
+    }
+
+    #[inline(always)]
+    pub fn set_remaining(&mut self, remaining: u64) {
+        self.remaining = remaining.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    /// Whether the offer has expired at unix timestamp `now`
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry() != 0 && now >= self.expiry()
+    }
+
+    /// Whether `taker` is allowed to take the offer
+    #[inline(always)]
+    pub fn can_take(&self, taker: &Pubkey) -> bool {
+        self.taker == Pubkey::default() || &self.taker == taker
+    }
+
+    /// Whether `preimage` unlocks the offer (always true without a hashlock)
+    #[inline(always)]
+    pub fn unlocks(&self, preimage: &[u8; 32]) -> bool {
+        self.hashlock == [0; 32] || sha256(&[preimage]) == self.hashlock
+    }
+
+    /// Amount of Token B owed for filling `amount` of the remaining Token A.
+    /// Rounds up so partial fills never pay the maker less than the quoted price,
+    /// and the final fill settles whatever is still owed.
+    #[inline(always)]
+    pub fn receive_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        receive_for(self.receive(), self.remaining(), amount)
+    }
+
+    /// Token B still asked once the maker withdraws `amount` of the remaining
+    /// Token A. Rounds up so shrinking an offer never lowers its price.
+    #[inline(always)]
+    pub fn receive_after_reduce(&self, amount: u64) -> Result<u64, ProgramError> {
+        if amount == 0 || amount >= self.remaining() {
+            return Err(EscrowError::InvalidAmount.into());
+        }
+
+        // u64 * u64 fits in u128, and remaining - amount < remaining keeps the
+        // result at most receive
+        let remaining = self.remaining() as u128;
+        let left = (remaining - amount as u128) * self.receive() as u128;
+
+        Ok(left.div_ceil(remaining) as u64)
+    }
+
+    /// The terms `ReadEscrow` returns for this escrow
+    #[inline(always)]
+    pub fn terms(&self) -> EscrowTerms {
+        EscrowTerms {
+            maker: self.maker,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            receive: self.receive,
+            remaining: self.remaining,
+            expiry: self.expiry,
+        }
+    }
+}
+
+/// Token B owed for filling `amount` of `remaining` Token A priced at `receive`
+#[inline(always)]
+fn receive_for(receive: u64, remaining: u64, amount: u64) -> Result<u64, ProgramError> {
+    if amount == 0 || amount > remaining {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if amount == remaining {
+        return Ok(receive);
+    }
+
+    // u64 * u64 fits in u128, and amount < remaining keeps the result below receive
+    let owed = (amount as u128 * receive as u128).div_ceil(remaining as u128);
+
+    Ok(owed as u64)
+}
+
+/// Escrow terms `ReadEscrow` writes as return data
+///
+/// Callers read them back with `get_return_data` and [`EscrowTerms::load`],
+/// then quote a fill with [`EscrowTerms::receive_for`]. Every field is a byte
+/// array, so the struct has alignment 1 and no padding.
+#[repr(C)]
+pub struct EscrowTerms {
+    /// Current owner of the offer, paid the Token B
+    pub maker: Pubkey,
+    /// Deposited token's mint (Token A)
+    pub mint_a: Pubkey,
+    /// Requested token's mint (Token B)
+    pub mint_b: Pubkey,
+    /// Amount of Token B still owed for the remaining Token A (little-endian u64)
+    receive: [u8; 8],
+    /// Amount of Token A still left to be filled (little-endian u64)
+    remaining: [u8; 8],
+    /// Unix timestamp after which the offer can no longer be taken (0 = never
+    /// expires, little-endian i64)
+    expiry: [u8; 8],
+}
+
+impl EscrowTerms {
+    /// Size of the return data in bytes
+    /// 32 (maker) + 32 (mint_a) + 32 (mint_b) + 8 (receive) + 8 (remaining) + 8 (expiry) = 120
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8;
+
+    /// Load terms from `ReadEscrow`'s return data
+    #[inline(always)]
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // Safety: the length matches and the struct has alignment 1
+        unsafe { Ok(&*(data.as_ptr() as *const Self)) }
+    }
+
+    /// Raw bytes, as written to return data
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        // Safety: the struct is `Self::LEN` bytes of plain byte arrays
+        unsafe { core::slice::from_raw_parts(self as *const Self as *const u8, Self::LEN) }
+    }
+
+    #[inline(always)]
+    pub fn receive(&self) -> u64 {
+        u64::from_le_bytes(self.receive)
+    }
+
+    #[inline(always)]
+    pub fn remaining(&self) -> u64 {
+        u64::from_le_bytes(self.remaining)
+    }
+
+    #[inline(always)]
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    /// Whether the offer has expired at unix timestamp `now`
+    #[inline(always)]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry() != 0 && now >= self.expiry()
+    }
+
+    /// Amount of Token B a `Take` of `amount` Token A would pay, as
+    /// [`Escrow::receive_for`] computes it
+    #[inline(always)]
+    pub fn receive_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        receive_for(self.receive(), self.remaining(), amount)
+    }
+}
+
+/// Program config PDA ([`CONFIG_SEED`]) - owns the protocol fee vaults
+///
+/// [`CONFIG_SEED`]: crate::CONFIG_SEED
+#[repr(C)]
+#[derive(ShankAccount)]
+pub struct Config {
+    /// Account type tag, always `Config::DISCRIMINATOR` once initialized
+    pub discriminator: u8,
+    /// Layout version, always `Config::VERSION` once initialized
+    pub version: u8,
+    /// Protocol fee in basis points of the Token B leg (little-endian u16)
+    fee_bps: [u8; 2],
+    /// Share of the protocol fee paid to a Take's referrer, in basis points
+    /// of the fee (little-endian u16)
+    referral_bps: [u8; 2],
+    /// Wallet allowed to claim accrued fees
+    pub authority: Pubkey,
+    /// PDA derivation bump seed
+    pub bump: [u8; 1],
+}
+
+impl Config {
+    /// Account type tag stored at offset 0
+    pub const DISCRIMINATOR: u8 = 2;
+
+    /// Current layout version stored at offset 1
+    pub const VERSION: u8 = 2;
+
+    /// Size of the Config account in bytes
+    /// 1 (discriminator) + 1 (version) + 2 (fee_bps) + 2 (referral_bps) + 32 (authority)
+    /// + 1 (bump) = 39
+    pub const LEN: usize = 1 + 1 + 2 + 2 + 32 + 1;
+
+    /// Maximum fee: 100% of the Token B leg
+    pub const MAX_FEE_BPS: u16 = 10_000;
+
+    /// Maximum referral share: the whole protocol fee
+    pub const MAX_REFERRAL_BPS: u16 = 10_000;
+
+    /// Load config from raw data slice
+    #[inline(always)]
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[0] != Self::DISCRIMINATOR || data[1] != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // A fee above 100% would make Take underflow paying the maker, and a
+        // referral share above 100% underflow paying the fee vault
+        if u16::from_le_bytes([data[2], data[3]]) > Self::MAX_FEE_BPS
+            || u16::from_le_bytes([data[4], data[5]]) > Self::MAX_REFERRAL_BPS
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
         unsafe {
             let ptr = data.as_ptr() as *const Self;
             Ok(&*ptr)
         }
     }
 
-    /// Load mutable escrow from raw data slice
+    /// Load a freshly created, still zeroed config for initialization
+    #[inline(always)]
+    pub fn load_uninit_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[0] != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        unsafe {
+            let ptr = data.as_mut_ptr() as *mut Self;
+            Ok(&mut *ptr)
+        }
+    }
+
+    /// Initialize config with all fields
+    #[inline(always)]
+    pub fn set_inner(
+        &mut self,
+        fee_bps: u16,
+        referral_bps: u16,
+        authority: Pubkey,
+        bump: [u8; 1],
+    ) {
+        self.discriminator = Self::DISCRIMINATOR;
+        self.version = Self::VERSION;
+        self.fee_bps = fee_bps.to_le_bytes();
+        self.referral_bps = referral_bps.to_le_bytes();
+        self.authority = authority;
+        self.bump = bump;
+    }
+
+    #[inline(always)]
+    pub fn fee_bps(&self) -> u16 {
+        u16::from_le_bytes(self.fee_bps)
+    }
+
+    #[inline(always)]
+    pub fn referral_bps(&self) -> u16 {
+        u16::from_le_bytes(self.referral_bps)
+    }
+
+    /// Protocol fee taken out of a Token B payment of `amount` (rounded down)
+    #[inline(always)]
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps() as u128 / Self::MAX_FEE_BPS as u128) as u64
+    }
+
+    /// Referrer's cut of a protocol `fee` (rounded down, the rest stays with the protocol)
+    #[inline(always)]
+    pub fn referral_for(&self, fee: u64) -> u64 {
+        (fee as u128 * self.referral_bps() as u128 / Self::MAX_REFERRAL_BPS as u128) as u64
+    }
+}
+
+/// Per-maker PDA ([`MAKER_SEED`], maker) tracking the maker's open escrows
+///
+/// Escrows are listed under their creator, so a transferred escrow stays in
+/// the original maker's counter until it is closed.
+///
+/// [`MAKER_SEED`]: crate::MAKER_SEED
+#[repr(C)]
+#[derive(ShankAccount)]
+pub struct MakerCounter {
+    /// Account type tag, always `MakerCounter::DISCRIMINATOR` once initialized
+    pub discriminator: u8,
+    /// Layout version, always `MakerCounter::VERSION` once initialized
+    pub version: u8,
+    /// Number of valid entries at the front of `seeds`
+    pub len: u8,
+    /// PDA derivation bump seed
+    pub bump: [u8; 1],
+    /// Number of open escrows, including any not listed in `seeds`
+    /// (little-endian u64)
+    open: [u8; 8],
+    /// Seeds of up to `MAX_SEEDS` open escrows, most recent last
+    /// (little-endian u64s)
+    seeds: [[u8; 8]; MakerCounter::MAX_SEEDS],
+}
+
+impl MakerCounter {
+    /// Account type tag stored at offset 0
+    pub const DISCRIMINATOR: u8 = 3;
+
+    /// Current layout version stored at offset 1
+    pub const VERSION: u8 = 2;
+
+    /// Open escrows listed by seed; beyond this only `open` is counted
+    pub const MAX_SEEDS: usize = 16;
+
+    /// Size of the MakerCounter account in bytes
+    /// 1 (discriminator) + 1 (version) + 1 (len) + 1 (bump) + 8 (open)
+    /// + 16 * 8 (seeds) = 140
+    pub const LEN: usize = 1 + 1 + 1 + 1 + 8 + Self::MAX_SEEDS * 8;
+
+    /// Check the length, the discriminator/version header and `len` of counter data
+    #[inline(always)]
+    fn check(data: &[u8]) -> Result<(), ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[0] != Self::DISCRIMINATOR || data[1] != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // `len` indexes `seeds`, so never trust it past the array
+        if data[2] as usize > Self::MAX_SEEDS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// Load counter from raw data slice
+    #[inline(always)]
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::check(data)?;
+        unsafe {
+            let ptr = data.as_ptr() as *const Self;
+            Ok(&*ptr)
+        }
+    }
+
+    /// Load mutable counter from raw data slice
     #[inline(always)]
     pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::check(data)?;
+        unsafe {
+            let ptr = data.as_mut_ptr() as *mut Self;
+            Ok(&mut *ptr)
+        }
+    }
+
+    /// Load a freshly created, still zeroed counter for initialization
+    #[inline(always)]
+    pub fn load_uninit_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
+        if data[0] != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
         unsafe {
             let ptr = data.as_mut_ptr() as *mut Self;
             Ok(&mut *ptr)
         }
     }
+
+    /// Initialize an empty counter
+    #[inline(always)]
+    pub fn set_inner(&mut self, bump: [u8; 1]) {
+        self.discriminator = Self::DISCRIMINATOR;
+        self.version = Self::VERSION;
+        self.len = 0;
+        self.bump = bump;
+        self.open = [0; 8];
+        self.seeds = [[0; 8]; Self::MAX_SEEDS];
+    }
+
+    #[inline(always)]
+    pub fn open(&self) -> u64 {
+        u64::from_le_bytes(self.open)
+    }
+
+    #[inline(always)]
+    pub fn set_open(&mut self, open: u64) {
+        self.open = open.to_le_bytes();
+    }
+
+    /// Seed of the `i`th listed escrow
+    #[inline(always)]
+    pub fn seed(&self, i: usize) -> u64 {
+        u64::from_le_bytes(self.seeds[i])
+    }
+
+    /// Check that `key` is this maker's counter PDA
+    #[inline(always)]
+    pub fn check_address(&self, key: &Pubkey, maker: &Pubkey) -> Result<(), ProgramError> {
+        let expected = create_program_address(&[MAKER_SEED, maker, &self.bump], &crate::ID)?;
+        if &expected != key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    /// Record a newly made escrow
+    #[inline(always)]
+    pub fn record_open(&mut self, seed: u64) {
+        self.set_open(self.open().saturating_add(1));
+        if (self.len as usize) < Self::MAX_SEEDS {
+            self.seeds[self.len as usize] = seed.to_le_bytes();
+            self.len += 1;
+        }
+    }
+
+    /// Record an escrow closed by Take or Refund
+    #[inline(always)]
+    pub fn record_close(&mut self, seed: u64) {
+        self.set_open(self.open().saturating_sub(1));
+        let len = self.len as usize;
+        let seed = seed.to_le_bytes();
+        if let Some(i) = self.seeds[..len].iter().position(|s| *s == seed) {
+            self.seeds.copy_within(i + 1..len, i);
+            self.seeds[len - 1] = [0; 8];
+            self.len -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-256 of 32 bytes of 7
+    const HASHLOCK: [u8; 32] = [
+        0x4b, 0xb0, 0x6f, 0x8e, 0x4e, 0x3a, 0x77, 0x15, 0xd2, 0x01, 0xd5, 0x73, 0xd0, 0xaa, 0x42,
+        0x37, 0x62, 0xe5, 0x5d, 0xab, 0xd6, 0x1a, 0x2c, 0x02, 0x27, 0x8f, 0xa5, 0x6c, 0xc6, 0xd2,
+        0x94, 0xe0,
+    ];
+
+    fn escrow(data: &mut [u8; Escrow::LEN], hashlock: [u8; 32]) -> &Escrow {
+        let escrow = Escrow::load_uninit_mut(data).unwrap();
+        escrow.set_inner(
+            1,
+            [1; 32],
+            [2; 32],
+            [3; 32],
+            500,
+            1_000,
+            0,
+            [0; 32],
+            hashlock,
+            [255],
+        );
+        escrow
+    }
+
+    #[test]
+    fn hashlock_unlocks_with_the_preimage_only() {
+        let mut data = [0; Escrow::LEN];
+        let escrow = escrow(&mut data, HASHLOCK);
+        assert!(escrow.unlocks(&[7; 32]));
+        assert!(!escrow.unlocks(&[8; 32]));
+        assert!(!escrow.unlocks(&[0; 32]));
+    }
+
+    #[test]
+    fn no_hashlock_unlocks_with_anything() {
+        let mut data = [0; Escrow::LEN];
+        let escrow = escrow(&mut data, [0; 32]);
+        assert!(escrow.unlocks(&[0; 32]));
+        assert!(escrow.unlocks(&[8; 32]));
+    }
 }
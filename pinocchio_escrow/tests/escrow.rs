@@ -0,0 +1,412 @@
+//! Make / Take / Refund run against the SBF build of the program in mollusk
+//!
+//! Needs the program built first (`cargo build-sbf`), then
+//! `SBF_OUT_DIR=$PWD/target/deploy cargo test --features client`.
+
+use mollusk_svm::{program::keyed_account_for_system_program, result::InstructionResult, Mollusk};
+use pinocchio_escrow::{
+    client::{
+        associated_token_address, config_address, escrow_address, make_ix, read_escrow_ix,
+        refund_ix, take_ix, vault_address, MakeArgs, PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    errors::EscrowError,
+    helpers::{MINT_SIZE, TOKEN_ACCOUNT_SIZE},
+    state::{Config, Escrow, EscrowTerms, CLOSED_ACCOUNT_DISCRIMINATOR},
+};
+use solana_account::Account;
+use solana_instruction::{error::InstructionError, Instruction};
+use solana_pubkey::Pubkey;
+
+const SEED: u64 = 7;
+/// Token A the maker deposits
+const DEPOSIT: u64 = 1_000;
+/// Token B the maker asks for the whole deposit
+const RECEIVE: u64 = 500;
+const FEE_BPS: u16 = 100;
+const LAMPORTS: u64 = 10_000_000_000;
+
+/// spl-token's `TokenError::InsufficientFunds`
+const TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+
+struct Env {
+    mollusk: Mollusk,
+    accounts: Vec<(Pubkey, Account)>,
+    maker: Pubkey,
+    taker: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+impl Env {
+    /// A maker holding `DEPOSIT` Token A, a taker holding `RECEIVE` Token B and
+    /// a config charging `FEE_BPS`
+    fn new() -> Self {
+        let mut mollusk = Mollusk::new(&PROGRAM_ID, "pinocchio_escrow");
+        mollusk_svm_programs_token::token::add_program(&mut mollusk);
+        mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+
+        let mut env = Self {
+            mollusk,
+            accounts: vec![
+                keyed_account_for_system_program(),
+                mollusk_svm_programs_token::token::keyed_account(),
+                mollusk_svm_programs_token::associated_token::keyed_account(),
+            ],
+            maker: Pubkey::new_unique(),
+            taker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+        };
+
+        env.set(env.maker, env.wallet());
+        env.set(env.taker, env.wallet());
+        env.set(env.mint_a, env.mint(6));
+        env.set(env.mint_b, env.mint(9));
+        env.set(env.ata(&env.maker, &env.mint_a), env.token_account(&env.mint_a, &env.maker, DEPOSIT));
+        env.set(env.ata(&env.taker, &env.mint_b), env.token_account(&env.mint_b, &env.taker, RECEIVE));
+        env.set(config_address().0, env.config(FEE_BPS));
+
+        env
+    }
+
+    fn set(&mut self, key: Pubkey, account: Account) {
+        match self.accounts.iter_mut().find(|(k, _)| k == &key) {
+            Some((_, existing)) => *existing = account,
+            None => self.accounts.push((key, account)),
+        }
+    }
+
+    fn get(&self, key: &Pubkey) -> &Account {
+        &self.accounts.iter().find(|(k, _)| k == key).unwrap().1
+    }
+
+    fn ata(&self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, mint, &TOKEN_PROGRAM_ID)
+    }
+
+    fn escrow(&self) -> Pubkey {
+        escrow_address(&self.maker, SEED).0
+    }
+
+    fn vault(&self) -> Pubkey {
+        vault_address(&self.maker, SEED, &self.mint_a, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Token balance of the token account at `key`
+    fn balance(&self, key: &Pubkey) -> u64 {
+        u64::from_le_bytes(self.get(key).data[64..72].try_into().unwrap())
+    }
+
+    fn wallet(&self) -> Account {
+        Account::new(LAMPORTS, 0, &Pubkey::default())
+    }
+
+    /// Initialized mint with no freeze authority
+    fn mint(&self, decimals: u8) -> Account {
+        let mut data = vec![0; MINT_SIZE];
+        data[0] = 1;
+        data[4..36].copy_from_slice(Pubkey::new_unique().as_ref());
+        data[36..44].copy_from_slice(&u64::MAX.to_le_bytes());
+        data[44] = decimals;
+        data[45] = 1;
+
+        self.rent_exempt(data, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Initialized token account with no delegate or close authority
+    fn token_account(&self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+        let mut data = vec![0; TOKEN_ACCOUNT_SIZE];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+
+        self.rent_exempt(data, &TOKEN_PROGRAM_ID)
+    }
+
+    fn config(&self, fee_bps: u16) -> Account {
+        let mut data = vec![0; Config::LEN];
+        Config::load_uninit_mut(&mut data).unwrap().set_inner(
+            fee_bps,
+            0,
+            Pubkey::new_unique().to_bytes(),
+            [config_address().1],
+        );
+
+        self.rent_exempt(data, &PROGRAM_ID)
+    }
+
+    fn rent_exempt(&self, data: Vec<u8>, owner: &Pubkey) -> Account {
+        Account {
+            lamports: self.mollusk.sysvars.rent.minimum_balance(data.len()),
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Run `instruction`, keeping the resulting accounts when it succeeds.
+    /// Accounts the instruction names that don't exist yet start out empty.
+    fn process(&mut self, instruction: &Instruction) -> InstructionResult {
+        for meta in &instruction.accounts {
+            if self.accounts.iter().all(|(k, _)| k != &meta.pubkey) {
+                self.accounts.push((meta.pubkey, Account::default()));
+            }
+        }
+
+        let result = self.mollusk.process_instruction(instruction, &self.accounts);
+        if result.raw_result.is_ok() {
+            for (key, account) in &result.resulting_accounts {
+                self.set(*key, account.clone());
+            }
+        }
+        result
+    }
+
+    fn make_ix(&self) -> Instruction {
+        let args = MakeArgs {
+            seed: SEED,
+            receive: RECEIVE,
+            amount: DEPOSIT,
+            expiry: 0,
+            taker: None,
+            hashlock: None,
+            reject_freezable: false,
+        };
+        make_ix(&self.maker, &self.mint_a, &self.mint_b, &TOKEN_PROGRAM_ID, &args, None)
+    }
+
+    fn take_ix(&self, amount: u64) -> Instruction {
+        take_ix(
+            &self.taker,
+            &self.maker,
+            &self.mint_a,
+            &self.mint_b,
+            &TOKEN_PROGRAM_ID,
+            &self.maker,
+            SEED,
+            amount,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn make(&mut self) {
+        let result = self.process(&self.make_ix());
+        assert_eq!(result.raw_result, Ok(()));
+    }
+}
+
+fn escrow_error(e: EscrowError) -> Result<(), InstructionError> {
+    Err(InstructionError::Custom(e as u32))
+}
+
+#[test]
+fn make_locks_the_deposit() {
+    let mut env = Env::new();
+    env.make();
+
+    let escrow = env.get(&env.escrow());
+    assert_eq!(escrow.owner, PROGRAM_ID);
+    let escrow = Escrow::load(&escrow.data).unwrap();
+    assert_eq!(escrow.maker, env.maker.to_bytes());
+    assert_eq!(escrow.creator, env.maker.to_bytes());
+    assert_eq!(escrow.seed(), SEED);
+    assert_eq!(escrow.receive(), RECEIVE);
+    assert_eq!(escrow.remaining(), DEPOSIT);
+
+    assert_eq!(env.balance(&env.vault()), DEPOSIT);
+    assert_eq!(env.balance(&env.ata(&env.maker, &env.mint_a)), 0);
+}
+
+#[test]
+fn take_settles_and_closes() {
+    let mut env = Env::new();
+    env.make();
+
+    let result = env.process(&env.take_ix(DEPOSIT));
+    assert_eq!(result.raw_result, Ok(()));
+
+    let fee = RECEIVE * FEE_BPS as u64 / 10_000;
+    assert_eq!(env.balance(&env.ata(&env.taker, &env.mint_a)), DEPOSIT);
+    assert_eq!(env.balance(&env.ata(&env.maker, &env.mint_b)), RECEIVE - fee);
+    assert_eq!(env.balance(&env.ata(&config_address().0, &env.mint_b)), fee);
+    assert_eq!(env.balance(&env.ata(&env.taker, &env.mint_b)), 0);
+
+    let escrow = env.get(&env.escrow());
+    assert_eq!(escrow.lamports, 0);
+    assert_eq!(escrow.data, [CLOSED_ACCOUNT_DISCRIMINATOR]);
+    assert_eq!(env.get(&env.vault()).lamports, 0);
+}
+
+#[test]
+fn take_returns_a_topped_up_vault_surplus_to_the_maker() {
+    let mut env = Env::new();
+    env.make();
+
+    let result = env.process(&env.take_ix(DEPOSIT / 2));
+    assert_eq!(result.raw_result, Ok(()));
+
+    // Someone sends Token A straight to the vault before the last fill
+    let vault = env.token_account(&env.mint_a, &env.escrow(), DEPOSIT / 2 + 7);
+    env.set(env.vault(), vault);
+
+    let result = env.process(&env.take_ix(DEPOSIT / 2));
+    assert_eq!(result.raw_result, Ok(()));
+
+    assert_eq!(env.balance(&env.ata(&env.taker, &env.mint_a)), DEPOSIT);
+    assert_eq!(env.balance(&env.ata(&env.maker, &env.mint_a)), 7);
+    assert_eq!(env.get(&env.vault()).lamports, 0);
+    assert_eq!(env.get(&env.escrow()).lamports, 0);
+}
+
+#[test]
+fn read_escrow_returns_the_terms() {
+    let mut env = Env::new();
+    env.make();
+
+    let result = env.process(&read_escrow_ix(&env.maker, SEED));
+    assert_eq!(result.raw_result, Ok(()));
+
+    let terms = EscrowTerms::load(&result.return_data).unwrap();
+    assert_eq!(terms.maker, env.maker.to_bytes());
+    assert_eq!(terms.mint_a, env.mint_a.to_bytes());
+    assert_eq!(terms.mint_b, env.mint_b.to_bytes());
+    assert_eq!(terms.receive(), RECEIVE);
+    assert_eq!(terms.remaining(), DEPOSIT);
+    assert_eq!(terms.expiry(), 0);
+    assert_eq!(terms.receive_for(DEPOSIT / 2), Ok(RECEIVE / 2));
+}
+
+#[test]
+fn refund_returns_the_deposit() {
+    let mut env = Env::new();
+    env.make();
+
+    let ix = refund_ix(&env.maker, &env.mint_a, &TOKEN_PROGRAM_ID, &env.maker, SEED, None);
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, Ok(()));
+
+    assert_eq!(env.balance(&env.ata(&env.maker, &env.mint_a)), DEPOSIT);
+    assert_eq!(env.get(&env.escrow()).lamports, 0);
+    assert_eq!(env.get(&env.vault()).lamports, 0);
+}
+
+#[test]
+fn refund_by_another_wallet_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    let stranger = Pubkey::new_unique();
+    env.set(stranger, env.wallet());
+
+    let ix = refund_ix(&stranger, &env.mint_a, &TOKEN_PROGRAM_ID, &env.maker, SEED, None);
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, escrow_error(EscrowError::MakerMismatch));
+    assert_eq!(env.balance(&env.vault()), DEPOSIT);
+}
+
+#[test]
+fn take_from_forged_escrow_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    // A copy of a real escrow's data at an address that isn't its PDA
+    let forged = Pubkey::new_unique();
+    env.set(forged, env.get(&env.escrow()).clone());
+
+    let mut ix = env.take_ix(DEPOSIT);
+    ix.accounts[2].pubkey = forged;
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, escrow_error(EscrowError::InvalidEscrowPda));
+}
+
+#[test]
+fn take_with_wrong_vault_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    // Token A account that the escrow doesn't own
+    let decoy = Pubkey::new_unique();
+    env.set(decoy, env.token_account(&env.mint_a, &env.taker, DEPOSIT));
+
+    let mut ix = env.take_ix(DEPOSIT);
+    ix.accounts[5].pubkey = decoy;
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, escrow_error(EscrowError::InvalidVault));
+}
+
+/// Token B account of another wallet with `allowance` approved to `delegate`
+fn delegated_account(env: &Env, delegate: &Pubkey, allowance: u64) -> Account {
+    let mut account = env.token_account(&env.mint_b, &Pubkey::new_unique(), RECEIVE);
+    account.data[72..76].copy_from_slice(&[1, 0, 0, 0]);
+    account.data[76..108].copy_from_slice(delegate.as_ref());
+    account.data[121..129].copy_from_slice(&allowance.to_le_bytes());
+    account
+}
+
+#[test]
+fn take_as_delegate_spends_the_allowance() {
+    let mut env = Env::new();
+    env.make();
+
+    let source = Pubkey::new_unique();
+    env.set(source, delegated_account(&env, &env.taker, RECEIVE));
+
+    let mut ix = env.take_ix(DEPOSIT);
+    ix.accounts[7].pubkey = source;
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, Ok(()));
+
+    let fee = RECEIVE * FEE_BPS as u64 / 10_000;
+    assert_eq!(env.balance(&source), 0);
+    assert_eq!(env.balance(&env.ata(&env.taker, &env.mint_a)), DEPOSIT);
+    assert_eq!(env.balance(&env.ata(&env.maker, &env.mint_b)), RECEIVE - fee);
+}
+
+#[test]
+fn take_from_unapproved_account_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    // Approved to someone other than the taker
+    let source = Pubkey::new_unique();
+    env.set(source, delegated_account(&env, &Pubkey::new_unique(), RECEIVE));
+
+    let mut ix = env.take_ix(DEPOSIT);
+    ix.accounts[7].pubkey = source;
+    let result = env.process(&ix);
+    assert_eq!(result.raw_result, escrow_error(EscrowError::InvalidTokenAuthority));
+}
+
+#[test]
+fn take_without_enough_token_b_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    let taker_ata_b = env.ata(&env.taker, &env.mint_b);
+    env.set(taker_ata_b, env.token_account(&env.mint_b, &env.taker, RECEIVE / 5));
+
+    let result = env.process(&env.take_ix(DEPOSIT));
+    assert_eq!(result.raw_result, Err(InstructionError::Custom(TOKEN_INSUFFICIENT_FUNDS)));
+
+    // Nothing moved and the offer is still open
+    assert_eq!(env.balance(&env.vault()), DEPOSIT);
+    assert_eq!(Escrow::load(&env.get(&env.escrow()).data).unwrap().remaining(), DEPOSIT);
+}
+
+#[test]
+fn take_after_close_fails() {
+    let mut env = Env::new();
+    env.make();
+
+    let result = env.process(&env.take_ix(DEPOSIT));
+    assert_eq!(result.raw_result, Ok(()));
+
+    // The closed escrow's tombstone, as a later instruction in the same
+    // transaction would see it
+    let result = env.process(&env.take_ix(DEPOSIT));
+    assert_eq!(result.raw_result, escrow_error(EscrowError::EscrowClosed));
+}
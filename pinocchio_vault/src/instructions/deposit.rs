@@ -5,8 +5,9 @@ use pinocchio::{
     ProgramResult,
 };
 use pinocchio_system::instructions::Transfer;
+use solana_challenge_common::seeds::vault as vault_seeds;
 
-use crate::{ID, VAULT_SEED};
+use crate::ID;
 
 /// Deposit instruction - transfers lamports from owner to vault PDA
 pub struct Deposit<'a> {
@@ -40,10 +41,7 @@ impl Deposit<'_> {
         }
 
         // Verify vault PDA derivation
-        let (expected_vault, _bump) = find_program_address(
-            &[VAULT_SEED, self.owner.key().as_ref()],
-            &ID,
-        );
+        let (expected_vault, _bump) = find_program_address(&vault_seeds(self.owner.key()), &ID);
 
         if self.vault.key() != &expected_vault {
             return Err(ProgramError::InvalidSeeds);
@@ -6,6 +6,7 @@ use pinocchio::{
     seeds, ProgramResult,
 };
 use pinocchio_system::instructions::Transfer;
+use solana_challenge_common::seeds::vault as vault_seeds;
 
 use crate::{ID, VAULT_SEED};
 
@@ -42,10 +43,7 @@ impl Withdraw<'_> {
         }
 
         // Verify vault PDA derivation
-        let (expected_vault, _) = find_program_address(
-            &[VAULT_SEED, self.owner.key().as_ref()],
-            &ID,
-        );
+        let (expected_vault, _) = find_program_address(&vault_seeds(self.owner.key()), &ID);
 
         if self.vault.key() != &expected_vault {
             return Err(ProgramError::InvalidSeeds);
@@ -78,10 +76,7 @@ impl<'a> TryFrom<&'a [AccountInfo]> for Withdraw<'a> {
         };
 
         // Derive PDA and get bump seed
-        let (_, bump) = find_program_address(
-            &[VAULT_SEED, owner.key().as_ref()],
-            &ID,
-        );
+        let (_, bump) = find_program_address(&vault_seeds(owner.key()), &ID);
 
         Ok(Self {
             owner,
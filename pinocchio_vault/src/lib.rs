@@ -1,12 +1,13 @@
 #![no_std]
 
 use pinocchio::{
-    account_info::AccountInfo, entrypoint, nostd_panic_handler,
-    program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
 
-entrypoint!(process_instruction);
-nostd_panic_handler!();
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::nostd_panic_handler!();
 
 pub mod instructions;
 pub use instructions::*;
@@ -20,9 +21,9 @@ pub const ID: Pubkey = [
 ];
 
 /// Vault PDA seed prefix
-pub const VAULT_SEED: &[u8] = b"vault";
+pub use solana_challenge_common::seeds::VAULT_SEED;
 
-fn process_instruction(
+pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
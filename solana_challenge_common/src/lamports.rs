@@ -0,0 +1,35 @@
+//! Checked lamport moves between accounts a program owns or closes. `None`
+//! means the move would underflow the source or overflow the destination;
+//! callers map it to their own `ProgramError`.
+
+/// Balances of `from` and `to` after moving `amount` from one to the other
+#[inline(always)]
+pub fn transfer(from: u64, to: u64, amount: u64) -> Option<(u64, u64)> {
+    Some((from.checked_sub(amount)?, to.checked_add(amount)?))
+}
+
+/// Balance of `to` after taking in all of `from`'s, as when closing `from`
+#[inline(always)]
+pub fn sweep(from: u64, to: u64) -> Option<u64> {
+    to.checked_add(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_moves_the_amount_or_nothing() {
+        assert_eq!(transfer(10, 5, 4), Some((6, 9)));
+        assert_eq!(transfer(10, 5, 10), Some((0, 15)));
+        assert_eq!(transfer(10, 5, 11), None);
+        assert_eq!(transfer(10, u64::MAX - 3, 4), None);
+    }
+
+    #[test]
+    fn sweep_takes_the_whole_balance() {
+        assert_eq!(sweep(10, 5), Some(15));
+        assert_eq!(sweep(0, u64::MAX), Some(u64::MAX));
+        assert_eq!(sweep(1, u64::MAX), None);
+    }
+}
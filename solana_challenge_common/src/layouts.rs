@@ -0,0 +1,14 @@
+//! Byte layouts of accounts one program reads out of another's, for readers
+//! that can't link the owning program (`anchor_escrow` can't pull in the
+//! AMM's pinocchio 0.10). The owning program asserts at compile time that its
+//! layout still matches.
+
+/// `blueshift_native_amm`'s pool config at its current layout version
+pub mod amm_config {
+    /// Account length
+    pub const LEN: usize = 481;
+    /// Offset of `mint_x`
+    pub const MINT_X: usize = 41;
+    /// Offset of `mint_y`
+    pub const MINT_Y: usize = 73;
+}
@@ -0,0 +1,16 @@
+//! What the pinocchio programs in this repo share: program IDs and account
+//! sizes, PDA seeds, checked lamport arithmetic, and the layouts of accounts
+//! read across programs.
+//!
+//! `pinocchio_escrow` and `pinocchio_vault` are on pinocchio 0.9, where an
+//! address is a `[u8; 32]`, and `blueshift_native_amm` on 0.10, where it is an
+//! `Address` built with `Address::new_from_array`. So everything here is plain
+//! bytes, and the programs derive PDAs from these seeds with their own
+//! `find_program_address`.
+
+#![no_std]
+
+pub mod ids;
+pub mod lamports;
+pub mod layouts;
+pub mod seeds;
@@ -0,0 +1,52 @@
+//! PDA seeds, as the slices `find_program_address` / `create_program_address`
+//! take; append the bump to sign with them
+
+/// `pinocchio_vault`'s lamport vault: ["vault", owner]
+pub const VAULT_SEED: &[u8] = b"vault";
+/// `pinocchio_escrow`'s offer: ["escrow", maker, seed (u64 LE)]
+pub const ESCROW_SEED: &[u8] = b"escrow";
+/// `pinocchio_escrow`'s program config: ["config"]
+pub const ESCROW_CONFIG_SEED: &[u8] = b"config";
+/// `blueshift_native_amm`'s pool config: ["config", seed (u64 LE), mint_x, mint_y]
+pub const POOL_CONFIG_SEED: &[u8] = b"config";
+/// `blueshift_native_amm`'s LP mint: ["mint_lp", pool config]
+pub const MINT_LP_SEED: &[u8] = b"mint_lp";
+
+/// Seeds of `owner`'s lamport vault
+#[inline(always)]
+pub fn vault(owner: &[u8; 32]) -> [&[u8]; 2] {
+    [VAULT_SEED, owner]
+}
+
+/// Seeds of `maker`'s escrow offer number `seed` (as LE bytes)
+#[inline(always)]
+pub fn escrow<'a>(maker: &'a [u8; 32], seed: &'a [u8; 8]) -> [&'a [u8]; 3] {
+    [ESCROW_SEED, maker, seed]
+}
+
+/// Seeds of the pool of `mint_x` / `mint_y` created with `seed` (as LE bytes)
+#[inline(always)]
+pub fn pool_config<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a [u8; 32],
+    mint_y: &'a [u8; 32],
+) -> [&'a [u8]; 4] {
+    [POOL_CONFIG_SEED, seed, mint_x, mint_y]
+}
+
+/// Seeds of the LP mint of the pool at `config`
+#[inline(always)]
+pub fn mint_lp(config: &[u8; 32]) -> [&[u8]; 2] {
+    [MINT_LP_SEED, config]
+}
+
+/// Seeds of `wallet`'s associated token account for `mint`, under the
+/// associated token program
+#[inline(always)]
+pub fn associated_token_account<'a>(
+    wallet: &'a [u8; 32],
+    token_program: &'a [u8; 32],
+    mint: &'a [u8; 32],
+) -> [&'a [u8]; 3] {
+    [wallet, token_program, mint]
+}
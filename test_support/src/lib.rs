@@ -0,0 +1,144 @@
+//! Accounts the benchmark, the fuzz targets and the integration tests build
+//! their ledgers from: wallets, SPL Token mints and token accounts, and
+//! upgradeable-loader ProgramData, at the runtime's default rent.
+//!
+//! The IDs are `solana_challenge_common::ids` as `Pubkey`s. Mollusk and
+//! LiteSVM both start from the default rent, so accounts built here are
+//! rent-exempt in either.
+
+use solana_account::Account;
+use solana_challenge_common::ids;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+
+/// The challenge's program ID, which every program is deployed at
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::CHALLENGE_PROGRAM_ID);
+
+/// SPL Token program ID
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::TOKEN_PROGRAM_ID);
+
+/// System Program ID
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey::new_from_array(ids::SYSTEM_PROGRAM_ID);
+
+/// Associated Token program ID
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::new_from_array(ids::ASSOCIATED_TOKEN_PROGRAM_ID);
+
+/// What each new wallet holds
+pub const LAMPORTS: u64 = 10_000_000_000;
+
+/// `data`, owned by `owner` and holding its rent-exempt minimum
+pub fn rent_exempt(data: Vec<u8>, owner: &Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A system account holding `LAMPORTS`
+pub fn wallet() -> Account {
+    Account::new(LAMPORTS, 0, &SYSTEM_PROGRAM_ID)
+}
+
+/// Initialized mint data with no freeze authority
+pub fn mint_data(authority: &Pubkey, supply: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![0; ids::MINT_LEN];
+    data[0] = 1;
+    data[4..36].copy_from_slice(authority.as_ref());
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = decimals;
+    data[45] = 1;
+    data
+}
+
+/// An initialized SPL Token mint of `decimals`, with no freeze authority
+pub fn mint(decimals: u8) -> Account {
+    rent_exempt(
+        mint_data(&Pubkey::new_unique(), u64::MAX, decimals),
+        &TOKEN_PROGRAM_ID,
+    )
+}
+
+/// Initialized token account data with no delegate or close authority
+pub fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0; ids::TOKEN_ACCOUNT_LEN];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1;
+    data
+}
+
+/// An initialized SPL Token account holding `amount`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    rent_exempt(token_account_data(mint, owner, amount), &TOKEN_PROGRAM_ID)
+}
+
+/// Token balance in token account `data`
+pub fn token_balance(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[64..72].try_into().unwrap())
+}
+
+/// Upgradeable-loader ProgramData header naming `authority` as the upgrade
+/// authority: `ProgramData { slot: 0, upgrade_authority_address: Some(authority) }`
+pub fn program_data(authority: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0; 45];
+    data[0..4].copy_from_slice(&3u32.to_le_bytes());
+    data[12] = 1;
+    data[13..45].copy_from_slice(authority.as_ref());
+    data
+}
+
+/// Accounts by address, in the form mollusk runs instructions against
+#[derive(Default)]
+pub struct Accounts(pub Vec<(Pubkey, Account)>);
+
+impl Accounts {
+    pub fn set(&mut self, key: Pubkey, account: Account) {
+        match self.0.iter_mut().find(|(k, _)| k == &key) {
+            Some((_, existing)) => *existing = account,
+            None => self.0.push((key, account)),
+        }
+    }
+
+    pub fn get(&self, key: &Pubkey) -> &Account {
+        &self.0.iter().find(|(k, _)| k == key).unwrap().1
+    }
+
+    /// Empty accounts for whatever `instruction` names that doesn't exist yet
+    pub fn add_missing(&mut self, instruction: &Instruction) {
+        for meta in &instruction.accounts {
+            if self.0.iter().all(|(k, _)| k != &meta.pubkey) {
+                self.0.push((meta.pubkey, Account::default()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_are_rent_exempt_token_accounts() {
+        let (mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let account = token_account(&mint, &owner, 42);
+        assert_eq!(account.owner, TOKEN_PROGRAM_ID);
+        assert_eq!(account.data.len(), ids::TOKEN_ACCOUNT_LEN);
+        assert_eq!(
+            account.lamports,
+            Rent::default().minimum_balance(ids::TOKEN_ACCOUNT_LEN)
+        );
+        assert_eq!(token_balance(&account.data), 42);
+
+        let mut accounts = Accounts::default();
+        accounts.set(owner, wallet());
+        accounts.set(owner, account);
+        assert_eq!(accounts.0.len(), 1);
+        assert_eq!(token_balance(&accounts.get(&owner).data), 42);
+    }
+}